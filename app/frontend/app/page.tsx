@@ -9,6 +9,7 @@ import {
 import { WalletMultiButton } from "@solana/wallet-adapter-react-ui";
 import { PublicKey } from "@solana/web3.js";
 import {
+  cancelRotation,
   finalizeRotation,
   getProgram,
   initiateRotation,
@@ -60,6 +61,8 @@ export default function Page() {
     cooldown: "0",
     bonus: "20",
     ttl: "3600",
+    grace: "0",
+    minSources: "1",
   });
   const [rotationForm, setRotationForm] = useState({
     verifier: "",
@@ -72,6 +75,7 @@ export default function Page() {
     config: false,
     rotateInit: false,
     rotateFinalize: false,
+    rotateCancel: false,
   });
   const [notice, setNotice] = useState<{
     type: "ok" | "error";
@@ -147,6 +151,8 @@ export default function Page() {
     assertInt(configForm.cooldown, "Cooldown", 0);
     assertInt(configForm.bonus, "Diversity bonus", 0, 100);
     assertInt(configForm.ttl, "Proof TTL", 1);
+    assertInt(configForm.grace, "Grace period", 0);
+    assertInt(configForm.minSources, "Min sources", 1, 8);
   };
 
   const validateRotationForm = () => {
@@ -438,6 +444,8 @@ export default function Page() {
                   cooldown: configForm.cooldown,
                   bonus: configForm.bonus,
                   ttl: configForm.ttl,
+                  grace: configForm.grace,
+                  minSources: configForm.minSources,
                 });
                 setNotice({ type: "ok", text: "Registry config updated." });
                 addLog(`Config update success: ${sig}`);
@@ -472,6 +480,24 @@ export default function Page() {
                 }
               />
             </label>
+            <label>
+              Grace Period Seconds
+              <input
+                value={configForm.grace}
+                onChange={(e) =>
+                  setConfigForm((s) => ({ ...s, grace: e.target.value }))
+                }
+              />
+            </label>
+            <label>
+              Min Active Sources
+              <input
+                value={configForm.minSources}
+                onChange={(e) =>
+                  setConfigForm((s) => ({ ...s, minSources: e.target.value }))
+                }
+              />
+            </label>
             <button className="btn" disabled={pending.config || !isConnected}>
               {pending.config ? "Updating..." : "Update Config"}
             </button>
@@ -548,6 +574,29 @@ export default function Page() {
               >
                 {pending.rotateFinalize ? "Finalizing..." : "Finalize"}
               </button>
+              <button
+                className="btn"
+                type="button"
+                onClick={async () => {
+                  await runAction("rotateCancel", async () => {
+                    if (!program || !publicKey)
+                      throw new Error("Connect wallet first");
+                    const sig = await cancelRotation({
+                      program,
+                      authority: publicKey,
+                    });
+                    setNotice({
+                      type: "ok",
+                      text: "Verifier rotation cancelled.",
+                    });
+                    addLog(`Rotation cancel success: ${sig}`);
+                    addLog(`Explorer: ${explorerTxUrl(sig)}`);
+                  });
+                }}
+                disabled={pending.rotateCancel || !isConnected}
+              >
+                {pending.rotateCancel ? "Cancelling..." : "Cancel"}
+              </button>
             </div>
           </form>
         </section>
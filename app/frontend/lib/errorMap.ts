@@ -28,6 +28,53 @@ const MESSAGES: Record<string, string> = {
   AttestationNonceAlreadyUsed: "Attestation nonce already used.",
   NoVerifierRotationPending: "No verifier rotation is pending.",
   VerifierRotationNotReady: "Rotation delay has not elapsed yet.",
+  ProofNotRevoked: "Proof must be revoked before closing.",
+  RegistryPaused: "Submissions are paused.",
+  InvalidLegacyAccount: "Proof account is not a legacy layout.",
+  InvalidBatchSize: "Batch must contain between one and three proofs.",
+  InvalidBatchAccounts: "Batch proof accounts are missing or out of order.",
+  StaleSlotHash: "Attested slot hash is missing or too old.",
+  InvalidProofAccounts: "Proof accounts do not cover every active source.",
+  ReclaimCooldownActive: "Source reclaim cooldown is still active.",
+  NonceRetentionActive: "Attestation nonce retention has not ended.",
+  MaxSourcesReached: "Maximum number of active sources reached.",
+  UnsupportedAttestationVersion: "Attestation version is not accepted.",
+  AttestationNonceOutOfOrder: "Attestation nonce is out of sequence.",
+  NonceModeMismatch: "Attestation nonce account does not match nonce mode.",
+  NoMinScoreChangePending: "No min score change is pending.",
+  MinScoreChangeNotReady: "Min score change delay has not elapsed yet.",
+  UserBanned: "This wallet is banned.",
+  InvalidMigrationAccount: "Account cannot be migrated.",
+  AccountVersionDowngrade: "Account is from a newer layout version.",
+  UserProofNotExpired: "Proof has not expired yet.",
+  InvalidFeeDestination: "Submission fee destination is invalid.",
+  RateLimited: "Registry is rate limited. Retry shortly.",
+  PersonBindingDisabled: "Person id linking is disabled.",
+  InvalidPersonId: "Person id hash is invalid.",
+  InvalidWalletFirstSeen: "Wallet first-seen time is invalid.",
+  SoulboundIdentity: "Soulbound identity cannot be migrated.",
+  IdentityUnderReview: "Identity is under review.",
+  IdentityNotFlagged: "Identity is not flagged.",
+  InvalidProofExpiry: "Proof expiry is invalid.",
+  PartiallyInitializedAccount: "Account was never fully initialized.",
+  ScoreIncreaseNotAllowed: "Downgrades cannot raise the score.",
+  InvalidVerifierProof: "Verifier key is not in the allowlist.",
+  IssuedAtMismatch: "Attestation time does not match the proof's issue time.",
+  NullifierReclaimWindowActive:
+    "This identity is still reserved for its previous wallet.",
+  NullifierNotClaimed: "This identity is not claimed by any wallet.",
+  CooldownExceedsTtl: "Cooldown must be shorter than the proof lifetime.",
+  ZeroScoreProof: "This proof has a score of zero and cannot be submitted.",
+  ScoreBelowThreshold: "This proof would leave your score below the minimum.",
+  ConflictingUserInstruction:
+    "Submit this proof in its own transaction without other account actions.",
+  SourceDwellActive: "This source was verified too recently to be replaced.",
+  AttestationExpired: "Verifier attestation expired. Request a new one.",
+  InvalidTrustMultiplier: "Trust multiplier is outside the allowed range.",
+  SourceDisabled: "This source is currently disabled.",
+  SourceNotAllowed: "This registry does not accept this source.",
+  VerifierRotationExpired: "Verifier rotation expired. Start it again.",
+  InvalidWeightOverride: "Weight override is above the allowed maximum.",
   ConstraintSeeds: "One or more account addresses are invalid for the action.",
 };
 
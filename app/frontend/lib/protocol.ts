@@ -1,12 +1,20 @@
 import { AnchorProvider, BN, Program, type Idl } from "@coral-xyz/anchor";
 import type { AnchorWallet } from "@solana/wallet-adapter-react";
-import { Ed25519Program, PublicKey, SystemProgram } from "@solana/web3.js";
+import {
+  Ed25519Program,
+  PublicKey,
+  SYSVAR_SLOT_HASHES_PUBKEY,
+  SystemProgram,
+} from "@solana/web3.js";
 import idl from "./solan_id.json";
 
 const PROGRAM_ID = new PublicKey(
   process.env.NEXT_PUBLIC_PROGRAM_ID || (idl as { address: string }).address
 );
 
+// Attestation message format signed by the local dev verifier.
+const ATTESTATION_VERSION = 1;
+
 const toBn = (value: string | number | bigint) => new BN(value.toString());
 
 const sourceToEnum = (source: string) => {
@@ -113,7 +121,7 @@ const buildAttestationMessage = (params: {
   ts.writeBigInt64LE(BigInt(params.timestamp.toString()));
 
   return Buffer.concat([
-    Buffer.from("sid1"),
+    Buffer.from(`sid${ATTESTATION_VERSION}`),
     PROGRAM_ID.toBuffer(),
     params.registry.toBuffer(),
     params.user.toBuffer(),
@@ -135,12 +143,24 @@ const scoringConfigPda = () =>
     PROGRAM_ID
   )[0];
 
+const decayConfigPda = () =>
+  PublicKey.findProgramAddressSync(
+    [Buffer.from("decay_config")],
+    PROGRAM_ID
+  )[0];
+
 const userProofPda = (user: PublicKey) =>
   PublicKey.findProgramAddressSync(
     [Buffer.from("user_proof"), user.toBuffer()],
     PROGRAM_ID
   )[0];
 
+const userStatusPda = (user: PublicKey) =>
+  PublicKey.findProgramAddressSync(
+    [Buffer.from("user_status"), user.toBuffer()],
+    PROGRAM_ID
+  )[0];
+
 const individualProofPda = (user: PublicKey, source: string) =>
   PublicKey.findProgramAddressSync(
     [
@@ -151,9 +171,19 @@ const individualProofPda = (user: PublicKey, source: string) =>
     PROGRAM_ID
   )[0];
 
-const identityNullifierPda = (identityNullifier: number[]) =>
+const identityNullifierPda = (source: string, identityNullifier: number[]) =>
+  PublicKey.findProgramAddressSync(
+    [
+      Buffer.from("identity_nullifier"),
+      Buffer.from([sourceIndex(source)]),
+      Buffer.from(identityNullifier),
+    ],
+    PROGRAM_ID
+  )[0];
+
+const proofHashRegistryPda = (user: PublicKey, proofHash: number[]) =>
   PublicKey.findProgramAddressSync(
-    [Buffer.from("identity_nullifier"), Buffer.from(identityNullifier)],
+    [Buffer.from("proof_hash"), user.toBuffer(), Buffer.from(proofHash)],
     PROGRAM_ID
   )[0];
 
@@ -197,6 +227,9 @@ export const submitProof = async (params: {
   }
 
   const registry = registryPda();
+  const registryAccount = await (params.program.account as any).registry.fetch(
+    registry
+  );
   const source = sourceToEnum(params.source);
   const proofData = normalizeProofData(params.source, params.payloadJson);
 
@@ -246,20 +279,38 @@ export const submitProof = async (params: {
       nonce,
       proofData,
       baseScore,
-      timestamp
+      timestamp,
+      new BN(0),
+      ATTESTATION_VERSION,
+      new BN(0),
+      100,
+      new BN(0),
+      []
     )
     .preInstructions([attestationIx])
     .accountsStrict({
       registry,
       userProof: userProofPda(params.user),
       individualProof: individualProofPda(params.user, params.source),
-      identityNullifierRegistry: identityNullifierPda(identityNullifierInput),
+      identityNullifierRegistry: identityNullifierPda(
+        params.source,
+        identityNullifierInput
+      ),
+      proofHashRegistry: proofHashRegistryPda(params.user, proofHash),
       attestationNonceRegistry: attestationNoncePda(registry, nonce),
+      userStatus: userStatusPda(params.user),
       scoringConfig: scoringConfigPda(),
+      decayConfig: decayConfigPda(),
       instructionsSysvar: new PublicKey(
         "Sysvar1nstructions1111111111111111111111111"
       ),
+      slotHashes: SYSVAR_SLOT_HASHES_PUBKEY,
+      feeDestination: registryAccount.submissionFee.isZero()
+        ? null
+        : registryAccount.feeDestination,
+      statusCache: null,
       user: params.user,
+      payer: params.user,
       systemProgram: SystemProgram.programId,
     })
     .rpc();
@@ -274,7 +325,10 @@ export const verifyProof = async (params: {
     .verifyProof()
     .accountsStrict({
       userProof: userProofPda(user),
+      userStatus: userStatusPda(user),
       registry: registryPda(),
+      scoringConfig: scoringConfigPda(),
+      decayConfig: decayConfigPda(),
       user,
     })
     .view();
@@ -295,7 +349,14 @@ export const revokeProof = async (params: {
       registry: registryPda(),
       userProof: userProofPda(params.user),
       individualProof: individualProofPda(params.user, params.source),
-      identityNullifierRegistry: identityNullifierPda(identityNullifier),
+      identityNullifierRegistry: identityNullifierPda(
+        params.source,
+        identityNullifier
+      ),
+      scoringConfig: scoringConfigPda(),
+      decayConfig: decayConfigPda(),
+      userStatus: userStatusPda(params.user),
+      statusCache: null,
       user: params.user,
     })
     .rpc();
@@ -307,12 +368,16 @@ export const updateRegistryConfig = async (params: {
   cooldown: string;
   bonus: string;
   ttl: string;
+  grace: string;
+  minSources: string;
 }) => {
   return params.program.methods
     .updateRegistryConfig(
       toBn(params.cooldown),
       Number(params.bonus),
-      toBn(params.ttl)
+      toBn(params.ttl),
+      toBn(params.grace),
+      Number(params.minSources)
     )
     .accountsStrict({
       registry: registryPda(),
@@ -351,3 +416,16 @@ export const finalizeRotation = async (params: {
     })
     .rpc();
 };
+
+export const cancelRotation = async (params: {
+  program: Program;
+  authority: PublicKey;
+}) => {
+  return params.program.methods
+    .cancelVerifierRotation()
+    .accountsStrict({
+      registry: registryPda(),
+      authority: params.authority,
+    })
+    .rpc();
+};
@@ -1,5 +1,6 @@
 import * as anchor from "@coral-xyz/anchor";
 import { BN, Program } from "@coral-xyz/anchor";
+import { createHash } from "crypto";
 import { SolanId } from "../../target/types/solan_id";
 
 type SourceInput =
@@ -10,7 +11,10 @@ type SourceInput =
   | { lens: {} }
   | { twitter: {} }
   | { google: {} }
-  | { discord: {} };
+  | { discord: {} }
+  | { coinbaseKyc: {} }
+  | { linkedIn: {} }
+  | { farcaster: {} };
 
 type SourceProofDataInput = Record<string, unknown>;
 
@@ -23,6 +27,26 @@ export type SubmitProofParams = {
   baseScore: BN | number | bigint;
   timestamp: BN | number | bigint;
   attestationNonce: BN | number | bigint;
+  recentSlotHash?: { slot: BN | number | bigint; hash: Uint8Array | number[] };
+  // Message format the verifier signed; defaults to 1.
+  attestationVersion?: number;
+  // Verifier-attested expiry for this proof alone; omitted or zero for none.
+  expiresAt?: BN | number | bigint;
+  // Verifier-attested percent applied after the source weight; defaults to 100.
+  trustMultiplier?: number;
+  // Verifier-attested weight in place of the source's configured one; omitted or
+  // zero keeps the configured weight.
+  weightOverride?: BN | number | bigint;
+  // Registries with sequential nonces take no per-nonce account.
+  sequentialNonce?: boolean;
+  // Pays rent in place of the user, who still signs and owns the proof.
+  relayer?: anchor.web3.Keypair;
+  // Receives the registry's submission fee, paid by the user, when one is set.
+  feeDestination?: anchor.web3.PublicKey;
+  // Refresh the user's StatusCache; set only once it has been enabled.
+  refreshStatusCache?: boolean;
+  // Merkle path proving the signer is in the registry's verifier allowlist.
+  verifierProof?: (Uint8Array | number[])[];
   verifierPrivateKey?: Uint8Array;
   attestationInstruction?: anchor.web3.TransactionInstruction;
 };
@@ -36,6 +60,9 @@ const SOURCE_INDEX: Record<string, number> = {
   twitter: 5,
   google: 6,
   discord: 7,
+  coinbaseKyc: 8,
+  linkedIn: 9,
+  farcaster: 10,
 };
 
 const asBN = (v: BN | number | bigint): BN =>
@@ -83,6 +110,12 @@ export const deriveScoringConfigPda = (programId: anchor.web3.PublicKey) =>
     programId
   )[0];
 
+export const deriveDecayConfigPda = (programId: anchor.web3.PublicKey) =>
+  anchor.web3.PublicKey.findProgramAddressSync(
+    [Buffer.from("decay_config")],
+    programId
+  )[0];
+
 export const deriveUserProofPda = (
   programId: anchor.web3.PublicKey,
   user: anchor.web3.PublicKey
@@ -92,6 +125,24 @@ export const deriveUserProofPda = (
     programId
   )[0];
 
+export const deriveUserStatusPda = (
+  programId: anchor.web3.PublicKey,
+  user: anchor.web3.PublicKey
+) =>
+  anchor.web3.PublicKey.findProgramAddressSync(
+    [Buffer.from("user_status"), user.toBuffer()],
+    programId
+  )[0];
+
+export const deriveStatusCachePda = (
+  programId: anchor.web3.PublicKey,
+  user: anchor.web3.PublicKey
+) =>
+  anchor.web3.PublicKey.findProgramAddressSync(
+    [Buffer.from("status"), user.toBuffer()],
+    programId
+  )[0];
+
 export const deriveIndividualProofPda = (
   programId: anchor.web3.PublicKey,
   user: anchor.web3.PublicKey,
@@ -108,10 +159,25 @@ export const deriveIndividualProofPda = (
 
 export const deriveIdentityNullifierPda = (
   programId: anchor.web3.PublicKey,
+  source: SourceInput,
   identityNullifier: Uint8Array | number[]
 ) =>
   anchor.web3.PublicKey.findProgramAddressSync(
-    [Buffer.from("identity_nullifier"), toFixed32(identityNullifier)],
+    [
+      Buffer.from("identity_nullifier"),
+      Buffer.from([SOURCE_INDEX[sourceKey(source)]]),
+      toFixed32(identityNullifier),
+    ],
+    programId
+  )[0];
+
+export const deriveProofHashRegistryPda = (
+  programId: anchor.web3.PublicKey,
+  user: anchor.web3.PublicKey,
+  proofHash: Uint8Array | number[]
+) =>
+  anchor.web3.PublicKey.findProgramAddressSync(
+    [Buffer.from("proof_hash"), user.toBuffer(), toFixed32(proofHash)],
     programId
   )[0];
 
@@ -138,6 +204,21 @@ export const identityNullifierFromProofData = (
   if ("worldId" in source && proofData["worldId"]) {
     return (proofData["worldId"] as any).nullifierHash;
   }
+  if ("coinbaseKyc" in source && proofData["coinbaseKyc"]) {
+    return (proofData["coinbaseKyc"] as any).accountHash;
+  }
+  if ("linkedIn" in source && proofData["linkedIn"]) {
+    return (proofData["linkedIn"] as any).memberHash;
+  }
+  if ("farcaster" in source && proofData["farcaster"]) {
+    // Keyed by fid so a custody transfer keeps the same nullifier.
+    const fid = u64Le((proofData["farcaster"] as any).fid);
+    return Array.from(
+      createHash("sha256")
+        .update(Buffer.concat([Buffer.from("farcaster"), fid]))
+        .digest()
+    );
+  }
   throw new Error("Cannot extract identity nullifier for source");
 };
 
@@ -151,9 +232,28 @@ export const buildAttestationMessage = (params: {
   baseScore: BN | number | bigint;
   timestamp: BN | number | bigint;
   proofHash: Uint8Array | number[];
+  recentSlotHash?: { slot: BN | number | bigint; hash: Uint8Array | number[] };
+  attestationVersion?: number;
+  expiresAt?: BN | number | bigint;
+  trustMultiplier?: number;
+  weightOverride?: BN | number | bigint;
 }) => {
+  // Each optional field present sets its bit in a byte that leads them.
+  const optionalFields: [number, Buffer][] = [
+    ...(params.expiresAt && !asBN(params.expiresAt).isZero()
+      ? [[1 << 0, i64Le(params.expiresAt)] as [number, Buffer]]
+      : []),
+    ...(params.trustMultiplier !== undefined && params.trustMultiplier !== 100
+      ? [[1 << 1, Buffer.from([params.trustMultiplier])] as [number, Buffer]]
+      : []),
+    ...(params.weightOverride && !asBN(params.weightOverride).isZero()
+      ? [[1 << 2, u64Le(params.weightOverride)] as [number, Buffer]]
+      : []),
+  ];
+  const presentFields = optionalFields.reduce((bits, [bit]) => bits | bit, 0);
+
   return Buffer.concat([
-    Buffer.from("sid1"),
+    Buffer.from(`sid${params.attestationVersion ?? 1}`),
     params.programId.toBuffer(),
     params.registry.toBuffer(),
     params.user.toBuffer(),
@@ -163,9 +263,48 @@ export const buildAttestationMessage = (params: {
     u64Le(params.baseScore),
     i64Le(params.timestamp),
     toFixed32(params.proofHash),
+    ...(params.recentSlotHash
+      ? [
+          u64Le(params.recentSlotHash.slot),
+          toFixed32(params.recentSlotHash.hash),
+        ]
+      : []),
+    ...(presentFields
+      ? [
+          Buffer.from([presentFields]),
+          ...optionalFields.map(([, bytes]) => bytes),
+        ]
+      : []),
+  ]);
+};
+
+export const buildMigrationMessage = (params: {
+  programId: anchor.web3.PublicKey;
+  registry: anchor.web3.PublicKey;
+  oldOwner: anchor.web3.PublicKey;
+  newOwner: anchor.web3.PublicKey;
+  source: SourceInput;
+  identityNullifier: Uint8Array | number[];
+  attestationNonce: BN | number | bigint;
+  timestamp: BN | number | bigint;
+}) => {
+  return Buffer.concat([
+    Buffer.from("sidm"),
+    params.programId.toBuffer(),
+    params.registry.toBuffer(),
+    params.oldOwner.toBuffer(),
+    params.newOwner.toBuffer(),
+    Buffer.from([SOURCE_INDEX[sourceKey(params.source)]]),
+    toFixed32(params.identityNullifier),
+    u64Le(params.attestationNonce),
+    i64Le(params.timestamp),
   ]);
 };
 
+/** SHA-256 of an attestation message, signed in its place in hashed mode. */
+export const attestationDigest = (message: Uint8Array) =>
+  createHash("sha256").update(message).digest();
+
 export const createVerifierAttestationInstruction = (params: {
   verifierPrivateKey: Uint8Array;
   message: Uint8Array;
@@ -194,29 +333,45 @@ export const submitProofTx = async (params: SubmitProofParams) => {
   );
   const identityNullifierRegistry = deriveIdentityNullifierPda(
     params.program.programId,
+    params.source,
     identityNullifier
   );
-  const attestationNonceRegistry = deriveAttestationNoncePda(
-    params.program.programId,
-    registry,
-    params.attestationNonce
-  );
+  // Proof hashes unique across all users are seeded with the default key.
+  const { proofHashScope, hashedAttestations } =
+    await params.program.account.registry.fetch(registry);
+  const proofHashOwner =
+    "global" in proofHashScope
+      ? anchor.web3.PublicKey.default
+      : params.user.publicKey;
+  const attestationNonceRegistry = params.sequentialNonce
+    ? null
+    : deriveAttestationNoncePda(
+        params.program.programId,
+        registry,
+        params.attestationNonce
+      );
 
+  const message = buildAttestationMessage({
+    programId: params.program.programId,
+    registry,
+    user: params.user.publicKey,
+    source: params.source,
+    identityNullifier,
+    attestationNonce: params.attestationNonce,
+    baseScore: params.baseScore,
+    timestamp: params.timestamp,
+    proofHash: params.proofHash,
+    recentSlotHash: params.recentSlotHash,
+    attestationVersion: params.attestationVersion,
+    expiresAt: params.expiresAt,
+    trustMultiplier: params.trustMultiplier,
+    weightOverride: params.weightOverride,
+  });
   const attestationInstruction =
     params.attestationInstruction ??
     createVerifierAttestationInstruction({
       verifierPrivateKey: params.verifierPrivateKey as Uint8Array,
-      message: buildAttestationMessage({
-        programId: params.program.programId,
-        registry,
-        user: params.user.publicKey,
-        source: params.source,
-        identityNullifier,
-        attestationNonce: params.attestationNonce,
-        baseScore: params.baseScore,
-        timestamp: params.timestamp,
-        proofHash: params.proofHash,
-      }),
+      message: hashedAttestations ? attestationDigest(message) : message,
     });
 
   return params.program.methods
@@ -227,7 +382,13 @@ export const submitProofTx = async (params: SubmitProofParams) => {
       asBN(params.attestationNonce),
       params.proofData as any,
       asBN(params.baseScore),
-      asBN(params.timestamp)
+      asBN(params.timestamp),
+      asBN(params.recentSlotHash?.slot ?? 0),
+      params.attestationVersion ?? 1,
+      asBN(params.expiresAt ?? 0),
+      params.trustMultiplier ?? 100,
+      asBN(params.weightOverride ?? 0),
+      (params.verifierProof ?? []).map((node) => Array.from(toFixed32(node)))
     )
     .preInstructions([attestationInstruction])
     .accountsStrict({
@@ -235,38 +396,179 @@ export const submitProofTx = async (params: SubmitProofParams) => {
       userProof,
       individualProof,
       identityNullifierRegistry,
+      proofHashRegistry: deriveProofHashRegistryPda(
+        params.program.programId,
+        proofHashOwner,
+        params.proofHash
+      ),
       attestationNonceRegistry,
+      userStatus: deriveUserStatusPda(
+        params.program.programId,
+        params.user.publicKey
+      ),
       scoringConfig,
+      decayConfig: deriveDecayConfigPda(params.program.programId),
       instructionsSysvar: anchor.web3.SYSVAR_INSTRUCTIONS_PUBKEY,
+      slotHashes: anchor.web3.SYSVAR_SLOT_HASHES_PUBKEY,
+      feeDestination: params.feeDestination ?? null,
+      statusCache: params.refreshStatusCache
+        ? deriveStatusCachePda(params.program.programId, params.user.publicKey)
+        : null,
       user: params.user.publicKey,
+      payer: (params.relayer ?? params.user).publicKey,
       systemProgram: anchor.web3.SystemProgram.programId,
     })
-    .signers([params.user])
+    .signers(params.relayer ? [params.user, params.relayer] : [params.user])
     .rpc();
 };
 
 export const getProofStatus = async (params: {
   program: Program<SolanId>;
   user: anchor.web3.PublicKey;
+  // Active sources to recompute `liveScore` and `isStale` against.
+  sources?: SourceInput[];
+  // Checks against this score instead of the registry's `min_score`.
+  minScore?: BN | number | bigint;
 }) => {
-  const registry = deriveRegistryPda(params.program.programId);
-  const userProof = deriveUserProofPda(params.program.programId, params.user);
+  const programId = params.program.programId;
+  const accounts = {
+    userProof: deriveUserProofPda(programId, params.user),
+    userStatus: deriveUserStatusPda(programId, params.user),
+    registry: deriveRegistryPda(programId),
+    scoringConfig: deriveScoringConfigPda(programId),
+    decayConfig: deriveDecayConfigPda(programId),
+    user: params.user,
+  };
+  const individualProofs = (params.sources ?? []).map((source) => ({
+    pubkey: deriveIndividualProofPda(programId, params.user, source),
+    isSigner: false,
+    isWritable: false,
+  }));
 
+  if (params.minScore !== undefined) {
+    return params.program.methods
+      .verifyProofWithThreshold(asBN(params.minScore))
+      .accountsStrict(accounts)
+      .remainingAccounts(individualProofs)
+      .view();
+  }
   return params.program.methods
     .verifyProof()
+    .accountsStrict(accounts)
+    .remainingAccounts(individualProofs)
+    .view();
+};
+
+/** Bit `i` of the result is set when `users[i]` is verified. */
+export const verifyProofsBatch = async (params: {
+  program: Program<SolanId>;
+  users: anchor.web3.PublicKey[];
+}) => {
+  const programId = params.program.programId;
+  return params.program.methods
+    .verifyProofsBatch()
+    .accountsStrict({ registry: deriveRegistryPda(programId) })
+    .remainingAccounts(
+      params.users.flatMap((user) => [
+        {
+          pubkey: deriveUserProofPda(programId, user),
+          isSigner: false,
+          isWritable: false,
+        },
+        {
+          pubkey: deriveUserStatusPda(programId, user),
+          isSigner: false,
+          isWritable: false,
+        },
+      ])
+    )
+    .view();
+};
+
+export const getNullifierStatus = async (params: {
+  program: Program<SolanId>;
+  source: SourceInput;
+  identityNullifier: Uint8Array | number[];
+}) => {
+  return params.program.methods
+    .getNullifierStatus(
+      params.source as any,
+      Array.from(toFixed32(params.identityNullifier))
+    )
     .accountsStrict({
-      userProof,
-      registry,
+      identityNullifierRegistry: deriveIdentityNullifierPda(
+        params.program.programId,
+        params.source,
+        params.identityNullifier
+      ),
+    })
+    .view();
+};
+
+export const getCooldownStatus = async (params: {
+  program: Program<SolanId>;
+  user: anchor.web3.PublicKey;
+}) => {
+  return params.program.methods
+    .getCooldownStatus()
+    .accountsStrict({
+      userProof: deriveUserProofPda(params.program.programId, params.user),
+      registry: deriveRegistryPda(params.program.programId),
       user: params.user,
     })
     .view();
 };
 
+export const verifyAndMarkTx = async (params: {
+  program: Program<SolanId>;
+  user: anchor.web3.Keypair;
+}) => {
+  return params.program.methods
+    .verifyAndMark()
+    .accountsStrict({
+      userProof: deriveUserProofPda(
+        params.program.programId,
+        params.user.publicKey
+      ),
+      userStatus: deriveUserStatusPda(
+        params.program.programId,
+        params.user.publicKey
+      ),
+      registry: deriveRegistryPda(params.program.programId),
+      scoringConfig: deriveScoringConfigPda(params.program.programId),
+      decayConfig: deriveDecayConfigPda(params.program.programId),
+      user: params.user.publicKey,
+    })
+    .signers([params.user])
+    .rpc();
+};
+
+export const setPrivateTx = async (params: {
+  program: Program<SolanId>;
+  user: anchor.web3.Keypair;
+  private: boolean;
+}) => {
+  return params.program.methods
+    .setPrivate(params.private)
+    .accountsStrict({
+      userProof: deriveUserProofPda(
+        params.program.programId,
+        params.user.publicKey
+      ),
+      user: params.user.publicKey,
+    })
+    .signers([params.user])
+    .rpc();
+};
+
 export const revokeProofTx = async (params: {
   program: Program<SolanId>;
   user: anchor.web3.Keypair;
   source: SourceInput;
   identityNullifier: Uint8Array | number[];
+  // Keep the identity nullifier claimable after the reclaim cooldown.
+  soft?: boolean;
+  refreshStatusCache?: boolean;
 }) => {
   const registry = deriveRegistryPda(params.program.programId);
   const userProof = deriveUserProofPda(
@@ -280,22 +582,112 @@ export const revokeProofTx = async (params: {
   );
   const identityNullifierRegistry = deriveIdentityNullifierPda(
     params.program.programId,
+    params.source,
     params.identityNullifier
   );
 
-  return params.program.methods
-    .revokeProof(params.source as any)
+  const method = params.soft
+    ? params.program.methods.softRevoke(params.source as any)
+    : params.program.methods.revokeProof(params.source as any);
+
+  return method
     .accountsStrict({
       registry,
       userProof,
       individualProof,
       identityNullifierRegistry,
+      scoringConfig: deriveScoringConfigPda(params.program.programId),
+      decayConfig: deriveDecayConfigPda(params.program.programId),
+      userStatus: deriveUserStatusPda(
+        params.program.programId,
+        params.user.publicKey
+      ),
+      statusCache: params.refreshStatusCache
+        ? deriveStatusCachePda(params.program.programId, params.user.publicKey)
+        : null,
+      user: params.user.publicKey,
+    })
+    .signers([params.user])
+    .rpc();
+};
+
+export const closeRevokedProofTx = async (params: {
+  program: Program<SolanId>;
+  user: anchor.web3.Keypair;
+  source: SourceInput;
+}) => {
+  const individualProof = deriveIndividualProofPda(
+    params.program.programId,
+    params.user.publicKey,
+    params.source
+  );
+
+  return params.program.methods
+    .closeRevokedProof(params.source as any)
+    .accountsStrict({
+      individualProof,
+      scoringConfig: deriveScoringConfigPda(params.program.programId),
       user: params.user.publicKey,
     })
     .signers([params.user])
     .rpc();
 };
 
+export const migrateIdentityTx = async (params: {
+  program: Program<SolanId>;
+  newOwner: anchor.web3.Keypair;
+  oldOwner: anchor.web3.PublicKey;
+  source: SourceInput;
+  identityNullifier: Uint8Array | number[];
+  attestationNonce: BN | number | bigint;
+  timestamp: BN | number | bigint;
+  attestationInstruction: anchor.web3.TransactionInstruction;
+}) => {
+  const programId = params.program.programId;
+  const registry = deriveRegistryPda(programId);
+
+  return params.program.methods
+    .migrateIdentity(
+      params.source as any,
+      asBN(params.attestationNonce),
+      asBN(params.timestamp)
+    )
+    .preInstructions([params.attestationInstruction])
+    .accountsStrict({
+      registry,
+      identityNullifierRegistry: deriveIdentityNullifierPda(
+        programId,
+        params.source,
+        params.identityNullifier
+      ),
+      oldUserProof: deriveUserProofPda(programId, params.oldOwner),
+      oldIndividualProof: deriveIndividualProofPda(
+        programId,
+        params.oldOwner,
+        params.source
+      ),
+      newUserProof: deriveUserProofPda(programId, params.newOwner.publicKey),
+      newIndividualProof: deriveIndividualProofPda(
+        programId,
+        params.newOwner.publicKey,
+        params.source
+      ),
+      attestationNonceRegistry: deriveAttestationNoncePda(
+        programId,
+        registry,
+        params.attestationNonce
+      ),
+      scoringConfig: deriveScoringConfigPda(programId),
+      decayConfig: deriveDecayConfigPda(programId),
+      oldOwner: params.oldOwner,
+      instructionsSysvar: anchor.web3.SYSVAR_INSTRUCTIONS_PUBKEY,
+      newOwner: params.newOwner.publicKey,
+      systemProgram: anchor.web3.SystemProgram.programId,
+    })
+    .signers([params.newOwner])
+    .rpc();
+};
+
 export const updateMinScoreTx = async (params: {
   program: Program<SolanId>;
   authority: anchor.web3.Keypair;
@@ -318,13 +710,17 @@ export const updateRegistryConfigTx = async (params: {
   cooldownPeriod: BN | number | bigint;
   diversityBonusPercent: number;
   proofTtlSeconds: BN | number | bigint;
+  gracePeriodSeconds?: BN | number | bigint;
+  minSourceCount?: number;
 }) => {
   const registry = deriveRegistryPda(params.program.programId);
   return params.program.methods
     .updateRegistryConfig(
       asBN(params.cooldownPeriod),
       params.diversityBonusPercent,
-      asBN(params.proofTtlSeconds)
+      asBN(params.proofTtlSeconds),
+      asBN(params.gracePeriodSeconds ?? 0),
+      params.minSourceCount ?? 1
     )
     .accountsStrict({
       registry,
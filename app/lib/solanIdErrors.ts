@@ -31,6 +31,76 @@ const ERROR_MESSAGES: Record<string, string> = {
   AttestationNonceAlreadyUsed: "This attestation nonce was already used.",
   NoVerifierRotationPending: "No verifier rotation is currently pending.",
   VerifierRotationNotReady: "Verifier rotation delay has not elapsed yet.",
+  ProofNotRevoked: "Only revoked proofs can be closed.",
+  RegistryPaused: "Proof submissions are temporarily paused.",
+  InvalidLegacyAccount: "This proof account does not need migration.",
+  InvalidBatchSize: "Submit between one and three proofs per batch.",
+  InvalidBatchAccounts:
+    "Batch accounts must list each proof's PDAs in submission order.",
+  StaleSlotHash: "The attested slot hash is missing or too old.",
+  InvalidProofAccounts: "Pass one proof account for each active source.",
+  ReclaimCooldownActive:
+    "This source was revoked too recently to be re-claimed.",
+  NonceRetentionActive:
+    "This attestation nonce cannot be closed until its retention ends.",
+  MaxSourcesReached:
+    "This identity already holds the maximum number of active sources.",
+  UnsupportedAttestationVersion:
+    "The verifier signed an attestation format the registry no longer accepts.",
+  AttestationNonceOutOfOrder:
+    "This attestation does not carry the next nonce for this wallet.",
+  NonceModeMismatch:
+    "The attestation nonce account does not match the registry's nonce mode.",
+  NoMinScoreChangePending: "No minimum score change is currently pending.",
+  MinScoreChangeNotReady: "Minimum score change delay has not elapsed yet.",
+  UserBanned: "This wallet has been banned from the registry.",
+  InvalidMigrationAccount: "This account cannot be migrated.",
+  AccountVersionDowngrade:
+    "This account was written by a newer program version.",
+  UserProofNotExpired: "This wallet's proof has not expired yet.",
+  InvalidFeeDestination:
+    "The submission fee destination is missing or does not match the registry.",
+  RateLimited: "The registry is busy right now. Retry in a moment.",
+  PersonBindingDisabled: "Person id linking is not enabled on this registry.",
+  InvalidPersonId: "The person id hash cannot be empty.",
+  InvalidWalletFirstSeen: "The attested wallet first-seen time is invalid.",
+  SoulboundIdentity:
+    "This identity is bound to its wallet and cannot be migrated.",
+  IdentityUnderReview: "This identity is flagged for review.",
+  IdentityNotFlagged: "This identity is not flagged.",
+  InvalidProofExpiry: "The proof expiry is outside the allowed window.",
+  PartiallyInitializedAccount:
+    "The account exists but was never fully initialized.",
+  ScoreIncreaseNotAllowed:
+    "A downgrade cannot raise the score; submit a new proof instead.",
+  InvalidVerifierProof:
+    "The attestation signer is not proven to be in the verifier allowlist.",
+  IssuedAtMismatch:
+    "The attestation timestamp is too far from the source proof's issued_at.",
+  NullifierReclaimWindowActive:
+    "The identity nullifier can only be reclaimed once its previous wallet has been expired for the full reclaim window.",
+  NullifierNotClaimed: "The identity nullifier is not claimed by any wallet.",
+  CooldownExceedsTtl:
+    "The cooldown period must be shorter than the proof TTL, or users could never refresh before expiry.",
+  ZeroScoreProof: "The proof's base score must be greater than zero.",
+  ScoreBelowThreshold:
+    "Your score would still be below the minimum after this proof. Add more sources first.",
+  ConflictingUserInstruction:
+    "Another instruction in the same transaction touches your proof accounts. Submit the proof in a separate transaction.",
+  SourceDwellActive:
+    "This source's identity was verified too recently to be replaced. Try again once the minimum dwell time has passed.",
+  AttestationExpired:
+    "The verifier attestation is too old to submit. Request a fresh attestation and try again.",
+  InvalidTrustMultiplier:
+    "The attested trust multiplier is zero or above the registry maximum. Request a new attestation.",
+  SourceDisabled:
+    "This source has been weighted to zero and is not accepted right now. Try another source.",
+  SourceNotAllowed:
+    "This registry only accepts certain sources, and this is not one of them. Try another source.",
+  VerifierRotationExpired:
+    "The pending verifier rotation was not finalized in time. Initiate the rotation again.",
+  InvalidWeightOverride:
+    "The attested weight override is above the registry maximum. Request a new attestation.",
   ConstraintSeeds:
     "One of the provided accounts does not match required PDA seeds.",
 };
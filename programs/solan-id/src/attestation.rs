@@ -0,0 +1,102 @@
+//! Byte layout of the verifier attestation `submit_proof` expects in the preceding
+//! ed25519 instruction. Only `core`, `alloc` and the SHA-256 hasher are used, so an
+//! off-chain signer can link this module and sign exactly the bytes the program
+//! rebuilds.
+
+extern crate alloc;
+
+use crate::{ProofSource, NEUTRAL_TRUST_MULTIPLIER};
+use alloc::vec::Vec;
+use anchor_lang::prelude::Pubkey;
+use solana_sha256_hasher::hash;
+
+/// First three bytes of every submission attestation; the fourth is the ASCII digit
+/// of the attestation version, e.g. `sid1`.
+pub const ATTESTATION_DOMAIN_TAG: [u8; 3] = *b"sid";
+/// Offset of the registry key in every signed message, after the 4-byte domain tag
+/// and the program id.
+pub const MESSAGE_REGISTRY_OFFSET: usize = 36;
+/// Length of `attestation_digest`, the whole signed message in hashed mode.
+pub const ATTESTATION_DIGEST_LEN: usize = 32;
+/// Bits of the byte leading the optional fields, one per field that follows it.
+pub const ATTESTS_EXPIRES_AT: u8 = 1 << 0;
+pub const ATTESTS_TRUST_MULTIPLIER: u8 = 1 << 1;
+pub const ATTESTS_WEIGHT_OVERRIDE: u8 = 1 << 2;
+
+/// The 4-byte domain tag for `attestation_version`.
+pub fn attestation_domain(attestation_version: u8) -> [u8; 4] {
+    let [s, i, d] = ATTESTATION_DOMAIN_TAG;
+    [s, i, d, b'0' + attestation_version]
+}
+
+/// Builds the message a verifier signs for one `submit_proof`. Integers are
+/// little-endian; `slot_binding` is only appended when the registry binds
+/// attestations to a recent slot hash.
+///
+/// A non-zero `expires_at`, a non-neutral `trust_multiplier` byte and a non-zero
+/// `weight_override` follow, in that order, behind a byte with an `ATTESTS_*` bit
+/// set for each one present. The bits keep one field from being read as another;
+/// attestations with none of them keep their original bytes.
+///
+/// Version 1 is the original `sid1` layout. Later versions keep the layout behind
+/// their own domain tag, so a new format can be slotted in per version without
+/// making older signatures valid under it.
+#[allow(clippy::too_many_arguments)]
+pub fn build_attestation_message(
+    attestation_version: u8,
+    program_id: &Pubkey,
+    registry: &Pubkey,
+    user: &Pubkey,
+    proof_hash: &[u8; 32],
+    source: ProofSource,
+    identity_nullifier: &[u8; 32],
+    attestation_nonce: u64,
+    base_score: u64,
+    timestamp: i64,
+    slot_binding: Option<(u64, [u8; 32])>,
+    expires_at: i64,
+    trust_multiplier: u8,
+    weight_override: u64,
+) -> Vec<u8> {
+    let mut message = Vec::with_capacity(247);
+    message.extend_from_slice(&attestation_domain(attestation_version));
+    message.extend_from_slice(program_id.as_ref());
+    message.extend_from_slice(registry.as_ref());
+    message.extend_from_slice(user.as_ref());
+    message.push(source as u8);
+    message.extend_from_slice(identity_nullifier);
+    message.extend_from_slice(&attestation_nonce.to_le_bytes());
+    message.extend_from_slice(&base_score.to_le_bytes());
+    message.extend_from_slice(&timestamp.to_le_bytes());
+    message.extend_from_slice(proof_hash);
+    if let Some((slot, slot_hash)) = slot_binding {
+        message.extend_from_slice(&slot.to_le_bytes());
+        message.extend_from_slice(&slot_hash);
+    }
+
+    let mut present_fields = 0u8;
+    let mut optional_fields = Vec::with_capacity(17);
+    if expires_at != 0 {
+        present_fields |= ATTESTS_EXPIRES_AT;
+        optional_fields.extend_from_slice(&expires_at.to_le_bytes());
+    }
+    if trust_multiplier != NEUTRAL_TRUST_MULTIPLIER {
+        present_fields |= ATTESTS_TRUST_MULTIPLIER;
+        optional_fields.push(trust_multiplier);
+    }
+    if weight_override != 0 {
+        present_fields |= ATTESTS_WEIGHT_OVERRIDE;
+        optional_fields.extend_from_slice(&weight_override.to_le_bytes());
+    }
+    if present_fields != 0 {
+        message.push(present_fields);
+        message.extend_from_slice(&optional_fields);
+    }
+    message
+}
+
+/// SHA-256 of a message from `build_attestation_message`, which verifiers sign in
+/// its place when the registry has `hashed_attestations` set.
+pub fn attestation_digest(message: &[u8]) -> [u8; ATTESTATION_DIGEST_LEN] {
+    hash(message).to_bytes()
+}
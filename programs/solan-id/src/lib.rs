@@ -3,10 +3,64 @@ use anchor_lang::solana_program::instruction::Instruction;
 use anchor_lang::solana_program::sysvar::instructions::{
     load_current_index_checked, load_instruction_at_checked,
 };
+use anchor_lang::system_program;
+use solana_sha256_hasher::hashv;
 use std::str::FromStr;
 
+pub mod attestation;
+
+pub use attestation::{
+    attestation_digest, build_attestation_message, ATTESTATION_DIGEST_LEN, MESSAGE_REGISTRY_OFFSET,
+};
+
 declare_id!("FGoa1MtyJRXew4FKdCSAMFfLEK7Y2GMfSjc2NsPrmX9p");
 
+pub const MAX_VERIFIERS: usize = 5;
+/// Deepest verifier allowlist proof `submit_proof` will fold, enough for 65536 keys.
+pub const MAX_VERIFIER_PROOF_DEPTH: usize = 16;
+pub const MAX_BATCH_SIZE: usize = 3;
+/// Most wallets `verify_proofs_batch` checks in one call, sized to stay within the
+/// default compute budget; each wallet's result is one bit of the returned `u32`.
+pub const MAX_VERIFY_BATCH_SIZE: usize = 32;
+pub const MAX_SLOT_HASH_ENTRIES: u64 = 512;
+pub const PROOF_SOURCE_COUNT: usize = ProofSource::VARIANT_COUNT;
+/// Slots reserved in per-source config arrays and source masks, so new sources fit
+/// without another account layout change.
+pub const MAX_PROOF_SOURCES: usize = 16;
+const _: () = assert!(PROOF_SOURCE_COUNT <= MAX_PROOF_SOURCES);
+/// Sources that existed when per-source arrays were sized to the variant count.
+const LEGACY_PROOF_SOURCE_COUNT: usize = 8;
+/// How far ahead of the validator clock an attested timestamp may be, unless the
+/// registry's `clock_skew_tolerance` says otherwise.
+pub const DEFAULT_CLOCK_SKEW_TOLERANCE: i64 = 300;
+pub const MAX_CLOCK_SKEW_TOLERANCE: i64 = 3600;
+/// Longest `proof_ttl_seconds` a registry accepts, well clear of the range where
+/// `now - proof_ttl_seconds` could wrap.
+pub const MAX_PROOF_TTL_SECONDS: i64 = 10 * 365 * 86_400;
+/// How long past the claiming wallet's `valid_until` an unburned identity stays
+/// pinned to it before `reclaim_expired_nullifier` may release it.
+pub const NULLIFIER_RECLAIM_WINDOW_SECONDS: i64 = 180 * 86_400;
+pub const BASIS_POINTS: u64 = 10_000;
+/// How long a verifier rotation stays finalizable once its delay has passed, unless
+/// the registry's `rotation_expiry_seconds` says otherwise.
+pub const DEFAULT_ROTATION_EXPIRY_SECONDS: i64 = 7 * 86_400;
+/// Default sides of the cross-ecosystem bonus, limited to sources `submit_proof`
+/// accepts a payload for: web2 account attestations and KYC on one side, onchain
+/// and personhood identities on the other.
+pub const DEFAULT_WEB2_MASK: u16 = source_bit(ProofSource::Reclaim)
+    | source_bit(ProofSource::CoinbaseKyc)
+    | source_bit(ProofSource::LinkedIn);
+pub const DEFAULT_WEB3_MASK: u16 = source_bit(ProofSource::GitcoinPassport)
+    | source_bit(ProofSource::WorldId)
+    | source_bit(ProofSource::Farcaster);
+/// Trust multiplier, in percent, that leaves a weighted score unchanged.
+pub const NEUTRAL_TRUST_MULTIPLIER: u8 = 100;
+/// Newest attestation message format `build_attestation_message` can produce.
+pub const MAX_ATTESTATION_VERSION: u8 = 2;
+/// Layout version written to every account's trailing `version` field. Bump it when
+/// fields are appended so `migrate_account` can bring existing accounts forward.
+pub const ACCOUNT_VERSION: u8 = 5;
+
 #[program]
 pub mod solan_id {
     use super::*;
@@ -18,10 +72,15 @@ pub mod solan_id {
         diversity_bonus_percent: u8,
         proof_ttl_seconds: i64,
         verifier_authority: Pubkey,
+        guardian: Pubkey,
     ) -> Result<()> {
         require!(cooldown_period >= 0, SolanIdError::InvalidConfig);
         require!(diversity_bonus_percent <= 100, SolanIdError::InvalidConfig);
-        require!(proof_ttl_seconds > 0, SolanIdError::InvalidConfig);
+        require!(
+            (1..=MAX_PROOF_TTL_SECONDS).contains(&proof_ttl_seconds),
+            SolanIdError::InvalidConfig
+        );
+        check_cooldown_within_ttl(cooldown_period, proof_ttl_seconds)?;
         require!(
             verifier_authority != Pubkey::default(),
             SolanIdError::InvalidConfig
@@ -37,10 +96,83 @@ pub mod solan_id {
         registry.verifier_authority = verifier_authority;
         registry.pending_verifier_authority = Pubkey::default();
         registry.verifier_rotation_available_at = 0;
+        registry.max_weighted_score = u64::MAX;
+        registry.paused = false;
+        registry.verifier_set = Vec::new();
+        registry.verifier_threshold = 1;
+        registry.min_base_for_bonus = 0;
+        registry.verifier_scheme = VerifierScheme::Ed25519;
+        registry.verifier_eth_address = [0; 20];
+        registry.rotation_grace_seconds = 0;
+        registry.campaign_start = 0;
+        registry.campaign_end = 0;
+        registry.campaign_bonus_percent = 0;
+        registry.slot_hash_binding = false;
+        registry.max_slot_hash_age = 0;
+        registry.max_recency_after_resubmit = 100;
+        registry.proof_hash_scope = ProofHashScope::PerUser;
+        registry.grace_period_seconds = 0;
+        registry.web2_mask = DEFAULT_WEB2_MASK;
+        registry.web3_mask = DEFAULT_WEB3_MASK;
+        registry.cross_ecosystem_bonus_percent = 0;
+        registry.nonce_retention_seconds = 0;
+        registry.max_active_sources = PROOF_SOURCE_COUNT as u8;
+        registry.global_score_multiplier_bps = BASIS_POINTS as u16;
+        registry.attestation_version = 1;
+        registry.min_attestation_version = 1;
+        registry.sequential_nonces = false;
+        registry.pending_min_score = 0;
+        registry.min_score_change_available_at = 0;
+        registry.tier_thresholds = [0; 4];
         registry.bump = ctx.bumps.registry;
+        registry.version = ACCOUNT_VERSION;
+        registry.min_source_count = 1;
+        registry.submission_fee = 0;
+        registry.fee_destination = Pubkey::default();
+        registry.max_submissions_per_slot = 0;
+        registry.rate_limit_slot = 0;
+        registry.rate_limit_count = 0;
+        registry.person_binding = false;
+        registry.wallet_age_bonus_percent = 0;
+        registry.min_wallet_age_seconds = 0;
+        registry.watcher = Pubkey::default();
+        registry.permissionless_flagging = false;
+        registry.normalized_scoring = false;
+        registry.max_total_score = 0;
+        registry.verifier_merkle_root = [0; 32];
+        registry.guardian = guardian;
+        registry.clock_skew_tolerance = DEFAULT_CLOCK_SKEW_TOLERANCE;
+        registry.max_aggregated_score = u64::MAX;
+        registry.strict_min_score = false;
+        registry.min_source_dwell_seconds = 0;
+        registry.attestation_ttl_seconds = 0;
+        registry.hashed_attestations = false;
+        registry.max_trust_multiplier = NEUTRAL_TRUST_MULTIPLIER;
+        registry.max_weight_override = 0;
+        registry.allowed_sources = u16::MAX;
+        registry.rotation_expiry_seconds = DEFAULT_ROTATION_EXPIRY_SECONDS;
+        registry.pending_verifier_set = Vec::new();
+        registry.pending_verifier_threshold = 0;
+        registry.verifier_set_change_available_at = 0;
+
+        emit!(RegistryConfigUpdated {
+            authority: registry.authority,
+            old_cooldown_period: 0,
+            new_cooldown_period: cooldown_period,
+            old_diversity_bonus_percent: 0,
+            new_diversity_bonus_percent: diversity_bonus_percent,
+            old_proof_ttl_seconds: 0,
+            new_proof_ttl_seconds: proof_ttl_seconds,
+        });
         Ok(())
     }
 
+    /// Records a verifier-attested proof for one source. The attestation must sit
+    /// directly before this instruction (a quorum's signatures in one unbroken run of
+    /// signature instructions, each of which may carry several), and no other
+    /// instruction of this program in the transaction may touch the user's
+    /// `UserProof` or the source's `IndividualProof`.
+    #[allow(clippy::too_many_arguments)]
     pub fn submit_proof(
         ctx: Context<SubmitProof>,
         proof_hash: [u8; 32],
@@ -50,15 +182,36 @@ pub mod solan_id {
         proof_data: SourceProofData,
         base_score: u64,
         timestamp: i64,
+        recent_slot: u64,
+        attestation_version: u8,
+        expires_at: i64,
+        trust_multiplier: u8,
+        weight_override: u64,
+        verifier_proof: Vec<[u8; 32]>,
     ) -> Result<()> {
+        let decay_config = load_decay_config(&ctx.accounts.decay_config, ctx.program_id)?;
         let registry = &mut ctx.accounts.registry;
         let user_proof = &mut ctx.accounts.user_proof;
-        let individual_proof = &mut ctx.accounts.individual_proof;
-        let identity_nullifier_registry = &mut ctx.accounts.identity_nullifier_registry;
-        let attestation_nonce_registry = &mut ctx.accounts.attestation_nonce_registry;
-        let scoring_config = &ctx.accounts.scoring_config;
         let clock = Clock::get()?;
 
+        require!(!registry.paused, SolanIdError::RegistryPaused);
+        registry.check_source_allowed(source)?;
+        require!(
+            !is_banned(&ctx.accounts.user_status, ctx.program_id)?,
+            SolanIdError::UserBanned
+        );
+        require!(
+            ctx.accounts.attestation_nonce_registry.is_none() == registry.sequential_nonces,
+            SolanIdError::NonceModeMismatch
+        );
+
+        let slot_binding = resolve_slot_binding(
+            registry,
+            &ctx.accounts.slot_hashes.to_account_info(),
+            recent_slot,
+            clock.slot,
+        )?;
+
         verify_verifier_attestation(
             &ctx.accounts.instructions_sysvar.to_account_info(),
             ctx.program_id,
@@ -70,176 +223,128 @@ pub mod solan_id {
             attestation_nonce,
             base_score,
             timestamp,
-            registry.verifier_authority,
+            slot_binding,
+            expires_at,
+            trust_multiplier,
+            weight_override,
+            attestation_version,
+            &verifier_proof,
+            registry,
+        )?;
+        check_sole_user_instruction(
+            &ctx.accounts.instructions_sysvar.to_account_info(),
+            ctx.program_id,
+            &[user_proof.key(), ctx.accounts.individual_proof.key()],
         )?;
 
-        validate_source_proof_data(source, &proof_data, base_score, clock.unix_timestamp)?;
-
-        require!(
-            !attestation_nonce_registry.is_used,
-            SolanIdError::AttestationNonceAlreadyUsed
-        );
-
-        require!(
-            identity_nullifier == extract_identity_nullifier(source, &proof_data)?,
-            SolanIdError::InvalidIdentityNullifier
-        );
-
-        if identity_nullifier_registry.claimed_by == Pubkey::default() {
-            identity_nullifier_registry.nullifier = identity_nullifier;
-            identity_nullifier_registry.source = source;
-            identity_nullifier_registry.claimed_by = ctx.accounts.user.key();
-            identity_nullifier_registry.is_burned = false;
-            identity_nullifier_registry.claimed_at = clock.unix_timestamp;
-            identity_nullifier_registry.last_proof_hash = proof_hash;
-            identity_nullifier_registry.bump = ctx.bumps.identity_nullifier_registry;
-        } else {
-            require!(
-                identity_nullifier_registry.source == source,
-                SolanIdError::InvalidIdentityNullifier
-            );
-            require!(
-                identity_nullifier_registry.nullifier == identity_nullifier,
-                SolanIdError::InvalidIdentityNullifier
-            );
-            require!(
-                identity_nullifier_registry.claimed_by == ctx.accounts.user.key(),
-                SolanIdError::DuplicateIdentityClaim
-            );
-            require!(
-                !identity_nullifier_registry.is_burned,
-                SolanIdError::IdentityRevokedPermanent
-            );
-            identity_nullifier_registry.last_proof_hash = proof_hash;
-        }
-
-        require!(
-            timestamp <= clock.unix_timestamp + 300,
-            SolanIdError::InvalidTimestamp
-        );
-
-        require!(
-            timestamp >= clock.unix_timestamp - registry.proof_ttl_seconds,
-            SolanIdError::ProofExpired
-        );
-
-        if user_proof.user != Pubkey::default() {
-            require!(
-                clock.unix_timestamp
-                    >= user_proof
-                        .last_submission
-                        .checked_add(registry.cooldown_period)
-                        .ok_or(SolanIdError::Overflow)?,
-                SolanIdError::CooldownPeriodActive
-            );
+        if is_recorded_submission(
+            user_proof,
+            &ctx.accounts.individual_proof,
+            ctx.accounts.attestation_nonce_registry.as_deref(),
+            ctx.accounts.user.key(),
+            source,
+            proof_hash,
+            attestation_nonce,
+        ) {
+            msg!("Submission already recorded");
+            return Ok(());
         }
 
-        let weight = scoring_config.weights[source as u8 as usize];
-        let weighted_score = base_score
-            .checked_mul(weight)
-            .and_then(|s| s.checked_div(100))
-            .ok_or(SolanIdError::Overflow)?;
-
-        let age_seconds = clock.unix_timestamp.checked_sub(timestamp).unwrap_or(0);
-        let recency_factor = if age_seconds < 2592000 {
-            100u8
-        } else if age_seconds < 7776000 {
-            75u8
-        } else if age_seconds < 15552000 {
-            50u8
-        } else {
-            25u8
-        };
-        let recency_adjusted_score = weighted_score
-            .checked_mul(recency_factor as u64)
-            .and_then(|s| s.checked_div(100))
-            .ok_or(SolanIdError::Overflow)?;
+        consume_slot_capacity(registry, clock.slot, 1)?;
+        collect_submission_fee(
+            registry,
+            ctx.accounts.fee_destination.as_ref(),
+            &ctx.accounts.user,
+            &ctx.accounts.system_program,
+            1,
+        )?;
 
-        let old_base_aggregated_score = strip_diversity_bonus(
-            user_proof.aggregated_score,
-            user_proof.active_source_count,
-            registry.diversity_bonus_percent,
+        let recorded = record_proof(
+            registry,
+            &ctx.accounts.scoring_config,
+            &decay_config,
+            &mut ctx.accounts.individual_proof,
+            &mut ctx.accounts.identity_nullifier_registry,
+            &mut ctx.accounts.proof_hash_registry,
+            ctx.accounts.attestation_nonce_registry.as_deref_mut(),
+            [
+                ctx.bumps.individual_proof,
+                ctx.bumps.identity_nullifier_registry,
+                ctx.bumps.proof_hash_registry,
+                ctx.bumps.attestation_nonce_registry.unwrap_or_default(),
+            ],
+            ctx.accounts.user.key(),
+            user_proof.expiry_epoch,
+            ProofSubmission {
+                proof_hash,
+                source,
+                identity_nullifier,
+                attestation_nonce,
+                proof_data,
+                base_score,
+                timestamp,
+                recent_slot,
+                attestation_version,
+                expires_at,
+                trust_multiplier,
+                weight_override,
+            },
+            clock.unix_timestamp,
         )?;
 
-        let old_score =
-            if individual_proof.user != Pubkey::default() && !individual_proof.is_revoked {
-                let age_seconds = clock
-                    .unix_timestamp
-                    .checked_sub(individual_proof.verified_at)
-                    .unwrap_or(0);
-                let recency = if age_seconds < 2592000 {
-                    100u8
-                } else if age_seconds < 7776000 {
-                    75u8
-                } else if age_seconds < 15552000 {
-                    50u8
-                } else {
-                    25u8
-                } as u64;
-                recency
-                    .checked_mul(individual_proof.weighted_score)
-                    .and_then(|s| s.checked_div(100))
-                    .ok_or(SolanIdError::Overflow)?
-            } else {
-                0
-            };
+        check_submission_cooldown(user_proof, registry, clock.unix_timestamp)?;
 
-        let is_new_user = user_proof.user == Pubkey::default();
+        let old_base_aggregated_score = strip_score_bonuses(user_proof, registry)?;
 
-        if is_new_user {
-            user_proof.user = ctx.accounts.user.key();
-            user_proof.last_submission = clock.unix_timestamp;
-            user_proof.aggregated_score = 0;
-            user_proof.active_source_count = 0;
-            user_proof.valid_until = clock
-                .unix_timestamp
-                .checked_add(registry.proof_ttl_seconds)
-                .ok_or(SolanIdError::Overflow)?;
-            user_proof.bump = ctx.bumps.user_proof;
+        if user_proof.user == Pubkey::default() {
+            init_user_proof(
+                user_proof,
+                ctx.accounts.user.key(),
+                ctx.bumps.user_proof,
+                clock.unix_timestamp,
+                registry,
+            )?;
             registry.total_verified_users = registry
                 .total_verified_users
                 .checked_add(1)
                 .ok_or(SolanIdError::Overflow)?;
         }
+        if registry.sequential_nonces {
+            advance_sequential_nonce(user_proof, attestation_nonce)?;
+        }
 
-        let was_source_active =
-            individual_proof.user != Pubkey::default() && !individual_proof.is_revoked;
-        if !was_source_active {
+        if !recorded.was_source_active {
+            require!(
+                user_proof.active_source_count < registry.max_active_sources,
+                SolanIdError::MaxSourcesReached
+            );
             user_proof.active_source_count = user_proof
                 .active_source_count
                 .checked_add(1)
                 .ok_or(SolanIdError::Overflow)?;
         }
+        user_proof.sources_mask |= source_bit(source);
+        set_source_score(user_proof, source, recorded.recency_adjusted_score)?;
+        set_source_expiry(user_proof, source, expires_at)?;
+        user_proof.total_submissions = user_proof
+            .total_submissions
+            .checked_add(1)
+            .ok_or(SolanIdError::Overflow)?;
 
-        individual_proof.user = ctx.accounts.user.key();
-        individual_proof.proof_hash = proof_hash;
-        individual_proof.base_score = base_score;
-        individual_proof.weighted_score = weighted_score;
-        individual_proof.source = source;
-        individual_proof.identity_nullifier = identity_nullifier;
-        individual_proof.proof_data = proof_data;
-        individual_proof.verified_at = timestamp;
-        individual_proof.is_revoked = false;
-        individual_proof.bump = ctx.bumps.individual_proof;
-
-        attestation_nonce_registry.nonce = attestation_nonce;
-        attestation_nonce_registry.is_used = true;
-        attestation_nonce_registry.user = ctx.accounts.user.key();
-        attestation_nonce_registry.used_at = clock.unix_timestamp;
-        attestation_nonce_registry.bump = ctx.bumps.attestation_nonce_registry;
-
-        let mut new_base_aggregated_score = old_base_aggregated_score
-            .checked_sub(old_score)
-            .unwrap_or(0);
+        let mut new_base_aggregated_score =
+            old_base_aggregated_score.saturating_sub(recorded.previous_score);
         new_base_aggregated_score = new_base_aggregated_score
-            .checked_add(recency_adjusted_score)
+            .checked_add(recorded.recency_adjusted_score)
             .ok_or(SolanIdError::Overflow)?;
 
-        user_proof.aggregated_score = apply_diversity_bonus(
+        apply_score_bonuses(
+            user_proof,
+            registry,
+            &ctx.accounts.scoring_config,
             new_base_aggregated_score,
-            user_proof.active_source_count,
-            registry.diversity_bonus_percent,
+            clock.unix_timestamp,
         )?;
+        let points_to_min_score = registry.check_min_score_gap(user_proof.aggregated_score)?;
 
         user_proof.last_submission = clock.unix_timestamp;
         user_proof.valid_until = clock
@@ -251,542 +356,7258 @@ pub mod solan_id {
             user: ctx.accounts.user.key(),
             proof_hash,
             base_score,
-            weighted_score,
+            weighted_score: recorded.weighted_score,
             source,
             timestamp,
+            aggregated_score: user_proof.aggregated_score,
+            active_source_count: user_proof.active_source_count,
+            points_to_min_score,
+            total_submissions: user_proof.total_submissions,
         });
 
+        if let Some(status_cache) = ctx.accounts.status_cache.as_mut() {
+            refresh_status_cache(
+                status_cache,
+                &ctx.accounts.user_proof,
+                &ctx.accounts.registry,
+                &ctx.accounts.scoring_config,
+                &decay_config,
+                false,
+                &[],
+                ctx.program_id,
+                clock.unix_timestamp,
+            )?;
+        }
+
         Ok(())
     }
 
-    pub fn revoke_proof(ctx: Context<RevokeProof>, _source: ProofSource) -> Result<()> {
-        let individual_proof = &mut ctx.accounts.individual_proof;
-        let user_proof = &mut ctx.accounts.user_proof;
-        let identity_nullifier_registry = &mut ctx.accounts.identity_nullifier_registry;
-        let registry = &ctx.accounts.registry;
+    /// Submits proofs for several sources at once. `remaining_accounts` holds the
+    /// individual proof, identity nullifier, proof hash and attestation nonce PDAs for
+    /// each entry, in that order (without the nonce PDA when the registry uses
+    /// sequential nonces), and the attestation for entry `i` sits `len - i` instructions
+    /// before this one. Any failing entry reverts the whole batch, and the diversity
+    /// bonus is applied once after every entry is folded in.
+    pub fn submit_proofs_batch<'info>(
+        ctx: Context<'_, '_, 'info, 'info, SubmitProofsBatch<'info>>,
+        submissions: Vec<ProofSubmission>,
+    ) -> Result<()> {
+        let decay_config = load_decay_config(&ctx.accounts.decay_config, ctx.program_id)?;
+        let registry = &mut ctx.accounts.registry;
+        let user = ctx.accounts.user.key();
+        let instructions_sysvar = ctx.accounts.instructions_sysvar.to_account_info();
         let clock = Clock::get()?;
 
+        require!(!registry.paused, SolanIdError::RegistryPaused);
         require!(
-            individual_proof.user == ctx.accounts.user.key(),
-            SolanIdError::Unauthorized
+            !is_banned(&ctx.accounts.user_status, ctx.program_id)?,
+            SolanIdError::UserBanned
         );
-
         require!(
-            !individual_proof.is_revoked,
-            SolanIdError::ProofAlreadyRevoked
+            !submissions.is_empty() && submissions.len() <= MAX_BATCH_SIZE,
+            SolanIdError::InvalidBatchSize
         );
-
+        for submission in &submissions {
+            registry.check_source_allowed(submission.source)?;
+        }
+        let accounts_per_entry = if registry.sequential_nonces { 3 } else { 4 };
         require!(
-            identity_nullifier_registry.nullifier == individual_proof.identity_nullifier,
-            SolanIdError::InvalidIdentityNullifier
+            ctx.remaining_accounts.len() == submissions.len() * accounts_per_entry,
+            SolanIdError::InvalidBatchAccounts
         );
+        for (i, submission) in submissions.iter().enumerate() {
+            require!(
+                submissions[..i]
+                    .iter()
+                    .all(|s| s.source != submission.source),
+                SolanIdError::InvalidBatchAccounts
+            );
+        }
+
+        check_submission_cooldown(&ctx.accounts.user_proof, registry, clock.unix_timestamp)?;
+
+        let current_index = load_current_index_checked(&instructions_sysvar)
+            .map_err(|_| error!(SolanIdError::InvalidAttestationInstruction))?
+            as usize;
         require!(
-            identity_nullifier_registry.claimed_by == ctx.accounts.user.key(),
-            SolanIdError::Unauthorized
+            current_index >= submissions.len(),
+            SolanIdError::InvalidAttestationInstruction
         );
+        let first_attestation_index = current_index - submissions.len();
+        check_sole_user_instruction(
+            &instructions_sysvar,
+            ctx.program_id,
+            &[ctx.accounts.user_proof.key()],
+        )?;
+
+        consume_slot_capacity(registry, clock.slot, submissions.len() as u32)?;
+        collect_submission_fee(
+            registry,
+            ctx.accounts.fee_destination.as_ref(),
+            &ctx.accounts.user,
+            &ctx.accounts.system_program,
+            submissions.len() as u64,
+        )?;
+
+        let user_proof = &mut ctx.accounts.user_proof;
+        let mut base_aggregated_score = strip_score_bonuses(user_proof, registry)?;
+
+        if user_proof.user == Pubkey::default() {
+            init_user_proof(
+                user_proof,
+                user,
+                ctx.bumps.user_proof,
+                clock.unix_timestamp,
+                registry,
+            )?;
+            registry.total_verified_users = registry
+                .total_verified_users
+                .checked_add(1)
+                .ok_or(SolanIdError::Overflow)?;
+        }
+
+        let slot_hashes = ctx.accounts.slot_hashes.to_account_info();
+        let mut submitted = Vec::with_capacity(submissions.len());
+        for (i, (submission, accounts)) in submissions
+            .into_iter()
+            .zip(ctx.remaining_accounts.chunks_exact(accounts_per_entry))
+            .enumerate()
+        {
+            check_attestation_version(registry, submission.attestation_version)?;
+            let expected_message = expected_attestation(
+                registry,
+                build_attestation_message(
+                    submission.attestation_version,
+                    ctx.program_id,
+                    &registry.key(),
+                    &user,
+                    &submission.proof_hash,
+                    submission.source,
+                    &submission.identity_nullifier,
+                    submission.attestation_nonce,
+                    submission.base_score,
+                    submission.timestamp,
+                    resolve_slot_binding(
+                        registry,
+                        &slot_hashes,
+                        submission.recent_slot,
+                        clock.slot,
+                    )?,
+                    submission.expires_at,
+                    submission.trust_multiplier,
+                    submission.weight_override,
+                ),
+            );
+            verify_attestation_message(
+                &instructions_sysvar,
+                first_attestation_index + i + 1,
+                &expected_message,
+                registry,
+            )?;
+
+            let source_seed = [submission.source as u8];
+            let nonce_seed = submission.attestation_nonce.to_le_bytes();
+            let registry_key = registry.key();
+            let (mut individual_proof, individual_proof_bump) =
+                load_or_create_pda::<IndividualProof>(
+                    &accounts[0],
+                    &[b"individual_proof", user.as_ref(), &source_seed],
+                    8 + IndividualProof::INIT_SPACE,
+                    &ctx.accounts.user,
+                    &ctx.accounts.system_program,
+                    ctx.program_id,
+                )?;
+            let (mut identity_nullifier_registry, identity_nullifier_bump) =
+                load_or_create_pda::<IdentityNullifierRegistry>(
+                    &accounts[1],
+                    &[
+                        b"identity_nullifier",
+                        &source_seed,
+                        &submission.identity_nullifier,
+                    ],
+                    8 + IdentityNullifierRegistry::INIT_SPACE,
+                    &ctx.accounts.user,
+                    &ctx.accounts.system_program,
+                    ctx.program_id,
+                )?;
+            let (mut proof_hash_registry, proof_hash_bump) = load_or_create_pda::<ProofHashRegistry>(
+                &accounts[2],
+                &[
+                    b"proof_hash",
+                    registry.proof_hash_owner(user).as_ref(),
+                    &submission.proof_hash,
+                ],
+                8 + ProofHashRegistry::INIT_SPACE,
+                &ctx.accounts.user,
+                &ctx.accounts.system_program,
+                ctx.program_id,
+            )?;
+            let (mut attestation_nonce_registry, attestation_nonce_bump) =
+                if registry.sequential_nonces {
+                    advance_sequential_nonce(user_proof, submission.attestation_nonce)?;
+                    (None, 0)
+                } else {
+                    let (account, bump) = load_or_create_pda::<AttestationNonceRegistry>(
+                        &accounts[3],
+                        &[b"attestation_nonce", registry_key.as_ref(), &nonce_seed],
+                        8 + AttestationNonceRegistry::INIT_SPACE,
+                        &ctx.accounts.user,
+                        &ctx.accounts.system_program,
+                        ctx.program_id,
+                    )?;
+                    (Some(account), bump)
+                };
+
+            let proof_hash = submission.proof_hash;
+            let source = submission.source;
+            let base_score = submission.base_score;
+            let timestamp = submission.timestamp;
+            let expires_at = submission.expires_at;
+            let recorded = record_proof(
+                registry,
+                &ctx.accounts.scoring_config,
+                &decay_config,
+                &mut individual_proof,
+                &mut identity_nullifier_registry,
+                &mut proof_hash_registry,
+                attestation_nonce_registry.as_deref_mut(),
+                [
+                    individual_proof_bump,
+                    identity_nullifier_bump,
+                    proof_hash_bump,
+                    attestation_nonce_bump,
+                ],
+                user,
+                user_proof.expiry_epoch,
+                submission,
+                clock.unix_timestamp,
+            )?;
+
+            individual_proof.exit(ctx.program_id)?;
+            identity_nullifier_registry.exit(ctx.program_id)?;
+            proof_hash_registry.exit(ctx.program_id)?;
+            if let Some(attestation_nonce_registry) = attestation_nonce_registry {
+                attestation_nonce_registry.exit(ctx.program_id)?;
+            }
+
+            if !recorded.was_source_active {
+                require!(
+                    user_proof.active_source_count < registry.max_active_sources,
+                    SolanIdError::MaxSourcesReached
+                );
+                user_proof.active_source_count = user_proof
+                    .active_source_count
+                    .checked_add(1)
+                    .ok_or(SolanIdError::Overflow)?;
+            }
+            user_proof.sources_mask |= source_bit(source);
+            set_source_score(user_proof, source, recorded.recency_adjusted_score)?;
+            set_source_expiry(user_proof, source, expires_at)?;
+            user_proof.total_submissions = user_proof
+                .total_submissions
+                .checked_add(1)
+                .ok_or(SolanIdError::Overflow)?;
+            base_aggregated_score = base_aggregated_score
+                .saturating_sub(recorded.previous_score)
+                .checked_add(recorded.recency_adjusted_score)
+                .ok_or(SolanIdError::Overflow)?;
+
+            submitted.push(ProofSubmitted {
+                user,
+                proof_hash,
+                base_score,
+                weighted_score: recorded.weighted_score,
+                source,
+                timestamp,
+                aggregated_score: 0,
+                active_source_count: 0,
+                points_to_min_score: 0,
+                total_submissions: user_proof.total_submissions,
+            });
+        }
 
-        let age_seconds = clock
+        apply_score_bonuses(
+            user_proof,
+            registry,
+            &ctx.accounts.scoring_config,
+            base_aggregated_score,
+            clock.unix_timestamp,
+        )?;
+        let points_to_min_score = registry.check_min_score_gap(user_proof.aggregated_score)?;
+
+        user_proof.last_submission = clock.unix_timestamp;
+        user_proof.valid_until = clock
             .unix_timestamp
-            .checked_sub(individual_proof.verified_at)
-            .unwrap_or(0);
-        let recency_factor = if age_seconds < 2592000 {
-            100u8
-        } else if age_seconds < 7776000 {
-            75u8
-        } else if age_seconds < 15552000 {
-            50u8
-        } else {
-            25u8
-        };
-        let recency_adjusted_score = individual_proof
-            .weighted_score
-            .checked_mul(recency_factor as u64)
-            .and_then(|s| s.checked_div(100))
+            .checked_add(registry.proof_ttl_seconds)
             .ok_or(SolanIdError::Overflow)?;
 
-        let old_base_aggregated_score = strip_diversity_bonus(
-            user_proof.aggregated_score,
-            user_proof.active_source_count,
-            registry.diversity_bonus_percent,
-        )?;
+        // Bonuses are only settled once the whole batch is recorded, so every event
+        // carries the final aggregate.
+        for mut event in submitted {
+            event.aggregated_score = user_proof.aggregated_score;
+            event.active_source_count = user_proof.active_source_count;
+            event.points_to_min_score = points_to_min_score;
+            emit!(event);
+        }
 
-        let new_base_aggregated_score = old_base_aggregated_score
-            .checked_sub(recency_adjusted_score)
-            .unwrap_or(0);
+        if let Some(status_cache) = ctx.accounts.status_cache.as_mut() {
+            refresh_status_cache(
+                status_cache,
+                &ctx.accounts.user_proof,
+                &ctx.accounts.registry,
+                &ctx.accounts.scoring_config,
+                &decay_config,
+                false,
+                &[],
+                ctx.program_id,
+                clock.unix_timestamp,
+            )?;
+        }
 
-        user_proof.active_source_count = user_proof.active_source_count.checked_sub(1).unwrap_or(0);
+        Ok(())
+    }
 
-        user_proof.aggregated_score = apply_diversity_bonus(
-            new_base_aggregated_score,
-            user_proof.active_source_count,
-            registry.diversity_bonus_percent,
-        )?;
+    pub fn revoke_proof(ctx: Context<RevokeProof>, source: ProofSource) -> Result<()> {
+        let decay_config = load_decay_config(&ctx.accounts.decay_config, ctx.program_id)?;
+        let individual_proof = &mut ctx.accounts.individual_proof;
+        let now = Clock::get()?.unix_timestamp;
 
-        individual_proof.is_revoked = true;
-        identity_nullifier_registry.is_burned = true;
+        individual_proof.check_source(source)?;
+        revoke_source(
+            &ctx.accounts.registry,
+            &ctx.accounts.scoring_config,
+            &mut ctx.accounts.user_proof,
+            individual_proof,
+            &mut ctx.accounts.identity_nullifier_registry,
+            ctx.accounts.user.key(),
+            now,
+            true,
+        )?;
 
         emit!(ProofRevoked {
             user: ctx.accounts.user.key(),
             proof_hash: individual_proof.proof_hash,
             source: individual_proof.source,
+            aggregated_score: ctx.accounts.user_proof.aggregated_score,
+            active_source_count: ctx.accounts.user_proof.active_source_count,
+            total_revocations: ctx.accounts.user_proof.total_revocations,
         });
 
+        if let Some(status_cache) = ctx.accounts.status_cache.as_mut() {
+            refresh_status_cache(
+                status_cache,
+                &ctx.accounts.user_proof,
+                &ctx.accounts.registry,
+                &ctx.accounts.scoring_config,
+                &decay_config,
+                is_banned(&ctx.accounts.user_status, ctx.program_id)?,
+                &[],
+                ctx.program_id,
+                now,
+            )?;
+        }
+
         Ok(())
     }
 
-    pub fn verify_proof(ctx: Context<VerifyProof>) -> Result<ProofStatus> {
-        let user_proof = &ctx.accounts.user_proof;
-        let registry = &ctx.accounts.registry;
-        let clock = Clock::get()?;
+    /// Revokes every active source of the signer and burns their identity
+    /// nullifiers, as `revoke_proof` does one at a time. `remaining_accounts` holds
+    /// an (`IndividualProof`, `IdentityNullifierRegistry`) pair, both writable, for
+    /// each active source; leaving one out fails the whole revocation.
+    pub fn revoke_all<'info>(ctx: Context<'_, '_, 'info, 'info, RevokeAll<'info>>) -> Result<()> {
+        let decay_config = load_decay_config(&ctx.accounts.decay_config, ctx.program_id)?;
+        let user = ctx.accounts.user.key();
+        let user_proof = &mut ctx.accounts.user_proof;
+        let now = Clock::get()?.unix_timestamp;
+
+        require!(
+            !ctx.remaining_accounts.is_empty() && ctx.remaining_accounts.len() % 2 == 0,
+            SolanIdError::InvalidProofAccounts
+        );
+        for (i, account) in ctx.remaining_accounts.iter().enumerate() {
+            require!(
+                ctx.remaining_accounts[..i]
+                    .iter()
+                    .all(|a| a.key() != account.key()),
+                SolanIdError::InvalidProofAccounts
+            );
+        }
 
-        let is_unexpired = clock.unix_timestamp <= user_proof.valid_until;
+        let mut revoked_count = 0u8;
+        for pair in ctx.remaining_accounts.chunks_exact(2) {
+            let mut individual_proof = Account::<IndividualProof>::try_from(&pair[0])?;
+            let source_seed = [individual_proof.source as u8];
+            let (expected_proof, _) = Pubkey::find_program_address(
+                &[b"individual_proof".as_ref(), user.as_ref(), &source_seed],
+                ctx.program_id,
+            );
+            require_keys_eq!(
+                pair[0].key(),
+                expected_proof,
+                SolanIdError::InvalidProofAccounts
+            );
+            let mut identity_nullifier_registry =
+                Account::<IdentityNullifierRegistry>::try_from(&pair[1])?;
+            let (expected_nullifier, _) = Pubkey::find_program_address(
+                &[
+                    b"identity_nullifier".as_ref(),
+                    &source_seed,
+                    individual_proof.identity_nullifier.as_ref(),
+                ],
+                ctx.program_id,
+            );
+            require_keys_eq!(
+                pair[1].key(),
+                expected_nullifier,
+                SolanIdError::InvalidProofAccounts
+            );
 
-        let is_valid = user_proof.user != Pubkey::default()
-            && user_proof.aggregated_score >= registry.min_score
-            && user_proof.aggregated_score > 0
-            && is_unexpired;
+            revoke_source(
+                &ctx.accounts.registry,
+                &ctx.accounts.scoring_config,
+                user_proof,
+                &mut individual_proof,
+                &mut identity_nullifier_registry,
+                user,
+                now,
+                true,
+            )?;
+            individual_proof.exit(ctx.program_id)?;
+            identity_nullifier_registry.exit(ctx.program_id)?;
+            revoked_count += 1;
+        }
 
-        Ok(ProofStatus {
-            is_verified: is_valid,
-            aggregated_score: user_proof.aggregated_score,
-            verified_at: user_proof.last_submission,
-        })
-    }
+        require!(
+            user_proof.active_source_count == 0,
+            SolanIdError::InvalidProofAccounts
+        );
+        user_proof.aggregated_score = 0;
+        user_proof.pre_cap_score = 0;
 
-    pub fn update_min_score(ctx: Context<UpdateMinScore>, new_min_score: u64) -> Result<()> {
-        let registry = &mut ctx.accounts.registry;
-        let old_score = registry.min_score;
-        registry.min_score = new_min_score;
-        emit!(MinScoreUpdated {
-            old_score,
-            new_score: new_min_score,
+        emit!(AllProofsRevoked {
+            user,
+            revoked_count,
         });
-        Ok(())
-    }
 
-    pub fn update_scoring_config(
-        ctx: Context<UpdateScoringConfig>,
-        source: ProofSource,
-        weight: u64,
-    ) -> Result<()> {
-        let scoring_config = &mut ctx.accounts.scoring_config;
-        scoring_config.weights[source as u8 as usize] = weight;
-        emit!(ScoringConfigUpdated { source, weight });
-        Ok(())
-    }
+        if let Some(status_cache) = ctx.accounts.status_cache.as_mut() {
+            refresh_status_cache(
+                status_cache,
+                &ctx.accounts.user_proof,
+                &ctx.accounts.registry,
+                &ctx.accounts.scoring_config,
+                &decay_config,
+                is_banned(&ctx.accounts.user_status, ctx.program_id)?,
+                &[],
+                ctx.program_id,
+                now,
+            )?;
+        }
 
-    pub fn initialize_scoring_config(ctx: Context<InitializeScoringConfig>) -> Result<()> {
-        let scoring_config = &mut ctx.accounts.scoring_config;
-        scoring_config.authority = ctx.accounts.authority.key();
-        scoring_config.weights = [100; 8];
-        scoring_config.bump = ctx.bumps.scoring_config;
         Ok(())
     }
 
-    pub fn update_registry_config(
-        ctx: Context<UpdateRegistryConfig>,
-        cooldown_period: i64,
-        diversity_bonus_percent: u8,
-        proof_ttl_seconds: i64,
-    ) -> Result<()> {
-        require!(cooldown_period >= 0, SolanIdError::InvalidConfig);
-        require!(diversity_bonus_percent <= 100, SolanIdError::InvalidConfig);
-        require!(proof_ttl_seconds > 0, SolanIdError::InvalidConfig);
+    /// Revokes a source without burning its identity nullifier, so a legitimately
+    /// rotated identity can be re-claimed once the source's reclaim cooldown elapses.
+    /// Use `revoke_proof` to burn the identity permanently.
+    pub fn soft_revoke(ctx: Context<RevokeProof>, _source: ProofSource) -> Result<()> {
+        let decay_config = load_decay_config(&ctx.accounts.decay_config, ctx.program_id)?;
+        let individual_proof = &mut ctx.accounts.individual_proof;
+        let now = Clock::get()?.unix_timestamp;
+
+        revoke_source(
+            &ctx.accounts.registry,
+            &ctx.accounts.scoring_config,
+            &mut ctx.accounts.user_proof,
+            individual_proof,
+            &mut ctx.accounts.identity_nullifier_registry,
+            ctx.accounts.user.key(),
+            now,
+            false,
+        )?;
+
+        emit!(SourceSoftRevoked {
+            user: ctx.accounts.user.key(),
+            proof_hash: individual_proof.proof_hash,
+            source: individual_proof.source,
+            revoked_at: individual_proof.revoked_at,
+        });
+
+        if let Some(status_cache) = ctx.accounts.status_cache.as_mut() {
+            refresh_status_cache(
+                status_cache,
+                &ctx.accounts.user_proof,
+                &ctx.accounts.registry,
+                &ctx.accounts.scoring_config,
+                &decay_config,
+                is_banned(&ctx.accounts.user_status, ctx.program_id)?,
+                &[],
+                ctx.program_id,
+                now,
+            )?;
+        }
 
-        let registry = &mut ctx.accounts.registry;
-        registry.cooldown_period = cooldown_period;
-        registry.diversity_bonus_percent = diversity_bonus_percent;
-        registry.proof_ttl_seconds = proof_ttl_seconds;
         Ok(())
     }
 
-    pub fn initiate_verifier_rotation(
-        ctx: Context<InitiateVerifierRotation>,
-        new_verifier_authority: Pubkey,
-        delay_seconds: i64,
-    ) -> Result<()> {
-        require!(
-            new_verifier_authority != Pubkey::default(),
-            SolanIdError::InvalidConfig
-        );
-        require!(delay_seconds >= 1, SolanIdError::InvalidConfig);
+    /// Lets the registry authority revoke a fraudulently obtained proof, e.g. after
+    /// a verifier key compromise, without the user's signature.
+    pub fn admin_revoke_proof(ctx: Context<AdminRevokeProof>, _source: ProofSource) -> Result<()> {
+        let individual_proof = &mut ctx.accounts.individual_proof;
 
-        let registry = &mut ctx.accounts.registry;
-        let now = Clock::get()?.unix_timestamp;
-        registry.pending_verifier_authority = new_verifier_authority;
-        registry.verifier_rotation_available_at = now
-            .checked_add(delay_seconds)
-            .ok_or(SolanIdError::Overflow)?;
+        revoke_source(
+            &ctx.accounts.registry,
+            &ctx.accounts.scoring_config,
+            &mut ctx.accounts.user_proof,
+            individual_proof,
+            &mut ctx.accounts.identity_nullifier_registry,
+            ctx.accounts.user.key(),
+            Clock::get()?.unix_timestamp,
+            true,
+        )?;
+        // The revocation resolves any pending review of this identity.
+        if ctx.accounts.identity_nullifier_registry.flagged {
+            clear_identity_flag(
+                &mut ctx.accounts.identity_nullifier_registry,
+                &mut ctx.accounts.user_proof,
+            );
+            emit!(IdentityFlagCleared {
+                authority: ctx.accounts.authority.key(),
+                user: ctx.accounts.user.key(),
+                source: individual_proof.source,
+                identity_nullifier: individual_proof.identity_nullifier,
+            });
+        }
 
-        emit!(VerifierRotationInitiated {
-            current_verifier: registry.verifier_authority,
-            pending_verifier: registry.pending_verifier_authority,
-            activate_at: registry.verifier_rotation_available_at,
+        emit!(ProofForceRevoked {
+            authority: ctx.accounts.authority.key(),
+            user: ctx.accounts.user.key(),
+            proof_hash: individual_proof.proof_hash,
+            source: individual_proof.source,
         });
 
         Ok(())
     }
 
-    pub fn finalize_verifier_rotation(ctx: Context<FinalizeVerifierRotation>) -> Result<()> {
-        let registry = &mut ctx.accounts.registry;
-        require!(
-            registry.pending_verifier_authority != Pubkey::default(),
-            SolanIdError::NoVerifierRotationPending
-        );
-
-        let now = Clock::get()?.unix_timestamp;
-        require!(
-            now >= registry.verifier_rotation_available_at,
-            SolanIdError::VerifierRotationNotReady
+    /// Lets the verifier force a revocation when a source deplatforms an identity after
+    /// its proof was issued, e.g. a suspended Twitter account. The verifier signs a
+    /// revocation message over the user, source and nullifier in the preceding ed25519
+    /// instruction; anyone may relay it. Burns the nullifier like `revoke_proof`.
+    pub fn revoke_proof_by_verifier(
+        ctx: Context<RevokeProofByVerifier>,
+        source: ProofSource,
+        attestation_nonce: u64,
+        timestamp: i64,
+    ) -> Result<()> {
+        let registry = &ctx.accounts.registry;
+        let individual_proof = &mut ctx.accounts.individual_proof;
+        let user = ctx.accounts.user.key();
+        let clock = Clock::get()?;
+
+        consume_attestation(
+            registry,
+            &mut ctx.accounts.user_proof,
+            ctx.accounts.attestation_nonce_registry.as_mut(),
+            ctx.bumps.attestation_nonce_registry,
+            attestation_nonce,
+            timestamp,
+            clock.unix_timestamp,
+        )?;
+
+        let instructions_sysvar = ctx.accounts.instructions_sysvar.to_account_info();
+        let current_index = attested_instruction_index(&instructions_sysvar)?;
+        let expected_message = build_revocation_message(
+            ctx.program_id,
+            &registry.key(),
+            &user,
+            source,
+            &individual_proof.identity_nullifier,
+            attestation_nonce,
+            timestamp,
+        );
+        verify_attestation_message(
+            &instructions_sysvar,
+            current_index,
+            &expected_message,
+            registry,
+        )?;
+
+        revoke_source(
+            registry,
+            &ctx.accounts.scoring_config,
+            &mut ctx.accounts.user_proof,
+            individual_proof,
+            &mut ctx.accounts.identity_nullifier_registry,
+            user,
+            clock.unix_timestamp,
+            true,
+        )?;
+
+        emit!(ProofRevokedByVerifier {
+            user,
+            proof_hash: individual_proof.proof_hash,
+            source,
+            identity_nullifier: individual_proof.identity_nullifier,
+            aggregated_score: ctx.accounts.user_proof.aggregated_score,
+        });
+
+        Ok(())
+    }
+
+    /// Lowers an active source's base score on a fresh verifier attestation, for a
+    /// proof whose confidence dropped without being invalidated. The source stays
+    /// active and its nullifier is untouched; raising a score goes through
+    /// `submit_proof`.
+    pub fn downgrade_proof(
+        ctx: Context<DowngradeProof>,
+        source: ProofSource,
+        base_score: u64,
+        attestation_nonce: u64,
+        timestamp: i64,
+    ) -> Result<()> {
+        let registry = &ctx.accounts.registry;
+        let user_proof = &mut ctx.accounts.user_proof;
+        let individual_proof = &mut ctx.accounts.individual_proof;
+        let user = ctx.accounts.user.key();
+        let clock = Clock::get()?;
+
+        require!(individual_proof.user == user, SolanIdError::Unauthorized);
+        require!(
+            counts_toward_score(individual_proof, user_proof.expiry_epoch),
+            SolanIdError::ProofAlreadyRevoked
+        );
+        require!(
+            base_score <= individual_proof.base_score,
+            SolanIdError::ScoreIncreaseNotAllowed
+        );
+        consume_attestation(
+            registry,
+            user_proof,
+            ctx.accounts.attestation_nonce_registry.as_mut(),
+            ctx.bumps.attestation_nonce_registry,
+            attestation_nonce,
+            timestamp,
+            clock.unix_timestamp,
+        )?;
+
+        let instructions_sysvar = ctx.accounts.instructions_sysvar.to_account_info();
+        let current_index = attested_instruction_index(&instructions_sysvar)?;
+        let expected_message = build_downgrade_message(
+            ctx.program_id,
+            &registry.key(),
+            &user,
+            source,
+            &individual_proof.identity_nullifier,
+            base_score,
+            attestation_nonce,
+            timestamp,
+        );
+        verify_attestation_message(
+            &instructions_sysvar,
+            current_index,
+            &expected_message,
+            registry,
+        )?;
+
+        let scoring_config = &ctx.accounts.scoring_config;
+        let mut weighted_score = apply_trust_multiplier(
+            weighted_base_score(
+                base_score,
+                source,
+                individual_proof.weight_override,
+                registry,
+                scoring_config,
+            )?,
+            individual_proof.trust_multiplier,
+        )?;
+        if individual_proof.campaign_bonus_applied {
+            weighted_score = scale_score(
+                weighted_score,
+                100 + registry.campaign_bonus_percent as u64,
+                100,
+            )?;
+        }
+        weighted_score = weighted_score.min(registry.max_weighted_score);
+
+        // Scale the recorded contribution rather than re-deriving it, so the recency
+        // factor it was folded in with carries over and the aggregate drops by exactly
+        // the difference.
+        let contributed_score = if individual_proof.weighted_score == 0 {
+            0
+        } else {
+            scale_score(
+                individual_proof.contributed_score,
+                weighted_score.min(individual_proof.weighted_score),
+                individual_proof.weighted_score,
+            )?
+        };
+
+        let new_base_aggregated_score = strip_score_bonuses(user_proof, registry)?
+            .saturating_sub(individual_proof.contributed_score - contributed_score);
+        set_source_score(user_proof, individual_proof.source, contributed_score)?;
+        apply_score_bonuses(
+            user_proof,
+            registry,
+            scoring_config,
+            new_base_aggregated_score,
+            clock.unix_timestamp,
+        )?;
+
+        let previous_base_score = individual_proof.base_score;
+        individual_proof.base_score = base_score;
+        individual_proof.weighted_score = weighted_score;
+        individual_proof.contributed_score = contributed_score;
+
+        emit!(ProofDowngraded {
+            user,
+            source,
+            previous_base_score,
+            base_score,
+            contributed_score,
+            aggregated_score: user_proof.aggregated_score,
+        });
+
+        Ok(())
+    }
+
+    /// Binds an identity to the wallet that claimed it, for high-assurance sources that
+    /// must not be traded. `migrate_identity` rejects a soulbound nullifier. The
+    /// binding is permanent.
+    pub fn mark_identity_soulbound(
+        ctx: Context<MarkIdentitySoulbound>,
+        source: ProofSource,
+        attestation_nonce: u64,
+        timestamp: i64,
+    ) -> Result<()> {
+        let registry = &ctx.accounts.registry;
+        let identity_nullifier_registry = &mut ctx.accounts.identity_nullifier_registry;
+        let user = ctx.accounts.user.key();
+        let clock = Clock::get()?;
+
+        require!(
+            identity_nullifier_registry.claimed_by == user,
+            SolanIdError::Unauthorized
+        );
+        consume_attestation(
+            registry,
+            &mut ctx.accounts.user_proof,
+            ctx.accounts.attestation_nonce_registry.as_mut(),
+            ctx.bumps.attestation_nonce_registry,
+            attestation_nonce,
+            timestamp,
+            clock.unix_timestamp,
+        )?;
+
+        let instructions_sysvar = ctx.accounts.instructions_sysvar.to_account_info();
+        let current_index = attested_instruction_index(&instructions_sysvar)?;
+        let expected_message = build_soulbound_message(
+            ctx.program_id,
+            &registry.key(),
+            &user,
+            source,
+            &identity_nullifier_registry.nullifier,
+            attestation_nonce,
+            timestamp,
+        );
+        verify_attestation_message(
+            &instructions_sysvar,
+            current_index,
+            &expected_message,
+            registry,
+        )?;
+
+        identity_nullifier_registry.soulbound = true;
+
+        emit!(IdentitySoulbound {
+            user,
+            source,
+            identity_nullifier: identity_nullifier_registry.nullifier,
+        });
+
+        Ok(())
+    }
+
+    /// Links one of the user's active sources to a verifier-attested person id, for
+    /// sources that can vouch for a unique human behind distinct nullifiers. Sources
+    /// linked to the same person stop stacking the diversity bonus, and the person id
+    /// cannot be linked from another wallet.
+    pub fn link_person_id(
+        ctx: Context<LinkPersonId>,
+        source: ProofSource,
+        person_id_hash: [u8; 32],
+        attestation_nonce: u64,
+        timestamp: i64,
+    ) -> Result<()> {
+        let registry = &ctx.accounts.registry;
+        let user_proof = &mut ctx.accounts.user_proof;
+        let individual_proof = &ctx.accounts.individual_proof;
+        let person_registry = &mut ctx.accounts.person_registry;
+        let user = ctx.accounts.user.key();
+        let clock = Clock::get()?;
+
+        require!(registry.person_binding, SolanIdError::PersonBindingDisabled);
+        require!(person_id_hash != [0u8; 32], SolanIdError::InvalidPersonId);
+        require!(
+            counts_toward_score(individual_proof, user_proof.expiry_epoch),
+            SolanIdError::ProofAlreadyRevoked
+        );
+        require!(
+            person_registry.claimed_by == Pubkey::default() || person_registry.claimed_by == user,
+            SolanIdError::DuplicateIdentityClaim
+        );
+        consume_attestation(
+            registry,
+            user_proof,
+            ctx.accounts.attestation_nonce_registry.as_mut(),
+            ctx.bumps.attestation_nonce_registry,
+            attestation_nonce,
+            timestamp,
+            clock.unix_timestamp,
+        )?;
+
+        let instructions_sysvar = ctx.accounts.instructions_sysvar.to_account_info();
+        let current_index = attested_instruction_index(&instructions_sysvar)?;
+        let expected_message = build_person_link_message(
+            ctx.program_id,
+            &registry.key(),
+            &user,
+            source,
+            &individual_proof.identity_nullifier,
+            &person_id_hash,
+            attestation_nonce,
+            timestamp,
+        );
+        verify_attestation_message(
+            &instructions_sysvar,
+            current_index,
+            &expected_message,
+            registry,
+        )?;
+
+        if person_registry.claimed_by == Pubkey::default() {
+            person_registry.person_id_hash = person_id_hash;
+            person_registry.claimed_by = user;
+            person_registry.claimed_at = clock.unix_timestamp;
+            person_registry.bump = ctx.bumps.person_registry;
+            person_registry.version = ACCOUNT_VERSION;
+        }
+
+        let base_aggregated_score = strip_score_bonuses(user_proof, registry)?;
+        user_proof.person_sources_mask |= source_bit(source);
+        apply_score_bonuses(
+            user_proof,
+            registry,
+            &ctx.accounts.scoring_config,
+            base_aggregated_score,
+            clock.unix_timestamp,
+        )?;
+
+        emit!(PersonLinked {
+            user,
+            source,
+            person_id_hash,
+            aggregated_score: user_proof.aggregated_score,
+        });
+
+        Ok(())
+    }
+
+    /// Records the verifier-attested time a wallet was first seen active, which the
+    /// wallet-age bonus is measured from, and re-applies the user's bonuses.
+    pub fn attest_wallet_age(
+        ctx: Context<AttestWalletAge>,
+        wallet_first_seen: i64,
+        attestation_nonce: u64,
+        timestamp: i64,
+    ) -> Result<()> {
+        let registry = &ctx.accounts.registry;
+        let user_proof = &mut ctx.accounts.user_proof;
+        let user = ctx.accounts.user.key();
+        let clock = Clock::get()?;
+
+        require!(
+            wallet_first_seen > 0 && wallet_first_seen <= clock.unix_timestamp,
+            SolanIdError::InvalidWalletFirstSeen
+        );
+        consume_attestation(
+            registry,
+            user_proof,
+            ctx.accounts.attestation_nonce_registry.as_mut(),
+            ctx.bumps.attestation_nonce_registry,
+            attestation_nonce,
+            timestamp,
+            clock.unix_timestamp,
+        )?;
+
+        let instructions_sysvar = ctx.accounts.instructions_sysvar.to_account_info();
+        let current_index = attested_instruction_index(&instructions_sysvar)?;
+        let expected_message = build_wallet_age_message(
+            ctx.program_id,
+            &registry.key(),
+            &user,
+            wallet_first_seen,
+            attestation_nonce,
+            timestamp,
+        );
+        verify_attestation_message(
+            &instructions_sysvar,
+            current_index,
+            &expected_message,
+            registry,
+        )?;
+
+        let base_aggregated_score = strip_score_bonuses(user_proof, registry)?;
+        user_proof.wallet_first_seen = wallet_first_seen;
+        apply_score_bonuses(
+            user_proof,
+            registry,
+            &ctx.accounts.scoring_config,
+            base_aggregated_score,
+            clock.unix_timestamp,
+        )?;
+
+        emit!(WalletAgeAttested {
+            user,
+            wallet_first_seen,
+            aggregated_score: user_proof.aggregated_score,
+        });
+
+        Ok(())
+    }
+
+    /// Bans an identity and revokes whatever the claiming wallet still holds from it,
+    /// so the score drops now rather than only blocking future submissions.
+    /// `remaining_accounts` holds the claiming wallet's `IndividualProof` PDAs
+    /// (writable); the ones that reference this nullifier and are still active are
+    /// revoked, the rest are left alone.
+    pub fn ban_and_revoke<'info>(
+        ctx: Context<'_, '_, 'info, 'info, BanAndRevoke<'info>>,
+        _source: ProofSource,
+        _identity_nullifier: [u8; 32],
+    ) -> Result<()> {
+        let registry = &ctx.accounts.registry;
+        let user_proof = &mut ctx.accounts.user_proof;
+        let identity_nullifier_registry = &mut ctx.accounts.identity_nullifier_registry;
+        let claimed_by = identity_nullifier_registry.claimed_by;
+        let now = Clock::get()?.unix_timestamp;
+
+        let mut revoked_count = 0u8;
+        for (i, account) in ctx.remaining_accounts.iter().enumerate() {
+            require!(
+                ctx.remaining_accounts[..i]
+                    .iter()
+                    .all(|a| a.key() != account.key()),
+                SolanIdError::InvalidProofAccounts
+            );
+            let mut individual_proof = Account::<IndividualProof>::try_from(account)?;
+            let (expected_address, _) = Pubkey::find_program_address(
+                &[
+                    b"individual_proof".as_ref(),
+                    claimed_by.as_ref(),
+                    &[individual_proof.source as u8],
+                ],
+                ctx.program_id,
+            );
+            require_keys_eq!(
+                account.key(),
+                expected_address,
+                SolanIdError::InvalidProofAccounts
+            );
+            if individual_proof.is_revoked
+                || individual_proof.identity_nullifier != identity_nullifier_registry.nullifier
+            {
+                continue;
+            }
+
+            revoke_source(
+                registry,
+                &ctx.accounts.scoring_config,
+                user_proof,
+                &mut individual_proof,
+                identity_nullifier_registry,
+                claimed_by,
+                now,
+                true,
+            )?;
+            individual_proof.exit(ctx.program_id)?;
+            revoked_count += 1;
+        }
+        identity_nullifier_registry.is_burned = true;
+
+        emit!(IdentityBanned {
+            authority: ctx.accounts.authority.key(),
+            nullifier: identity_nullifier_registry.nullifier,
+            source: identity_nullifier_registry.source,
+            claimed_by,
+            revoked_count,
+        });
+
+        Ok(())
+    }
+
+    /// Returns the rent of a revoked `IndividualProof` to its owner. The score was
+    /// already adjusted by `revoke_proof`, and the identity nullifier registry is
+    /// left in place and burned, so the identity cannot be re-claimed after closing.
+    /// A soft-revoked proof holds the reclaim cooldown, so it stays open until the
+    /// cooldown elapses.
+    pub fn close_revoked_proof(ctx: Context<CloseRevokedProof>, source: ProofSource) -> Result<()> {
+        let individual_proof = &ctx.accounts.individual_proof;
+
+        require!(
+            individual_proof.user == ctx.accounts.user.key(),
+            SolanIdError::Unauthorized
+        );
+        require!(individual_proof.is_revoked, SolanIdError::ProofNotRevoked);
+        let reclaim_cooldowns = &ctx.accounts.scoring_config.reclaim_cooldowns;
+        require!(
+            Clock::get()?.unix_timestamp
+                >= individual_proof
+                    .revoked_at
+                    .checked_add(reclaim_cooldowns[source.as_index(reclaim_cooldowns)?])
+                    .ok_or(SolanIdError::Overflow)?,
+            SolanIdError::ReclaimCooldownActive
+        );
+
+        Ok(())
+    }
+
+    /// Grows a program account written before its newest trailing fields existed,
+    /// zero-fills them and stamps `ACCOUNT_VERSION`. Accounts from before per-source
+    /// storage was widened to `MAX_PROOF_SOURCES` are rewritten into the new layout.
+    /// Current accounts are left as they are, and accounts from a newer layout are
+    /// rejected.
+    pub fn migrate_account(ctx: Context<MigrateAccount>) -> Result<()> {
+        let accounts = &ctx.accounts;
+        require!(
+            accounts.account.owner == ctx.program_id,
+            SolanIdError::InvalidMigrationAccount
+        );
+        let discriminator = accounts
+            .account
+            .try_borrow_data()?
+            .get(..8)
+            .map(<[u8]>::to_vec)
+            .ok_or(SolanIdError::InvalidMigrationAccount)?;
+
+        let from_version = match discriminator.as_slice() {
+            d if d == Registry::DISCRIMINATOR
+                && accounts.account.data_len() == 8 + BaselineRegistry::INIT_SPACE =>
+            {
+                migrate_widened::<BaselineRegistry, Registry>(accounts)?
+            }
+            d if d == Registry::DISCRIMINATOR => {
+                migrate_widened::<LegacyRegistry, Registry>(accounts)?
+            }
+            d if d == UserProof::DISCRIMINATOR => {
+                migrate_widened::<LegacyUserProof, UserProof>(accounts)?
+            }
+            d if d == IndividualProof::DISCRIMINATOR => {
+                migrate_versioned::<IndividualProof>(accounts)?
+            }
+            d if d == ProofHashRegistry::DISCRIMINATOR => {
+                migrate_versioned::<ProofHashRegistry>(accounts)?
+            }
+            d if d == ScoringConfig::DISCRIMINATOR => {
+                migrate_widened::<LegacyScoringConfig, ScoringConfig>(accounts)?
+            }
+            d if d == DecayConfig::DISCRIMINATOR => {
+                migrate_widened::<LegacyDecayConfig, DecayConfig>(accounts)?
+            }
+            d if d == UserStatus::DISCRIMINATOR => migrate_versioned::<UserStatus>(accounts)?,
+            d if d == IdentityNullifierRegistry::DISCRIMINATOR => {
+                migrate_versioned::<IdentityNullifierRegistry>(accounts)?
+            }
+            d if d == AttestationNonceRegistry::DISCRIMINATOR => {
+                migrate_versioned::<AttestationNonceRegistry>(accounts)?
+            }
+            d if d == ScoreSnapshot::DISCRIMINATOR => migrate_versioned::<ScoreSnapshot>(accounts)?,
+            d if d == PersonRegistry::DISCRIMINATOR => {
+                migrate_versioned::<PersonRegistry>(accounts)?
+            }
+            d if d == StatusCache::DISCRIMINATOR => migrate_versioned::<StatusCache>(accounts)?,
+            // `ConfigSnapshot` embeds the registry and scoring config, so new fields
+            // land mid-account rather than at the end; snapshots are never rewritten.
+            _ => return err!(SolanIdError::InvalidMigrationAccount),
+        };
+
+        if from_version != ACCOUNT_VERSION {
+            emit!(AccountMigrated {
+                account: accounts.account.key(),
+                from_version,
+                to_version: ACCOUNT_VERSION,
+            });
+        }
+
+        Ok(())
+    }
+
+    /// Upgrades an `IndividualProof` written with the original account layout in
+    /// place, keeping its score so the owner does not have to re-verify.
+    pub fn import_legacy_proof(ctx: Context<ImportLegacyProof>, source: ProofSource) -> Result<()> {
+        let individual_proof = ctx.accounts.individual_proof.to_account_info();
+        let user = &ctx.accounts.user;
+
+        require!(
+            individual_proof.owner == ctx.program_id,
+            SolanIdError::InvalidLegacyAccount
+        );
+
+        let legacy = {
+            let data = individual_proof.try_borrow_data()?;
+            require!(
+                data.len() == 8 + LegacyIndividualProof::INIT_SPACE
+                    && data[..8] == *IndividualProof::DISCRIMINATOR,
+                SolanIdError::InvalidLegacyAccount
+            );
+            LegacyIndividualProof::deserialize(&mut &data[8..])
+                .map_err(|_| error!(SolanIdError::InvalidLegacyAccount))?
+        };
+
+        require!(legacy.user == user.key(), SolanIdError::Unauthorized);
+        require!(legacy.source == source, SolanIdError::SourcePayloadMismatch);
+
+        let new_len = 8 + IndividualProof::INIT_SPACE;
+        let rent_shortfall = Rent::get()?
+            .minimum_balance(new_len)
+            .saturating_sub(individual_proof.lamports());
+        if rent_shortfall > 0 {
+            system_program::transfer(
+                CpiContext::new(
+                    ctx.accounts.system_program.to_account_info(),
+                    system_program::Transfer {
+                        from: user.to_account_info(),
+                        to: individual_proof.clone(),
+                    },
+                ),
+                rent_shortfall,
+            )?;
+        }
+        individual_proof.resize(new_len)?;
+
+        let upgraded = IndividualProof {
+            user: legacy.user,
+            proof_hash: legacy.proof_hash,
+            base_score: legacy.base_score,
+            weighted_score: legacy.weighted_score,
+            source: legacy.source,
+            identity_nullifier: legacy.identity_nullifier,
+            proof_data: legacy.proof_data,
+            verified_at: legacy.verified_at,
+            is_revoked: legacy.is_revoked,
+            bump: legacy.bump,
+            migrated: true,
+            campaign_bonus_applied: false,
+            first_verified_at: legacy.verified_at,
+            revoked_at: 0,
+            // Legacy submissions were folded in fresh, at their full weighted score.
+            contributed_score: if legacy.is_revoked {
+                0
+            } else {
+                legacy.weighted_score
+            },
+            version: ACCOUNT_VERSION,
+            expiry_epoch: 0,
+            expires_at: 0,
+            trust_multiplier: NEUTRAL_TRUST_MULTIPLIER,
+            weight_override: 0,
+        };
+        upgraded.try_serialize(&mut &mut individual_proof.try_borrow_mut_data()?[..])?;
+
+        emit!(LegacyProofImported {
+            user: user.key(),
+            source,
+        });
+
+        Ok(())
+    }
+
+    /// Returns the rent of a used attestation nonce to the wallet that submitted it once
+    /// the registry's nonce retention window has passed. Callable by anyone.
+    pub fn close_attestation_nonce(
+        ctx: Context<CloseAttestationNonce>,
+        _attestation_nonce: u64,
+    ) -> Result<()> {
+        let registry = &ctx.accounts.registry;
+        require!(
+            registry.nonce_retention_seconds > 0,
+            SolanIdError::NonceRetentionActive
+        );
+        require!(
+            Clock::get()?.unix_timestamp
+                >= ctx
+                    .accounts
+                    .attestation_nonce_registry
+                    .used_at
+                    .checked_add(registry.nonce_retention_seconds)
+                    .ok_or(SolanIdError::Overflow)?,
+            SolanIdError::NonceRetentionActive
+        );
+
+        Ok(())
+    }
+
+    /// Moves a claimed identity to a new wallet. Both PDAs are re-derived under
+    /// `new_owner`; the old ones are closed, with rent going to `new_owner` since the
+    /// old wallet is assumed lost. The old `UserProof` is only closed once it has no
+    /// remaining active sources.
+    pub fn migrate_identity(
+        ctx: Context<MigrateIdentity>,
+        source: ProofSource,
+        attestation_nonce: u64,
+        timestamp: i64,
+    ) -> Result<()> {
+        let decay_config = load_decay_config(&ctx.accounts.decay_config, ctx.program_id)?;
+        let registry = &mut ctx.accounts.registry;
+        let identity_nullifier_registry = &mut ctx.accounts.identity_nullifier_registry;
+        let old_user_proof = &mut ctx.accounts.old_user_proof;
+        let old_individual_proof = &ctx.accounts.old_individual_proof;
+        let new_user_proof = &mut ctx.accounts.new_user_proof;
+        let new_individual_proof = &mut ctx.accounts.new_individual_proof;
+        let old_owner = ctx.accounts.old_owner.key();
+        let new_owner = ctx.accounts.new_owner.key();
+        let clock = Clock::get()?;
+
+        check_identity_move(
+            identity_nullifier_registry,
+            old_user_proof,
+            old_individual_proof,
+            old_owner,
+            new_owner,
+        )?;
+
+        consume_attestation(
+            registry,
+            old_user_proof,
+            ctx.accounts.attestation_nonce_registry.as_deref_mut(),
+            ctx.bumps.attestation_nonce_registry,
+            attestation_nonce,
+            timestamp,
+            clock.unix_timestamp,
+        )?;
+
+        let instructions_sysvar = ctx.accounts.instructions_sysvar.to_account_info();
+        let current_index = attested_instruction_index(&instructions_sysvar)?;
+        let expected_message = build_migration_message(
+            ctx.program_id,
+            &registry.key(),
+            &old_owner,
+            &new_owner,
+            source,
+            &old_individual_proof.identity_nullifier,
+            attestation_nonce,
+            timestamp,
+        );
+        verify_attestation_message(
+            &instructions_sysvar,
+            current_index,
+            &expected_message,
+            registry,
+        )?;
+
+        move_identity(
+            registry,
+            &ctx.accounts.scoring_config,
+            &decay_config,
+            identity_nullifier_registry,
+            old_user_proof,
+            old_individual_proof,
+            new_user_proof,
+            new_individual_proof,
+            [ctx.bumps.new_user_proof, ctx.bumps.new_individual_proof],
+            ctx.accounts.new_owner.to_account_info(),
+            clock.unix_timestamp,
+        )?;
+
+        emit!(IdentityMigrated {
+            nullifier: identity_nullifier_registry.nullifier,
+            old_owner,
+            new_owner,
+        });
+
+        Ok(())
+    }
+
+    /// Hands a non-soulbound identity to a new wallet with both wallets signing, for
+    /// a consensual transfer rather than recovery. Unlike `migrate_identity` no
+    /// verifier attestation is needed. The PDAs are re-derived under `new_owner` and
+    /// the old ones closed the same way.
+    pub fn transfer_identity(ctx: Context<TransferIdentity>, _source: ProofSource) -> Result<()> {
+        let decay_config = load_decay_config(&ctx.accounts.decay_config, ctx.program_id)?;
+        let registry = &mut ctx.accounts.registry;
+        let identity_nullifier_registry = &mut ctx.accounts.identity_nullifier_registry;
+        let old_individual_proof = &ctx.accounts.old_individual_proof;
+        let old_owner = ctx.accounts.old_owner.key();
+        let new_owner = ctx.accounts.new_owner.key();
+
+        require!(!registry.paused, SolanIdError::RegistryPaused);
+        check_identity_move(
+            identity_nullifier_registry,
+            &ctx.accounts.old_user_proof,
+            old_individual_proof,
+            old_owner,
+            new_owner,
+        )?;
+
+        move_identity(
+            registry,
+            &ctx.accounts.scoring_config,
+            &decay_config,
+            identity_nullifier_registry,
+            &mut ctx.accounts.old_user_proof,
+            old_individual_proof,
+            &mut ctx.accounts.new_user_proof,
+            &mut ctx.accounts.new_individual_proof,
+            [ctx.bumps.new_user_proof, ctx.bumps.new_individual_proof],
+            ctx.accounts.new_owner.to_account_info(),
+            Clock::get()?.unix_timestamp,
+        )?;
+
+        emit!(IdentityTransferred {
+            nullifier: identity_nullifier_registry.nullifier,
+            old_owner,
+            new_owner,
+        });
+
+        Ok(())
+    }
+
+    /// Unknown wallets are reported as unverified with a zero score rather than
+    /// failing to load, so gates can query any wallet.
+    pub fn verify_proof<'info>(
+        ctx: Context<'_, '_, 'info, 'info, VerifyProof<'info>>,
+    ) -> Result<ProofStatus> {
+        let decay_config = load_decay_config(&ctx.accounts.decay_config, ctx.program_id)?;
+        let Some(user_proof) = load_user_proof(&ctx.accounts.user_proof, ctx.program_id)? else {
+            return Ok(ProofStatus::default());
+        };
+        proof_status(
+            &user_proof,
+            &ctx.accounts.registry,
+            &ctx.accounts.scoring_config,
+            &decay_config,
+            is_banned(&ctx.accounts.user_status, ctx.program_id)?,
+            ctx.accounts.registry.min_score,
+            ctx.remaining_accounts,
+            ctx.program_id,
+            Clock::get()?.unix_timestamp,
+        )
+    }
+
+    /// Same checks as `verify_proof`, but against the caller's `min_score` rather
+    /// than the registry's, so one registry can back gates with different bars. The
+    /// score must still be non-zero and unexpired.
+    pub fn verify_proof_with_threshold<'info>(
+        ctx: Context<'_, '_, 'info, 'info, VerifyProof<'info>>,
+        min_score: u64,
+    ) -> Result<ProofStatus> {
+        let decay_config = load_decay_config(&ctx.accounts.decay_config, ctx.program_id)?;
+        let Some(user_proof) = load_user_proof(&ctx.accounts.user_proof, ctx.program_id)? else {
+            return Ok(ProofStatus::default());
+        };
+        proof_status(
+            &user_proof,
+            &ctx.accounts.registry,
+            &ctx.accounts.scoring_config,
+            &decay_config,
+            is_banned(&ctx.accounts.user_status, ctx.program_id)?,
+            min_score,
+            ctx.remaining_accounts,
+            ctx.program_id,
+            Clock::get()?.unix_timestamp,
+        )
+    }
+
+    pub fn verify_and_mark<'info>(
+        ctx: Context<'_, '_, 'info, 'info, VerifyAndMark<'info>>,
+    ) -> Result<ProofStatus> {
+        let decay_config = load_decay_config(&ctx.accounts.decay_config, ctx.program_id)?;
+        let status = proof_status(
+            &ctx.accounts.user_proof,
+            &ctx.accounts.registry,
+            &ctx.accounts.scoring_config,
+            &decay_config,
+            is_banned(&ctx.accounts.user_status, ctx.program_id)?,
+            ctx.accounts.registry.min_score,
+            ctx.remaining_accounts,
+            ctx.program_id,
+            Clock::get()?.unix_timestamp,
+        )?;
+
+        let user_proof = &mut ctx.accounts.user_proof;
+        if status.is_verified && !user_proof.marked {
+            user_proof.marked = true;
+            emit!(UserMarked {
+                user: user_proof.user,
+                aggregated_score: user_proof.aggregated_score,
+            });
+        }
+
+        Ok(status)
+    }
+
+    /// Checks many wallets in one call for airdrop-style eligibility. `remaining_accounts`
+    /// holds each wallet's `UserProof` and `UserStatus` PDAs in pairs, and bit `i` of
+    /// the result is set when wallet `i` passes `verify_proof`'s checks against the
+    /// registry's `min_score`. Scores are read as stored, without decaying them against
+    /// the wallet's `IndividualProof` accounts. A wallet that never submitted reports
+    /// false rather than failing the batch.
+    pub fn verify_proofs_batch<'info>(
+        ctx: Context<'_, '_, 'info, 'info, VerifyProofsBatch<'info>>,
+    ) -> Result<u32> {
+        let accounts = ctx.remaining_accounts;
+        require!(
+            accounts.len() % 2 == 0 && accounts.len() / 2 <= MAX_VERIFY_BATCH_SIZE,
+            SolanIdError::InvalidProofAccounts
+        );
+        let registry = &ctx.accounts.registry;
+        let now = Clock::get()?.unix_timestamp;
+
+        let mut verified_mask = 0u32;
+        for (i, pair) in accounts.chunks_exact(2).enumerate() {
+            let Some(user_proof) = load_user_proof(&pair[0], ctx.program_id)? else {
+                continue;
+            };
+            let (expected_user_status, _) = Pubkey::find_program_address(
+                &[b"user_status".as_ref(), user_proof.user.as_ref()],
+                ctx.program_id,
+            );
+            require_keys_eq!(
+                pair[1].key(),
+                expected_user_status,
+                SolanIdError::InvalidProofAccounts
+            );
+            let banned = is_banned(&pair[1], ctx.program_id)?;
+            if verification_reason(&user_proof, registry, registry.min_score, now, banned)
+                == VerificationReason::Ok
+            {
+                verified_mask |= 1 << i;
+            }
+        }
+        Ok(verified_mask)
+    }
+
+    /// Buckets the user's live score into the registry's tiers, decaying against the
+    /// clock when the user's active `IndividualProof` accounts are passed as in
+    /// `verify_proof`. Banned and private users, and registries without tiers, get
+    /// `ReputationTier::None`.
+    pub fn get_tier<'info>(
+        ctx: Context<'_, '_, 'info, 'info, VerifyProof<'info>>,
+    ) -> Result<ReputationTier> {
+        let decay_config = load_decay_config(&ctx.accounts.decay_config, ctx.program_id)?;
+        let registry = &ctx.accounts.registry;
+        let Some(user_proof) = load_user_proof(&ctx.accounts.user_proof, ctx.program_id)? else {
+            return Ok(ReputationTier::None);
+        };
+        let banned = is_banned(&ctx.accounts.user_status, ctx.program_id)?;
+        let status = proof_status(
+            &user_proof,
+            registry,
+            &ctx.accounts.scoring_config,
+            &decay_config,
+            banned,
+            registry.min_score,
+            ctx.remaining_accounts,
+            ctx.program_id,
+            Clock::get()?.unix_timestamp,
+        )?;
+        Ok(reputation_tier(registry, status.live_score, banned))
+    }
+
+    /// Lists which of the user's sources currently count and what each one adds to
+    /// the score at the current clock, before bonuses. `remaining_accounts` holds the
+    /// user's `IndividualProof` PDAs; PDAs that were never created are skipped, so a
+    /// caller can pass every source's PDA without probing first. Revoked, expired and
+    /// hard-expired sources are left out.
+    pub fn get_active_sources<'info>(
+        ctx: Context<'_, '_, 'info, 'info, VerifyProof<'info>>,
+    ) -> Result<ActiveSources> {
+        let decay_config = load_decay_config(&ctx.accounts.decay_config, ctx.program_id)?;
+        let now = Clock::get()?.unix_timestamp;
+
+        let mut active_sources = ActiveSources {
+            sources_mask: 0,
+            sources: Vec::new(),
+        };
+        let Some(user_proof) = load_user_proof(&ctx.accounts.user_proof, ctx.program_id)? else {
+            return Ok(active_sources);
+        };
+        for (i, account) in ctx.remaining_accounts.iter().enumerate() {
+            require!(
+                ctx.remaining_accounts[..i]
+                    .iter()
+                    .all(|a| a.key() != account.key()),
+                SolanIdError::InvalidProofAccounts
+            );
+            if account.owner != ctx.program_id || account.data_is_empty() {
+                continue;
+            }
+            let individual_proof = Account::<IndividualProof>::try_from(account)?;
+            let (expected_address, _) = Pubkey::find_program_address(
+                &[
+                    b"individual_proof".as_ref(),
+                    user_proof.user.as_ref(),
+                    &[individual_proof.source as u8],
+                ],
+                ctx.program_id,
+            );
+            require_keys_eq!(
+                account.key(),
+                expected_address,
+                SolanIdError::InvalidProofAccounts
+            );
+            if !counts_toward_score(&individual_proof, user_proof.expiry_epoch)
+                || (individual_proof.expires_at != 0 && now >= individual_proof.expires_at)
+            {
+                continue;
+            }
+
+            let live_factor = capped_recency_factor(
+                individual_proof.source,
+                individual_proof.verified_at,
+                individual_proof.first_verified_at,
+                now,
+                &ctx.accounts.registry,
+                &decay_config,
+            );
+            if live_factor == 0 {
+                continue;
+            }
+            active_sources.sources_mask |= source_bit(individual_proof.source);
+            active_sources.sources.push(ActiveSource {
+                source: individual_proof.source,
+                live_score: scale_score(individual_proof.weighted_score, live_factor, 100)?,
+            });
+        }
+
+        Ok(active_sources)
+    }
+
+    /// Opts the user into a `StatusCache` that `submit_proof`, `revoke_proof` and
+    /// `refresh_score` keep up to date, so downstream programs can read one small
+    /// account instead of recomputing over every `IndividualProof`.
+    pub fn enable_status_cache(ctx: Context<EnableStatusCache>) -> Result<()> {
+        let decay_config = load_decay_config(&ctx.accounts.decay_config, ctx.program_id)?;
+        let status_cache = &mut ctx.accounts.status_cache;
+        status_cache.user = ctx.accounts.user.key();
+        status_cache.bump = ctx.bumps.status_cache;
+        status_cache.version = ACCOUNT_VERSION;
+
+        refresh_status_cache(
+            status_cache,
+            &ctx.accounts.user_proof,
+            &ctx.accounts.registry,
+            &ctx.accounts.scoring_config,
+            &decay_config,
+            is_banned(&ctx.accounts.user_status, ctx.program_id)?,
+            &[],
+            ctx.program_id,
+            Clock::get()?.unix_timestamp,
+        )
+    }
+
+    /// Recomputes the user's `StatusCache`. Permissionless; passing the user's active
+    /// `IndividualProof` accounts as in `verify_proof` caches the decayed live score.
+    pub fn refresh_score<'info>(
+        ctx: Context<'_, '_, 'info, 'info, RefreshScore<'info>>,
+    ) -> Result<()> {
+        let decay_config = load_decay_config(&ctx.accounts.decay_config, ctx.program_id)?;
+        refresh_status_cache(
+            &mut ctx.accounts.status_cache,
+            &ctx.accounts.user_proof,
+            &ctx.accounts.registry,
+            &ctx.accounts.scoring_config,
+            &decay_config,
+            is_banned(&ctx.accounts.user_status, ctx.program_id)?,
+            ctx.remaining_accounts,
+            ctx.program_id,
+            Clock::get()?.unix_timestamp,
+        )
+    }
+
+    /// Freezes the user's live score under an existing `ConfigSnapshot`, so a
+    /// governance moment or airdrop can be evaluated later without picking up decay or
+    /// new submissions. The score is recomputed at the current clock under the
+    /// snapshot's config, from the user's active `IndividualProof` accounts passed as
+    /// in `verify_proof`. Anyone may take it, and only the first one per user and
+    /// snapshot id is kept.
+    pub fn snapshot_score<'info>(
+        ctx: Context<'_, '_, 'info, 'info, SnapshotScore<'info>>,
+        snapshot_id: u64,
+    ) -> Result<()> {
+        let decay_config = load_decay_config(&ctx.accounts.decay_config, ctx.program_id)?;
+        let clock = Clock::get()?;
+        let user_proof = &ctx.accounts.user_proof;
+        let config_snapshot = &ctx.accounts.config_snapshot;
+        let live = compute_live_score(
+            user_proof,
+            &config_snapshot.registry,
+            &config_snapshot.scoring_config,
+            &decay_config,
+            ctx.remaining_accounts,
+            ctx.program_id,
+            clock.unix_timestamp,
+        )?;
+
+        let score_snapshot = &mut ctx.accounts.score_snapshot;
+        score_snapshot.user = user_proof.user;
+        score_snapshot.snapshot_id = snapshot_id;
+        score_snapshot.aggregated_score = live.score;
+        score_snapshot.valid_until = user_proof.valid_until;
+        score_snapshot.created_at = clock.unix_timestamp;
+        score_snapshot.bump = ctx.bumps.score_snapshot;
+        score_snapshot.version = ACCOUNT_VERSION;
+        score_snapshot.computed_at_slot = clock.slot;
+        score_snapshot.source_count = live.live_source_count;
+        Ok(())
+    }
+
+    /// Evaluates verification from a `ScoreSnapshot` against the config frozen in the
+    /// matching `ConfigSnapshot`, ignoring anything the user submitted afterwards. A
+    /// ban or flag on the user still applies.
+    pub fn verify_proof_at_snapshot(
+        ctx: Context<VerifyProofAtSnapshot>,
+        _snapshot_id: u64,
+    ) -> Result<bool> {
+        let flagged = load_user_proof(&ctx.accounts.user_proof, ctx.program_id)?
+            .is_some_and(|user_proof| user_proof.flagged_sources_mask != 0);
+        Ok(is_snapshot_verified(
+            &ctx.accounts.score_snapshot,
+            &ctx.accounts.config_snapshot.registry,
+            is_banned(&ctx.accounts.user_status, ctx.program_id)?,
+            flagged,
+        ))
+    }
+
+    pub fn set_private(ctx: Context<SetPrivate>, private: bool) -> Result<()> {
+        ctx.accounts.user_proof.private = private;
+        Ok(())
+    }
+
+    /// Recomputes `active_source_count`, `sources_mask` and `source_scores` from the
+    /// user's `IndividualProof` PDAs, passed as remaining accounts for every
+    /// `ProofSource` in discriminant order, and re-applies the score bonuses for the
+    /// corrected count. Accounts written before `source_scores` existed need this
+    /// before a non-additive `ScoringMode` can score them.
+    /// Sources that were never submitted are passed as their empty PDA. Callable by
+    /// anyone.
+    pub fn reconcile_source_count<'info>(
+        ctx: Context<'_, '_, 'info, 'info, ReconcileSourceCount<'info>>,
+    ) -> Result<u8> {
+        let registry = &ctx.accounts.registry;
+        let user_proof = &mut ctx.accounts.user_proof;
+        require!(
+            ctx.remaining_accounts.len() == PROOF_SOURCE_COUNT,
+            SolanIdError::InvalidProofAccounts
+        );
+
+        let mut active_source_count = 0u8;
+        let mut sources_mask = 0u16;
+        let mut source_scores = [0u64; MAX_PROOF_SOURCES];
+        let mut source_expires_at = [0i64; MAX_PROOF_SOURCES];
+        for (index, account) in ctx.remaining_accounts.iter().enumerate() {
+            let (expected_address, _) = Pubkey::find_program_address(
+                &[
+                    b"individual_proof".as_ref(),
+                    user_proof.user.as_ref(),
+                    &[index as u8],
+                ],
+                ctx.program_id,
+            );
+            require_keys_eq!(
+                account.key(),
+                expected_address,
+                SolanIdError::InvalidProofAccounts
+            );
+            if account.owner != ctx.program_id || account.data_is_empty() {
+                continue;
+            }
+
+            let individual_proof = Account::<IndividualProof>::try_from(account)?;
+            if counts_toward_score(&individual_proof, user_proof.expiry_epoch) {
+                active_source_count += 1;
+                sources_mask |= source_bit(individual_proof.source);
+                source_scores[index] = individual_proof.contributed_score;
+                source_expires_at[index] = individual_proof.expires_at;
+            }
+        }
+
+        user_proof.source_scores = source_scores;
+        user_proof.source_expires_at = source_expires_at;
+        let base_aggregated_score = strip_score_bonuses(user_proof, registry)?;
+        user_proof.active_source_count = active_source_count;
+        user_proof.sources_mask = sources_mask;
+        user_proof.person_sources_mask &= sources_mask;
+        apply_score_bonuses(
+            user_proof,
+            registry,
+            &ctx.accounts.scoring_config,
+            base_aggregated_score,
+            Clock::get()?.unix_timestamp,
+        )?;
+
+        Ok(active_source_count)
+    }
+
+    /// Zeroes the stored score and source count of a user whose proof is past
+    /// `valid_until` plus the grace period, so programs reading `UserProof` directly
+    /// see the expiry. The user's existing proofs stop counting; resubmitting starts
+    /// the aggregate from scratch. Callable by anyone.
+    pub fn expire_user_proof(ctx: Context<ExpireUserProof>) -> Result<()> {
+        let registry = &ctx.accounts.registry;
+        let user_proof = &mut ctx.accounts.user_proof;
+        let now = Clock::get()?.unix_timestamp;
+
+        require!(
+            now > user_proof
+                .valid_until
+                .checked_add(registry.grace_period_seconds)
+                .ok_or(SolanIdError::Overflow)?,
+            SolanIdError::UserProofNotExpired
+        );
+        if user_proof.aggregated_score == 0 && user_proof.active_source_count == 0 {
+            return Ok(());
+        }
+
+        let expired_score = user_proof.aggregated_score;
+        let expired_source_count = user_proof.active_source_count;
+        user_proof.aggregated_score = 0;
+        user_proof.active_source_count = 0;
+        user_proof.sources_mask = 0;
+        user_proof.person_sources_mask = 0;
+        user_proof.bonus_applied = false;
+        user_proof.cross_ecosystem_bonus_applied = false;
+        user_proof.wallet_age_bonus_applied = false;
+        user_proof.applied_diversity_bonus_percent = 0;
+        user_proof.applied_cross_ecosystem_bonus_percent = 0;
+        user_proof.applied_wallet_age_bonus_percent = 0;
+        user_proof.pre_cap_score = 0;
+        user_proof.source_scores = [0; MAX_PROOF_SOURCES];
+        user_proof.source_expires_at = [0; MAX_PROOF_SOURCES];
+        user_proof.expiry_epoch = user_proof
+            .expiry_epoch
+            .checked_add(1)
+            .ok_or(SolanIdError::Overflow)?;
+
+        emit!(UserProofExpired {
+            user: user_proof.user,
+            expired_score,
+            expired_source_count,
+        });
+
+        Ok(())
+    }
+
+    /// Releases an identity pinned to a wallet that stopped refreshing, once
+    /// `NULLIFIER_RECLAIM_WINDOW_SECONDS` have passed since its `valid_until`, so the
+    /// same identity can be claimed again from a new wallet. Burned and soulbound
+    /// identities are never released. Callable by anyone.
+    pub fn reclaim_expired_nullifier(
+        ctx: Context<ReclaimExpiredNullifier>,
+        _source: ProofSource,
+        _identity_nullifier: [u8; 32],
+    ) -> Result<()> {
+        let identity_nullifier_registry = &mut ctx.accounts.identity_nullifier_registry;
+        let previous_owner = identity_nullifier_registry.claimed_by;
+        identity_nullifier_registry.reclaim_expired(
+            ctx.accounts.user_proof.valid_until,
+            Clock::get()?.unix_timestamp,
+        )?;
+
+        emit!(IdentityNullifierReclaimed {
+            nullifier: identity_nullifier_registry.nullifier,
+            source: identity_nullifier_registry.source,
+            previous_owner,
+        });
+
+        Ok(())
+    }
+
+    /// Re-applies the registry's current bonus percents to the user's stored score,
+    /// for aggregates computed before a bonus config change. Callable by anyone.
+    pub fn recompute_bonus(ctx: Context<RecomputeBonus>) -> Result<u64> {
+        let registry = &ctx.accounts.registry;
+        let user_proof = &mut ctx.accounts.user_proof;
+
+        let old_score = user_proof.aggregated_score;
+        let base_aggregated_score = strip_score_bonuses(user_proof, registry)?;
+        apply_score_bonuses(
+            user_proof,
+            registry,
+            &ctx.accounts.scoring_config,
+            base_aggregated_score,
+            Clock::get()?.unix_timestamp,
+        )?;
+
+        emit!(BonusRecomputed {
+            user: user_proof.user,
+            old_score,
+            new_score: user_proof.aggregated_score,
+        });
+
+        Ok(user_proof.aggregated_score)
+    }
+
+    /// Returns a digest of the user's verification state so off-chain consumers can
+    /// detect changes by comparing a single value.
+    pub fn get_verification_fingerprint(
+        ctx: Context<GetVerificationFingerprint>,
+    ) -> Result<[u8; 32]> {
+        let user_proof = &ctx.accounts.user_proof;
+        Ok(hashv(&[
+            b"sidf",
+            user_proof.user.as_ref(),
+            &user_proof.aggregated_score.to_le_bytes(),
+            &user_proof.sources_mask.to_le_bytes(),
+            &[user_proof.active_source_count],
+            &user_proof.valid_until.to_le_bytes(),
+        ])
+        .to_bytes())
+    }
+
+    /// Returns the registry and scoring figures dashboards display, so front ends get
+    /// a stable typed view instead of decoding the raw accounts.
+    pub fn get_registry_stats(ctx: Context<GetRegistryStats>) -> Result<RegistryStats> {
+        let registry = &ctx.accounts.registry;
+        Ok(RegistryStats {
+            total_verified_users: registry.total_verified_users,
+            min_score: registry.min_score,
+            active_source_weights: std::array::from_fn(|index| {
+                ctx.accounts.scoring_config.weights[index]
+            }),
+            verifier_authority: registry.verifier_authority,
+            paused: registry.paused,
+            proof_ttl_seconds: registry.proof_ttl_seconds,
+        })
+    }
+
+    /// Reports who holds an identity nullifier for `source`. An unclaimed nullifier
+    /// has no account yet, so that case returns an unclaimed status instead of failing.
+    pub fn get_nullifier_status(
+        ctx: Context<GetNullifierStatus>,
+        source: ProofSource,
+        _identity_nullifier: [u8; 32],
+    ) -> Result<NullifierStatus> {
+        let account = &ctx.accounts.identity_nullifier_registry;
+        if account.owner != ctx.program_id || account.data_is_empty() {
+            return Ok(NullifierStatus {
+                is_claimed: false,
+                claimed_by: Pubkey::default(),
+                source,
+                is_burned: false,
+                claimed_at: 0,
+            });
+        }
+
+        let identity_nullifier_registry =
+            IdentityNullifierRegistry::try_deserialize(&mut &account.try_borrow_data()?[..])?;
+        Ok(NullifierStatus {
+            is_claimed: identity_nullifier_registry.claimed_by != Pubkey::default(),
+            claimed_by: identity_nullifier_registry.claimed_by,
+            source: identity_nullifier_registry.source,
+            is_burned: identity_nullifier_registry.is_burned,
+            claimed_at: identity_nullifier_registry.claimed_at,
+        })
+    }
+
+    /// Reports how long the user has left in the submission cooldown, so a client can
+    /// show a countdown instead of learning about it from a failed `submit_proof`. A
+    /// wallet that never submitted is never in cooldown.
+    pub fn get_cooldown_status(ctx: Context<GetCooldownStatus>) -> Result<CooldownStatus> {
+        let Some(user_proof) = load_user_proof(&ctx.accounts.user_proof, ctx.program_id)? else {
+            return Ok(CooldownStatus::default());
+        };
+        cooldown_status(
+            &user_proof,
+            &ctx.accounts.registry,
+            Clock::get()?.unix_timestamp,
+        )
+    }
+
+    pub fn points_to_threshold(ctx: Context<PointsToThreshold>, min_score: u64) -> Result<u64> {
+        Ok(min_score.saturating_sub(ctx.accounts.user_proof.aggregated_score))
+    }
+
+    pub fn preview_diversity_bonus(
+        ctx: Context<PreviewDiversityBonus>,
+        base_score: u64,
+        active_source_count: u8,
+    ) -> Result<u64> {
+        let registry = &ctx.accounts.registry;
+        let (total_score, _) = apply_diversity_bonus(
+            base_score,
+            active_source_count,
+            ctx.accounts
+                .scoring_config
+                .diversity_bonus_percent(active_source_count, registry.diversity_bonus_percent),
+            registry.min_base_for_bonus,
+        )?;
+        Ok(total_score)
+    }
+
+    pub fn update_min_score(ctx: Context<UpdateMinScore>, new_min_score: u64) -> Result<()> {
+        let registry = &mut ctx.accounts.registry;
+        registry.check_min_score_within_cap(new_min_score)?;
+        let old_score = registry.min_score;
+        registry.min_score = new_min_score;
+        emit!(MinScoreUpdated {
+            old_score,
+            new_score: new_min_score,
+        });
+        Ok(())
+    }
+
+    /// Timelocked alternative to `update_min_score`, giving users notice before the
+    /// threshold moves.
+    pub fn initiate_min_score_change(
+        ctx: Context<UpdateMinScore>,
+        new_min_score: u64,
+        delay_seconds: i64,
+    ) -> Result<()> {
+        require!(delay_seconds >= 1, SolanIdError::InvalidConfig);
+
+        let registry = &mut ctx.accounts.registry;
+        registry.check_min_score_within_cap(new_min_score)?;
+        let now = Clock::get()?.unix_timestamp;
+        registry.pending_min_score = new_min_score;
+        registry.min_score_change_available_at = now
+            .checked_add(delay_seconds)
+            .ok_or(SolanIdError::Overflow)?;
+
+        emit!(MinScoreChangeInitiated {
+            current_score: registry.min_score,
+            pending_score: new_min_score,
+            activate_at: registry.min_score_change_available_at,
+        });
+
+        Ok(())
+    }
+
+    pub fn finalize_min_score_change(ctx: Context<UpdateMinScore>) -> Result<()> {
+        let registry = &mut ctx.accounts.registry;
+        require!(
+            registry.min_score_change_available_at != 0,
+            SolanIdError::NoMinScoreChangePending
+        );
+
+        let now = Clock::get()?.unix_timestamp;
+        require!(
+            now >= registry.min_score_change_available_at,
+            SolanIdError::MinScoreChangeNotReady
+        );
+        registry.check_min_score_within_cap(registry.pending_min_score)?;
+
+        let old_score = registry.min_score;
+        registry.min_score = registry.pending_min_score;
+        registry.pending_min_score = 0;
+        registry.min_score_change_available_at = 0;
+
+        emit!(MinScoreUpdated {
+            old_score,
+            new_score: registry.min_score,
+        });
+
+        Ok(())
+    }
+
+    pub fn update_scoring_config(
+        ctx: Context<UpdateScoringConfig>,
+        source: ProofSource,
+        weight: u64,
+    ) -> Result<()> {
+        let scoring_config = &mut ctx.accounts.scoring_config;
+        let index = source.as_index(&scoring_config.weights)?;
+        scoring_config.weights[index] = weight;
+        emit!(ScoringConfigUpdated { source, weight });
+        Ok(())
+    }
+
+    pub fn update_reclaim_cooldown(
+        ctx: Context<UpdateScoringConfig>,
+        source: ProofSource,
+        reclaim_cooldown: i64,
+    ) -> Result<()> {
+        require!(reclaim_cooldown >= 0, SolanIdError::InvalidConfig);
+
+        let scoring_config = &mut ctx.accounts.scoring_config;
+        let index = source.as_index(&scoring_config.reclaim_cooldowns)?;
+        scoring_config.reclaim_cooldowns[index] = reclaim_cooldown;
+        Ok(())
+    }
+
+    pub fn update_gitcoin_requirements(
+        ctx: Context<UpdateScoringConfig>,
+        min_stamp_count: u16,
+        min_passport_score: u16,
+        min_model_version: u8,
+    ) -> Result<()> {
+        let scoring_config = &mut ctx.accounts.scoring_config;
+        scoring_config.min_stamp_count = min_stamp_count;
+        scoring_config.min_passport_score = min_passport_score;
+        scoring_config.min_model_version = min_model_version;
+        Ok(())
+    }
+
+    pub fn update_min_world_id_level(
+        ctx: Context<UpdateScoringConfig>,
+        min_world_id_level: u8,
+    ) -> Result<()> {
+        require!(
+            (1..=2).contains(&min_world_id_level),
+            SolanIdError::InvalidConfig
+        );
+
+        ctx.accounts.scoring_config.min_world_id_level = min_world_id_level;
+        Ok(())
+    }
+
+    /// Caps how far the attestation `timestamp` may drift from the `issued_at` a
+    /// source carries (Reclaim). Zero turns the cross-check off.
+    pub fn update_max_issued_at_skew(
+        ctx: Context<UpdateScoringConfig>,
+        max_issued_at_skew: i64,
+    ) -> Result<()> {
+        require!(max_issued_at_skew >= 0, SolanIdError::InvalidConfig);
+
+        ctx.accounts.scoring_config.max_issued_at_skew = max_issued_at_skew;
+        Ok(())
+    }
+
+    /// Groups `source` with related sources for the diversity bonus, which only
+    /// counts distinct categories. Stored aggregates pick up a change on the user's
+    /// next submission or revocation.
+    pub fn update_source_category(
+        ctx: Context<UpdateScoringConfig>,
+        source: ProofSource,
+        category: u8,
+    ) -> Result<()> {
+        require!(
+            (category as usize) < PROOF_SOURCE_COUNT,
+            SolanIdError::InvalidConfig
+        );
+
+        let scoring_config = &mut ctx.accounts.scoring_config;
+        let index = source.as_index(&scoring_config.category)?;
+        scoring_config.category[index] = category;
+        Ok(())
+    }
+
+    /// Sets the diversity bonus percent per distinct source count, where entry `i`
+    /// applies to `i + 1` distinct sources. Percents must not decrease and are capped
+    /// at 100. An all-zero schedule falls back to the registry's flat bonus.
+    pub fn update_diversity_bonus_schedule(
+        ctx: Context<UpdateScoringConfig>,
+        schedule: [u8; MAX_PROOF_SOURCES],
+    ) -> Result<()> {
+        require!(
+            schedule[0] == 0
+                && schedule.windows(2).all(|pair| pair[0] <= pair[1])
+                && schedule[MAX_PROOF_SOURCES - 1] <= 100,
+            SolanIdError::InvalidConfig
+        );
+
+        ctx.accounts.scoring_config.diversity_bonus_schedule = schedule;
+        Ok(())
+    }
+
+    /// Hands scoring tuning to a separate signer. Registry controls such as
+    /// verifier rotation and pausing stay with the registry authority.
+    pub fn set_scoring_authority(
+        ctx: Context<UpdateScoringConfig>,
+        new_authority: Pubkey,
+    ) -> Result<()> {
+        require!(
+            new_authority != Pubkey::default(),
+            SolanIdError::InvalidConfig
+        );
+
+        let scoring_config = &mut ctx.accounts.scoring_config;
+        let old_authority = scoring_config.authority;
+        scoring_config.authority = new_authority;
+        emit!(ScoringAuthorityUpdated {
+            old_authority,
+            new_authority,
+        });
+        Ok(())
+    }
+
+    /// Caps the base score the verifier may sign for `source`. Zero leaves the
+    /// source uncapped.
+    pub fn update_source_max_score(
+        ctx: Context<UpdateScoringConfig>,
+        source: ProofSource,
+        max_base_score: u64,
+    ) -> Result<()> {
+        let scoring_config = &mut ctx.accounts.scoring_config;
+        let index = source.as_index(&scoring_config.max_base_score)?;
+        scoring_config.max_base_score[index] = max_base_score;
+        Ok(())
+    }
+
+    /// Switches how per-source scores fold into the aggregate. Stored aggregates pick
+    /// up the new mode on the user's next submission or revocation.
+    pub fn update_scoring_mode(
+        ctx: Context<UpdateScoringConfig>,
+        scoring_mode: ScoringMode,
+    ) -> Result<()> {
+        ctx.accounts.scoring_config.scoring_mode = scoring_mode;
+        Ok(())
+    }
+
+    /// Makes `submit_proof` refuse sources whose weight has been set to zero. Proofs
+    /// already recorded for such a source stay, but no longer count towards diversity.
+    pub fn set_reject_zero_weight_sources(
+        ctx: Context<UpdateScoringConfig>,
+        reject_zero_weight_sources: bool,
+    ) -> Result<()> {
+        ctx.accounts.scoring_config.reject_zero_weight_sources = reject_zero_weight_sources;
+        Ok(())
+    }
+
+    pub fn initialize_scoring_config(ctx: Context<InitializeScoringConfig>) -> Result<()> {
+        let scoring_config = &mut ctx.accounts.scoring_config;
+        scoring_config.authority = ctx.accounts.authority.key();
+        scoring_config.weights = [100; MAX_PROOF_SOURCES];
+        scoring_config.bump = ctx.bumps.scoring_config;
+        scoring_config.version = ACCOUNT_VERSION;
+        scoring_config.reclaim_cooldowns = [0; MAX_PROOF_SOURCES];
+        scoring_config.min_stamp_count = 0;
+        scoring_config.min_passport_score = 0;
+        scoring_config.min_model_version = 0;
+        scoring_config.min_world_id_level = 1;
+        scoring_config.category = default_source_categories();
+        scoring_config.max_base_score = [0; MAX_PROOF_SOURCES];
+        scoring_config.diversity_bonus_schedule = [0; MAX_PROOF_SOURCES];
+        scoring_config.max_issued_at_skew = 300;
+        scoring_config.scoring_mode = ScoringMode::Additive;
+        scoring_config.reject_zero_weight_sources = false;
+        Ok(())
+    }
+
+    pub fn initialize_decay_config(ctx: Context<InitializeDecayConfig>) -> Result<()> {
+        ctx.accounts.decay_config.set_inner(DecayConfig {
+            authority: ctx.accounts.authority.key(),
+            bump: ctx.bumps.decay_config,
+            ..DecayConfig::default()
+        });
+        Ok(())
+    }
+
+    pub fn update_decay_config(
+        ctx: Context<UpdateDecayConfig>,
+        age_boundaries: [i64; 4],
+        factors: [u64; 4],
+    ) -> Result<()> {
+        require!(age_boundaries[0] > 0, SolanIdError::InvalidConfig);
+        require!(
+            age_boundaries.windows(2).all(|w| w[0] < w[1]),
+            SolanIdError::InvalidConfig
+        );
+        require!(factors[0] <= 100, SolanIdError::InvalidConfig);
+        require!(
+            factors.windows(2).all(|w| w[0] >= w[1]),
+            SolanIdError::InvalidConfig
+        );
+
+        let decay_config = &mut ctx.accounts.decay_config;
+        decay_config.age_boundaries = age_boundaries;
+        decay_config.factors = factors;
+        Ok(())
+    }
+
+    /// Switches between stepped and continuous decay. The brackets set with
+    /// `update_decay_config` still supply the start, floor and timescale.
+    pub fn update_decay_mode(ctx: Context<UpdateDecayConfig>, decay_mode: DecayMode) -> Result<()> {
+        ctx.accounts.decay_config.decay_mode = decay_mode;
+        Ok(())
+    }
+
+    pub fn update_source_hard_expiry(
+        ctx: Context<UpdateDecayConfig>,
+        source_hard_expiry: [i64; MAX_PROOF_SOURCES],
+    ) -> Result<()> {
+        require!(
+            source_hard_expiry.iter().all(|expiry| *expiry >= 0),
+            SolanIdError::InvalidConfig
+        );
+
+        ctx.accounts.decay_config.source_hard_expiry = source_hard_expiry;
+        Ok(())
+    }
+
+    pub fn update_registry_config(
+        ctx: Context<UpdateRegistryConfig>,
+        cooldown_period: i64,
+        diversity_bonus_percent: u8,
+        proof_ttl_seconds: i64,
+        grace_period_seconds: i64,
+        min_source_count: u8,
+    ) -> Result<()> {
+        require!(cooldown_period >= 0, SolanIdError::InvalidConfig);
+        require!(diversity_bonus_percent <= 100, SolanIdError::InvalidConfig);
+        require!(
+            (1..=MAX_PROOF_TTL_SECONDS).contains(&proof_ttl_seconds),
+            SolanIdError::InvalidConfig
+        );
+        require!(grace_period_seconds >= 0, SolanIdError::InvalidConfig);
+        check_cooldown_within_ttl(cooldown_period, proof_ttl_seconds)?;
+        require!(
+            min_source_count >= 1 && min_source_count as usize <= PROOF_SOURCE_COUNT,
+            SolanIdError::InvalidConfig
+        );
+
+        let registry = &mut ctx.accounts.registry;
+        require!(
+            registry.nonce_retention_seconds == 0
+                || registry.nonce_retention_seconds
+                    > max_attestation_age(proof_ttl_seconds, registry.clock_skew_tolerance)?,
+            SolanIdError::InvalidConfig
+        );
+        check_attestation_ttl_within_proof_ttl(
+            registry.attestation_ttl_seconds,
+            proof_ttl_seconds,
+        )?;
+        let old_cooldown_period = registry.cooldown_period;
+        let old_diversity_bonus_percent = registry.diversity_bonus_percent;
+        let old_proof_ttl_seconds = registry.proof_ttl_seconds;
+        registry.cooldown_period = cooldown_period;
+        registry.diversity_bonus_percent = diversity_bonus_percent;
+        registry.proof_ttl_seconds = proof_ttl_seconds;
+        registry.grace_period_seconds = grace_period_seconds;
+        registry.min_source_count = min_source_count;
+
+        emit!(RegistryConfigUpdated {
+            authority: registry.authority,
+            old_cooldown_period,
+            new_cooldown_period: cooldown_period,
+            old_diversity_bonus_percent,
+            new_diversity_bonus_percent: diversity_bonus_percent,
+            old_proof_ttl_seconds,
+            new_proof_ttl_seconds: proof_ttl_seconds,
+        });
+        Ok(())
+    }
+
+    pub fn update_max_weighted_score(
+        ctx: Context<UpdateRegistryConfig>,
+        max_weighted_score: u64,
+    ) -> Result<()> {
+        require!(max_weighted_score > 0, SolanIdError::InvalidConfig);
+
+        let registry = &mut ctx.accounts.registry;
+        registry.max_weighted_score = max_weighted_score;
+        Ok(())
+    }
+
+    pub fn update_submission_fee(
+        ctx: Context<UpdateRegistryConfig>,
+        submission_fee: u64,
+        fee_destination: Pubkey,
+    ) -> Result<()> {
+        require!(
+            submission_fee == 0 || fee_destination != Pubkey::default(),
+            SolanIdError::InvalidConfig
+        );
+
+        let registry = &mut ctx.accounts.registry;
+        registry.submission_fee = submission_fee;
+        registry.fee_destination = fee_destination;
+        Ok(())
+    }
+
+    /// Caps how many proofs the registry accepts per slot across all users, as a
+    /// coarse throttle during submission floods. Zero removes the cap.
+    pub fn update_submission_rate_limit(
+        ctx: Context<UpdateRegistryConfig>,
+        max_submissions_per_slot: u32,
+    ) -> Result<()> {
+        ctx.accounts.registry.max_submissions_per_slot = max_submissions_per_slot;
+        Ok(())
+    }
+
+    /// Extends the effective `valid_until` checked by `verify_proof` while a verifier
+    /// rotation is pending, so users are not locked out if attestations pause during
+    /// the handover. This trades freshness for availability: a proof that would have
+    /// expired keeps verifying for up to `rotation_grace_seconds`, including proofs of
+    /// users whose circumstances changed after their last attestation. Zero disables it.
+    pub fn update_rotation_grace(
+        ctx: Context<UpdateRegistryConfig>,
+        rotation_grace_seconds: i64,
+    ) -> Result<()> {
+        require!(rotation_grace_seconds >= 0, SolanIdError::InvalidConfig);
+        ctx.accounts.registry.rotation_grace_seconds = rotation_grace_seconds;
+        Ok(())
+    }
+
+    /// Submissions whose attestation `timestamp` falls inside
+    /// `[campaign_start, campaign_end]` earn `campaign_bonus_percent` extra weight.
+    /// The bonus is folded into the stored `weighted_score`, so revocation removes
+    /// exactly what was added. A zero percent disables the campaign.
+    pub fn set_campaign(
+        ctx: Context<UpdateRegistryConfig>,
+        campaign_start: i64,
+        campaign_end: i64,
+        campaign_bonus_percent: u8,
+    ) -> Result<()> {
+        require!(campaign_start <= campaign_end, SolanIdError::InvalidConfig);
+        require!(campaign_bonus_percent <= 100, SolanIdError::InvalidConfig);
+
+        let registry = &mut ctx.accounts.registry;
+        registry.campaign_start = campaign_start;
+        registry.campaign_end = campaign_end;
+        registry.campaign_bonus_percent = campaign_bonus_percent;
+        Ok(())
+    }
+
+    /// When enabled, attestations must also cover a recent `(slot, slot_hash)` pair
+    /// taken from the slot hashes sysvar, and the slot must be at most
+    /// `max_slot_hash_age` slots old. Off by default so existing signers keep working.
+    pub fn set_slot_hash_binding(
+        ctx: Context<UpdateRegistryConfig>,
+        enabled: bool,
+        max_slot_hash_age: u64,
+    ) -> Result<()> {
+        require!(
+            !enabled || (max_slot_hash_age > 0 && max_slot_hash_age < MAX_SLOT_HASH_ENTRIES),
+            SolanIdError::InvalidConfig
+        );
+
+        let registry = &mut ctx.accounts.registry;
+        registry.slot_hash_binding = enabled;
+        registry.max_slot_hash_age = max_slot_hash_age;
+        Ok(())
+    }
+
+    pub fn update_max_recency_after_resubmit(
+        ctx: Context<UpdateRegistryConfig>,
+        max_recency_after_resubmit: u8,
+    ) -> Result<()> {
+        require!(
+            max_recency_after_resubmit <= 100,
+            SolanIdError::InvalidConfig
+        );
+        ctx.accounts.registry.max_recency_after_resubmit = max_recency_after_resubmit;
+        Ok(())
+    }
+
+    /// Configures the bonus for holding at least one source from `web2_mask` and one
+    /// from `web3_mask`; the masks may not share a source. It is applied on top of
+    /// the diversity bonus.
+    pub fn set_cross_ecosystem_bonus(
+        ctx: Context<UpdateRegistryConfig>,
+        web2_mask: u16,
+        web3_mask: u16,
+        cross_ecosystem_bonus_percent: u8,
+    ) -> Result<()> {
+        require!(web2_mask & web3_mask == 0, SolanIdError::InvalidConfig);
+        require!(
+            cross_ecosystem_bonus_percent <= 100,
+            SolanIdError::InvalidConfig
+        );
+
+        let registry = &mut ctx.accounts.registry;
+        registry.web2_mask = web2_mask;
+        registry.web3_mask = web3_mask;
+        registry.cross_ecosystem_bonus_percent = cross_ecosystem_bonus_percent;
+        Ok(())
+    }
+
+    /// Sets how long a used attestation nonce must be kept before
+    /// `close_attestation_nonce` can reclaim it. It has to outlive every attestation
+    /// that could still pass the timestamp checks, so a closed nonce can't be replayed.
+    /// Zero disables closing.
+    pub fn set_nonce_retention(
+        ctx: Context<UpdateRegistryConfig>,
+        nonce_retention_seconds: i64,
+    ) -> Result<()> {
+        let registry = &mut ctx.accounts.registry;
+        require!(
+            nonce_retention_seconds == 0
+                || nonce_retention_seconds
+                    > max_attestation_age(
+                        registry.proof_ttl_seconds,
+                        registry.clock_skew_tolerance,
+                    )?,
+            SolanIdError::InvalidConfig
+        );
+
+        registry.nonce_retention_seconds = nonce_retention_seconds;
+        Ok(())
+    }
+
+    /// Bounds how old a verifier attestation may be at submission, so a signed
+    /// attestation cannot be replayed for the whole proof lifetime. Zero disables the
+    /// bound; otherwise it may not exceed `proof_ttl_seconds`.
+    pub fn set_attestation_ttl(
+        ctx: Context<UpdateRegistryConfig>,
+        attestation_ttl_seconds: i64,
+    ) -> Result<()> {
+        let registry = &mut ctx.accounts.registry;
+        check_attestation_ttl_within_proof_ttl(
+            attestation_ttl_seconds,
+            registry.proof_ttl_seconds,
+        )?;
+
+        registry.attestation_ttl_seconds = attestation_ttl_seconds;
+        Ok(())
+    }
+
+    /// Switches submission attestations between the full message and its SHA-256
+    /// digest. Verifiers have to sign the matching form once this changes.
+    pub fn set_hashed_attestations(
+        ctx: Context<UpdateRegistryConfig>,
+        hashed_attestations: bool,
+    ) -> Result<()> {
+        ctx.accounts.registry.hashed_attestations = hashed_attestations;
+        Ok(())
+    }
+
+    /// Caps the trust multiplier verifiers may attest for high-trust flows, so a
+    /// compromised verifier key can only inflate a proof this far.
+    pub fn set_max_trust_multiplier(
+        ctx: Context<UpdateRegistryConfig>,
+        max_trust_multiplier: u8,
+    ) -> Result<()> {
+        require!(
+            max_trust_multiplier >= NEUTRAL_TRUST_MULTIPLIER,
+            SolanIdError::InvalidConfig
+        );
+
+        ctx.accounts.registry.max_trust_multiplier = max_trust_multiplier;
+        Ok(())
+    }
+
+    /// Caps the weight a verifier may attest in place of the configured one for a
+    /// single proof. Zero turns weight overrides off.
+    pub fn set_max_weight_override(
+        ctx: Context<UpdateRegistryConfig>,
+        max_weight_override: u64,
+    ) -> Result<()> {
+        ctx.accounts.registry.max_weight_override = max_weight_override;
+        Ok(())
+    }
+
+    /// Sets how long after its delay a pending verifier rotation can still be
+    /// finalized. Zero falls back to `DEFAULT_ROTATION_EXPIRY_SECONDS`.
+    pub fn set_rotation_expiry(
+        ctx: Context<UpdateRegistryConfig>,
+        rotation_expiry_seconds: i64,
+    ) -> Result<()> {
+        require!(rotation_expiry_seconds >= 0, SolanIdError::InvalidConfig);
+
+        ctx.accounts.registry.rotation_expiry_seconds = rotation_expiry_seconds;
+        Ok(())
+    }
+
+    /// Restricts which sources the registry accepts, one bit per `ProofSource`.
+    /// Proofs already recorded for a source that drops out of the mask are kept.
+    pub fn set_allowed_sources(
+        ctx: Context<UpdateRegistryConfig>,
+        allowed_sources: u16,
+    ) -> Result<()> {
+        require!(allowed_sources != 0, SolanIdError::InvalidConfig);
+
+        ctx.accounts.registry.allowed_sources = allowed_sources;
+        Ok(())
+    }
+
+    /// Sets how long a source's identity must stay in place before it can be
+    /// replaced by a different one, to stop rapid flip-flopping between identities.
+    pub fn set_min_source_dwell(
+        ctx: Context<UpdateRegistryConfig>,
+        min_source_dwell_seconds: i64,
+    ) -> Result<()> {
+        require!(min_source_dwell_seconds >= 0, SolanIdError::InvalidConfig);
+        ctx.accounts.registry.min_source_dwell_seconds = min_source_dwell_seconds;
+        Ok(())
+    }
+
+    /// When set, `submit_proof` and `submit_proofs_batch` fail with
+    /// `ScoreBelowThreshold` instead of recording a proof that leaves the user short
+    /// of `min_score`.
+    pub fn set_strict_min_score(
+        ctx: Context<UpdateRegistryConfig>,
+        strict_min_score: bool,
+    ) -> Result<()> {
+        ctx.accounts.registry.strict_min_score = strict_min_score;
+        Ok(())
+    }
+
+    /// Caps the post-bonus aggregate so `min_score` and tier thresholds stay
+    /// calibratable however many bonuses stack. Aggregates are re-clamped the next
+    /// time they are recomputed. `u64::MAX` removes the cap.
+    pub fn set_max_aggregated_score(
+        ctx: Context<UpdateRegistryConfig>,
+        max_aggregated_score: u64,
+    ) -> Result<()> {
+        let registry = &mut ctx.accounts.registry;
+        require!(
+            max_aggregated_score > 0 && max_aggregated_score >= registry.min_score,
+            SolanIdError::InvalidConfig
+        );
+
+        registry.max_aggregated_score = max_aggregated_score;
+        Ok(())
+    }
+
+    /// Sets how far ahead of the validator clock attested timestamps may be, for every
+    /// attestation the registry checks. Nonce retention must still outlive the
+    /// widened attestation window.
+    pub fn set_clock_skew_tolerance(
+        ctx: Context<UpdateRegistryConfig>,
+        clock_skew_tolerance: i64,
+    ) -> Result<()> {
+        let registry = &mut ctx.accounts.registry;
+        require!(
+            (0..=MAX_CLOCK_SKEW_TOLERANCE).contains(&clock_skew_tolerance),
+            SolanIdError::InvalidConfig
+        );
+        require!(
+            registry.nonce_retention_seconds == 0
+                || registry.nonce_retention_seconds
+                    > max_attestation_age(registry.proof_ttl_seconds, clock_skew_tolerance)?,
+            SolanIdError::InvalidConfig
+        );
+
+        registry.clock_skew_tolerance = clock_skew_tolerance;
+        Ok(())
+    }
+
+    /// Caps how many distinct sources a user can hold at once, which bounds how far
+    /// the diversity bonus can compound. Resubmitting an active source is unaffected.
+    pub fn set_max_active_sources(
+        ctx: Context<UpdateRegistryConfig>,
+        max_active_sources: u8,
+    ) -> Result<()> {
+        require!(
+            (1..=PROOF_SOURCE_COUNT as u8).contains(&max_active_sources),
+            SolanIdError::InvalidConfig
+        );
+
+        ctx.accounts.registry.max_active_sources = max_active_sources;
+        Ok(())
+    }
+
+    /// Thresholds must be strictly ascending. Passing all zeros turns tiers off.
+    pub fn update_tier_thresholds(
+        ctx: Context<UpdateRegistryConfig>,
+        tier_thresholds: [u64; 4],
+    ) -> Result<()> {
+        require!(
+            tier_thresholds == [0; 4]
+                || (tier_thresholds[0] > 0 && tier_thresholds.windows(2).all(|w| w[0] < w[1])),
+            SolanIdError::InvalidConfig
+        );
+
+        ctx.accounts.registry.tier_thresholds = tier_thresholds;
+        Ok(())
+    }
+
+    /// Scales every user's aggregate by `multiplier_bps / 10000` at verification time,
+    /// so a policy can be trialled without reweighting each source. Stored scores are
+    /// left untouched, and a scaled score saturates at `u64::MAX` rather than overflowing.
+    pub fn set_global_score_multiplier(
+        ctx: Context<UpdateRegistryConfig>,
+        multiplier_bps: u16,
+    ) -> Result<()> {
+        require!(multiplier_bps > 0, SolanIdError::InvalidConfig);
+
+        ctx.accounts.registry.global_score_multiplier_bps = multiplier_bps;
+        Ok(())
+    }
+
+    /// Moves the verifier to a new attestation format. Setting `min_attestation_version`
+    /// below `attestation_version` keeps older signatures valid during the rollover.
+    pub fn set_attestation_version(
+        ctx: Context<UpdateRegistryConfig>,
+        attestation_version: u8,
+        min_attestation_version: u8,
+    ) -> Result<()> {
+        require!(
+            min_attestation_version >= 1
+                && min_attestation_version <= attestation_version
+                && attestation_version <= MAX_ATTESTATION_VERSION,
+            SolanIdError::InvalidConfig
+        );
+
+        let registry = &mut ctx.accounts.registry;
+        registry.attestation_version = attestation_version;
+        registry.min_attestation_version = min_attestation_version;
+        Ok(())
+    }
+
+    /// Switches replay protection between one `AttestationNonceRegistry` per nonce and a
+    /// per-user counter on `UserProof`, where each attestation must use the next nonce.
+    pub fn set_sequential_nonces(
+        ctx: Context<UpdateRegistryConfig>,
+        sequential_nonces: bool,
+    ) -> Result<()> {
+        ctx.accounts.registry.sequential_nonces = sequential_nonces;
+        Ok(())
+    }
+
+    /// Chooses whether a proof hash may be used once per user or once across all
+    /// users. Hashes recorded under the other scope are not carried over.
+    pub fn set_proof_hash_scope(
+        ctx: Context<UpdateRegistryConfig>,
+        proof_hash_scope: ProofHashScope,
+    ) -> Result<()> {
+        ctx.accounts.registry.proof_hash_scope = proof_hash_scope;
+        Ok(())
+    }
+
+    /// Configures the bonus for wallets the verifier attests were first active at least
+    /// `min_wallet_age_seconds` ago. It is applied on top of the other bonuses.
+    pub fn set_wallet_age_bonus(
+        ctx: Context<UpdateRegistryConfig>,
+        min_wallet_age_seconds: i64,
+        wallet_age_bonus_percent: u8,
+    ) -> Result<()> {
+        require!(
+            min_wallet_age_seconds >= 0 && wallet_age_bonus_percent <= 100,
+            SolanIdError::InvalidConfig
+        );
+
+        let registry = &mut ctx.accounts.registry;
+        registry.min_wallet_age_seconds = min_wallet_age_seconds;
+        registry.wallet_age_bonus_percent = wallet_age_bonus_percent;
+        Ok(())
+    }
+
+    /// Lets the verifier link sources to a shared person id with `link_person_id`. Off
+    /// by default; existing links keep applying after it is turned off.
+    pub fn set_person_binding(ctx: Context<UpdateRegistryConfig>, enabled: bool) -> Result<()> {
+        ctx.accounts.registry.person_binding = enabled;
+        Ok(())
+    }
+
+    pub fn update_min_base_for_bonus(
+        ctx: Context<UpdateRegistryConfig>,
+        min_base_for_bonus: u64,
+    ) -> Result<()> {
+        let registry = &mut ctx.accounts.registry;
+        registry.min_base_for_bonus = min_base_for_bonus;
+        Ok(())
+    }
+
+    pub fn set_paused(ctx: Context<UpdateRegistryConfig>, paused: bool) -> Result<()> {
+        let registry = &mut ctx.accounts.registry;
+        registry.paused = paused;
+        emit!(RegistryPauseToggled {
+            authority: ctx.accounts.authority.key(),
+            paused,
+        });
+        Ok(())
+    }
+
+    /// Bans a wallet outright: `submit_proof` rejects it and `verify_proof` reports
+    /// it unverified whatever its score. Its proofs stay in place.
+    pub fn ban_user(ctx: Context<BanUser>) -> Result<()> {
+        let user_status = &mut ctx.accounts.user_status;
+        user_status.user = ctx.accounts.user.key();
+        user_status.banned = true;
+        user_status.bump = ctx.bumps.user_status;
+        user_status.version = ACCOUNT_VERSION;
+        emit!(UserBanToggled {
+            authority: ctx.accounts.authority.key(),
+            user: user_status.user,
+            banned: true,
+        });
+        Ok(())
+    }
+
+    pub fn unban_user(ctx: Context<UnbanUser>) -> Result<()> {
+        let user_status = &mut ctx.accounts.user_status;
+        user_status.banned = false;
+        emit!(UserBanToggled {
+            authority: ctx.accounts.authority.key(),
+            user: user_status.user,
+            banned: false,
+        });
+        Ok(())
+    }
+
+    /// Flags an identity for review. Its owner reports unverified until the authority
+    /// calls `clear_flag` or resolves it with `admin_revoke_proof`. Callable by the
+    /// authority and the watcher, or by anyone when flagging is permissionless.
+    pub fn flag_identity(
+        ctx: Context<FlagIdentity>,
+        _source: ProofSource,
+        identity_nullifier: [u8; 32],
+    ) -> Result<()> {
+        let registry = &ctx.accounts.registry;
+        let identity_nullifier_registry = &mut ctx.accounts.identity_nullifier_registry;
+        let flagger = ctx.accounts.flagger.key();
+
+        require!(
+            registry.permissionless_flagging
+                || flagger == registry.authority
+                || (registry.watcher != Pubkey::default() && flagger == registry.watcher),
+            SolanIdError::Unauthorized
+        );
+        require!(
+            !identity_nullifier_registry.is_burned,
+            SolanIdError::IdentityRevokedPermanent
+        );
+        require!(
+            !identity_nullifier_registry.flagged,
+            SolanIdError::IdentityUnderReview
+        );
+
+        identity_nullifier_registry.flagged = true;
+        identity_nullifier_registry.flagged_at = Clock::get()?.unix_timestamp;
+        ctx.accounts.user_proof.flagged_sources_mask |=
+            source_bit(identity_nullifier_registry.source);
+
+        emit!(IdentityFlagged {
+            flagger,
+            user: identity_nullifier_registry.claimed_by,
+            source: identity_nullifier_registry.source,
+            identity_nullifier,
+        });
+        Ok(())
+    }
+
+    pub fn clear_flag(
+        ctx: Context<ClearFlag>,
+        _source: ProofSource,
+        identity_nullifier: [u8; 32],
+    ) -> Result<()> {
+        require!(
+            ctx.accounts.identity_nullifier_registry.flagged,
+            SolanIdError::IdentityNotFlagged
+        );
+
+        clear_identity_flag(
+            &mut ctx.accounts.identity_nullifier_registry,
+            &mut ctx.accounts.user_proof,
+        );
+
+        emit!(IdentityFlagCleared {
+            authority: ctx.accounts.authority.key(),
+            user: ctx.accounts.identity_nullifier_registry.claimed_by,
+            source: ctx.accounts.identity_nullifier_registry.source,
+            identity_nullifier,
+        });
+        Ok(())
+    }
+
+    /// Sets who besides the authority may call `flag_identity`. A default `watcher`
+    /// means no watcher; `permissionless` opens flagging to anyone.
+    pub fn set_flagging_policy(
+        ctx: Context<UpdateRegistryConfig>,
+        watcher: Pubkey,
+        permissionless: bool,
+    ) -> Result<()> {
+        let registry = &mut ctx.accounts.registry;
+        registry.watcher = watcher;
+        registry.permissionless_flagging = permissionless;
+        Ok(())
+    }
+
+    /// Treats source weights as shares of `max_total_score` instead of percentages.
+    /// Each normalized source is measured against its `max_base_score`, so a user
+    /// holding every weighted source at its cap reaches `max_total_score` before
+    /// bonuses. Proofs already recorded keep their score until resubmitted.
+    pub fn set_normalized_scoring(
+        ctx: Context<UpdateRegistryConfig>,
+        enabled: bool,
+        max_total_score: u64,
+    ) -> Result<()> {
+        require!(!enabled || max_total_score > 0, SolanIdError::InvalidConfig);
+
+        let registry = &mut ctx.accounts.registry;
+        registry.normalized_scoring = enabled;
+        registry.max_total_score = max_total_score;
+        Ok(())
+    }
+
+    /// Stages an M-of-N verifier set to replace the single `verifier_authority` once
+    /// `delay_seconds` pass, under the same timelock as `initiate_verifier_rotation`.
+    /// An empty set reverts `submit_proof` to the single-verifier path.
+    pub fn initiate_verifier_set_change(
+        ctx: Context<InitiateVerifierRotation>,
+        verifiers: Vec<Pubkey>,
+        threshold: u8,
+        delay_seconds: i64,
+    ) -> Result<()> {
+        require!(delay_seconds >= 1, SolanIdError::InvalidConfig);
+        require!(
+            verifiers.len() <= MAX_VERIFIERS,
+            SolanIdError::InvalidConfig
+        );
+        if verifiers.is_empty() {
+            require!(threshold <= 1, SolanIdError::InvalidConfig);
+        } else {
+            require!(
+                threshold >= 1 && threshold as usize <= verifiers.len(),
+                SolanIdError::InvalidConfig
+            );
+        }
+        for (i, verifier) in verifiers.iter().enumerate() {
+            require!(*verifier != Pubkey::default(), SolanIdError::InvalidConfig);
+            require!(
+                !verifiers[..i].contains(verifier),
+                SolanIdError::InvalidConfig
+            );
+        }
+
+        let registry = &mut ctx.accounts.registry;
+        let now = Clock::get()?.unix_timestamp;
+        registry.pending_verifier_threshold = threshold.max(1);
+        registry.pending_verifier_set = verifiers;
+        registry.verifier_set_change_available_at = now
+            .checked_add(delay_seconds)
+            .ok_or(SolanIdError::Overflow)?;
+
+        emit!(VerifierSetChangeInitiated {
+            verifier_count: registry.pending_verifier_set.len() as u8,
+            threshold: registry.pending_verifier_threshold,
+            activate_at: registry.verifier_set_change_available_at,
+        });
+
+        Ok(())
+    }
+
+    pub fn finalize_verifier_set_change(ctx: Context<FinalizeVerifierRotation>) -> Result<()> {
+        let registry = &mut ctx.accounts.registry;
+        require!(
+            registry.verifier_set_change_available_at != 0,
+            SolanIdError::NoVerifierRotationPending
+        );
+
+        let now = Clock::get()?.unix_timestamp;
+        require!(
+            now >= registry.verifier_set_change_available_at,
+            SolanIdError::VerifierRotationNotReady
+        );
+        registry.check_verifier_set_change_not_expired(now)?;
+
+        registry.verifier_set = std::mem::take(&mut registry.pending_verifier_set);
+        registry.verifier_threshold = registry.pending_verifier_threshold;
+        registry.pending_verifier_threshold = 0;
+        registry.verifier_set_change_available_at = 0;
+
+        emit!(VerifierSetUpdated {
+            verifier_count: registry.verifier_set.len() as u8,
+            threshold: registry.verifier_threshold,
+        });
+
+        Ok(())
+    }
+
+    /// Sets the root of the verifier key allowlist for `submit_proof`, alongside the
+    /// configured verifier keys. Replacing the root revokes every key only the old
+    /// tree held; zero turns the allowlist off.
+    pub fn set_verifier_merkle_root(
+        ctx: Context<UpdateRegistryConfig>,
+        verifier_merkle_root: [u8; 32],
+    ) -> Result<()> {
+        let registry = &mut ctx.accounts.registry;
+        let old_root = registry.verifier_merkle_root;
+        registry.verifier_merkle_root = verifier_merkle_root;
+
+        emit!(VerifierMerkleRootUpdated {
+            old_root,
+            new_root: verifier_merkle_root,
+        });
+        Ok(())
+    }
+
+    pub fn set_verifier_scheme(
+        ctx: Context<UpdateRegistryConfig>,
+        verifier_scheme: VerifierScheme,
+        verifier_eth_address: [u8; 20],
+    ) -> Result<()> {
+        if verifier_scheme == VerifierScheme::Secp256k1 {
+            require!(verifier_eth_address != [0; 20], SolanIdError::InvalidConfig);
+        }
+
+        let registry = &mut ctx.accounts.registry;
+        registry.verifier_scheme = verifier_scheme;
+        registry.verifier_eth_address = verifier_eth_address;
+        Ok(())
+    }
+
+    pub fn snapshot_config(ctx: Context<SnapshotConfig>, snapshot_id: u64) -> Result<()> {
+        let config_snapshot = &mut ctx.accounts.config_snapshot;
+        config_snapshot.snapshot_id = snapshot_id;
+        config_snapshot.registry = Registry::clone(&ctx.accounts.registry);
+        config_snapshot.scoring_config = ScoringConfig::clone(&ctx.accounts.scoring_config);
+        config_snapshot.created_at = Clock::get()?.unix_timestamp;
+        config_snapshot.bump = ctx.bumps.config_snapshot;
+        config_snapshot.version = ACCOUNT_VERSION;
+        Ok(())
+    }
+
+    pub fn initiate_verifier_rotation(
+        ctx: Context<InitiateVerifierRotation>,
+        new_verifier_authority: Pubkey,
+        delay_seconds: i64,
+    ) -> Result<()> {
+        require!(
+            new_verifier_authority != Pubkey::default(),
+            SolanIdError::InvalidConfig
+        );
+        require!(delay_seconds >= 1, SolanIdError::InvalidConfig);
+
+        let registry = &mut ctx.accounts.registry;
+        let now = Clock::get()?.unix_timestamp;
+        registry.pending_verifier_authority = new_verifier_authority;
+        registry.verifier_rotation_available_at = now
+            .checked_add(delay_seconds)
+            .ok_or(SolanIdError::Overflow)?;
+
+        emit!(VerifierRotationInitiated {
+            current_verifier: registry.verifier_authority,
+            pending_verifier: registry.pending_verifier_authority,
+            activate_at: registry.verifier_rotation_available_at,
+        });
+
+        Ok(())
+    }
+
+    pub fn finalize_verifier_rotation(ctx: Context<FinalizeVerifierRotation>) -> Result<()> {
+        let registry = &mut ctx.accounts.registry;
+        require!(
+            registry.pending_verifier_authority != Pubkey::default(),
+            SolanIdError::NoVerifierRotationPending
+        );
+
+        let now = Clock::get()?.unix_timestamp;
+        require!(
+            now >= registry.verifier_rotation_available_at,
+            SolanIdError::VerifierRotationNotReady
+        );
+        registry.check_rotation_not_expired(now)?;
+
+        let old_verifier = registry.verifier_authority;
+        registry.verifier_authority = registry.pending_verifier_authority;
+        registry.pending_verifier_authority = Pubkey::default();
+        registry.verifier_rotation_available_at = 0;
+
+        emit!(VerifierRotationFinalized {
+            old_verifier,
+            new_verifier: registry.verifier_authority,
+        });
+
+        Ok(())
+    }
+
+    /// Sets the cold key allowed to call `emergency_rotate_verifier`. A default
+    /// `guardian` disables emergency rotation.
+    pub fn set_guardian(ctx: Context<UpdateRegistryConfig>, guardian: Pubkey) -> Result<()> {
+        let registry = &mut ctx.accounts.registry;
+        let old_guardian = registry.guardian;
+        registry.guardian = guardian;
+
+        emit!(GuardianUpdated {
+            old_guardian,
+            new_guardian: guardian,
+        });
+        Ok(())
+    }
+
+    /// Replaces a leaking verifier immediately, skipping the rotation timelock. Only
+    /// the guardian may call this. The new key becomes the only one accepted: see
+    /// `Registry::reset_verifiers`.
+    pub fn emergency_rotate_verifier(
+        ctx: Context<EmergencyRotateVerifier>,
+        new_verifier_authority: Pubkey,
+    ) -> Result<()> {
+        require!(
+            new_verifier_authority != Pubkey::default(),
+            SolanIdError::InvalidConfig
+        );
+
+        let registry = &mut ctx.accounts.registry;
+        let old_verifier = registry.verifier_authority;
+        registry.reset_verifiers(new_verifier_authority);
+
+        emit!(EmergencyVerifierRotation {
+            guardian: ctx.accounts.guardian.key(),
+            old_verifier,
+            new_verifier: new_verifier_authority,
+        });
+
+        Ok(())
+    }
+
+    /// Aborts a pending rotation or verifier set change before it is finalized,
+    /// leaving the current verifiers in place.
+    pub fn cancel_verifier_rotation(ctx: Context<CancelVerifierRotation>) -> Result<()> {
+        let registry = &mut ctx.accounts.registry;
+        require!(
+            registry.pending_verifier_authority != Pubkey::default()
+                || registry.verifier_set_change_available_at != 0,
+            SolanIdError::NoVerifierRotationPending
+        );
+
+        let cancelled_verifier = registry.pending_verifier_authority;
+        registry.pending_verifier_authority = Pubkey::default();
+        registry.verifier_rotation_available_at = 0;
+        registry.pending_verifier_set = Vec::new();
+        registry.pending_verifier_threshold = 0;
+        registry.verifier_set_change_available_at = 0;
+
+        emit!(VerifierRotationCancelled {
+            current_verifier: registry.verifier_authority,
+            cancelled_verifier,
+        });
+
+        Ok(())
+    }
+}
+
+#[derive(Accounts)]
+pub struct InitializeRegistry<'info> {
+    #[account(
+        init,
+        payer = authority,
+        space = 8 + Registry::INIT_SPACE,
+        seeds = [b"registry"],
+        bump
+    )]
+    pub registry: Account<'info, Registry>,
+    #[account(mut)]
+    pub authority: Signer<'info>,
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+#[instruction(
+    proof_hash: [u8; 32],
+    source: ProofSource,
+    identity_nullifier: [u8; 32],
+    attestation_nonce: u64
+)]
+pub struct SubmitProof<'info> {
+    #[account(mut)]
+    pub registry: Account<'info, Registry>,
+    #[account(
+        init_if_needed,
+        payer = payer,
+        space = 8 + UserProof::INIT_SPACE,
+        seeds = [b"user_proof", user.key().as_ref()],
+        bump
+    )]
+    pub user_proof: Account<'info, UserProof>,
+    #[account(
+        init_if_needed,
+        payer = payer,
+        space = 8 + IndividualProof::INIT_SPACE,
+        seeds = [b"individual_proof", user.key().as_ref(), &[source as u8]],
+        bump
+    )]
+    pub individual_proof: Box<Account<'info, IndividualProof>>,
+    #[account(
+        init_if_needed,
+        payer = payer,
+        space = 8 + IdentityNullifierRegistry::INIT_SPACE,
+        seeds = [
+            b"identity_nullifier".as_ref(),
+            &[source as u8],
+            identity_nullifier.as_ref(),
+        ],
+        bump
+    )]
+    pub identity_nullifier_registry: Account<'info, IdentityNullifierRegistry>,
+    #[account(
+        init_if_needed,
+        payer = payer,
+        space = 8 + ProofHashRegistry::INIT_SPACE,
+        seeds = [
+            b"proof_hash".as_ref(),
+            registry.proof_hash_owner(user.key()).as_ref(),
+            proof_hash.as_ref(),
+        ],
+        bump
+    )]
+    pub proof_hash_registry: Box<Account<'info, ProofHashRegistry>>,
+    /// Omitted when the registry uses sequential nonces.
+    #[account(
+        init_if_needed,
+        payer = payer,
+        space = 8 + AttestationNonceRegistry::INIT_SPACE,
+        seeds = [
+            b"attestation_nonce",
+            registry.key().as_ref(),
+            &attestation_nonce.to_le_bytes(),
+        ],
+        bump
+    )]
+    pub attestation_nonce_registry: Option<Account<'info, AttestationNonceRegistry>>,
+    /// CHECK: May not exist yet; read through `is_banned`.
+    #[account(seeds = [b"user_status", user.key().as_ref()], bump)]
+    pub user_status: UncheckedAccount<'info>,
+    pub scoring_config: Account<'info, ScoringConfig>,
+    /// CHECK: May not exist yet; read through `load_decay_config`.
+    #[account(seeds = [b"decay_config"], bump)]
+    pub decay_config: UncheckedAccount<'info>,
+    /// CHECK: Verified via sysvar instructions address constraint.
+    #[account(address = anchor_lang::solana_program::sysvar::instructions::id())]
+    pub instructions_sysvar: UncheckedAccount<'info>,
+    /// CHECK: Verified via slot hashes sysvar address constraint.
+    #[account(address = anchor_lang::solana_program::sysvar::slot_hashes::id())]
+    pub slot_hashes: UncheckedAccount<'info>,
+    /// CHECK: Receives the submission fee. Required while the registry charges one.
+    #[account(mut, address = registry.fee_destination @ SolanIdError::InvalidFeeDestination)]
+    pub fee_destination: Option<UncheckedAccount<'info>>,
+    /// Refreshed after the proof is recorded when the user has opted in.
+    #[account(
+        mut,
+        seeds = [b"status", user.key().as_ref()],
+        bump = status_cache.bump
+    )]
+    pub status_cache: Option<Box<Account<'info, StatusCache>>>,
+    /// Owns the proof; every PDA derives from this key and the attestation is bound
+    /// to it. Pays the submission fee.
+    #[account(mut)]
+    pub user: Signer<'info>,
+    /// Covers rent for any new accounts. Either the user or a relayer submitting on
+    /// their behalf.
+    #[account(mut)]
+    pub payer: Signer<'info>,
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct SubmitProofsBatch<'info> {
+    #[account(mut)]
+    pub registry: Account<'info, Registry>,
+    #[account(
+        init_if_needed,
+        payer = user,
+        space = 8 + UserProof::INIT_SPACE,
+        seeds = [b"user_proof", user.key().as_ref()],
+        bump
+    )]
+    pub user_proof: Account<'info, UserProof>,
+    /// CHECK: May not exist yet; read through `is_banned`.
+    #[account(seeds = [b"user_status", user.key().as_ref()], bump)]
+    pub user_status: UncheckedAccount<'info>,
+    pub scoring_config: Account<'info, ScoringConfig>,
+    /// CHECK: May not exist yet; read through `load_decay_config`.
+    #[account(seeds = [b"decay_config"], bump)]
+    pub decay_config: UncheckedAccount<'info>,
+    /// CHECK: Verified via sysvar instructions address constraint.
+    #[account(address = anchor_lang::solana_program::sysvar::instructions::id())]
+    pub instructions_sysvar: UncheckedAccount<'info>,
+    /// CHECK: Verified via slot hashes sysvar address constraint.
+    #[account(address = anchor_lang::solana_program::sysvar::slot_hashes::id())]
+    pub slot_hashes: UncheckedAccount<'info>,
+    /// CHECK: Receives the submission fee. Required while the registry charges one.
+    #[account(mut, address = registry.fee_destination @ SolanIdError::InvalidFeeDestination)]
+    pub fee_destination: Option<UncheckedAccount<'info>>,
+    /// Refreshed after the whole batch is recorded when the user has opted in.
+    #[account(
+        mut,
+        seeds = [b"status", user.key().as_ref()],
+        bump = status_cache.bump
+    )]
+    pub status_cache: Option<Box<Account<'info, StatusCache>>>,
+    #[account(mut)]
+    pub user: Signer<'info>,
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+#[instruction(source: ProofSource)]
+pub struct RevokeProof<'info> {
+    #[account(mut)]
+    pub registry: Account<'info, Registry>,
+    #[account(
+        mut,
+        seeds = [b"user_proof", user.key().as_ref()],
+        bump = user_proof.bump
+    )]
+    pub user_proof: Account<'info, UserProof>,
+    #[account(
+        mut,
+        seeds = [b"individual_proof", user.key().as_ref(), &[source as u8]],
+        bump = individual_proof.bump
+    )]
+    pub individual_proof: Account<'info, IndividualProof>,
+    #[account(
+        mut,
+        seeds = [
+            b"identity_nullifier".as_ref(),
+            &[source as u8],
+            individual_proof.identity_nullifier.as_ref(),
+        ],
+        bump = identity_nullifier_registry.bump
+    )]
+    pub identity_nullifier_registry: Account<'info, IdentityNullifierRegistry>,
+    #[account(seeds = [b"scoring_config"], bump = scoring_config.bump)]
+    pub scoring_config: Account<'info, ScoringConfig>,
+    /// CHECK: May not exist yet; read through `load_decay_config`.
+    #[account(seeds = [b"decay_config"], bump)]
+    pub decay_config: UncheckedAccount<'info>,
+    /// CHECK: May not exist yet; read through `is_banned`.
+    #[account(seeds = [b"user_status", user.key().as_ref()], bump)]
+    pub user_status: UncheckedAccount<'info>,
+    /// Refreshed after the revocation when the user has opted in.
+    #[account(
+        mut,
+        seeds = [b"status", user.key().as_ref()],
+        bump = status_cache.bump
+    )]
+    pub status_cache: Option<Account<'info, StatusCache>>,
+    #[account(mut)]
+    pub user: Signer<'info>,
+}
+
+#[derive(Accounts)]
+pub struct RevokeAll<'info> {
+    #[account(seeds = [b"registry"], bump = registry.bump)]
+    pub registry: Account<'info, Registry>,
+    #[account(
+        mut,
+        seeds = [b"user_proof", user.key().as_ref()],
+        bump = user_proof.bump
+    )]
+    pub user_proof: Account<'info, UserProof>,
+    #[account(seeds = [b"scoring_config"], bump = scoring_config.bump)]
+    pub scoring_config: Account<'info, ScoringConfig>,
+    /// CHECK: May not exist yet; read through `load_decay_config`.
+    #[account(seeds = [b"decay_config"], bump)]
+    pub decay_config: UncheckedAccount<'info>,
+    /// CHECK: May not exist yet; read through `is_banned`.
+    #[account(seeds = [b"user_status", user.key().as_ref()], bump)]
+    pub user_status: UncheckedAccount<'info>,
+    /// Refreshed after the revocation when the user has opted in.
+    #[account(
+        mut,
+        seeds = [b"status", user.key().as_ref()],
+        bump = status_cache.bump
+    )]
+    pub status_cache: Option<Account<'info, StatusCache>>,
+    pub user: Signer<'info>,
+}
+
+#[derive(Accounts)]
+#[instruction(source: ProofSource)]
+pub struct AdminRevokeProof<'info> {
+    #[account(
+        seeds = [b"registry"],
+        bump = registry.bump,
+        has_one = authority @ SolanIdError::Unauthorized
+    )]
+    pub registry: Account<'info, Registry>,
+    #[account(
+        mut,
+        seeds = [b"user_proof", user.key().as_ref()],
+        bump = user_proof.bump
+    )]
+    pub user_proof: Account<'info, UserProof>,
+    #[account(
+        mut,
+        seeds = [b"individual_proof", user.key().as_ref(), &[source as u8]],
+        bump = individual_proof.bump
+    )]
+    pub individual_proof: Account<'info, IndividualProof>,
+    #[account(
+        mut,
+        seeds = [
+            b"identity_nullifier".as_ref(),
+            &[source as u8],
+            individual_proof.identity_nullifier.as_ref(),
+        ],
+        bump = identity_nullifier_registry.bump
+    )]
+    pub identity_nullifier_registry: Account<'info, IdentityNullifierRegistry>,
+    #[account(seeds = [b"scoring_config"], bump = scoring_config.bump)]
+    pub scoring_config: Account<'info, ScoringConfig>,
+    /// CHECK: Only used to derive the proof PDAs; the proof must belong to this key.
+    pub user: UncheckedAccount<'info>,
+    pub authority: Signer<'info>,
+}
+
+#[derive(Accounts)]
+pub struct MigrateAccount<'info> {
+    /// CHECK: Owner and discriminator are checked before the account is rewritten.
+    #[account(mut)]
+    pub account: UncheckedAccount<'info>,
+    #[account(mut)]
+    pub payer: Signer<'info>,
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+#[instruction(source: ProofSource, attestation_nonce: u64)]
+pub struct MarkIdentitySoulbound<'info> {
+    #[account(seeds = [b"registry"], bump = registry.bump)]
+    pub registry: Account<'info, Registry>,
+    #[account(
+        mut,
+        seeds = [b"user_proof", user.key().as_ref()],
+        bump = user_proof.bump
+    )]
+    pub user_proof: Account<'info, UserProof>,
+    #[account(
+        seeds = [b"individual_proof", user.key().as_ref(), &[source as u8]],
+        bump = individual_proof.bump
+    )]
+    pub individual_proof: Account<'info, IndividualProof>,
+    #[account(
+        mut,
+        seeds = [
+            b"identity_nullifier".as_ref(),
+            &[source as u8],
+            individual_proof.identity_nullifier.as_ref(),
+        ],
+        bump = identity_nullifier_registry.bump
+    )]
+    pub identity_nullifier_registry: Account<'info, IdentityNullifierRegistry>,
+    /// Omitted when the registry uses sequential nonces.
+    #[account(
+        init_if_needed,
+        payer = payer,
+        space = 8 + AttestationNonceRegistry::INIT_SPACE,
+        seeds = [
+            b"attestation_nonce",
+            registry.key().as_ref(),
+            &attestation_nonce.to_le_bytes(),
+        ],
+        bump
+    )]
+    pub attestation_nonce_registry: Option<Account<'info, AttestationNonceRegistry>>,
+    /// CHECK: Only used to derive the proof PDAs; the attestation names this key.
+    pub user: UncheckedAccount<'info>,
+    /// CHECK: Verified via sysvar instructions address constraint.
+    #[account(address = anchor_lang::solana_program::sysvar::instructions::id())]
+    pub instructions_sysvar: UncheckedAccount<'info>,
+    #[account(mut)]
+    pub payer: Signer<'info>,
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+#[instruction(source: ProofSource, person_id_hash: [u8; 32], attestation_nonce: u64)]
+pub struct LinkPersonId<'info> {
+    #[account(seeds = [b"registry"], bump = registry.bump)]
+    pub registry: Account<'info, Registry>,
+    #[account(
+        mut,
+        seeds = [b"user_proof", user.key().as_ref()],
+        bump = user_proof.bump
+    )]
+    pub user_proof: Account<'info, UserProof>,
+    #[account(
+        seeds = [b"individual_proof", user.key().as_ref(), &[source as u8]],
+        bump = individual_proof.bump
+    )]
+    pub individual_proof: Account<'info, IndividualProof>,
+    #[account(
+        init_if_needed,
+        payer = payer,
+        space = 8 + PersonRegistry::INIT_SPACE,
+        seeds = [b"person", person_id_hash.as_ref()],
+        bump
+    )]
+    pub person_registry: Account<'info, PersonRegistry>,
+    /// Omitted when the registry uses sequential nonces.
+    #[account(
+        init_if_needed,
+        payer = payer,
+        space = 8 + AttestationNonceRegistry::INIT_SPACE,
+        seeds = [
+            b"attestation_nonce",
+            registry.key().as_ref(),
+            &attestation_nonce.to_le_bytes(),
+        ],
+        bump
+    )]
+    pub attestation_nonce_registry: Option<Account<'info, AttestationNonceRegistry>>,
+    #[account(seeds = [b"scoring_config"], bump = scoring_config.bump)]
+    pub scoring_config: Account<'info, ScoringConfig>,
+    /// CHECK: Only used to derive the proof PDAs; the attestation names this key.
+    pub user: UncheckedAccount<'info>,
+    /// CHECK: Verified via sysvar instructions address constraint.
+    #[account(address = anchor_lang::solana_program::sysvar::instructions::id())]
+    pub instructions_sysvar: UncheckedAccount<'info>,
+    #[account(mut)]
+    pub payer: Signer<'info>,
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+#[instruction(wallet_first_seen: i64, attestation_nonce: u64)]
+pub struct AttestWalletAge<'info> {
+    #[account(seeds = [b"registry"], bump = registry.bump)]
+    pub registry: Account<'info, Registry>,
+    #[account(
+        mut,
+        seeds = [b"user_proof", user.key().as_ref()],
+        bump = user_proof.bump
+    )]
+    pub user_proof: Account<'info, UserProof>,
+    /// Omitted when the registry uses sequential nonces.
+    #[account(
+        init_if_needed,
+        payer = payer,
+        space = 8 + AttestationNonceRegistry::INIT_SPACE,
+        seeds = [
+            b"attestation_nonce",
+            registry.key().as_ref(),
+            &attestation_nonce.to_le_bytes(),
+        ],
+        bump
+    )]
+    pub attestation_nonce_registry: Option<Account<'info, AttestationNonceRegistry>>,
+    #[account(seeds = [b"scoring_config"], bump = scoring_config.bump)]
+    pub scoring_config: Account<'info, ScoringConfig>,
+    /// CHECK: Only used to derive the user proof PDA; the attestation names this key.
+    pub user: UncheckedAccount<'info>,
+    /// CHECK: Verified via sysvar instructions address constraint.
+    #[account(address = anchor_lang::solana_program::sysvar::instructions::id())]
+    pub instructions_sysvar: UncheckedAccount<'info>,
+    #[account(mut)]
+    pub payer: Signer<'info>,
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+#[instruction(source: ProofSource, attestation_nonce: u64)]
+pub struct RevokeProofByVerifier<'info> {
+    #[account(seeds = [b"registry"], bump = registry.bump)]
+    pub registry: Account<'info, Registry>,
+    #[account(
+        mut,
+        seeds = [b"user_proof", user.key().as_ref()],
+        bump = user_proof.bump
+    )]
+    pub user_proof: Account<'info, UserProof>,
+    #[account(
+        mut,
+        seeds = [b"individual_proof", user.key().as_ref(), &[source as u8]],
+        bump = individual_proof.bump
+    )]
+    pub individual_proof: Account<'info, IndividualProof>,
+    #[account(
+        mut,
+        seeds = [
+            b"identity_nullifier".as_ref(),
+            &[source as u8],
+            individual_proof.identity_nullifier.as_ref(),
+        ],
+        bump = identity_nullifier_registry.bump
+    )]
+    pub identity_nullifier_registry: Account<'info, IdentityNullifierRegistry>,
+    /// Omitted when the registry uses sequential nonces.
+    #[account(
+        init_if_needed,
+        payer = payer,
+        space = 8 + AttestationNonceRegistry::INIT_SPACE,
+        seeds = [
+            b"attestation_nonce",
+            registry.key().as_ref(),
+            &attestation_nonce.to_le_bytes(),
+        ],
+        bump
+    )]
+    pub attestation_nonce_registry: Option<Account<'info, AttestationNonceRegistry>>,
+    #[account(seeds = [b"scoring_config"], bump = scoring_config.bump)]
+    pub scoring_config: Account<'info, ScoringConfig>,
+    /// CHECK: Only used to derive the proof PDAs; the attestation names this key.
+    pub user: UncheckedAccount<'info>,
+    /// CHECK: Verified via sysvar instructions address constraint.
+    #[account(address = anchor_lang::solana_program::sysvar::instructions::id())]
+    pub instructions_sysvar: UncheckedAccount<'info>,
+    #[account(mut)]
+    pub payer: Signer<'info>,
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+#[instruction(source: ProofSource, base_score: u64, attestation_nonce: u64)]
+pub struct DowngradeProof<'info> {
+    #[account(seeds = [b"registry"], bump = registry.bump)]
+    pub registry: Account<'info, Registry>,
+    #[account(
+        mut,
+        seeds = [b"user_proof", user.key().as_ref()],
+        bump = user_proof.bump
+    )]
+    pub user_proof: Account<'info, UserProof>,
+    #[account(
+        mut,
+        seeds = [b"individual_proof", user.key().as_ref(), &[source as u8]],
+        bump = individual_proof.bump
+    )]
+    pub individual_proof: Account<'info, IndividualProof>,
+    /// Omitted when the registry uses sequential nonces.
+    #[account(
+        init_if_needed,
+        payer = payer,
+        space = 8 + AttestationNonceRegistry::INIT_SPACE,
+        seeds = [
+            b"attestation_nonce",
+            registry.key().as_ref(),
+            &attestation_nonce.to_le_bytes(),
+        ],
+        bump
+    )]
+    pub attestation_nonce_registry: Option<Account<'info, AttestationNonceRegistry>>,
+    #[account(seeds = [b"scoring_config"], bump = scoring_config.bump)]
+    pub scoring_config: Account<'info, ScoringConfig>,
+    /// CHECK: Only used to derive the proof PDAs; the attestation names this key.
+    pub user: UncheckedAccount<'info>,
+    /// CHECK: Verified via sysvar instructions address constraint.
+    #[account(address = anchor_lang::solana_program::sysvar::instructions::id())]
+    pub instructions_sysvar: UncheckedAccount<'info>,
+    #[account(mut)]
+    pub payer: Signer<'info>,
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+#[instruction(source: ProofSource, identity_nullifier: [u8; 32])]
+pub struct BanAndRevoke<'info> {
+    #[account(
+        seeds = [b"registry"],
+        bump = registry.bump,
+        has_one = authority @ SolanIdError::Unauthorized
+    )]
+    pub registry: Account<'info, Registry>,
+    #[account(
+        mut,
+        seeds = [
+            b"identity_nullifier".as_ref(),
+            &[source as u8],
+            identity_nullifier.as_ref(),
+        ],
+        bump = identity_nullifier_registry.bump
+    )]
+    pub identity_nullifier_registry: Account<'info, IdentityNullifierRegistry>,
+    #[account(
+        mut,
+        seeds = [b"user_proof", identity_nullifier_registry.claimed_by.as_ref()],
+        bump = user_proof.bump
+    )]
+    pub user_proof: Account<'info, UserProof>,
+    #[account(seeds = [b"scoring_config"], bump = scoring_config.bump)]
+    pub scoring_config: Account<'info, ScoringConfig>,
+    pub authority: Signer<'info>,
+}
+
+#[derive(Accounts)]
+#[instruction(source: ProofSource)]
+pub struct CloseRevokedProof<'info> {
+    #[account(
+        mut,
+        close = user,
+        seeds = [b"individual_proof", user.key().as_ref(), &[source as u8]],
+        bump = individual_proof.bump
+    )]
+    pub individual_proof: Account<'info, IndividualProof>,
+    #[account(seeds = [b"scoring_config"], bump = scoring_config.bump)]
+    pub scoring_config: Account<'info, ScoringConfig>,
+    #[account(mut)]
+    pub user: Signer<'info>,
+}
+
+#[derive(Accounts)]
+#[instruction(attestation_nonce: u64)]
+pub struct CloseAttestationNonce<'info> {
+    #[account(seeds = [b"registry"], bump = registry.bump)]
+    pub registry: Account<'info, Registry>,
+    #[account(
+        mut,
+        close = user,
+        has_one = user @ SolanIdError::Unauthorized,
+        seeds = [
+            b"attestation_nonce",
+            registry.key().as_ref(),
+            &attestation_nonce.to_le_bytes(),
+        ],
+        bump = attestation_nonce_registry.bump
+    )]
+    pub attestation_nonce_registry: Account<'info, AttestationNonceRegistry>,
+    /// CHECK: Rent destination; must be the submitter recorded on the nonce account.
+    #[account(mut)]
+    pub user: UncheckedAccount<'info>,
+}
+
+#[derive(Accounts)]
+#[instruction(source: ProofSource)]
+pub struct ImportLegacyProof<'info> {
+    /// CHECK: Deserialized manually from the legacy layout; address checked via seeds.
+    #[account(
+        mut,
+        seeds = [b"individual_proof", user.key().as_ref(), &[source as u8]],
+        bump
+    )]
+    pub individual_proof: UncheckedAccount<'info>,
+    #[account(mut)]
+    pub user: Signer<'info>,
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+#[instruction(source: ProofSource, attestation_nonce: u64)]
+pub struct MigrateIdentity<'info> {
+    #[account(mut)]
+    pub registry: Account<'info, Registry>,
+    #[account(
+        mut,
+        seeds = [
+            b"identity_nullifier".as_ref(),
+            &[source as u8],
+            old_individual_proof.identity_nullifier.as_ref(),
+        ],
+        bump = identity_nullifier_registry.bump
+    )]
+    pub identity_nullifier_registry: Box<Account<'info, IdentityNullifierRegistry>>,
+    #[account(
+        mut,
+        seeds = [b"user_proof", old_owner.key().as_ref()],
+        bump = old_user_proof.bump
+    )]
+    pub old_user_proof: Box<Account<'info, UserProof>>,
+    #[account(
+        mut,
+        close = new_owner,
+        seeds = [b"individual_proof", old_owner.key().as_ref(), &[source as u8]],
+        bump = old_individual_proof.bump
+    )]
+    pub old_individual_proof: Box<Account<'info, IndividualProof>>,
+    #[account(
+        init_if_needed,
+        payer = new_owner,
+        space = 8 + UserProof::INIT_SPACE,
+        seeds = [b"user_proof", new_owner.key().as_ref()],
+        bump
+    )]
+    pub new_user_proof: Box<Account<'info, UserProof>>,
+    #[account(
+        init,
+        payer = new_owner,
+        space = 8 + IndividualProof::INIT_SPACE,
+        seeds = [b"individual_proof", new_owner.key().as_ref(), &[source as u8]],
+        bump
+    )]
+    pub new_individual_proof: Box<Account<'info, IndividualProof>>,
+    /// Omitted when the registry uses sequential nonces.
+    #[account(
+        init_if_needed,
+        payer = new_owner,
+        space = 8 + AttestationNonceRegistry::INIT_SPACE,
+        seeds = [
+            b"attestation_nonce",
+            registry.key().as_ref(),
+            &attestation_nonce.to_le_bytes(),
+        ],
+        bump
+    )]
+    pub attestation_nonce_registry: Option<Box<Account<'info, AttestationNonceRegistry>>>,
+    /// CHECK: May not exist yet; read through `load_decay_config`.
+    #[account(seeds = [b"decay_config"], bump)]
+    pub decay_config: UncheckedAccount<'info>,
+    #[account(seeds = [b"scoring_config"], bump = scoring_config.bump)]
+    pub scoring_config: Account<'info, ScoringConfig>,
+    /// CHECK: Only used to derive the old PDAs; ownership is checked against the nullifier registry.
+    pub old_owner: UncheckedAccount<'info>,
+    /// CHECK: Verified via sysvar instructions address constraint.
+    #[account(address = anchor_lang::solana_program::sysvar::instructions::id())]
+    pub instructions_sysvar: UncheckedAccount<'info>,
+    #[account(mut)]
+    pub new_owner: Signer<'info>,
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+#[instruction(source: ProofSource)]
+pub struct TransferIdentity<'info> {
+    #[account(mut, seeds = [b"registry"], bump = registry.bump)]
+    pub registry: Account<'info, Registry>,
+    #[account(
+        mut,
+        seeds = [
+            b"identity_nullifier".as_ref(),
+            &[source as u8],
+            old_individual_proof.identity_nullifier.as_ref(),
+        ],
+        bump = identity_nullifier_registry.bump
+    )]
+    pub identity_nullifier_registry: Box<Account<'info, IdentityNullifierRegistry>>,
+    #[account(
+        mut,
+        seeds = [b"user_proof", old_owner.key().as_ref()],
+        bump = old_user_proof.bump
+    )]
+    pub old_user_proof: Box<Account<'info, UserProof>>,
+    #[account(
+        mut,
+        close = new_owner,
+        seeds = [b"individual_proof", old_owner.key().as_ref(), &[source as u8]],
+        bump = old_individual_proof.bump
+    )]
+    pub old_individual_proof: Box<Account<'info, IndividualProof>>,
+    #[account(
+        init_if_needed,
+        payer = new_owner,
+        space = 8 + UserProof::INIT_SPACE,
+        seeds = [b"user_proof", new_owner.key().as_ref()],
+        bump
+    )]
+    pub new_user_proof: Box<Account<'info, UserProof>>,
+    #[account(
+        init,
+        payer = new_owner,
+        space = 8 + IndividualProof::INIT_SPACE,
+        seeds = [b"individual_proof", new_owner.key().as_ref(), &[source as u8]],
+        bump
+    )]
+    pub new_individual_proof: Box<Account<'info, IndividualProof>>,
+    /// CHECK: May not exist yet; read through `load_decay_config`.
+    #[account(seeds = [b"decay_config"], bump)]
+    pub decay_config: UncheckedAccount<'info>,
+    #[account(seeds = [b"scoring_config"], bump = scoring_config.bump)]
+    pub scoring_config: Account<'info, ScoringConfig>,
+    pub old_owner: Signer<'info>,
+    #[account(mut)]
+    pub new_owner: Signer<'info>,
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct VerifyProof<'info> {
+    /// CHECK: May not exist for a wallet that never submitted; read through
+    /// `load_user_proof`.
+    #[account(seeds = [b"user_proof", user.key().as_ref()], bump)]
+    pub user_proof: UncheckedAccount<'info>,
+    /// CHECK: May not exist yet; read through `is_banned`.
+    #[account(seeds = [b"user_status", user.key().as_ref()], bump)]
+    pub user_status: UncheckedAccount<'info>,
+    pub registry: Account<'info, Registry>,
+    /// CHECK: May not exist yet; read through `load_decay_config`.
+    #[account(seeds = [b"decay_config"], bump)]
+    pub decay_config: UncheckedAccount<'info>,
+    #[account(seeds = [b"scoring_config"], bump = scoring_config.bump)]
+    pub scoring_config: Account<'info, ScoringConfig>,
+    /// CHECK: User account is only used to derive the PDA for user_proof. The user_proof account validation ensures correctness.
+    pub user: UncheckedAccount<'info>,
+}
+
+#[derive(Accounts)]
+pub struct VerifyProofsBatch<'info> {
+    #[account(seeds = [b"registry"], bump = registry.bump)]
+    pub registry: Account<'info, Registry>,
+}
+
+#[derive(Accounts)]
+pub struct EnableStatusCache<'info> {
+    #[account(
+        init,
+        payer = user,
+        space = 8 + StatusCache::INIT_SPACE,
+        seeds = [b"status", user.key().as_ref()],
+        bump
+    )]
+    pub status_cache: Account<'info, StatusCache>,
+    #[account(
+        seeds = [b"user_proof", user.key().as_ref()],
+        bump = user_proof.bump
+    )]
+    pub user_proof: Account<'info, UserProof>,
+    /// CHECK: May not exist yet; read through `is_banned`.
+    #[account(seeds = [b"user_status", user.key().as_ref()], bump)]
+    pub user_status: UncheckedAccount<'info>,
+    #[account(seeds = [b"registry"], bump = registry.bump)]
+    pub registry: Account<'info, Registry>,
+    /// CHECK: May not exist yet; read through `load_decay_config`.
+    #[account(seeds = [b"decay_config"], bump)]
+    pub decay_config: UncheckedAccount<'info>,
+    #[account(seeds = [b"scoring_config"], bump = scoring_config.bump)]
+    pub scoring_config: Account<'info, ScoringConfig>,
+    #[account(mut)]
+    pub user: Signer<'info>,
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct RefreshScore<'info> {
+    #[account(
+        mut,
+        seeds = [b"status", user.key().as_ref()],
+        bump = status_cache.bump
+    )]
+    pub status_cache: Account<'info, StatusCache>,
+    #[account(
+        seeds = [b"user_proof", user.key().as_ref()],
+        bump = user_proof.bump
+    )]
+    pub user_proof: Account<'info, UserProof>,
+    /// CHECK: May not exist yet; read through `is_banned`.
+    #[account(seeds = [b"user_status", user.key().as_ref()], bump)]
+    pub user_status: UncheckedAccount<'info>,
+    #[account(seeds = [b"registry"], bump = registry.bump)]
+    pub registry: Account<'info, Registry>,
+    /// CHECK: May not exist yet; read through `load_decay_config`.
+    #[account(seeds = [b"decay_config"], bump)]
+    pub decay_config: UncheckedAccount<'info>,
+    #[account(seeds = [b"scoring_config"], bump = scoring_config.bump)]
+    pub scoring_config: Account<'info, ScoringConfig>,
+    /// CHECK: Only used to derive the status_cache and user_proof PDAs.
+    pub user: UncheckedAccount<'info>,
+}
+
+#[derive(Accounts)]
+pub struct VerifyAndMark<'info> {
+    #[account(
+        mut,
+        seeds = [b"user_proof", user.key().as_ref()],
+        bump = user_proof.bump
+    )]
+    pub user_proof: Account<'info, UserProof>,
+    /// CHECK: May not exist yet; read through `is_banned`.
+    #[account(seeds = [b"user_status", user.key().as_ref()], bump)]
+    pub user_status: UncheckedAccount<'info>,
+    #[account(seeds = [b"registry"], bump = registry.bump)]
+    pub registry: Account<'info, Registry>,
+    /// CHECK: May not exist yet; read through `load_decay_config`.
+    #[account(seeds = [b"decay_config"], bump)]
+    pub decay_config: UncheckedAccount<'info>,
+    #[account(seeds = [b"scoring_config"], bump = scoring_config.bump)]
+    pub scoring_config: Account<'info, ScoringConfig>,
+    pub user: Signer<'info>,
+}
+
+#[derive(Accounts)]
+pub struct BanUser<'info> {
+    #[account(
+        seeds = [b"registry"],
+        bump = registry.bump,
+        has_one = authority @ SolanIdError::Unauthorized
+    )]
+    pub registry: Account<'info, Registry>,
+    #[account(
+        init_if_needed,
+        payer = authority,
+        space = 8 + UserStatus::INIT_SPACE,
+        seeds = [b"user_status", user.key().as_ref()],
+        bump
+    )]
+    pub user_status: Account<'info, UserStatus>,
+    /// CHECK: User account is only used to derive the PDA for user_status.
+    pub user: UncheckedAccount<'info>,
+    #[account(mut)]
+    pub authority: Signer<'info>,
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+#[instruction(source: ProofSource, identity_nullifier: [u8; 32])]
+pub struct FlagIdentity<'info> {
+    #[account(seeds = [b"registry"], bump = registry.bump)]
+    pub registry: Account<'info, Registry>,
+    #[account(
+        mut,
+        seeds = [
+            b"identity_nullifier".as_ref(),
+            &[source as u8],
+            identity_nullifier.as_ref(),
+        ],
+        bump = identity_nullifier_registry.bump
+    )]
+    pub identity_nullifier_registry: Account<'info, IdentityNullifierRegistry>,
+    #[account(
+        mut,
+        seeds = [b"user_proof", identity_nullifier_registry.claimed_by.as_ref()],
+        bump = user_proof.bump
+    )]
+    pub user_proof: Account<'info, UserProof>,
+    pub flagger: Signer<'info>,
+}
+
+#[derive(Accounts)]
+#[instruction(source: ProofSource, identity_nullifier: [u8; 32])]
+pub struct ClearFlag<'info> {
+    #[account(
+        seeds = [b"registry"],
+        bump = registry.bump,
+        has_one = authority @ SolanIdError::Unauthorized
+    )]
+    pub registry: Account<'info, Registry>,
+    #[account(
+        mut,
+        seeds = [
+            b"identity_nullifier".as_ref(),
+            &[source as u8],
+            identity_nullifier.as_ref(),
+        ],
+        bump = identity_nullifier_registry.bump
+    )]
+    pub identity_nullifier_registry: Account<'info, IdentityNullifierRegistry>,
+    #[account(
+        mut,
+        seeds = [b"user_proof", identity_nullifier_registry.claimed_by.as_ref()],
+        bump = user_proof.bump
+    )]
+    pub user_proof: Account<'info, UserProof>,
+    pub authority: Signer<'info>,
+}
+
+#[derive(Accounts)]
+pub struct UnbanUser<'info> {
+    #[account(
+        seeds = [b"registry"],
+        bump = registry.bump,
+        has_one = authority @ SolanIdError::Unauthorized
+    )]
+    pub registry: Account<'info, Registry>,
+    #[account(
+        mut,
+        seeds = [b"user_status", user.key().as_ref()],
+        bump = user_status.bump
+    )]
+    pub user_status: Account<'info, UserStatus>,
+    /// CHECK: User account is only used to derive the PDA for user_status.
+    pub user: UncheckedAccount<'info>,
+    pub authority: Signer<'info>,
+}
+
+#[derive(Accounts)]
+pub struct SetPrivate<'info> {
+    #[account(
+        mut,
+        seeds = [b"user_proof", user.key().as_ref()],
+        bump = user_proof.bump
+    )]
+    pub user_proof: Account<'info, UserProof>,
+    pub user: Signer<'info>,
+}
+
+#[derive(Accounts)]
+pub struct ReconcileSourceCount<'info> {
+    #[account(seeds = [b"registry"], bump = registry.bump)]
+    pub registry: Account<'info, Registry>,
+    #[account(
+        mut,
+        seeds = [b"user_proof", user.key().as_ref()],
+        bump = user_proof.bump
+    )]
+    pub user_proof: Account<'info, UserProof>,
+    #[account(seeds = [b"scoring_config"], bump = scoring_config.bump)]
+    pub scoring_config: Account<'info, ScoringConfig>,
+    /// CHECK: User account is only used to derive the PDA for user_proof.
+    pub user: UncheckedAccount<'info>,
+}
+
+#[derive(Accounts)]
+pub struct ExpireUserProof<'info> {
+    #[account(seeds = [b"registry"], bump = registry.bump)]
+    pub registry: Account<'info, Registry>,
+    #[account(
+        mut,
+        seeds = [b"user_proof", user.key().as_ref()],
+        bump = user_proof.bump
+    )]
+    pub user_proof: Account<'info, UserProof>,
+    /// CHECK: User account is only used to derive the PDA for user_proof.
+    pub user: UncheckedAccount<'info>,
+}
+
+#[derive(Accounts)]
+#[instruction(source: ProofSource, identity_nullifier: [u8; 32])]
+pub struct ReclaimExpiredNullifier<'info> {
+    #[account(
+        mut,
+        seeds = [
+            b"identity_nullifier".as_ref(),
+            &[source as u8],
+            identity_nullifier.as_ref(),
+        ],
+        bump = identity_nullifier_registry.bump
+    )]
+    pub identity_nullifier_registry: Account<'info, IdentityNullifierRegistry>,
+    #[account(
+        seeds = [b"user_proof", identity_nullifier_registry.claimed_by.as_ref()],
+        bump = user_proof.bump
+    )]
+    pub user_proof: Account<'info, UserProof>,
+}
+
+#[derive(Accounts)]
+pub struct RecomputeBonus<'info> {
+    #[account(seeds = [b"registry"], bump = registry.bump)]
+    pub registry: Account<'info, Registry>,
+    #[account(
+        mut,
+        seeds = [b"user_proof", user.key().as_ref()],
+        bump = user_proof.bump
+    )]
+    pub user_proof: Account<'info, UserProof>,
+    #[account(seeds = [b"scoring_config"], bump = scoring_config.bump)]
+    pub scoring_config: Account<'info, ScoringConfig>,
+    /// CHECK: User account is only used to derive the PDA for user_proof.
+    pub user: UncheckedAccount<'info>,
+}
+
+#[derive(Accounts)]
+pub struct GetVerificationFingerprint<'info> {
+    #[account(
+        seeds = [b"user_proof", user.key().as_ref()],
+        bump = user_proof.bump
+    )]
+    pub user_proof: Account<'info, UserProof>,
+    /// CHECK: User account is only used to derive the PDA for user_proof.
+    pub user: UncheckedAccount<'info>,
+}
+
+#[derive(Accounts)]
+pub struct GetRegistryStats<'info> {
+    #[account(seeds = [b"registry"], bump = registry.bump)]
+    pub registry: Account<'info, Registry>,
+    #[account(seeds = [b"scoring_config"], bump = scoring_config.bump)]
+    pub scoring_config: Account<'info, ScoringConfig>,
+}
+
+#[derive(Accounts)]
+#[instruction(source: ProofSource, identity_nullifier: [u8; 32])]
+pub struct GetNullifierStatus<'info> {
+    /// CHECK: May not exist yet; the handler checks the owner before deserializing.
+    #[account(
+        seeds = [
+            b"identity_nullifier".as_ref(),
+            &[source as u8],
+            identity_nullifier.as_ref(),
+        ],
+        bump
+    )]
+    pub identity_nullifier_registry: UncheckedAccount<'info>,
+}
+
+#[derive(Accounts)]
+pub struct GetCooldownStatus<'info> {
+    /// CHECK: May not exist for a wallet that never submitted; read through
+    /// `load_user_proof`.
+    #[account(seeds = [b"user_proof", user.key().as_ref()], bump)]
+    pub user_proof: UncheckedAccount<'info>,
+    #[account(seeds = [b"registry"], bump = registry.bump)]
+    pub registry: Account<'info, Registry>,
+    /// CHECK: User account is only used to derive the PDA for user_proof.
+    pub user: UncheckedAccount<'info>,
+}
+
+#[derive(Accounts)]
+pub struct PointsToThreshold<'info> {
+    #[account(
+        seeds = [b"user_proof", user.key().as_ref()],
+        bump = user_proof.bump
+    )]
+    pub user_proof: Account<'info, UserProof>,
+    /// CHECK: User account is only used to derive the PDA for user_proof.
+    pub user: UncheckedAccount<'info>,
+}
+
+#[derive(Accounts)]
+pub struct PreviewDiversityBonus<'info> {
+    #[account(seeds = [b"registry"], bump = registry.bump)]
+    pub registry: Account<'info, Registry>,
+    #[account(seeds = [b"scoring_config"], bump = scoring_config.bump)]
+    pub scoring_config: Account<'info, ScoringConfig>,
+}
+
+#[derive(Accounts)]
+pub struct UpdateMinScore<'info> {
+    #[account(
+        mut,
+        seeds = [b"registry"],
+        bump = registry.bump,
+        has_one = authority @ SolanIdError::Unauthorized
+    )]
+    pub registry: Account<'info, Registry>,
+    pub authority: Signer<'info>,
+}
+
+#[derive(Accounts)]
+pub struct InitializeScoringConfig<'info> {
+    #[account(
+        init,
+        payer = authority,
+        space = 8 + ScoringConfig::INIT_SPACE,
+        seeds = [b"scoring_config"],
+        bump
+    )]
+    pub scoring_config: Account<'info, ScoringConfig>,
+    #[account(mut)]
+    pub authority: Signer<'info>,
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct UpdateScoringConfig<'info> {
+    #[account(
+        mut,
+        seeds = [b"scoring_config"],
+        bump = scoring_config.bump,
+        has_one = authority @ SolanIdError::Unauthorized
+    )]
+    pub scoring_config: Account<'info, ScoringConfig>,
+    pub authority: Signer<'info>,
+}
+
+#[derive(Accounts)]
+pub struct InitializeDecayConfig<'info> {
+    #[account(
+        seeds = [b"registry"],
+        bump = registry.bump,
+        has_one = authority @ SolanIdError::Unauthorized
+    )]
+    pub registry: Account<'info, Registry>,
+    #[account(
+        init,
+        payer = authority,
+        space = 8 + DecayConfig::INIT_SPACE,
+        seeds = [b"decay_config"],
+        bump
+    )]
+    pub decay_config: Account<'info, DecayConfig>,
+    #[account(mut)]
+    pub authority: Signer<'info>,
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct UpdateDecayConfig<'info> {
+    #[account(
+        mut,
+        seeds = [b"decay_config"],
+        bump = decay_config.bump,
+        has_one = authority @ SolanIdError::Unauthorized
+    )]
+    pub decay_config: Account<'info, DecayConfig>,
+    pub authority: Signer<'info>,
+}
+
+#[derive(Accounts)]
+pub struct UpdateRegistryConfig<'info> {
+    #[account(
+        mut,
+        seeds = [b"registry"],
+        bump = registry.bump,
+        has_one = authority @ SolanIdError::Unauthorized
+    )]
+    pub registry: Account<'info, Registry>,
+    pub authority: Signer<'info>,
+}
+
+#[derive(Accounts)]
+#[instruction(snapshot_id: u64)]
+pub struct SnapshotConfig<'info> {
+    #[account(
+        init,
+        payer = authority,
+        space = 8 + ConfigSnapshot::INIT_SPACE,
+        seeds = [b"config_snapshot".as_ref(), &snapshot_id.to_le_bytes()],
+        bump
+    )]
+    pub config_snapshot: Box<Account<'info, ConfigSnapshot>>,
+    #[account(
+        seeds = [b"registry"],
+        bump = registry.bump,
+        has_one = authority @ SolanIdError::Unauthorized
+    )]
+    pub registry: Account<'info, Registry>,
+    #[account(
+        seeds = [b"scoring_config"],
+        bump = scoring_config.bump
+    )]
+    pub scoring_config: Account<'info, ScoringConfig>,
+    #[account(mut)]
+    pub authority: Signer<'info>,
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+#[instruction(snapshot_id: u64)]
+pub struct SnapshotScore<'info> {
+    #[account(
+        init,
+        payer = payer,
+        space = 8 + ScoreSnapshot::INIT_SPACE,
+        seeds = [b"snapshot".as_ref(), user.key().as_ref(), &snapshot_id.to_le_bytes()],
+        bump
+    )]
+    pub score_snapshot: Account<'info, ScoreSnapshot>,
+    #[account(
+        seeds = [b"config_snapshot".as_ref(), &snapshot_id.to_le_bytes()],
+        bump = config_snapshot.bump
+    )]
+    pub config_snapshot: Box<Account<'info, ConfigSnapshot>>,
+    #[account(
+        seeds = [b"user_proof", user.key().as_ref()],
+        bump = user_proof.bump
+    )]
+    pub user_proof: Account<'info, UserProof>,
+    /// CHECK: May not exist yet; read through `load_decay_config`.
+    #[account(seeds = [b"decay_config"], bump)]
+    pub decay_config: UncheckedAccount<'info>,
+    /// CHECK: User account is only used to derive the PDAs for user_proof and score_snapshot.
+    pub user: UncheckedAccount<'info>,
+    #[account(mut)]
+    pub payer: Signer<'info>,
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+#[instruction(snapshot_id: u64)]
+pub struct VerifyProofAtSnapshot<'info> {
+    #[account(
+        seeds = [b"snapshot".as_ref(), user.key().as_ref(), &snapshot_id.to_le_bytes()],
+        bump = score_snapshot.bump
+    )]
+    pub score_snapshot: Account<'info, ScoreSnapshot>,
+    #[account(
+        seeds = [b"config_snapshot".as_ref(), &snapshot_id.to_le_bytes()],
+        bump = config_snapshot.bump
+    )]
+    pub config_snapshot: Box<Account<'info, ConfigSnapshot>>,
+    /// CHECK: May not exist for a wallet that never submitted; read through
+    /// `load_user_proof`.
+    #[account(seeds = [b"user_proof", user.key().as_ref()], bump)]
+    pub user_proof: UncheckedAccount<'info>,
+    /// CHECK: May not exist yet; read through `is_banned`.
+    #[account(seeds = [b"user_status", user.key().as_ref()], bump)]
+    pub user_status: UncheckedAccount<'info>,
+    /// CHECK: User account is only used to derive the PDA for score_snapshot.
+    pub user: UncheckedAccount<'info>,
+}
+
+#[derive(Accounts)]
+pub struct InitiateVerifierRotation<'info> {
+    #[account(
+        mut,
+        seeds = [b"registry"],
+        bump = registry.bump,
+        has_one = authority @ SolanIdError::Unauthorized
+    )]
+    pub registry: Account<'info, Registry>,
+    pub authority: Signer<'info>,
+}
+
+#[derive(Accounts)]
+pub struct FinalizeVerifierRotation<'info> {
+    #[account(
+        mut,
+        seeds = [b"registry"],
+        bump = registry.bump,
+        has_one = authority @ SolanIdError::Unauthorized
+    )]
+    pub registry: Account<'info, Registry>,
+    pub authority: Signer<'info>,
+}
+
+#[derive(Accounts)]
+pub struct CancelVerifierRotation<'info> {
+    #[account(
+        mut,
+        seeds = [b"registry"],
+        bump = registry.bump,
+        has_one = authority @ SolanIdError::Unauthorized
+    )]
+    pub registry: Account<'info, Registry>,
+    pub authority: Signer<'info>,
+}
+
+#[derive(Accounts)]
+pub struct EmergencyRotateVerifier<'info> {
+    #[account(
+        mut,
+        seeds = [b"registry"],
+        bump = registry.bump,
+        has_one = guardian @ SolanIdError::Unauthorized
+    )]
+    pub registry: Account<'info, Registry>,
+    pub guardian: Signer<'info>,
+}
+
+#[account]
+#[derive(InitSpace)]
+pub struct Registry {
+    pub authority: Pubkey,
+    pub verifier_authority: Pubkey,
+    pub pending_verifier_authority: Pubkey,
+    pub verifier_rotation_available_at: i64,
+    pub total_verified_users: u64,
+    pub min_score: u64,
+    pub cooldown_period: i64,
+    pub diversity_bonus_percent: u8,
+    pub proof_ttl_seconds: i64,
+    pub max_weighted_score: u64,
+    pub paused: bool,
+    #[max_len(MAX_VERIFIERS)]
+    pub verifier_set: Vec<Pubkey>,
+    pub verifier_threshold: u8,
+    pub min_base_for_bonus: u64,
+    pub verifier_scheme: VerifierScheme,
+    pub verifier_eth_address: [u8; 20],
+    pub rotation_grace_seconds: i64,
+    pub campaign_start: i64,
+    pub campaign_end: i64,
+    pub campaign_bonus_percent: u8,
+    pub slot_hash_binding: bool,
+    pub max_slot_hash_age: u64,
+    pub max_recency_after_resubmit: u8,
+    pub proof_hash_scope: ProofHashScope,
+    pub grace_period_seconds: i64,
+    pub web2_mask: u16,
+    pub web3_mask: u16,
+    pub cross_ecosystem_bonus_percent: u8,
+    pub nonce_retention_seconds: i64,
+    pub max_active_sources: u8,
+    pub global_score_multiplier_bps: u16,
+    pub attestation_version: u8,
+    pub min_attestation_version: u8,
+    pub sequential_nonces: bool,
+    pub pending_min_score: u64,
+    /// Zero when no timelocked min score change is pending.
+    pub min_score_change_available_at: i64,
+    /// Minimum live score for `Bronze`, `Silver`, `Gold` and `Platinum`. All zero until
+    /// the authority configures tiers.
+    pub tier_thresholds: [u64; 4],
+    pub bump: u8,
+    pub version: u8,
+    /// Active sources a user needs on top of `min_score` to verify. Zero on accounts
+    /// migrated from before it existed, which behaves like one.
+    pub min_source_count: u8,
+    /// Lamports charged to the user per submitted proof and sent to `fee_destination`.
+    /// Zero keeps submissions free.
+    pub submission_fee: u64,
+    pub fee_destination: Pubkey,
+    /// Proofs accepted across all users per slot. Zero disables the limit.
+    pub max_submissions_per_slot: u32,
+    /// Slot that `rate_limit_count` is counting submissions for.
+    pub rate_limit_slot: u64,
+    pub rate_limit_count: u32,
+    /// Accepts verifier-attested person ids through `link_person_id`.
+    pub person_binding: bool,
+    /// Bonus for wallets the verifier has seen active for at least
+    /// `min_wallet_age_seconds`. Zero disables it.
+    pub wallet_age_bonus_percent: u8,
+    pub min_wallet_age_seconds: i64,
+    /// May call `flag_identity` alongside the authority. Default when unset.
+    pub watcher: Pubkey,
+    pub permissionless_flagging: bool,
+    /// When set, weights are relative shares of `max_total_score`. See
+    /// `weighted_base_score`.
+    pub normalized_scoring: bool,
+    pub max_total_score: u64,
+    /// Root of an allowlist of extra verifier keys `submit_proof` accepts with a
+    /// merkle proof. Zero disables it.
+    pub verifier_merkle_root: [u8; 32],
+    /// Cold key that may replace the verifier without the rotation delay. Default
+    /// when unset.
+    pub guardian: Pubkey,
+    /// How far ahead of the validator clock an attested timestamp may be, at most
+    /// `MAX_CLOCK_SKEW_TOLERANCE`.
+    pub clock_skew_tolerance: i64,
+    /// Ceiling on a user's aggregate once every bonus is applied. `u64::MAX` when
+    /// uncapped.
+    pub max_aggregated_score: u64,
+    /// Rejects submissions that would leave the aggregate below `min_score`.
+    pub strict_min_score: bool,
+    /// How long an active source must keep its identity before a submission may
+    /// swap in a different one. Zero disables the check.
+    pub min_source_dwell_seconds: i64,
+    /// How old a submission's attestation may be when it lands, independent of how
+    /// long the resulting proof stays valid. Zero leaves only `proof_ttl_seconds`.
+    pub attestation_ttl_seconds: i64,
+    /// Verifiers sign `attestation_digest` of each submission message instead of the
+    /// message itself, keeping the ed25519 instruction at a fixed 32-byte message.
+    pub hashed_attestations: bool,
+    /// Highest trust multiplier, in percent, a verifier may attest for a submission.
+    /// 100 allows only the neutral multiplier.
+    pub max_trust_multiplier: u8,
+    /// Highest weight, in percent, a verifier may attest for a single proof in place
+    /// of the configured source weight. Zero accepts no overrides.
+    pub max_weight_override: u64,
+    /// Sources `submit_proof` accepts, one bit per `ProofSource`. All ones accepts
+    /// every source.
+    pub allowed_sources: u16,
+    /// Seconds past `verifier_rotation_available_at` a pending rotation can still be
+    /// finalized. Zero, as on registries written before it existed, reads as
+    /// `DEFAULT_ROTATION_EXPIRY_SECONDS`.
+    pub rotation_expiry_seconds: i64,
+    /// Verifier set and threshold `finalize_verifier_set_change` installs.
+    #[max_len(MAX_VERIFIERS)]
+    pub pending_verifier_set: Vec<Pubkey>,
+    pub pending_verifier_threshold: u8,
+    /// Zero when no verifier set change is pending.
+    pub verifier_set_change_available_at: i64,
+}
+
+impl Registry {
+    /// Makes `verifier_authority` the sole ed25519 verifier. The verifier set, the
+    /// secp256k1 address and the merkle allowlist are cleared, since any of them
+    /// could still hold the leaking key, and any pending rotation or verifier set
+    /// change is dropped.
+    pub fn reset_verifiers(&mut self, verifier_authority: Pubkey) {
+        self.verifier_authority = verifier_authority;
+        self.verifier_set = Vec::new();
+        self.verifier_threshold = 0;
+        self.verifier_scheme = VerifierScheme::Ed25519;
+        self.verifier_eth_address = [0; 20];
+        self.verifier_merkle_root = [0; 32];
+        self.pending_verifier_authority = Pubkey::default();
+        self.verifier_rotation_available_at = 0;
+        self.pending_verifier_set = Vec::new();
+        self.pending_verifier_threshold = 0;
+        self.verifier_set_change_available_at = 0;
+    }
+
+    /// Rejects an attested timestamp more than `clock_skew_tolerance` ahead of `now`.
+    pub fn check_clock_skew(&self, timestamp: i64, now: i64) -> Result<()> {
+        require!(
+            timestamp
+                <= now
+                    .checked_add(self.clock_skew_tolerance)
+                    .ok_or(SolanIdError::Overflow)?,
+            SolanIdError::InvalidTimestamp
+        );
+        Ok(())
+    }
+
+    /// Rejects an attested timestamp more than `proof_ttl_seconds` before `now`.
+    pub fn check_timestamp_within_ttl(&self, timestamp: i64, now: i64) -> Result<()> {
+        require!(
+            timestamp
+                >= now
+                    .checked_sub(self.proof_ttl_seconds)
+                    .ok_or(SolanIdError::Overflow)?,
+            SolanIdError::ProofExpired
+        );
+        Ok(())
+    }
+
+    /// Rejects a `min_score` above `max_aggregated_score`, which no user could reach.
+    pub fn check_min_score_within_cap(&self, min_score: u64) -> Result<()> {
+        require!(
+            min_score <= self.max_aggregated_score,
+            SolanIdError::InvalidConfig
+        );
+        Ok(())
+    }
+
+    /// Points the aggregate still needs to reach `min_score`. Under
+    /// `strict_min_score` a submission that leaves any gap is rejected, so the user
+    /// does not pay for a proof that keeps them unverified.
+    pub fn check_min_score_gap(&self, aggregated_score: u64) -> Result<u64> {
+        let gap = self.min_score.saturating_sub(aggregated_score);
+        if self.strict_min_score && gap > 0 {
+            msg!("Aggregate is {} points below min_score", gap);
+            return err!(SolanIdError::ScoreBelowThreshold);
+        }
+        Ok(gap)
+    }
+
+    /// Rejects finalizing a rotation left pending past its expiry window, so a key
+    /// initiated long ago can't be activated without initiating it again.
+    pub fn check_rotation_not_expired(&self, now: i64) -> Result<()> {
+        self.check_within_rotation_expiry(self.verifier_rotation_available_at, now)
+    }
+
+    /// The same expiry window for a pending verifier set change.
+    pub fn check_verifier_set_change_not_expired(&self, now: i64) -> Result<()> {
+        self.check_within_rotation_expiry(self.verifier_set_change_available_at, now)
+    }
+
+    fn check_within_rotation_expiry(&self, available_at: i64, now: i64) -> Result<()> {
+        let rotation_expiry_seconds = match self.rotation_expiry_seconds {
+            0 => DEFAULT_ROTATION_EXPIRY_SECONDS,
+            seconds => seconds,
+        };
+        require!(
+            now <= available_at.saturating_add(rotation_expiry_seconds),
+            SolanIdError::VerifierRotationExpired
+        );
+        Ok(())
+    }
+
+    /// Rejects a source whose bit is clear in `allowed_sources`.
+    pub fn check_source_allowed(&self, source: ProofSource) -> Result<()> {
+        require!(
+            self.allowed_sources & source_bit(source) != 0,
+            SolanIdError::SourceNotAllowed
+        );
+        Ok(())
+    }
+
+    /// Rejects a weight override above `max_weight_override`. Zero means no override.
+    pub fn check_weight_override(&self, weight_override: u64) -> Result<()> {
+        require!(
+            weight_override <= self.max_weight_override,
+            SolanIdError::InvalidWeightOverride
+        );
+        Ok(())
+    }
+
+    /// Rejects a zero trust multiplier or one above `max_trust_multiplier`. The
+    /// neutral 100 is always accepted.
+    pub fn check_trust_multiplier(&self, trust_multiplier: u8) -> Result<()> {
+        require!(
+            trust_multiplier == NEUTRAL_TRUST_MULTIPLIER
+                || (trust_multiplier > 0 && trust_multiplier <= self.max_trust_multiplier),
+            SolanIdError::InvalidTrustMultiplier
+        );
+        Ok(())
+    }
+
+    /// Rejects an attestation dated more than `attestation_ttl_seconds` before `now`.
+    pub fn check_attestation_age(&self, timestamp: i64, now: i64) -> Result<()> {
+        require!(
+            self.attestation_ttl_seconds == 0
+                || timestamp >= now.saturating_sub(self.attestation_ttl_seconds),
+            SolanIdError::AttestationExpired
+        );
+        Ok(())
+    }
+
+    /// Rejects replacing a source's identity before `min_source_dwell_seconds` have
+    /// passed since the current one was verified.
+    pub fn check_source_dwell(&self, verified_at: i64, now: i64) -> Result<()> {
+        require!(
+            now >= verified_at.saturating_add(self.min_source_dwell_seconds),
+            SolanIdError::SourceDwellActive
+        );
+        Ok(())
+    }
+
+    /// The key a proof hash account is seeded with: the submitting user, or the
+    /// default key when hashes are unique across all users.
+    pub fn proof_hash_owner(&self, user: Pubkey) -> Pubkey {
+        match self.proof_hash_scope {
+            ProofHashScope::PerUser => user,
+            ProofHashScope::Global => Pubkey::default(),
+        }
+    }
+
+    fn verifier_quorum(&self) -> (&[Pubkey], u8) {
+        if self.verifier_set.is_empty() {
+            (std::slice::from_ref(&self.verifier_authority), 1)
+        } else {
+            (&self.verifier_set, self.verifier_threshold)
+        }
+    }
+}
+
+#[account]
+#[derive(InitSpace)]
+pub struct UserProof {
+    pub user: Pubkey,
+    pub aggregated_score: u64,
+    pub last_submission: i64,
+    pub valid_until: i64,
+    pub active_source_count: u8,
+    pub bonus_applied: bool,
+    pub bump: u8,
+    /// Set by `verify_and_mark` once the user passes verification.
+    pub marked: bool,
+    /// Hides the score and timestamps from `verify_proof`. The account data itself
+    /// stays public.
+    pub private: bool,
+    /// One bit per active `ProofSource`, indexed by its discriminant.
+    pub sources_mask: u16,
+    pub cross_ecosystem_bonus_applied: bool,
+    /// Last attestation nonce consumed while the registry uses sequential nonces.
+    pub last_nonce: u64,
+    /// Bonus percents in effect when the stored aggregate was last computed, so the
+    /// bonuses can be stripped after the registry changes them. Zero on accounts
+    /// written before these were tracked, which fall back to the registry's percents.
+    pub applied_diversity_bonus_percent: u8,
+    pub applied_cross_ecosystem_bonus_percent: u8,
+    pub version: u8,
+    /// Bumped by `expire_user_proof`. Proofs recorded under an earlier epoch no longer
+    /// count toward the aggregate or the active source count.
+    pub expiry_epoch: u32,
+    /// Active sources the verifier has linked to this user's person id. They count as
+    /// one source towards the diversity bonus.
+    pub person_sources_mask: u16,
+    /// Verifier-attested time of the wallet's first observed activity. Zero until
+    /// `attest_wallet_age` records it.
+    pub wallet_first_seen: i64,
+    pub wallet_age_bonus_applied: bool,
+    pub applied_wallet_age_bonus_percent: u8,
+    /// Sources whose identity is flagged for review. Any bit set keeps the user
+    /// unverified.
+    pub flagged_sources_mask: u16,
+    /// Post-bonus total before `max_aggregated_score` clamped it into
+    /// `aggregated_score`, so the bonuses can still be stripped exactly. Zero when
+    /// the cap did not apply.
+    pub pre_cap_score: u64,
+    /// Score each counted source contributes, indexed by its discriminant, so the
+    /// aggregate can be refolded under any `ScoringMode`. Zero on accounts written
+    /// before these were tracked until `reconcile_source_count` rebuilds them.
+    pub source_scores: [u64; MAX_PROOF_SOURCES],
+    /// Mode the stored aggregate was folded under.
+    pub scoring_mode: ScoringMode,
+    /// Every proof recorded for this user, refreshes of an active source included.
+    /// Never reset, not even by `expire_user_proof`.
+    pub total_submissions: u64,
+    /// Every proof revoked from this user, by the user or an admin.
+    pub total_revocations: u32,
+    /// `IndividualProof::expires_at` of each counted source, indexed by its
+    /// discriminant, so verification without the proof accounts still sees a source
+    /// lapse. Zero for sources that set none.
+    pub source_expires_at: [i64; MAX_PROOF_SOURCES],
+}
+
+impl UserProof {
+    /// Earliest `expires_at` among the counted sources, or zero when none set one.
+    pub fn earliest_source_expiry(&self) -> i64 {
+        (0..MAX_PROOF_SOURCES)
+            .filter(|index| self.sources_mask & (1 << index) != 0)
+            .map(|index| self.source_expires_at[index])
+            .filter(|&expires_at| expires_at != 0)
+            .min()
+            .unwrap_or(0)
+    }
+}
+
+#[account]
+#[derive(InitSpace)]
+pub struct IndividualProof {
+    pub user: Pubkey,
+    pub proof_hash: [u8; 32],
+    pub base_score: u64,
+    pub weighted_score: u64,
+    pub source: ProofSource,
+    pub identity_nullifier: [u8; 32],
+    pub proof_data: SourceProofData,
+    pub verified_at: i64,
+    pub is_revoked: bool,
+    pub bump: u8,
+    pub migrated: bool,
+    pub campaign_bonus_applied: bool,
+    pub first_verified_at: i64,
+    pub revoked_at: i64,
+    /// Recency-adjusted score folded into the user's aggregate when this proof was
+    /// last recorded. Replacing or revoking the proof subtracts exactly this amount.
+    pub contributed_score: u64,
+    pub version: u8,
+    /// The owner's `UserProof::expiry_epoch` when this proof was last recorded.
+    pub expiry_epoch: u32,
+    /// Verifier-attested time this source stops counting in `verify_proof`. Zero when
+    /// the attestation set none.
+    pub expires_at: i64,
+    /// Verifier-attested trust multiplier folded into `weighted_score`. Zero on
+    /// proofs recorded before multipliers existed, which is read as neutral.
+    pub trust_multiplier: u8,
+    /// Verifier-attested weight used instead of the configured source weight. Zero
+    /// when the attestation carried none.
+    pub weight_override: u64,
+}
+
+impl IndividualProof {
+    /// Rejects an existing proof stored under another source than the one its PDA was
+    /// derived for, which only a derivation or client bug could produce. A fresh
+    /// account has no source yet and passes.
+    pub fn check_source(&self, source: ProofSource) -> Result<()> {
+        require!(
+            self.user == Pubkey::default() || self.source == source,
+            SolanIdError::SourcePayloadMismatch
+        );
+        Ok(())
+    }
+}
+
+#[derive(AnchorDeserialize, InitSpace)]
+pub struct LegacyIndividualProof {
+    pub user: Pubkey,
+    pub proof_hash: [u8; 32],
+    pub base_score: u64,
+    pub weighted_score: u64,
+    pub source: ProofSource,
+    pub identity_nullifier: [u8; 32],
+    pub proof_data: SourceProofData,
+    pub verified_at: i64,
+    pub is_revoked: bool,
+    pub bump: u8,
+}
+
+/// `Registry` as first deployed, before any field followed `proof_ttl_seconds`.
+#[derive(AnchorDeserialize, InitSpace)]
+pub struct BaselineRegistry {
+    pub authority: Pubkey,
+    pub verifier_authority: Pubkey,
+    pub pending_verifier_authority: Pubkey,
+    pub verifier_rotation_available_at: i64,
+    pub total_verified_users: u64,
+    pub min_score: u64,
+    pub cooldown_period: i64,
+    pub diversity_bonus_percent: u8,
+    pub proof_ttl_seconds: i64,
+    pub bump: u8,
+}
+
+/// Every field the baseline layout lacks takes the value `initialize_registry` gives
+/// it, so an upgraded registry scores and verifies as it did before.
+impl From<BaselineRegistry> for Registry {
+    fn from(baseline: BaselineRegistry) -> Self {
+        Self {
+            authority: baseline.authority,
+            verifier_authority: baseline.verifier_authority,
+            pending_verifier_authority: baseline.pending_verifier_authority,
+            verifier_rotation_available_at: baseline.verifier_rotation_available_at,
+            total_verified_users: baseline.total_verified_users,
+            min_score: baseline.min_score,
+            cooldown_period: baseline.cooldown_period,
+            diversity_bonus_percent: baseline.diversity_bonus_percent,
+            proof_ttl_seconds: baseline.proof_ttl_seconds,
+            max_weighted_score: u64::MAX,
+            paused: false,
+            verifier_set: Vec::new(),
+            verifier_threshold: 1,
+            min_base_for_bonus: 0,
+            verifier_scheme: VerifierScheme::Ed25519,
+            verifier_eth_address: [0; 20],
+            rotation_grace_seconds: 0,
+            campaign_start: 0,
+            campaign_end: 0,
+            campaign_bonus_percent: 0,
+            slot_hash_binding: false,
+            max_slot_hash_age: 0,
+            max_recency_after_resubmit: 100,
+            proof_hash_scope: ProofHashScope::PerUser,
+            grace_period_seconds: 0,
+            web2_mask: DEFAULT_WEB2_MASK,
+            web3_mask: DEFAULT_WEB3_MASK,
+            cross_ecosystem_bonus_percent: 0,
+            nonce_retention_seconds: 0,
+            max_active_sources: PROOF_SOURCE_COUNT as u8,
+            global_score_multiplier_bps: BASIS_POINTS as u16,
+            attestation_version: 1,
+            min_attestation_version: 1,
+            sequential_nonces: false,
+            pending_min_score: 0,
+            min_score_change_available_at: 0,
+            tier_thresholds: [0; 4],
+            bump: baseline.bump,
+            version: 0,
+            min_source_count: 1,
+            submission_fee: 0,
+            fee_destination: Pubkey::default(),
+            max_submissions_per_slot: 0,
+            rate_limit_slot: 0,
+            rate_limit_count: 0,
+            person_binding: false,
+            wallet_age_bonus_percent: 0,
+            min_wallet_age_seconds: 0,
+            watcher: Pubkey::default(),
+            permissionless_flagging: false,
+            normalized_scoring: false,
+            max_total_score: 0,
+            verifier_merkle_root: [0; 32],
+            guardian: Pubkey::default(),
+            clock_skew_tolerance: DEFAULT_CLOCK_SKEW_TOLERANCE,
+            max_aggregated_score: u64::MAX,
+            strict_min_score: false,
+            min_source_dwell_seconds: 0,
+            attestation_ttl_seconds: 0,
+            hashed_attestations: false,
+            max_trust_multiplier: NEUTRAL_TRUST_MULTIPLIER,
+            max_weight_override: 0,
+            allowed_sources: u16::MAX,
+            rotation_expiry_seconds: DEFAULT_ROTATION_EXPIRY_SECONDS,
+            pending_verifier_set: Vec::new(),
+            pending_verifier_threshold: 0,
+            verifier_set_change_available_at: 0,
+        }
+    }
+}
+
+/// `Registry` as laid out before `web2_mask` and `web3_mask` were widened to `u16`.
+#[derive(AnchorDeserialize, InitSpace)]
+pub struct LegacyRegistry {
+    pub authority: Pubkey,
+    pub verifier_authority: Pubkey,
+    pub pending_verifier_authority: Pubkey,
+    pub verifier_rotation_available_at: i64,
+    pub total_verified_users: u64,
+    pub min_score: u64,
+    pub cooldown_period: i64,
+    pub diversity_bonus_percent: u8,
+    pub proof_ttl_seconds: i64,
+    pub max_weighted_score: u64,
+    pub paused: bool,
+    #[max_len(MAX_VERIFIERS)]
+    pub verifier_set: Vec<Pubkey>,
+    pub verifier_threshold: u8,
+    pub min_base_for_bonus: u64,
+    pub verifier_scheme: VerifierScheme,
+    pub verifier_eth_address: [u8; 20],
+    pub rotation_grace_seconds: i64,
+    pub campaign_start: i64,
+    pub campaign_end: i64,
+    pub campaign_bonus_percent: u8,
+    pub slot_hash_binding: bool,
+    pub max_slot_hash_age: u64,
+    pub max_recency_after_resubmit: u8,
+    pub proof_hash_per_user: bool,
+    pub grace_period_seconds: i64,
+    pub web2_mask: u8,
+    pub web3_mask: u8,
+    pub cross_ecosystem_bonus_percent: u8,
+    pub nonce_retention_seconds: i64,
+    pub max_active_sources: u8,
+    pub global_score_multiplier_bps: u16,
+    pub attestation_version: u8,
+    pub min_attestation_version: u8,
+    pub sequential_nonces: bool,
+    pub pending_min_score: u64,
+    pub min_score_change_available_at: i64,
+    pub tier_thresholds: [u64; 4],
+    pub bump: u8,
+    pub version: u8,
+    pub min_source_count: u8,
+    pub submission_fee: u64,
+    pub fee_destination: Pubkey,
+    pub max_submissions_per_slot: u32,
+    pub rate_limit_slot: u64,
+    pub rate_limit_count: u32,
+    pub person_binding: bool,
+    pub wallet_age_bonus_percent: u8,
+    pub min_wallet_age_seconds: i64,
+    pub watcher: Pubkey,
+    pub permissionless_flagging: bool,
+    pub normalized_scoring: bool,
+    pub max_total_score: u64,
+    pub verifier_merkle_root: [u8; 32],
+    pub guardian: Pubkey,
+}
+
+impl From<LegacyRegistry> for Registry {
+    fn from(legacy: LegacyRegistry) -> Self {
+        Self {
+            authority: legacy.authority,
+            verifier_authority: legacy.verifier_authority,
+            pending_verifier_authority: legacy.pending_verifier_authority,
+            verifier_rotation_available_at: legacy.verifier_rotation_available_at,
+            total_verified_users: legacy.total_verified_users,
+            min_score: legacy.min_score,
+            cooldown_period: legacy.cooldown_period,
+            diversity_bonus_percent: legacy.diversity_bonus_percent,
+            proof_ttl_seconds: legacy.proof_ttl_seconds,
+            max_weighted_score: legacy.max_weighted_score,
+            paused: legacy.paused,
+            verifier_set: legacy.verifier_set,
+            verifier_threshold: legacy.verifier_threshold,
+            min_base_for_bonus: legacy.min_base_for_bonus,
+            verifier_scheme: legacy.verifier_scheme,
+            verifier_eth_address: legacy.verifier_eth_address,
+            rotation_grace_seconds: legacy.rotation_grace_seconds,
+            campaign_start: legacy.campaign_start,
+            campaign_end: legacy.campaign_end,
+            campaign_bonus_percent: legacy.campaign_bonus_percent,
+            slot_hash_binding: legacy.slot_hash_binding,
+            max_slot_hash_age: legacy.max_slot_hash_age,
+            max_recency_after_resubmit: legacy.max_recency_after_resubmit,
+            proof_hash_scope: ProofHashScope::PerUser,
+            grace_period_seconds: legacy.grace_period_seconds,
+            web2_mask: legacy.web2_mask.into(),
+            web3_mask: legacy.web3_mask.into(),
+            cross_ecosystem_bonus_percent: legacy.cross_ecosystem_bonus_percent,
+            nonce_retention_seconds: legacy.nonce_retention_seconds,
+            max_active_sources: legacy.max_active_sources,
+            global_score_multiplier_bps: legacy.global_score_multiplier_bps,
+            attestation_version: legacy.attestation_version,
+            min_attestation_version: legacy.min_attestation_version,
+            sequential_nonces: legacy.sequential_nonces,
+            pending_min_score: legacy.pending_min_score,
+            min_score_change_available_at: legacy.min_score_change_available_at,
+            tier_thresholds: legacy.tier_thresholds,
+            bump: legacy.bump,
+            version: legacy.version,
+            min_source_count: legacy.min_source_count,
+            submission_fee: legacy.submission_fee,
+            fee_destination: legacy.fee_destination,
+            max_submissions_per_slot: legacy.max_submissions_per_slot,
+            rate_limit_slot: legacy.rate_limit_slot,
+            rate_limit_count: legacy.rate_limit_count,
+            person_binding: legacy.person_binding,
+            wallet_age_bonus_percent: legacy.wallet_age_bonus_percent,
+            min_wallet_age_seconds: legacy.min_wallet_age_seconds,
+            watcher: legacy.watcher,
+            permissionless_flagging: legacy.permissionless_flagging,
+            normalized_scoring: legacy.normalized_scoring,
+            max_total_score: legacy.max_total_score,
+            verifier_merkle_root: legacy.verifier_merkle_root,
+            guardian: legacy.guardian,
+            clock_skew_tolerance: DEFAULT_CLOCK_SKEW_TOLERANCE,
+            max_aggregated_score: u64::MAX,
+            strict_min_score: false,
+            min_source_dwell_seconds: 0,
+            attestation_ttl_seconds: 0,
+            hashed_attestations: false,
+            max_trust_multiplier: NEUTRAL_TRUST_MULTIPLIER,
+            max_weight_override: 0,
+            allowed_sources: u16::MAX,
+            rotation_expiry_seconds: DEFAULT_ROTATION_EXPIRY_SECONDS,
+            pending_verifier_set: Vec::new(),
+            pending_verifier_threshold: 0,
+            verifier_set_change_available_at: 0,
+        }
+    }
+}
+
+/// `UserProof` as laid out before its source masks were widened to `u16`.
+#[derive(AnchorDeserialize, InitSpace)]
+pub struct LegacyUserProof {
+    pub user: Pubkey,
+    pub aggregated_score: u64,
+    pub last_submission: i64,
+    pub valid_until: i64,
+    pub active_source_count: u8,
+    pub bonus_applied: bool,
+    pub bump: u8,
+    pub marked: bool,
+    pub private: bool,
+    pub sources_mask: u8,
+    pub cross_ecosystem_bonus_applied: bool,
+    pub last_nonce: u64,
+    pub applied_diversity_bonus_percent: u8,
+    pub applied_cross_ecosystem_bonus_percent: u8,
+    pub version: u8,
+    pub expiry_epoch: u32,
+    pub person_sources_mask: u8,
+    pub wallet_first_seen: i64,
+    pub wallet_age_bonus_applied: bool,
+    pub applied_wallet_age_bonus_percent: u8,
+    pub flagged_sources_mask: u8,
+}
+
+impl From<LegacyUserProof> for UserProof {
+    fn from(legacy: LegacyUserProof) -> Self {
+        Self {
+            user: legacy.user,
+            aggregated_score: legacy.aggregated_score,
+            last_submission: legacy.last_submission,
+            valid_until: legacy.valid_until,
+            active_source_count: legacy.active_source_count,
+            bonus_applied: legacy.bonus_applied,
+            bump: legacy.bump,
+            marked: legacy.marked,
+            private: legacy.private,
+            sources_mask: legacy.sources_mask.into(),
+            cross_ecosystem_bonus_applied: legacy.cross_ecosystem_bonus_applied,
+            last_nonce: legacy.last_nonce,
+            applied_diversity_bonus_percent: legacy.applied_diversity_bonus_percent,
+            applied_cross_ecosystem_bonus_percent: legacy.applied_cross_ecosystem_bonus_percent,
+            version: legacy.version,
+            expiry_epoch: legacy.expiry_epoch,
+            person_sources_mask: legacy.person_sources_mask.into(),
+            wallet_first_seen: legacy.wallet_first_seen,
+            wallet_age_bonus_applied: legacy.wallet_age_bonus_applied,
+            applied_wallet_age_bonus_percent: legacy.applied_wallet_age_bonus_percent,
+            flagged_sources_mask: legacy.flagged_sources_mask.into(),
+            pre_cap_score: 0,
+            source_scores: [0; MAX_PROOF_SOURCES],
+            scoring_mode: ScoringMode::Additive,
+            total_submissions: 0,
+            total_revocations: 0,
+            source_expires_at: [0; MAX_PROOF_SOURCES],
+        }
+    }
+}
+
+/// `ScoringConfig` as laid out before its per-source arrays grew to
+/// `MAX_PROOF_SOURCES` slots.
+#[derive(AnchorDeserialize, InitSpace)]
+pub struct LegacyScoringConfig {
+    pub authority: Pubkey,
+    pub weights: [u64; LEGACY_PROOF_SOURCE_COUNT],
+    pub bump: u8,
+    pub reclaim_cooldowns: [i64; LEGACY_PROOF_SOURCE_COUNT],
+    pub min_stamp_count: u16,
+    pub min_passport_score: u16,
+    pub min_model_version: u8,
+    pub min_world_id_level: u8,
+    pub category: [u8; LEGACY_PROOF_SOURCE_COUNT],
+    pub max_base_score: [u64; LEGACY_PROOF_SOURCE_COUNT],
+    pub version: u8,
+    pub diversity_bonus_schedule: [u8; LEGACY_PROOF_SOURCE_COUNT],
+    pub max_issued_at_skew: i64,
+}
+
+impl From<LegacyScoringConfig> for ScoringConfig {
+    fn from(legacy: LegacyScoringConfig) -> Self {
+        Self {
+            authority: legacy.authority,
+            weights: widen_per_source(legacy.weights, [100; MAX_PROOF_SOURCES]),
+            bump: legacy.bump,
+            reclaim_cooldowns: widen_per_source(legacy.reclaim_cooldowns, [0; MAX_PROOF_SOURCES]),
+            min_stamp_count: legacy.min_stamp_count,
+            min_passport_score: legacy.min_passport_score,
+            min_model_version: legacy.min_model_version,
+            min_world_id_level: legacy.min_world_id_level,
+            category: widen_per_source(legacy.category, default_source_categories()),
+            max_base_score: widen_per_source(legacy.max_base_score, [0; MAX_PROOF_SOURCES]),
+            version: legacy.version,
+            diversity_bonus_schedule: widen_per_source(
+                legacy.diversity_bonus_schedule,
+                [legacy.diversity_bonus_schedule[LEGACY_PROOF_SOURCE_COUNT - 1]; MAX_PROOF_SOURCES],
+            ),
+            max_issued_at_skew: legacy.max_issued_at_skew,
+            scoring_mode: ScoringMode::Additive,
+            reject_zero_weight_sources: false,
+        }
+    }
+}
+
+/// `DecayConfig` as laid out before `source_hard_expiry` grew to `MAX_PROOF_SOURCES` slots.
+#[derive(AnchorDeserialize, InitSpace)]
+pub struct LegacyDecayConfig {
+    pub authority: Pubkey,
+    pub age_boundaries: [i64; 4],
+    pub factors: [u64; 4],
+    pub source_hard_expiry: [i64; LEGACY_PROOF_SOURCE_COUNT],
+    pub bump: u8,
+    pub version: u8,
+}
+
+impl From<LegacyDecayConfig> for DecayConfig {
+    fn from(legacy: LegacyDecayConfig) -> Self {
+        Self {
+            authority: legacy.authority,
+            age_boundaries: legacy.age_boundaries,
+            factors: legacy.factors,
+            source_hard_expiry: widen_per_source(legacy.source_hard_expiry, [0; MAX_PROOF_SOURCES]),
+            bump: legacy.bump,
+            version: legacy.version,
+            decay_mode: DecayMode::Bracketed,
+        }
+    }
+}
+
+#[account]
+#[derive(InitSpace)]
+pub struct ProofHashRegistry {
+    pub is_used: bool,
+    pub user: Pubkey,
+    pub source: ProofSource,
+    pub bump: u8,
+    pub version: u8,
+}
+
+impl ProofHashRegistry {
+    /// Records the first use of the hash. Any later submission of it, even with a
+    /// fresh attestation nonce, is a replay.
+    pub fn mark_used(&mut self, user: Pubkey, source: ProofSource, bump: u8) -> Result<()> {
+        require!(!self.is_used, SolanIdError::ProofHashAlreadyUsed);
+        self.is_used = true;
+        self.user = user;
+        self.source = source;
+        self.bump = bump;
+        self.version = ACCOUNT_VERSION;
+        Ok(())
+    }
+}
+
+#[account]
+#[derive(InitSpace)]
+pub struct ScoringConfig {
+    pub authority: Pubkey,
+    /// Percent weight per source. Slots past `PROOF_SOURCE_COUNT` are reserved for
+    /// sources added later and start at 100.
+    pub weights: [u64; MAX_PROOF_SOURCES],
+    pub bump: u8,
+    /// Seconds after a revocation before the same source can be submitted again.
+    pub reclaim_cooldowns: [i64; MAX_PROOF_SOURCES],
+    /// Gitcoin Passport minimums. Zero keeps the baseline non-zero check.
+    pub min_stamp_count: u16,
+    pub min_passport_score: u16,
+    pub min_model_version: u8,
+    /// Lowest accepted World ID verification level: 1 is device, 2 is orb.
+    pub min_world_id_level: u8,
+    /// Diversity category per source. Sources sharing a category count once towards
+    /// the diversity bonus.
+    pub category: [u8; MAX_PROOF_SOURCES],
+    /// Highest accepted base score per source. Zero means no cap.
+    pub max_base_score: [u64; MAX_PROOF_SOURCES],
+    pub version: u8,
+    /// Diversity bonus percent for `i + 1` distinct sources. All zero keeps the
+    /// registry's flat `diversity_bonus_percent`.
+    pub diversity_bonus_schedule: [u8; MAX_PROOF_SOURCES],
+    /// Largest gap in seconds between the attestation `timestamp` and a source's own
+    /// `issued_at`. Zero skips the check.
+    pub max_issued_at_skew: i64,
+    pub scoring_mode: ScoringMode,
+    /// Rejects submissions for a source whose weight is zero instead of recording a
+    /// proof that adds nothing to the score.
+    pub reject_zero_weight_sources: bool,
+}
+
+impl ScoringConfig {
+    /// Rejects an attestation `timestamp` further than `max_issued_at_skew` from the
+    /// `issued_at` the source reported, so a fresh attestation can't wrap an old proof.
+    pub fn check_issued_at_skew(&self, timestamp: i64, issued_at: i64) -> Result<()> {
+        require!(
+            self.max_issued_at_skew == 0
+                || timestamp.abs_diff(issued_at) <= self.max_issued_at_skew as u64,
+            SolanIdError::IssuedAtMismatch
+        );
+        Ok(())
+    }
+
+    /// Diversity bonus percent earned by `distinct_sources`, or `flat_percent` when no
+    /// schedule is configured.
+    pub fn diversity_bonus_percent(&self, distinct_sources: u8, flat_percent: u8) -> u8 {
+        if self
+            .diversity_bonus_schedule
+            .iter()
+            .all(|&percent| percent == 0)
+        {
+            return flat_percent;
+        }
+        match distinct_sources {
+            0 => 0,
+            count => self.diversity_bonus_schedule[(count as usize).min(MAX_PROOF_SOURCES) - 1],
+        }
+    }
+}
+
+#[account]
+#[derive(InitSpace)]
+pub struct DecayConfig {
+    pub authority: Pubkey,
+    pub age_boundaries: [i64; 4],
+    pub factors: [u64; 4],
+    /// Maximum age per `ProofSource` before the source stops counting. Zero disables it.
+    pub source_hard_expiry: [i64; MAX_PROOF_SOURCES],
+    pub bump: u8,
+    pub version: u8,
+    /// How `recency_factor` turns a source's age into a factor.
+    pub decay_mode: DecayMode,
+}
+
+impl Default for DecayConfig {
+    /// The brackets `initialize_decay_config` writes, which also apply while no
+    /// `DecayConfig` account exists.
+    fn default() -> Self {
+        Self {
+            authority: Pubkey::default(),
+            age_boundaries: [2592000, 7776000, 15552000, i64::MAX],
+            factors: [100, 75, 50, 25],
+            source_hard_expiry: [0; MAX_PROOF_SOURCES],
+            bump: 0,
+            version: ACCOUNT_VERSION,
+            decay_mode: DecayMode::Bracketed,
+        }
+    }
+}
+
+/// Wallet-level ban flag, created the first time the user is banned.
+#[account]
+#[derive(InitSpace)]
+pub struct UserStatus {
+    pub user: Pubkey,
+    pub banned: bool,
+    pub bump: u8,
+    pub version: u8,
+}
+
+#[account]
+#[derive(InitSpace)]
+pub struct IdentityNullifierRegistry {
+    pub nullifier: [u8; 32],
+    pub source: ProofSource,
+    pub claimed_by: Pubkey,
+    pub is_burned: bool,
+    pub claimed_at: i64,
+    pub last_proof_hash: [u8; 32],
+    pub bump: u8,
+    pub version: u8,
+    /// Set by `mark_identity_soulbound`; the identity can no longer be migrated.
+    pub soulbound: bool,
+    pub flagged: bool,
+    pub flagged_at: i64,
+    /// Set on the first write. `submit_proof` only treats a registry without it as
+    /// fresh when every other byte is still zero.
+    pub initialized: bool,
+}
+
+impl IdentityNullifierRegistry {
+    /// Whether this registry still needs its first write, as left by `init_if_needed`.
+    /// Errors when it was written without being marked `initialized`.
+    pub fn needs_first_write(&self) -> Result<bool> {
+        needs_first_write(self, self.initialized)
+    }
+
+    /// Clears `claimed_by` once the claiming wallet's proof has been expired for
+    /// `NULLIFIER_RECLAIM_WINDOW_SECONDS`, leaving the rest of the record in place
+    /// for the next claimant.
+    pub fn reclaim_expired(&mut self, valid_until: i64, now: i64) -> Result<()> {
+        require!(!self.is_burned, SolanIdError::IdentityRevokedPermanent);
+        require!(!self.soulbound, SolanIdError::SoulboundIdentity);
+        require!(
+            self.claimed_by != Pubkey::default(),
+            SolanIdError::NullifierNotClaimed
+        );
+        require!(
+            now >= valid_until
+                .checked_add(NULLIFIER_RECLAIM_WINDOW_SECONDS)
+                .ok_or(SolanIdError::Overflow)?,
+            SolanIdError::NullifierReclaimWindowActive
+        );
+        self.claimed_by = Pubkey::default();
+        Ok(())
+    }
+}
+
+/// Wallet a verifier-attested person id was first linked from. Another wallet linking
+/// the same person is rejected.
+#[account]
+#[derive(InitSpace)]
+pub struct PersonRegistry {
+    pub person_id_hash: [u8; 32],
+    pub claimed_by: Pubkey,
+    pub claimed_at: i64,
+    pub bump: u8,
+    pub version: u8,
+}
+
+#[account]
+#[derive(InitSpace)]
+pub struct AttestationNonceRegistry {
+    pub nonce: u64,
+    pub is_used: bool,
+    pub user: Pubkey,
+    pub used_at: i64,
+    pub bump: u8,
+    pub version: u8,
+    /// Set alongside `is_used`. See `IdentityNullifierRegistry::initialized`.
+    pub initialized: bool,
+}
+
+impl AttestationNonceRegistry {
+    /// Whether this nonce still needs its first write, as left by `init_if_needed`.
+    /// Errors when it was written without being marked `initialized`.
+    pub fn needs_first_write(&self) -> Result<bool> {
+        needs_first_write(self, self.initialized)
+    }
+}
+
+/// A never-initialized account must serialize to all zeros; anything else was part
+/// written by a path that skipped the `initialized` marker and is not trusted.
+fn needs_first_write<T: AnchorSerialize>(account: &T, initialized: bool) -> Result<bool> {
+    if initialized {
+        return Ok(false);
+    }
+    let data = account.try_to_vec()?;
+    require!(
+        data.iter().all(|&byte| byte == 0),
+        SolanIdError::PartiallyInitializedAccount
+    );
+    Ok(true)
+}
+
+#[account]
+#[derive(InitSpace)]
+pub struct ConfigSnapshot {
+    pub snapshot_id: u64,
+    pub registry: Registry,
+    pub scoring_config: ScoringConfig,
+    pub created_at: i64,
+    pub bump: u8,
+    pub version: u8,
+}
+
+#[account]
+#[derive(InitSpace)]
+pub struct ScoreSnapshot {
+    pub user: Pubkey,
+    pub snapshot_id: u64,
+    /// Live score at `created_at`, before the registry's global multiplier.
+    pub aggregated_score: u64,
+    pub valid_until: i64,
+    pub created_at: i64,
+    pub bump: u8,
+    pub version: u8,
+    pub computed_at_slot: u64,
+    /// Sources that still added to `aggregated_score` when it was taken.
+    pub source_count: u8,
+}
+
+/// Last status computed for `user`, for programs that want to gate on one small
+/// account. It is only as fresh as the last `submit_proof`, `revoke_proof` or
+/// `refresh_score`: decay, expiry and config changes since `computed_at` are not
+/// reflected, so readers should check `computed_at` against their own bound.
+#[account]
+#[derive(InitSpace)]
+pub struct StatusCache {
+    pub user: Pubkey,
+    pub is_verified: bool,
+    pub tier: ReputationTier,
+    pub live_score: u64,
+    pub computed_at: i64,
+    pub bump: u8,
+    pub version: u8,
+}
+
+struct RecordedProof {
+    previous_score: u64,
+    recency_adjusted_score: u64,
+    weighted_score: u64,
+    was_source_active: bool,
+}
+
+#[allow(clippy::too_many_arguments)]
+fn record_proof(
+    registry: &Registry,
+    scoring_config: &ScoringConfig,
+    decay_config: &DecayConfig,
+    individual_proof: &mut IndividualProof,
+    identity_nullifier_registry: &mut IdentityNullifierRegistry,
+    proof_hash_registry: &mut ProofHashRegistry,
+    attestation_nonce_registry: Option<&mut AttestationNonceRegistry>,
+    bumps: [u8; 4],
+    user: Pubkey,
+    expiry_epoch: u32,
+    submission: ProofSubmission,
+    now: i64,
+) -> Result<RecordedProof> {
+    let ProofSubmission {
+        proof_hash,
+        source,
+        identity_nullifier,
+        attestation_nonce,
+        proof_data,
+        base_score,
+        timestamp,
+        expires_at,
+        trust_multiplier,
+        weight_override,
+        ..
+    } = submission;
+
+    individual_proof.check_source(source)?;
+    validate_source_proof_data(
+        source,
+        &proof_data,
+        base_score,
+        timestamp,
+        scoring_config,
+        registry.clock_skew_tolerance,
+        now,
+    )?;
+
+    if let Some(attestation_nonce_registry) = attestation_nonce_registry.as_deref() {
+        attestation_nonce_registry.needs_first_write()?;
+        require!(
+            !attestation_nonce_registry.is_used,
+            SolanIdError::AttestationNonceAlreadyUsed
+        );
+    }
+
+    proof_hash_registry.mark_used(user, source, bumps[2])?;
+
+    require!(
+        identity_nullifier == extract_identity_nullifier(source, &proof_data)?,
+        SolanIdError::InvalidIdentityNullifier
+    );
+
+    if identity_nullifier_registry.needs_first_write()? {
+        identity_nullifier_registry.nullifier = identity_nullifier;
+        identity_nullifier_registry.source = source;
+        identity_nullifier_registry.claimed_by = user;
+        identity_nullifier_registry.is_burned = false;
+        identity_nullifier_registry.claimed_at = now;
+        identity_nullifier_registry.last_proof_hash = proof_hash;
+        identity_nullifier_registry.bump = bumps[1];
+        identity_nullifier_registry.version = ACCOUNT_VERSION;
+        identity_nullifier_registry.soulbound = false;
+        identity_nullifier_registry.flagged = false;
+        identity_nullifier_registry.flagged_at = 0;
+        identity_nullifier_registry.initialized = true;
+    } else {
+        require!(
+            identity_nullifier_registry.source == source,
+            SolanIdError::InvalidIdentityNullifier
+        );
+        require!(
+            identity_nullifier_registry.nullifier == identity_nullifier,
+            SolanIdError::InvalidIdentityNullifier
+        );
+        require!(
+            identity_nullifier_registry.claimed_by == Pubkey::default()
+                || identity_nullifier_registry.claimed_by == user,
+            SolanIdError::DuplicateIdentityClaim
+        );
+        require!(
+            !identity_nullifier_registry.is_burned,
+            SolanIdError::IdentityRevokedPermanent
+        );
+        if identity_nullifier_registry.claimed_by == Pubkey::default() {
+            identity_nullifier_registry.claimed_by = user;
+            identity_nullifier_registry.claimed_at = now;
+        }
+        identity_nullifier_registry.last_proof_hash = proof_hash;
+    }
+
+    registry.check_clock_skew(timestamp, now)?;
+    registry.check_timestamp_within_ttl(timestamp, now)?;
+    registry.check_attestation_age(timestamp, now)?;
+    // The registry TTL stays the upper bound on how long a verifier can vouch for.
+    require!(
+        expires_at == 0
+            || (expires_at > now
+                && expires_at
+                    <= timestamp
+                        .checked_add(registry.proof_ttl_seconds)
+                        .ok_or(SolanIdError::Overflow)?),
+        SolanIdError::InvalidProofExpiry
+    );
+
+    registry.check_trust_multiplier(trust_multiplier)?;
+    registry.check_weight_override(weight_override)?;
+    let mut weighted_score = apply_trust_multiplier(
+        weighted_base_score(
+            base_score,
+            source,
+            weight_override,
+            registry,
+            scoring_config,
+        )?,
+        trust_multiplier,
+    )?;
+
+    let campaign_bonus_applied = registry.campaign_bonus_percent > 0
+        && timestamp >= registry.campaign_start
+        && timestamp <= registry.campaign_end;
+    if campaign_bonus_applied {
+        weighted_score = scale_score(
+            weighted_score,
+            100 + registry.campaign_bonus_percent as u64,
+            100,
+        )?;
+    }
+    weighted_score = weighted_score.min(registry.max_weighted_score);
+
+    let first_verified_at = if individual_proof.user == Pubkey::default() {
+        timestamp
+    } else {
+        individual_proof.first_verified_at
+    };
+    let recency_adjusted_score = scale_score(
+        weighted_score,
+        capped_recency_factor(
+            source,
+            timestamp,
+            first_verified_at,
+            now,
+            registry,
+            decay_config,
+        ),
+        100,
+    )?;
+
+    if individual_proof.is_revoked {
+        require!(
+            now >= individual_proof
+                .revoked_at
+                .checked_add(
+                    scoring_config.reclaim_cooldowns
+                        [source.as_index(&scoring_config.reclaim_cooldowns)?]
+                )
+                .ok_or(SolanIdError::Overflow)?,
+            SolanIdError::ReclaimCooldownActive
+        );
+    }
+
+    let was_source_active = individual_proof.user != Pubkey::default()
+        && counts_toward_score(individual_proof, expiry_epoch);
+    // Refreshing the same identity is always allowed; only swapping it out waits.
+    if was_source_active && individual_proof.identity_nullifier != identity_nullifier {
+        registry.check_source_dwell(individual_proof.verified_at, now)?;
+    }
+    // Re-deriving the outgoing contribution at `now` would decay it past what was
+    // added, so the aggregate would drift on every refresh.
+    let previous_score = if was_source_active {
+        individual_proof.contributed_score
+    } else {
+        0
+    };
+
+    individual_proof.user = user;
+    individual_proof.proof_hash = proof_hash;
+    individual_proof.base_score = base_score;
+    individual_proof.weighted_score = weighted_score;
+    individual_proof.source = source;
+    individual_proof.identity_nullifier = identity_nullifier;
+    individual_proof.proof_data = proof_data;
+    individual_proof.verified_at = timestamp;
+    individual_proof.first_verified_at = first_verified_at;
+    individual_proof.is_revoked = false;
+    individual_proof.bump = bumps[0];
+    individual_proof.version = ACCOUNT_VERSION;
+    individual_proof.campaign_bonus_applied = campaign_bonus_applied;
+    individual_proof.revoked_at = 0;
+    individual_proof.contributed_score = recency_adjusted_score;
+    individual_proof.expiry_epoch = expiry_epoch;
+    individual_proof.expires_at = expires_at;
+    individual_proof.trust_multiplier = trust_multiplier;
+    individual_proof.weight_override = weight_override;
+
+    if let Some(attestation_nonce_registry) = attestation_nonce_registry {
+        attestation_nonce_registry.nonce = attestation_nonce;
+        attestation_nonce_registry.is_used = true;
+        attestation_nonce_registry.user = user;
+        attestation_nonce_registry.used_at = now;
+        attestation_nonce_registry.bump = bumps[3];
+        attestation_nonce_registry.version = ACCOUNT_VERSION;
+        attestation_nonce_registry.initialized = true;
+
+        emit!(AttestationNonceUsed {
+            nonce: attestation_nonce,
+            user,
+            verifier: registry.verifier_authority,
+            used_at: now,
+        });
+    }
+
+    Ok(RecordedProof {
+        previous_score,
+        recency_adjusted_score,
+        weighted_score,
+        was_source_active,
+    })
+}
+
+/// Scales a weighted score by a verifier-attested trust multiplier in percent; zero is neutral.
+fn apply_trust_multiplier(weighted_score: u64, trust_multiplier: u8) -> Result<u64> {
+    if trust_multiplier == 0 || trust_multiplier == NEUTRAL_TRUST_MULTIPLIER {
+        return Ok(weighted_score);
+    }
+    scale_score(weighted_score, trust_multiplier as u64, 100)
+}
+
+/// Weighted score for one source before campaign bonus and recency. The weight is
+/// the source's configured one, or `weight_override` when the verifier attested one.
+/// Raw mode scales `base_score` by `weight / 100`. Normalized mode scales it by the
+/// source's share of all weights and by how close it is to its `max_base_score`, so
+/// the weighted scores of every source at its cap sum to at most `max_total_score`;
+/// an override there can only lower the configured weight, since a higher one isn't
+/// part of that sum.
+pub fn weighted_base_score(
+    base_score: u64,
+    source: ProofSource,
+    weight_override: u64,
+    registry: &Registry,
+    scoring_config: &ScoringConfig,
+) -> Result<u64> {
+    let index = source.as_index(&scoring_config.weights)?;
+    let weight = match weight_override {
+        0 => scoring_config.weights[index],
+        weight_override => weight_override,
+    };
+    if !registry.normalized_scoring {
+        return scale_score(base_score, weight, 100);
+    }
+    let weight = weight.min(scoring_config.weights[index]);
+    if weight == 0 {
+        return Ok(0);
+    }
+
+    // Without a cap there is no fully-verified reference to normalize against.
+    let max_base_score = scoring_config.max_base_score[index];
+    require!(max_base_score > 0, SolanIdError::InvalidConfig);
+    let total_weight = scoring_config.weights[..PROOF_SOURCE_COUNT]
+        .iter()
+        .try_fold(0u128, |sum, &w| sum.checked_add(w as u128))
+        .ok_or(SolanIdError::Overflow)?;
+
+    let normalized = (base_score.min(max_base_score) as u128)
+        .checked_mul(weight as u128)
+        .and_then(|s| s.checked_mul(registry.max_total_score as u128))
+        .and_then(|s| s.checked_div(total_weight.checked_mul(max_base_score as u128)?))
+        .ok_or(SolanIdError::Overflow)?;
+    u64::try_from(normalized).map_err(|_| SolanIdError::Overflow.into())
+}
+
+#[allow(clippy::too_many_arguments)]
+fn revoke_source(
+    registry: &Registry,
+    scoring_config: &ScoringConfig,
+    user_proof: &mut UserProof,
+    individual_proof: &mut IndividualProof,
+    identity_nullifier_registry: &mut IdentityNullifierRegistry,
+    user: Pubkey,
+    now: i64,
+    burn_nullifier: bool,
+) -> Result<()> {
+    require!(individual_proof.user == user, SolanIdError::Unauthorized);
+
+    require!(
+        !individual_proof.is_revoked,
+        SolanIdError::ProofAlreadyRevoked
+    );
+
+    require!(
+        identity_nullifier_registry.nullifier == individual_proof.identity_nullifier,
+        SolanIdError::InvalidIdentityNullifier
+    );
+    require!(
+        identity_nullifier_registry.claimed_by == user,
+        SolanIdError::Unauthorized
+    );
+
+    // A proof from before the user's last expiry was already zeroed out of the
+    // aggregate, so revoking it only has to flag it.
+    if counts_toward_score(individual_proof, user_proof.expiry_epoch) {
+        let old_base_aggregated_score = strip_score_bonuses(user_proof, registry)?;
+
+        let new_base_aggregated_score =
+            old_base_aggregated_score.saturating_sub(individual_proof.contributed_score);
+
+        user_proof.active_source_count = user_proof.active_source_count.saturating_sub(1);
+        user_proof.sources_mask &= !source_bit(individual_proof.source);
+        user_proof.person_sources_mask &= !source_bit(individual_proof.source);
+        set_source_score(user_proof, individual_proof.source, 0)?;
+        set_source_expiry(user_proof, individual_proof.source, 0)?;
+
+        apply_score_bonuses(
+            user_proof,
+            registry,
+            scoring_config,
+            new_base_aggregated_score,
+            now,
+        )?;
+    }
+
+    individual_proof.is_revoked = true;
+    individual_proof.revoked_at = now;
+    user_proof.total_revocations = user_proof
+        .total_revocations
+        .checked_add(1)
+        .ok_or(SolanIdError::Overflow)?;
+    if burn_nullifier {
+        identity_nullifier_registry.is_burned = true;
+    }
+
+    Ok(())
+}
+
+/// Counts `submissions` against the registry-wide per-slot limit, starting a fresh
+/// count whenever the slot has advanced.
+fn consume_slot_capacity(registry: &mut Registry, slot: u64, submissions: u32) -> Result<()> {
+    if registry.max_submissions_per_slot == 0 {
+        return Ok(());
+    }
+    if registry.rate_limit_slot != slot {
+        registry.rate_limit_slot = slot;
+        registry.rate_limit_count = 0;
+    }
+    let count = registry
+        .rate_limit_count
+        .checked_add(submissions)
+        .ok_or(SolanIdError::Overflow)?;
+    require!(
+        count <= registry.max_submissions_per_slot,
+        SolanIdError::RateLimited
+    );
+    registry.rate_limit_count = count;
+    Ok(())
+}
+
+/// Charges the registry's submission fee for `proof_count` proofs. It runs in the
+/// same instruction as the proofs it pays for, so a failed submission is not charged.
+fn collect_submission_fee<'info>(
+    registry: &Registry,
+    fee_destination: Option<&UncheckedAccount<'info>>,
+    user: &Signer<'info>,
+    system_program: &Program<'info, System>,
+    proof_count: u64,
+) -> Result<()> {
+    if registry.submission_fee == 0 {
+        return Ok(());
+    }
+    let fee_destination = fee_destination.ok_or(SolanIdError::InvalidFeeDestination)?;
+    let fee = registry
+        .submission_fee
+        .checked_mul(proof_count)
+        .ok_or(SolanIdError::Overflow)?;
+    system_program::transfer(
+        CpiContext::new(
+            system_program.to_account_info(),
+            system_program::Transfer {
+                from: user.to_account_info(),
+                to: fee_destination.to_account_info(),
+            },
+        ),
+        fee,
+    )
+}
+
+fn counts_toward_score(individual_proof: &IndividualProof, expiry_epoch: u32) -> bool {
+    !individual_proof.is_revoked && individual_proof.expiry_epoch == expiry_epoch
+}
+
+/// Whether `submit_proof` already recorded this exact submission, so a client
+/// retrying after a timeout gets a no-op instead of a nonce error. The nonce must
+/// have been consumed by `user` and the source's live proof must carry the same
+/// hash; a used nonce arriving with any other payload is still a replay.
+pub fn is_recorded_submission(
+    user_proof: &UserProof,
+    individual_proof: &IndividualProof,
+    attestation_nonce_registry: Option<&AttestationNonceRegistry>,
+    user: Pubkey,
+    source: ProofSource,
+    proof_hash: [u8; 32],
+    attestation_nonce: u64,
+) -> bool {
+    let nonce_consumed = match attestation_nonce_registry {
+        Some(attestation_nonce_registry) => {
+            attestation_nonce_registry.is_used
+                && attestation_nonce_registry.user == user
+                && attestation_nonce_registry.nonce == attestation_nonce
+        }
+        None => user_proof.last_nonce == attestation_nonce,
+    };
+    nonce_consumed
+        && user_proof.user == user
+        && individual_proof.user == user
+        && individual_proof.source == source
+        && individual_proof.proof_hash == proof_hash
+        && counts_toward_score(individual_proof, user_proof.expiry_epoch)
+}
+
+/// A user can only resubmit once `cooldown_period` has passed, so a cooldown that
+/// reaches the TTL lets every proof lapse before it can be refreshed.
+pub fn check_cooldown_within_ttl(cooldown_period: i64, proof_ttl_seconds: i64) -> Result<()> {
+    require!(
+        cooldown_period < proof_ttl_seconds,
+        SolanIdError::CooldownExceedsTtl
+    );
+    Ok(())
+}
+
+/// The proof TTL already bounds how old an attestation may be, so a tighter
+/// `attestation_ttl_seconds` must not exceed it.
+pub fn check_attestation_ttl_within_proof_ttl(
+    attestation_ttl_seconds: i64,
+    proof_ttl_seconds: i64,
+) -> Result<()> {
+    require!(
+        (0..=proof_ttl_seconds).contains(&attestation_ttl_seconds),
+        SolanIdError::InvalidConfig
+    );
+    Ok(())
+}
+
+/// Oldest age, relative to its first use, at which an attestation could still be
+/// accepted: it may be dated up to `clock_skew_tolerance` ahead and then stays
+/// valid for `proof_ttl_seconds`.
+fn max_attestation_age(proof_ttl_seconds: i64, clock_skew_tolerance: i64) -> Result<i64> {
+    proof_ttl_seconds
+        .checked_add(clock_skew_tolerance)
+        .ok_or(SolanIdError::Overflow.into())
+}
+
+/// In sequential mode each attestation must carry the user's next nonce, which
+/// rules out both replays and out-of-order submissions without a per-nonce account.
+fn advance_sequential_nonce(user_proof: &mut UserProof, attestation_nonce: u64) -> Result<()> {
+    require!(
+        Some(attestation_nonce) == user_proof.last_nonce.checked_add(1),
+        SolanIdError::AttestationNonceOutOfOrder
+    );
+    user_proof.last_nonce = attestation_nonce;
+    Ok(())
+}
+
+/// The checks every single verifier attestation shares outside of submissions: the
+/// registry must be live and the attestation fresh, and its nonce is consumed under
+/// the registry's nonce mode, either as the user's next sequential nonce or through
+/// its own `AttestationNonceRegistry`.
+fn consume_attestation(
+    registry: &Registry,
+    user_proof: &mut UserProof,
+    attestation_nonce_registry: Option<&mut Account<AttestationNonceRegistry>>,
+    attestation_nonce_bump: Option<u8>,
+    attestation_nonce: u64,
+    timestamp: i64,
+    now: i64,
+) -> Result<()> {
+    require!(!registry.paused, SolanIdError::RegistryPaused);
+    require!(
+        attestation_nonce_registry.is_none() == registry.sequential_nonces,
+        SolanIdError::NonceModeMismatch
+    );
+    registry.check_clock_skew(timestamp, now)?;
+    registry.check_timestamp_within_ttl(timestamp, now)?;
+
+    let Some(attestation_nonce_registry) = attestation_nonce_registry else {
+        return advance_sequential_nonce(user_proof, attestation_nonce);
+    };
+    attestation_nonce_registry.needs_first_write()?;
+    require!(
+        !attestation_nonce_registry.is_used,
+        SolanIdError::AttestationNonceAlreadyUsed
+    );
+    attestation_nonce_registry.nonce = attestation_nonce;
+    attestation_nonce_registry.is_used = true;
+    attestation_nonce_registry.user = user_proof.user;
+    attestation_nonce_registry.used_at = now;
+    attestation_nonce_registry.bump = attestation_nonce_bump.unwrap_or_default();
+    attestation_nonce_registry.version = ACCOUNT_VERSION;
+    attestation_nonce_registry.initialized = true;
+    Ok(())
+}
+
+/// Index of the executing instruction, which the verifier's ed25519 instruction
+/// must precede.
+fn attested_instruction_index(instruction_sysvar: &AccountInfo) -> Result<usize> {
+    let current_index = load_current_index_checked(instruction_sysvar)
+        .map_err(|_| error!(SolanIdError::InvalidAttestationInstruction))?
+        as usize;
+    require!(
+        current_index > 0,
+        SolanIdError::InvalidAttestationInstruction
+    );
+    Ok(current_index)
+}
+
+fn check_submission_cooldown(user_proof: &UserProof, registry: &Registry, now: i64) -> Result<()> {
+    require!(
+        !cooldown_status(user_proof, registry, now)?.in_cooldown,
+        SolanIdError::CooldownPeriodActive
+    );
+    Ok(())
+}
+
+/// Where `user_proof` stands against the registry's submission cooldown at `now`. A
+/// user that has not submitted yet gets the default, out-of-cooldown status.
+pub fn cooldown_status(
+    user_proof: &UserProof,
+    registry: &Registry,
+    now: i64,
+) -> Result<CooldownStatus> {
+    if user_proof.user == Pubkey::default() {
+        return Ok(CooldownStatus::default());
+    }
+    let next_eligible_at = user_proof
+        .last_submission
+        .checked_add(registry.cooldown_period)
+        .ok_or(SolanIdError::Overflow)?;
+    let seconds_remaining = next_eligible_at.saturating_sub(now).max(0);
+    Ok(CooldownStatus {
+        in_cooldown: seconds_remaining > 0,
+        seconds_remaining,
+        next_eligible_at,
+    })
+}
+
+fn load_or_create_pda<'info, T>(
+    account: &'info AccountInfo<'info>,
+    seeds: &[&[u8]],
+    space: usize,
+    payer: &Signer<'info>,
+    system_program: &Program<'info, System>,
+    program_id: &Pubkey,
+) -> Result<(Account<'info, T>, u8)>
+where
+    T: AccountSerialize + AccountDeserialize + Owner + Clone,
+{
+    let (address, bump) = Pubkey::find_program_address(seeds, program_id);
+    require_keys_eq!(account.key(), address, SolanIdError::InvalidBatchAccounts);
+
+    if account.owner == &system_program::ID {
+        let bump_seed = [bump];
+        let mut signer_seeds = seeds.to_vec();
+        signer_seeds.push(&bump_seed);
+        let rent = Rent::get()?.minimum_balance(space);
+        let current_lamports = account.lamports();
+        if current_lamports == 0 {
+            system_program::create_account(
+                CpiContext::new_with_signer(
+                    system_program.to_account_info(),
+                    system_program::CreateAccount {
+                        from: payer.to_account_info(),
+                        to: account.clone(),
+                    },
+                    &[&signer_seeds],
+                ),
+                rent,
+                space as u64,
+                program_id,
+            )?;
+        } else {
+            // Anyone can send lamports to a derivable address, and `create_account`
+            // refuses a funded one; top up the rent and claim it the way `init` does.
+            let rent_shortfall = rent.saturating_sub(current_lamports);
+            if rent_shortfall > 0 {
+                system_program::transfer(
+                    CpiContext::new(
+                        system_program.to_account_info(),
+                        system_program::Transfer {
+                            from: payer.to_account_info(),
+                            to: account.clone(),
+                        },
+                    ),
+                    rent_shortfall,
+                )?;
+            }
+            system_program::allocate(
+                CpiContext::new_with_signer(
+                    system_program.to_account_info(),
+                    system_program::Allocate {
+                        account_to_allocate: account.clone(),
+                    },
+                    &[&signer_seeds],
+                ),
+                space as u64,
+            )?;
+            system_program::assign(
+                CpiContext::new_with_signer(
+                    system_program.to_account_info(),
+                    system_program::Assign {
+                        account_to_assign: account.clone(),
+                    },
+                    &[&signer_seeds],
+                ),
+                program_id,
+            )?;
+        }
+        return Ok((Account::try_from_unchecked(account)?, bump));
+    }
+
+    Ok((Account::try_from(account)?, bump))
+}
+
+fn is_hard_expired(source: ProofSource, age_seconds: i64, decay_config: &DecayConfig) -> bool {
+    let Ok(index) = source.as_index(&decay_config.source_hard_expiry) else {
+        return false;
+    };
+    let hard_expiry = decay_config.source_hard_expiry[index];
+    hard_expiry > 0 && age_seconds >= hard_expiry
+}
+
+/// Percent of a source's weighted score kept at `age_seconds`. Bracketed mode steps
+/// through `factors` at each of `age_boundaries`. Linear mode glides from
+/// `factors[0]` down to the `factors[3]` floor, reaching it at `age_boundaries[2]`
+/// where the last bracket starts. Exponential mode halves the distance to the floor
+/// every `age_boundaries[0]` seconds, interpolating linearly within each half-life.
+pub fn recency_factor(age_seconds: i64, decay_config: &DecayConfig) -> u64 {
+    let boundaries = &decay_config.age_boundaries;
+    let factors = &decay_config.factors;
+    let (start, floor) = (factors[0], factors[3]);
+    let age = age_seconds.max(0) as u128;
+    let gap = start.saturating_sub(floor) as u128;
+
+    match decay_config.decay_mode {
+        DecayMode::Bracketed => boundaries
+            .iter()
+            .position(|boundary| age_seconds < *boundary)
+            .map_or(factors[3], |i| factors[i]),
+        DecayMode::Linear => {
+            let span = boundaries[2].max(1) as u128;
+            if age >= span {
+                return floor;
+            }
+            floor + (gap - gap * age / span) as u64
+        }
+        DecayMode::Exponential => {
+            let half_life = boundaries[0].max(1) as u128;
+            let halvings = age / half_life;
+            if halvings >= 64 {
+                return floor;
+            }
+            let gap = gap >> halvings;
+            let into = age % half_life;
+            floor + (gap - gap * into / (2 * half_life)) as u64
+        }
+    }
+}
+
+/// Buckets a live score into the registry's tiers. Banned users and registries
+/// without tiers get `ReputationTier::None`.
+fn reputation_tier(registry: &Registry, live_score: u64, banned: bool) -> ReputationTier {
+    if banned || registry.tier_thresholds[0] == 0 {
+        return ReputationTier::None;
+    }
+
+    let reached = registry
+        .tier_thresholds
+        .iter()
+        .take_while(|threshold| live_score >= **threshold)
+        .count();
+    match reached {
+        0 => ReputationTier::None,
+        1 => ReputationTier::Bronze,
+        2 => ReputationTier::Silver,
+        3 => ReputationTier::Gold,
+        _ => ReputationTier::Platinum,
+    }
+}
+
+/// Rewrites the user's opt-in `StatusCache` with what `verify_proof` and `get_tier`
+/// would report at `now`.
+#[allow(clippy::too_many_arguments)]
+fn refresh_status_cache<'info>(
+    status_cache: &mut StatusCache,
+    user_proof: &UserProof,
+    registry: &Registry,
+    scoring_config: &ScoringConfig,
+    decay_config: &DecayConfig,
+    banned: bool,
+    remaining_accounts: &'info [AccountInfo<'info>],
+    program_id: &Pubkey,
+    now: i64,
+) -> Result<()> {
+    let status = proof_status(
+        user_proof,
+        registry,
+        scoring_config,
+        decay_config,
+        banned,
+        registry.min_score,
+        remaining_accounts,
+        program_id,
+        now,
+    )?;
+    status_cache.is_verified = status.is_verified;
+    status_cache.tier = reputation_tier(registry, status.live_score, banned);
+    status_cache.live_score = status.live_score;
+    status_cache.computed_at = now;
+    Ok(())
+}
+
+/// Reads the user's `UserStatus` PDA, which only exists once the user has been
+/// banned at least once.
+fn is_banned(user_status: &AccountInfo, program_id: &Pubkey) -> Result<bool> {
+    if user_status.owner != program_id || user_status.data_is_empty() {
+        return Ok(false);
+    }
+
+    let user_status = UserStatus::try_deserialize(&mut &user_status.try_borrow_data()?[..])?;
+    Ok(user_status.banned)
+}
+
+/// Reads the `DecayConfig` PDA, falling back to the default brackets until
+/// `initialize_decay_config` has run.
+fn load_decay_config(decay_config: &AccountInfo, program_id: &Pubkey) -> Result<DecayConfig> {
+    if decay_config.owner != program_id || decay_config.data_is_empty() {
+        return Ok(DecayConfig::default());
+    }
+
+    DecayConfig::try_deserialize(&mut &decay_config.try_borrow_data()?[..])
+}
+
+fn load_user_proof(user_proof: &AccountInfo, program_id: &Pubkey) -> Result<Option<UserProof>> {
+    if user_proof.owner != program_id || user_proof.data_is_empty() {
+        return Ok(None);
+    }
+
+    UserProof::try_deserialize(&mut &user_proof.try_borrow_data()?[..]).map(Some)
+}
+
+#[derive(Default)]
+struct LiveScore {
+    score: u64,
+    /// True when an active source has crossed a decay boundary since `last_submission`.
+    is_stale: bool,
+    /// Active sources past their own `expires_at`.
+    expired_source_count: u8,
+    /// Sources still adding to `score`, leaving out expired and hard-expired ones.
+    live_source_count: u8,
+}
+
+/// Recomputes the user's aggregate at `now` from their active `IndividualProof`
+/// accounts, which must all be passed, re-applying decay and the bonuses the remaining
+/// sources still earn. Not scaled by `global_score_multiplier_bps`.
+fn compute_live_score<'info>(
+    user_proof: &UserProof,
+    registry: &Registry,
+    scoring_config: &ScoringConfig,
+    decay_config: &DecayConfig,
+    remaining_accounts: &'info [AccountInfo<'info>],
+    program_id: &Pubkey,
+    now: i64,
+) -> Result<LiveScore> {
+    let mut is_stale = false;
+    let mut expired_source_count = 0u8;
+    let mut live_source_scores = [0u64; MAX_PROOF_SOURCES];
+    let mut scored_sources_mask = 0u16;
+    let mut active_source_count = 0u8;
+    let mut live_sources_mask = 0u16;
+    for (i, account) in remaining_accounts.iter().enumerate() {
+        require!(
+            remaining_accounts[..i]
+                .iter()
+                .all(|a| a.key() != account.key()),
+            SolanIdError::InvalidProofAccounts
+        );
+        let individual_proof = Account::<IndividualProof>::try_from(account)?;
+        let (expected_address, _) = Pubkey::find_program_address(
+            &[
+                b"individual_proof".as_ref(),
+                user_proof.user.as_ref(),
+                &[individual_proof.source as u8],
+            ],
+            program_id,
+        );
+        require_keys_eq!(
+            account.key(),
+            expected_address,
+            SolanIdError::InvalidProofAccounts
         );
+        if !counts_toward_score(&individual_proof, user_proof.expiry_epoch) {
+            continue;
+        }
+        active_source_count += 1;
+        if individual_proof.expires_at != 0 && now >= individual_proof.expires_at {
+            expired_source_count += 1;
+            continue;
+        }
 
-        let old_verifier = registry.verifier_authority;
-        registry.verifier_authority = registry.pending_verifier_authority;
-        registry.pending_verifier_authority = Pubkey::default();
-        registry.verifier_rotation_available_at = 0;
+        let live_factor = capped_recency_factor(
+            individual_proof.source,
+            individual_proof.verified_at,
+            individual_proof.first_verified_at,
+            now,
+            registry,
+            decay_config,
+        );
+        let stored_factor = capped_recency_factor(
+            individual_proof.source,
+            individual_proof.verified_at,
+            individual_proof.first_verified_at,
+            user_proof.last_submission,
+            registry,
+            decay_config,
+        );
+        is_stale |= live_factor != stored_factor;
 
-        emit!(VerifierRotationFinalized {
-            old_verifier,
-            new_verifier: registry.verifier_authority,
+        live_source_scores[individual_proof.source.as_index(&live_source_scores)?] =
+            scale_score(individual_proof.weighted_score, live_factor, 100)?;
+        scored_sources_mask |= source_bit(individual_proof.source);
+        if !is_hard_expired(
+            individual_proof.source,
+            now.saturating_sub(individual_proof.verified_at),
+            decay_config,
+        ) {
+            live_sources_mask |= source_bit(individual_proof.source);
+        }
+    }
+    require!(
+        active_source_count == user_proof.active_source_count,
+        SolanIdError::InvalidProofAccounts
+    );
+
+    let live_base_score = fold_base_score(
+        scoring_config.scoring_mode,
+        &live_source_scores,
+        scored_sources_mask,
+        &scoring_config.weights,
+    )?;
+    let cross_ecosystem_score = if scoring_config.scoring_mode == ScoringMode::Max {
+        live_base_score
+    } else {
+        let distinct_sources = distinct_category_count(
+            diversity_sources_mask(live_sources_mask, user_proof.person_sources_mask),
+            scoring_config,
+        );
+        let (diversity_score, _) = apply_diversity_bonus(
+            live_base_score,
+            distinct_sources,
+            scoring_config
+                .diversity_bonus_percent(distinct_sources, registry.diversity_bonus_percent),
+            registry.min_base_for_bonus,
+        )?;
+        apply_cross_ecosystem_bonus(diversity_score, live_sources_mask, registry)?.0
+    };
+    let (score, _) = apply_wallet_age_bonus(
+        cross_ecosystem_score,
+        user_proof.wallet_first_seen,
+        registry,
+        now,
+    )?;
+
+    Ok(LiveScore {
+        score: score.min(registry.max_aggregated_score),
+        is_stale,
+        expired_source_count,
+        live_source_count: live_sources_mask.count_ones() as u8,
+    })
+}
+
+#[allow(clippy::too_many_arguments)]
+fn proof_status<'info>(
+    user_proof: &UserProof,
+    registry: &Registry,
+    scoring_config: &ScoringConfig,
+    decay_config: &DecayConfig,
+    banned: bool,
+    min_score: u64,
+    remaining_accounts: &'info [AccountInfo<'info>],
+    program_id: &Pubkey,
+    now: i64,
+) -> Result<ProofStatus> {
+    // The stored aggregate is only refreshed on submit/revoke. When the caller passes
+    // the user's active `IndividualProof` accounts we re-apply decay at `now` and drop
+    // sources past their own `expires_at`.
+    let LiveScore {
+        score: live_score,
+        is_stale,
+        expired_source_count,
+        ..
+    } = if remaining_accounts.is_empty() {
+        LiveScore {
+            score: user_proof.aggregated_score,
+            ..LiveScore::default()
+        }
+    } else {
+        compute_live_score(
+            user_proof,
+            registry,
+            scoring_config,
+            decay_config,
+            remaining_accounts,
+            program_id,
+            now,
+        )?
+    };
+
+    // Without the proof accounts a source past its own `expires_at` fails
+    // verification outright. With them, the expired sources are dropped and
+    // verification falls back to what the remaining sources are worth now.
+    let reason = if remaining_accounts.is_empty() {
+        verification_reason(user_proof, registry, min_score, now, banned)
+    } else {
+        let mut unexpired = user_proof.clone();
+        unexpired.source_expires_at = [0; MAX_PROOF_SOURCES];
+        if expired_source_count > 0 {
+            unexpired.aggregated_score = live_score;
+            unexpired.active_source_count -= expired_source_count;
+        }
+        verification_reason(&unexpired, registry, min_score, now, banned)
+    };
+    let is_verified = reason == VerificationReason::Ok;
+    if user_proof.private {
+        return Ok(ProofStatus {
+            is_verified,
+            aggregated_score: 0,
+            verified_at: 0,
+            is_stale: false,
+            live_score: 0,
+            in_grace: false,
+            reason,
         });
+    }
 
-        Ok(())
+    Ok(ProofStatus {
+        is_verified,
+        aggregated_score: apply_global_multiplier(user_proof.aggregated_score, registry),
+        verified_at: user_proof.last_submission,
+        is_stale,
+        live_score: apply_global_multiplier(live_score, registry),
+        in_grace: is_verified && now > user_proof.valid_until,
+        reason,
+    })
+}
+
+trait Versioned: AccountSerialize + AccountDeserialize + Space {
+    fn version_mut(&mut self) -> &mut u8;
+
+    /// Fills fields whose zero value would misdescribe an account written before they
+    /// existed. Returns whether anything changed.
+    fn backfill(&mut self) -> bool {
+        false
     }
 }
 
-#[derive(Accounts)]
-pub struct InitializeRegistry<'info> {
-    #[account(
-        init,
-        payer = authority,
-        space = 8 + Registry::INIT_SPACE,
-        seeds = [b"registry"],
-        bump
-    )]
-    pub registry: Account<'info, Registry>,
-    #[account(mut)]
-    pub authority: Signer<'info>,
-    pub system_program: Program<'info, System>,
+macro_rules! impl_versioned {
+    ($($account:ty),* $(,)?) => {
+        $(impl Versioned for $account {
+            fn version_mut(&mut self) -> &mut u8 {
+                &mut self.version
+            }
+        })*
+    };
 }
 
-#[derive(Accounts)]
-#[instruction(
-    proof_hash: [u8; 32],
-    source: ProofSource,
-    identity_nullifier: [u8; 32],
-    attestation_nonce: u64
-)]
-pub struct SubmitProof<'info> {
-    #[account(mut)]
-    pub registry: Account<'info, Registry>,
-    #[account(
-        init_if_needed,
-        payer = user,
-        space = 8 + UserProof::INIT_SPACE,
-        seeds = [b"user_proof", user.key().as_ref()],
-        bump
-    )]
-    pub user_proof: Account<'info, UserProof>,
-    #[account(
-        init_if_needed,
-        payer = user,
-        space = 8 + IndividualProof::INIT_SPACE,
-        seeds = [b"individual_proof", user.key().as_ref(), &[source as u8]],
-        bump
-    )]
-    pub individual_proof: Account<'info, IndividualProof>,
-    #[account(
-        init_if_needed,
-        payer = user,
-        space = 8 + IdentityNullifierRegistry::INIT_SPACE,
-        seeds = [b"identity_nullifier", identity_nullifier.as_ref()],
-        bump
-    )]
-    pub identity_nullifier_registry: Account<'info, IdentityNullifierRegistry>,
-    #[account(
-        init_if_needed,
-        payer = user,
-        space = 8 + AttestationNonceRegistry::INIT_SPACE,
-        seeds = [
-            b"attestation_nonce",
-            registry.key().as_ref(),
-            &attestation_nonce.to_le_bytes(),
-        ],
-        bump
-    )]
-    pub attestation_nonce_registry: Account<'info, AttestationNonceRegistry>,
-    pub scoring_config: Account<'info, ScoringConfig>,
-    /// CHECK: Verified via sysvar instructions address constraint.
-    #[account(address = anchor_lang::solana_program::sysvar::instructions::id())]
-    pub instructions_sysvar: UncheckedAccount<'info>,
-    #[account(mut)]
-    pub user: Signer<'info>,
-    pub system_program: Program<'info, System>,
+// Registries written before `initialized` existed are marked from the state they hold.
+impl Versioned for IdentityNullifierRegistry {
+    fn version_mut(&mut self) -> &mut u8 {
+        &mut self.version
+    }
+
+    fn backfill(&mut self) -> bool {
+        let backfilled = !self.initialized && self.claimed_by != Pubkey::default();
+        self.initialized |= backfilled;
+        backfilled
+    }
 }
 
-#[derive(Accounts)]
-#[instruction(source: ProofSource)]
-pub struct RevokeProof<'info> {
-    #[account(mut)]
-    pub registry: Account<'info, Registry>,
-    #[account(
-        mut,
-        seeds = [b"user_proof", user.key().as_ref()],
-        bump = user_proof.bump
-    )]
-    pub user_proof: Account<'info, UserProof>,
-    #[account(
-        mut,
-        seeds = [b"individual_proof", user.key().as_ref(), &[source as u8]],
-        bump = individual_proof.bump
-    )]
-    pub individual_proof: Account<'info, IndividualProof>,
-    #[account(mut)]
-    pub identity_nullifier_registry: Account<'info, IdentityNullifierRegistry>,
-    #[account(mut)]
-    pub user: Signer<'info>,
+impl Versioned for AttestationNonceRegistry {
+    fn version_mut(&mut self) -> &mut u8 {
+        &mut self.version
+    }
+
+    fn backfill(&mut self) -> bool {
+        let backfilled = !self.initialized && self.is_used;
+        self.initialized |= backfilled;
+        backfilled
+    }
+}
+
+// Registries from before `clock_skew_tolerance` existed keep the tolerance that
+// used to be hardcoded rather than dropping to zero, and a zero-filled
+// `max_aggregated_score` means the cap did not exist yet. Before version 4 the
+// `proof_hash_scope` byte was a per-user flag, so it is reset to `PerUser` to keep
+// deriving the same proof hash accounts. Before version 5 a zero-filled
+// `allowed_sources` would turn every source away and a zero `max_trust_multiplier`
+// would reject discounts the default allows.
+impl Versioned for Registry {
+    fn version_mut(&mut self) -> &mut u8 {
+        &mut self.version
+    }
+
+    fn backfill(&mut self) -> bool {
+        let mut backfilled = false;
+        if self.version < 3 {
+            self.clock_skew_tolerance = DEFAULT_CLOCK_SKEW_TOLERANCE;
+            backfilled = true;
+        }
+        if self.version < 4 {
+            self.proof_hash_scope = ProofHashScope::PerUser;
+            backfilled = true;
+        }
+        if self.max_aggregated_score == 0 {
+            self.max_aggregated_score = u64::MAX;
+            backfilled = true;
+        }
+        if self.version < 5 {
+            if self.allowed_sources == 0 {
+                self.allowed_sources = u16::MAX;
+            }
+            if self.max_trust_multiplier == 0 {
+                self.max_trust_multiplier = NEUTRAL_TRUST_MULTIPLIER;
+            }
+            backfilled = true;
+        }
+        backfilled
+    }
+}
+
+impl_versioned!(
+    UserProof,
+    IndividualProof,
+    ProofHashRegistry,
+    ScoringConfig,
+    DecayConfig,
+    UserStatus,
+    ScoreSnapshot,
+    PersonRegistry,
+    StatusCache,
+);
+
+/// Resizes `accounts.account` to the current `T` layout with zeroed trailing bytes and
+/// stamps `ACCOUNT_VERSION`, returning the version it held before.
+fn migrate_versioned<T: Versioned>(accounts: &MigrateAccount) -> Result<u8> {
+    let account = accounts.account.to_account_info();
+    require!(
+        account.data_len() <= 8 + T::INIT_SPACE,
+        SolanIdError::AccountVersionDowngrade
+    );
+    grow_account(accounts, 8 + T::INIT_SPACE)?;
+
+    let mut state = T::try_deserialize(&mut &account.try_borrow_data()?[..])?;
+    let from_version = *state.version_mut();
+    require!(
+        from_version <= ACCOUNT_VERSION,
+        SolanIdError::AccountVersionDowngrade
+    );
+    let backfilled = state.backfill();
+    if from_version < ACCOUNT_VERSION || backfilled {
+        *state.version_mut() = ACCOUNT_VERSION;
+        state.try_serialize(&mut &mut account.try_borrow_mut_data()?[..])?;
+    }
+    Ok(from_version)
+}
+
+/// Rewrites an account still in its `L` layout, from before per-source masks and
+/// arrays were widened, as `T` and stamps `ACCOUNT_VERSION`. Accounts already past
+/// that layout go through `migrate_versioned`.
+fn migrate_widened<L, T>(accounts: &MigrateAccount) -> Result<u8>
+where
+    L: AnchorDeserialize + Space,
+    T: Versioned + From<L>,
+{
+    let account = accounts.account.to_account_info();
+    if account.data_len() > 8 + L::INIT_SPACE {
+        return migrate_versioned::<T>(accounts);
+    }
+
+    // Legacy accounts written before their own trailing fields existed read them as zero.
+    let mut legacy_data = account.try_borrow_data()?[8..].to_vec();
+    legacy_data.resize(L::INIT_SPACE, 0);
+    let legacy = L::deserialize(&mut legacy_data.as_slice())
+        .map_err(|_| error!(SolanIdError::InvalidMigrationAccount))?;
+    let mut state = T::from(legacy);
+    let from_version = *state.version_mut();
+    require!(
+        from_version <= ACCOUNT_VERSION,
+        SolanIdError::AccountVersionDowngrade
+    );
+    state.backfill();
+    *state.version_mut() = ACCOUNT_VERSION;
+
+    grow_account(accounts, 8 + T::INIT_SPACE)?;
+    state.try_serialize(&mut &mut account.try_borrow_mut_data()?[..])?;
+    Ok(from_version)
+}
+
+/// Grows `accounts.account` to `new_len`, topping its rent up from the payer and zeroing the
+/// new bytes. Accounts already that long are left alone.
+fn grow_account(accounts: &MigrateAccount, new_len: usize) -> Result<()> {
+    let account = accounts.account.to_account_info();
+    let old_len = account.data_len();
+    if old_len >= new_len {
+        return Ok(());
+    }
+
+    let rent_shortfall = Rent::get()?
+        .minimum_balance(new_len)
+        .saturating_sub(account.lamports());
+    if rent_shortfall > 0 {
+        system_program::transfer(
+            CpiContext::new(
+                accounts.system_program.to_account_info(),
+                system_program::Transfer {
+                    from: accounts.payer.to_account_info(),
+                    to: account.clone(),
+                },
+            ),
+            rent_shortfall,
+        )?;
+    }
+    account.resize(new_len)?;
+    account.try_borrow_mut_data()?[old_len..].fill(0);
+    Ok(())
+}
+
+/// Copies a legacy per-source array over the front of `widened`, whose remaining
+/// slots keep the defaults for sources added since.
+fn widen_per_source<T: Copy>(
+    legacy: [T; LEGACY_PROOF_SOURCE_COUNT],
+    mut widened: [T; MAX_PROOF_SOURCES],
+) -> [T; MAX_PROOF_SOURCES] {
+    widened[..LEGACY_PROOF_SOURCE_COUNT].copy_from_slice(&legacy);
+    widened
+}
+
+/// Returns whether `user_proof` currently passes `registry`'s checks, for programs
+/// that gate their own instructions on SolanID without a CPI. Build with the `cpi`
+/// feature and load both accounts as `Account<'info, solan_id::UserProof>` and
+/// `Account<'info, solan_id::Registry>`, which check the owner and discriminator.
+///
+/// `UserProof` is the PDA `["user_proof", user]`: the 8-byte discriminator, then
+/// `user: Pubkey`, `aggregated_score: u64`, `last_submission: i64`,
+/// `valid_until: i64`, `active_source_count: u8`, `bonus_applied: bool`, `bump: u8`,
+/// `marked: bool`, `private: bool`, `sources_mask: u16`,
+/// `cross_ecosystem_bonus_applied: bool`, `last_nonce: u64`,
+/// `applied_diversity_bonus_percent: u8`, `applied_cross_ecosystem_bonus_percent: u8`,
+/// `version: u8`, `expiry_epoch: u32`, `person_sources_mask: u16`,
+/// `wallet_first_seen: i64`, `wallet_age_bonus_applied: bool`,
+/// `applied_wallet_age_bonus_percent: u8`, `flagged_sources_mask: u16`, and further
+/// on `source_expires_at: [i64; 16]` after `total_revocations`.
+/// `Registry` is the PDA `["registry"]`; this reads `min_score`,
+/// `verifier_rotation_available_at`, `rotation_grace_seconds`,
+/// `grace_period_seconds`, `global_score_multiplier_bps` and `min_source_count`.
+pub fn is_verified(user_proof: &UserProof, registry: &Registry) -> bool {
+    Clock::get()
+        .map(|clock| is_verified_at(user_proof, registry, clock.unix_timestamp))
+        .unwrap_or(false)
+}
+
+#[inline]
+pub fn is_verified_at(user_proof: &UserProof, registry: &Registry, now: i64) -> bool {
+    verification_reason(user_proof, registry, registry.min_score, now, false)
+        == VerificationReason::Ok
+}
+
+/// Why `user_proof` does or doesn't verify against `min_score` at `now`. Checks run
+/// from the hardest to fix to the easiest (no proof, ban, flag, expiry, then score)
+/// and the first failing one is reported.
+pub fn verification_reason(
+    user_proof: &UserProof,
+    registry: &Registry,
+    min_score: u64,
+    now: i64,
+    banned: bool,
+) -> VerificationReason {
+    if user_proof.user == Pubkey::default() {
+        return VerificationReason::NoProof;
+    }
+    if banned {
+        return VerificationReason::Banned;
+    }
+    if user_proof.flagged_sources_mask != 0 {
+        return VerificationReason::Flagged;
+    }
+
+    let mut effective_valid_until = user_proof
+        .valid_until
+        .saturating_add(registry.grace_period_seconds);
+    if registry.verifier_rotation_available_at != 0 {
+        effective_valid_until =
+            effective_valid_until.saturating_add(registry.rotation_grace_seconds);
+    }
+
+    let earliest_source_expiry = user_proof.earliest_source_expiry();
+    if now > effective_valid_until || (earliest_source_expiry != 0 && now >= earliest_source_expiry)
+    {
+        return VerificationReason::Expired;
+    }
+
+    let effective_score = apply_global_multiplier(user_proof.aggregated_score, registry);
+    if effective_score < min_score
+        || effective_score == 0
+        || user_proof.active_source_count < registry.min_source_count
+    {
+        return VerificationReason::BelowThreshold;
+    }
+    VerificationReason::Ok
 }
 
-#[derive(Accounts)]
-pub struct VerifyProof<'info> {
-    #[account(
-        seeds = [b"user_proof", user.key().as_ref()],
-        bump = user_proof.bump
-    )]
-    pub user_proof: Account<'info, UserProof>,
-    pub registry: Account<'info, Registry>,
-    /// CHECK: User account is only used to derive the PDA for user_proof. The user_proof account validation ensures correctness.
-    pub user: UncheckedAccount<'info>,
+/// Whether a `ScoreSnapshot` verifies under the registry frozen alongside it, with
+/// the same ban, flag and `min_source_count` checks as `verification_reason`.
+pub fn is_snapshot_verified(
+    score_snapshot: &ScoreSnapshot,
+    registry: &Registry,
+    banned: bool,
+    flagged: bool,
+) -> bool {
+    if banned || flagged {
+        return false;
+    }
+    let effective_score = apply_global_multiplier(score_snapshot.aggregated_score, registry);
+    effective_score >= registry.min_score
+        && effective_score > 0
+        && score_snapshot.source_count >= registry.min_source_count
+        && score_snapshot.created_at
+            <= score_snapshot
+                .valid_until
+                .saturating_add(registry.grace_period_seconds)
 }
 
-#[derive(Accounts)]
-pub struct UpdateMinScore<'info> {
-    #[account(
-        mut,
-        seeds = [b"registry"],
-        bump = registry.bump,
-        has_one = authority @ SolanIdError::Unauthorized
-    )]
-    pub registry: Account<'info, Registry>,
-    pub authority: Signer<'info>,
+fn clear_identity_flag(
+    identity_nullifier_registry: &mut IdentityNullifierRegistry,
+    user_proof: &mut UserProof,
+) {
+    identity_nullifier_registry.flagged = false;
+    identity_nullifier_registry.flagged_at = 0;
+    user_proof.flagged_sources_mask &= !source_bit(identity_nullifier_registry.source);
 }
 
-#[derive(Accounts)]
-pub struct InitializeScoringConfig<'info> {
-    #[account(
-        init,
-        payer = authority,
-        space = 8 + ScoringConfig::INIT_SPACE,
-        seeds = [b"scoring_config"],
-        bump
-    )]
-    pub scoring_config: Account<'info, ScoringConfig>,
-    #[account(mut)]
-    pub authority: Signer<'info>,
-    pub system_program: Program<'info, System>,
+/// A zero multiplier can only come from a registry that never set one (the setter
+/// rejects it), so it is read as 1x rather than failing every user.
+fn apply_global_multiplier(score: u64, registry: &Registry) -> u64 {
+    let multiplier_bps = match registry.global_score_multiplier_bps {
+        0 => BASIS_POINTS,
+        bps => bps as u64,
+    };
+    let scaled = score as u128 * multiplier_bps as u128 / BASIS_POINTS as u128;
+    u64::try_from(scaled).unwrap_or(u64::MAX)
 }
 
-#[derive(Accounts)]
-pub struct UpdateScoringConfig<'info> {
-    #[account(
-        mut,
-        seeds = [b"scoring_config"],
-        bump = scoring_config.bump,
-        has_one = authority @ SolanIdError::Unauthorized
-    )]
-    pub scoring_config: Account<'info, ScoringConfig>,
-    pub authority: Signer<'info>,
+/// Resubmitting refreshes `verified_at`, so once the source's first verification has
+/// aged out of the full-weight bracket the factor is capped at
+/// `max_recency_after_resubmit` to stop recency farming. A source past its
+/// `source_hard_expiry` is worth nothing wherever its contribution is recomputed.
+fn capped_recency_factor(
+    source: ProofSource,
+    verified_at: i64,
+    first_verified_at: i64,
+    now: i64,
+    registry: &Registry,
+    decay_config: &DecayConfig,
+) -> u64 {
+    let age_seconds = now.checked_sub(verified_at).unwrap_or(0);
+    if is_hard_expired(source, age_seconds, decay_config) {
+        return 0;
+    }
+
+    let factor = recency_factor(age_seconds, decay_config);
+    let first_age_seconds = now.checked_sub(first_verified_at).unwrap_or(0);
+    if first_verified_at != verified_at && first_age_seconds >= decay_config.age_boundaries[0] {
+        factor.min(registry.max_recency_after_resubmit as u64)
+    } else {
+        factor
+    }
 }
 
-#[derive(Accounts)]
-pub struct UpdateRegistryConfig<'info> {
-    #[account(
-        mut,
-        seeds = [b"registry"],
-        bump = registry.bump,
-        has_one = authority @ SolanIdError::Unauthorized
-    )]
-    pub registry: Account<'info, Registry>,
-    pub authority: Signer<'info>,
+/// `score * numerator / denominator`, rounded down. The product is taken in `u128`,
+/// so only a result that doesn't fit back in a `u64` is an `Overflow`.
+pub fn scale_score(score: u64, numerator: u64, denominator: u64) -> Result<u64> {
+    let scaled = (score as u128 * numerator as u128)
+        .checked_div(denominator as u128)
+        .ok_or(SolanIdError::Overflow)?;
+    u64::try_from(scaled).map_err(|_| SolanIdError::Overflow.into())
 }
 
-#[derive(Accounts)]
-pub struct InitiateVerifierRotation<'info> {
-    #[account(
-        mut,
-        seeds = [b"registry"],
-        bump = registry.bump,
-        has_one = authority @ SolanIdError::Unauthorized
-    )]
-    pub registry: Account<'info, Registry>,
-    pub authority: Signer<'info>,
+/// Adds `diversity_bonus_percent` of `base_score` once more than one distinct source
+/// counts and the base clears `min_base_for_bonus`.
+pub fn apply_diversity_bonus(
+    base_score: u64,
+    active_source_count: u8,
+    diversity_bonus_percent: u8,
+    min_base_for_bonus: u64,
+) -> Result<(u64, bool)> {
+    if active_source_count <= 1 || diversity_bonus_percent == 0 || base_score <= min_base_for_bonus
+    {
+        return Ok((base_score, false));
+    }
+
+    let diversity_bonus = scale_score(base_score, diversity_bonus_percent as u64, 100)?;
+
+    let total_score = base_score
+        .checked_add(diversity_bonus)
+        .ok_or(SolanIdError::Overflow)?;
+
+    Ok((total_score, true))
 }
 
-#[derive(Accounts)]
-pub struct FinalizeVerifierRotation<'info> {
-    #[account(
-        mut,
-        seeds = [b"registry"],
-        bump = registry.bump,
-        has_one = authority @ SolanIdError::Unauthorized
-    )]
-    pub registry: Account<'info, Registry>,
-    pub authority: Signer<'info>,
+pub fn apply_cross_ecosystem_bonus(
+    score: u64,
+    sources_mask: u16,
+    registry: &Registry,
+) -> Result<(u64, bool)> {
+    if registry.cross_ecosystem_bonus_percent == 0
+        || sources_mask & registry.web2_mask == 0
+        || sources_mask & registry.web3_mask == 0
+    {
+        return Ok((score, false));
+    }
+
+    let total_score = scale_score(
+        score,
+        100 + registry.cross_ecosystem_bonus_percent as u64,
+        100,
+    )?;
+
+    Ok((total_score, true))
 }
 
-#[account]
-#[derive(InitSpace)]
-pub struct Registry {
-    pub authority: Pubkey,
-    pub verifier_authority: Pubkey,
-    pub pending_verifier_authority: Pubkey,
-    pub verifier_rotation_available_at: i64,
-    pub total_verified_users: u64,
-    pub min_score: u64,
-    pub cooldown_period: i64,
-    pub diversity_bonus_percent: u8,
-    pub proof_ttl_seconds: i64,
-    pub bump: u8,
+/// Applies the wallet-age bonus when the verifier has attested when the wallet was
+/// first seen and it has been around for at least `min_wallet_age_seconds`.
+pub fn apply_wallet_age_bonus(
+    score: u64,
+    wallet_first_seen: i64,
+    registry: &Registry,
+    now: i64,
+) -> Result<(u64, bool)> {
+    if registry.wallet_age_bonus_percent == 0
+        || wallet_first_seen == 0
+        || now.saturating_sub(wallet_first_seen) < registry.min_wallet_age_seconds
+    {
+        return Ok((score, false));
+    }
+
+    let total_score = scale_score(score, 100 + registry.wallet_age_bonus_percent as u64, 100)?;
+
+    Ok((total_score, true))
 }
 
-#[account]
-#[derive(InitSpace)]
-pub struct UserProof {
-    pub user: Pubkey,
-    pub aggregated_score: u64,
-    pub last_submission: i64,
-    pub valid_until: i64,
-    pub active_source_count: u8,
-    pub bump: u8,
+/// Folds the per-source contributions in `sources_mask` into a base score. The
+/// weighted average divides the summed contributions, which already carry their
+/// source's weight, by the summed weights of the counted sources.
+pub fn fold_base_score(
+    scoring_mode: ScoringMode,
+    source_scores: &[u64; MAX_PROOF_SOURCES],
+    sources_mask: u16,
+    weights: &[u64; MAX_PROOF_SOURCES],
+) -> Result<u64> {
+    let counted = (0..MAX_PROOF_SOURCES).filter(|index| sources_mask & (1 << index) != 0);
+    match scoring_mode {
+        ScoringMode::Additive => counted
+            .map(|index| source_scores[index])
+            .try_fold(0u64, |sum, score| sum.checked_add(score))
+            .ok_or(SolanIdError::Overflow.into()),
+        ScoringMode::Max => Ok(counted.map(|index| source_scores[index]).max().unwrap_or(0)),
+        ScoringMode::WeightedAverage => {
+            let (total, total_weight) =
+                counted.fold((0u128, 0u128), |(total, total_weight), index| {
+                    (
+                        total + source_scores[index] as u128,
+                        total_weight + weights[index] as u128,
+                    )
+                });
+            if total_weight == 0 {
+                return Ok(0);
+            }
+            u64::try_from(total * 100 / total_weight).map_err(|_| SolanIdError::Overflow.into())
+        }
+    }
 }
 
-#[account]
-#[derive(InitSpace)]
-pub struct IndividualProof {
-    pub user: Pubkey,
-    pub proof_hash: [u8; 32],
-    pub base_score: u64,
-    pub weighted_score: u64,
-    pub source: ProofSource,
-    pub identity_nullifier: [u8; 32],
-    pub proof_data: SourceProofData,
-    pub verified_at: i64,
-    pub is_revoked: bool,
-    pub bump: u8,
+/// Fills a freshly created `UserProof` for `user`, valid for `proof_ttl_seconds` from
+/// `now`, with no sources yet.
+fn init_user_proof(
+    user_proof: &mut UserProof,
+    user: Pubkey,
+    bump: u8,
+    now: i64,
+    registry: &Registry,
+) -> Result<()> {
+    *user_proof = UserProof {
+        user,
+        aggregated_score: 0,
+        last_submission: now,
+        valid_until: now
+            .checked_add(registry.proof_ttl_seconds)
+            .ok_or(SolanIdError::Overflow)?,
+        active_source_count: 0,
+        bonus_applied: false,
+        bump,
+        marked: false,
+        private: false,
+        sources_mask: 0,
+        cross_ecosystem_bonus_applied: false,
+        last_nonce: 0,
+        applied_diversity_bonus_percent: 0,
+        applied_cross_ecosystem_bonus_percent: 0,
+        version: ACCOUNT_VERSION,
+        expiry_epoch: user_proof.expiry_epoch,
+        person_sources_mask: 0,
+        wallet_first_seen: 0,
+        wallet_age_bonus_applied: false,
+        applied_wallet_age_bonus_percent: 0,
+        flagged_sources_mask: 0,
+        pre_cap_score: 0,
+        source_scores: [0; MAX_PROOF_SOURCES],
+        scoring_mode: ScoringMode::Additive,
+        total_submissions: 0,
+        total_revocations: 0,
+        source_expires_at: [0; MAX_PROOF_SOURCES],
+    };
+    Ok(())
 }
 
-#[account]
-#[derive(InitSpace)]
-pub struct ProofHashRegistry {
-    pub is_used: bool,
-    pub user: Pubkey,
-    pub bump: u8,
+fn set_source_score(user_proof: &mut UserProof, source: ProofSource, score: u64) -> Result<()> {
+    let index = source.as_index(&user_proof.source_scores)?;
+    user_proof.source_scores[index] = score;
+    Ok(())
 }
 
-#[account]
-#[derive(InitSpace)]
-pub struct ScoringConfig {
-    pub authority: Pubkey,
-    pub weights: [u64; 8],
-    pub bump: u8,
+fn set_source_expiry(
+    user_proof: &mut UserProof,
+    source: ProofSource,
+    expires_at: i64,
+) -> Result<()> {
+    let index = source.as_index(&user_proof.source_expires_at)?;
+    user_proof.source_expires_at[index] = expires_at;
+    Ok(())
 }
 
-#[account]
-#[derive(InitSpace)]
-pub struct IdentityNullifierRegistry {
-    pub nullifier: [u8; 32],
-    pub source: ProofSource,
-    pub claimed_by: Pubkey,
-    pub is_burned: bool,
-    pub claimed_at: i64,
-    pub last_proof_hash: [u8; 32],
-    pub bump: u8,
+/// Sets `aggregated_score` to `base_score` with the diversity bonus, the
+/// cross-ecosystem bonus and then the wallet-age bonus applied, recording which ones were folded in so
+/// `strip_score_bonuses` can undo them in reverse order. The result is clamped to
+/// `max_aggregated_score`, keeping the unclamped total in `pre_cap_score`. Diversity is counted in
+/// distinct source categories, so stacking related sources earns no bonus, and
+/// sources linked to the same person count once. Outside `ScoringMode::Additive` the
+/// base is refolded from `source_scores` instead, and `ScoringMode::Max` skips the
+/// breadth bonuses.
+fn apply_score_bonuses(
+    user_proof: &mut UserProof,
+    registry: &Registry,
+    scoring_config: &ScoringConfig,
+    base_score: u64,
+    now: i64,
+) -> Result<()> {
+    let scoring_mode = scoring_config.scoring_mode;
+    let base_score = match scoring_mode {
+        ScoringMode::Additive => base_score,
+        _ => fold_base_score(
+            scoring_mode,
+            &user_proof.source_scores,
+            user_proof.sources_mask,
+            &scoring_config.weights,
+        )?,
+    };
+    let distinct_sources = distinct_category_count(
+        diversity_sources_mask(user_proof.sources_mask, user_proof.person_sources_mask),
+        scoring_config,
+    );
+    let diversity_bonus_percent =
+        scoring_config.diversity_bonus_percent(distinct_sources, registry.diversity_bonus_percent);
+    let (total_score, bonus_applied, cross_ecosystem_bonus_applied) =
+        if scoring_mode == ScoringMode::Max {
+            (base_score, false, false)
+        } else {
+            let (diversity_score, bonus_applied) = apply_diversity_bonus(
+                base_score,
+                distinct_sources,
+                diversity_bonus_percent,
+                registry.min_base_for_bonus,
+            )?;
+            let (total_score, cross_ecosystem_bonus_applied) =
+                apply_cross_ecosystem_bonus(diversity_score, user_proof.sources_mask, registry)?;
+            (total_score, bonus_applied, cross_ecosystem_bonus_applied)
+        };
+    let (total_score, wallet_age_bonus_applied) =
+        apply_wallet_age_bonus(total_score, user_proof.wallet_first_seen, registry, now)?;
+    let capped_score = total_score.min(registry.max_aggregated_score);
+
+    user_proof.aggregated_score = capped_score;
+    user_proof.pre_cap_score = if capped_score < total_score {
+        total_score
+    } else {
+        0
+    };
+    user_proof.bonus_applied = bonus_applied;
+    user_proof.cross_ecosystem_bonus_applied = cross_ecosystem_bonus_applied;
+    user_proof.wallet_age_bonus_applied = wallet_age_bonus_applied;
+    user_proof.applied_diversity_bonus_percent = diversity_bonus_percent;
+    user_proof.applied_cross_ecosystem_bonus_percent = registry.cross_ecosystem_bonus_percent;
+    user_proof.applied_wallet_age_bonus_percent = registry.wallet_age_bonus_percent;
+    user_proof.scoring_mode = scoring_mode;
+    Ok(())
 }
 
-#[account]
-#[derive(InitSpace)]
-pub struct AttestationNonceRegistry {
-    pub nonce: u64,
-    pub is_used: bool,
-    pub user: Pubkey,
-    pub used_at: i64,
-    pub bump: u8,
+/// Undoes the bonuses at the percents they were applied with, which may differ from
+/// the registry's current ones. A clamped aggregate is unwound from `pre_cap_score`.
+/// Returns the additive base score; an aggregate folded under another mode can't be
+/// inverted, so that base is summed from `source_scores` instead.
+fn strip_score_bonuses(user_proof: &UserProof, registry: &Registry) -> Result<u64> {
+    if user_proof.scoring_mode != ScoringMode::Additive {
+        return fold_base_score(
+            ScoringMode::Additive,
+            &user_proof.source_scores,
+            user_proof.sources_mask,
+            &[0; MAX_PROOF_SOURCES],
+        );
+    }
+    let wallet_age_bonus_percent = match user_proof.applied_wallet_age_bonus_percent {
+        0 => registry.wallet_age_bonus_percent,
+        percent => percent,
+    };
+    let cross_ecosystem_bonus_percent = match user_proof.applied_cross_ecosystem_bonus_percent {
+        0 => registry.cross_ecosystem_bonus_percent,
+        percent => percent,
+    };
+    let diversity_bonus_percent = match user_proof.applied_diversity_bonus_percent {
+        0 => registry.diversity_bonus_percent,
+        percent => percent,
+    };
+
+    let score = match user_proof.pre_cap_score {
+        0 => user_proof.aggregated_score,
+        pre_cap_score => pre_cap_score,
+    };
+    let score = strip_wallet_age_bonus(
+        score,
+        wallet_age_bonus_percent,
+        user_proof.wallet_age_bonus_applied,
+    )?;
+    let score = strip_cross_ecosystem_bonus(
+        score,
+        cross_ecosystem_bonus_percent,
+        user_proof.cross_ecosystem_bonus_applied,
+    )?;
+
+    strip_diversity_bonus(
+        score,
+        user_proof.active_source_count,
+        diversity_bonus_percent,
+        user_proof.bonus_applied,
+    )
 }
 
-fn apply_diversity_bonus(
-    base_score: u64,
+const fn source_bit(source: ProofSource) -> u16 {
+    1 << source as u16
+}
+
+/// Puts every source, including the reserved slots, in a category of its own.
+fn default_source_categories() -> [u8; MAX_PROOF_SOURCES] {
+    std::array::from_fn(|index| index as u8)
+}
+
+/// Collapses the sources linked to a person id into the lowest one of them, so a
+/// single human holding several sources only counts once towards diversity.
+fn diversity_sources_mask(sources_mask: u16, person_sources_mask: u16) -> u16 {
+    let linked = sources_mask & person_sources_mask;
+    (sources_mask & !person_sources_mask) | (linked & linked.wrapping_neg())
+}
+
+/// Counts the distinct categories among the sources in `sources_mask`. A source
+/// weighted to zero adds nothing to the base score, so it doesn't count either.
+pub fn distinct_category_count(sources_mask: u16, scoring_config: &ScoringConfig) -> u8 {
+    let mut categories_mask = 0u16;
+    for (index, category) in scoring_config.category.iter().enumerate() {
+        if sources_mask & (1 << index) != 0 && scoring_config.weights[index] > 0 {
+            categories_mask |= 1 << category;
+        }
+    }
+    categories_mask.count_ones() as u8
+}
+
+/// Inverts `apply_diversity_bonus` at the percent it was applied with. Rounding up
+/// recovers the exact base score, since the bonus itself was rounded down. Whether
+/// to strip follows `bonus_applied` rather than a recount, so zeroing a source's
+/// weight afterwards doesn't change what comes off.
+pub fn strip_diversity_bonus(
+    total_score: u64,
     active_source_count: u8,
     diversity_bonus_percent: u8,
+    bonus_applied: bool,
+) -> Result<u64> {
+    if !bonus_applied || active_source_count <= 1 || diversity_bonus_percent == 0 {
+        return Ok(total_score);
+    }
+
+    strip_percent_bonus(total_score, diversity_bonus_percent)
+}
+
+/// Inverts `apply_cross_ecosystem_bonus` at the percent it was applied with,
+/// rounding up like `strip_diversity_bonus`.
+pub fn strip_cross_ecosystem_bonus(
+    total_score: u64,
+    cross_ecosystem_bonus_percent: u8,
+    bonus_applied: bool,
+) -> Result<u64> {
+    if !bonus_applied || cross_ecosystem_bonus_percent == 0 {
+        return Ok(total_score);
+    }
+
+    strip_percent_bonus(total_score, cross_ecosystem_bonus_percent)
+}
+
+/// Inverts `apply_wallet_age_bonus` at the percent it was applied with, rounding up
+/// like `strip_diversity_bonus`.
+pub fn strip_wallet_age_bonus(
+    total_score: u64,
+    wallet_age_bonus_percent: u8,
+    bonus_applied: bool,
 ) -> Result<u64> {
-    if active_source_count <= 1 || diversity_bonus_percent == 0 {
-        return Ok(base_score);
+    if !bonus_applied || wallet_age_bonus_percent == 0 {
+        return Ok(total_score);
+    }
+
+    strip_percent_bonus(total_score, wallet_age_bonus_percent)
+}
+
+/// Recovers the score a `percent` bonus rounded down to `total_score` was taken from.
+/// Rounding up lands back on the exact original rather than one point below it.
+fn strip_percent_bonus(total_score: u64, percent: u8) -> Result<u64> {
+    let base_score = (total_score as u128 * 100).div_ceil(100 + percent as u128);
+    u64::try_from(base_score).map_err(|_| SolanIdError::Overflow.into())
+}
+
+fn read_u16_le(data: &[u8], offset: usize) -> Result<u16> {
+    let end = offset
+        .checked_add(2)
+        .ok_or(SolanIdError::InvalidAttestationInstruction)?;
+    let bytes = data
+        .get(offset..end)
+        .ok_or(SolanIdError::InvalidAttestationInstruction)?;
+
+    Ok(u16::from_le_bytes([bytes[0], bytes[1]]))
+}
+
+/// Checks shared by `migrate_identity` and `transfer_identity` before an identity
+/// and its proof move from `old_owner` to `new_owner`. The proof must still count
+/// toward the old owner's score, so a revoked or expired proof is not revived on
+/// the new wallet.
+pub fn check_identity_move(
+    identity_nullifier_registry: &IdentityNullifierRegistry,
+    old_user_proof: &UserProof,
+    old_individual_proof: &IndividualProof,
+    old_owner: Pubkey,
+    new_owner: Pubkey,
+) -> Result<()> {
+    require!(old_owner != new_owner, SolanIdError::InvalidConfig);
+
+    require!(
+        !identity_nullifier_registry.is_burned,
+        SolanIdError::IdentityRevokedPermanent
+    );
+    require!(
+        !identity_nullifier_registry.soulbound,
+        SolanIdError::SoulboundIdentity
+    );
+    require!(
+        !identity_nullifier_registry.flagged,
+        SolanIdError::IdentityUnderReview
+    );
+    require!(
+        identity_nullifier_registry.claimed_by == old_owner,
+        SolanIdError::Unauthorized
+    );
+    require!(
+        identity_nullifier_registry.nullifier == old_individual_proof.identity_nullifier,
+        SolanIdError::InvalidIdentityNullifier
+    );
+    require!(
+        old_individual_proof.user == old_owner
+            && counts_toward_score(old_individual_proof, old_user_proof.expiry_epoch),
+        SolanIdError::ProofAlreadyRevoked
+    );
+    Ok(())
+}
+
+/// Moves `old_individual_proof`'s contribution and identity onto `new_owner`'s
+/// PDAs. The old `UserProof` is closed to `new_owner_info` once it has no active
+/// sources left.
+#[allow(clippy::too_many_arguments)]
+fn move_identity<'info>(
+    registry: &mut Registry,
+    scoring_config: &ScoringConfig,
+    decay_config: &DecayConfig,
+    identity_nullifier_registry: &mut IdentityNullifierRegistry,
+    old_user_proof: &mut Account<'info, UserProof>,
+    old_individual_proof: &IndividualProof,
+    new_user_proof: &mut UserProof,
+    new_individual_proof: &mut IndividualProof,
+    bumps: [u8; 2],
+    new_owner_info: AccountInfo<'info>,
+    now: i64,
+) -> Result<()> {
+    let new_owner = new_owner_info.key();
+    let recency_adjusted_score = scale_score(
+        old_individual_proof.weighted_score,
+        capped_recency_factor(
+            old_individual_proof.source,
+            old_individual_proof.verified_at,
+            old_individual_proof.first_verified_at,
+            now,
+            registry,
+            decay_config,
+        ),
+        100,
+    )?;
+
+    // `check_identity_move` has already required the proof to count toward the score.
+    let old_base_aggregated_score = strip_score_bonuses(old_user_proof, registry)?;
+    old_user_proof.active_source_count = old_user_proof.active_source_count.saturating_sub(1);
+    old_user_proof.sources_mask &= !source_bit(old_individual_proof.source);
+    old_user_proof.person_sources_mask &= !source_bit(old_individual_proof.source);
+    set_source_score(old_user_proof, old_individual_proof.source, 0)?;
+    set_source_expiry(old_user_proof, old_individual_proof.source, 0)?;
+    apply_score_bonuses(
+        old_user_proof,
+        registry,
+        scoring_config,
+        old_base_aggregated_score.saturating_sub(old_individual_proof.contributed_score),
+        now,
+    )?;
+
+    let is_new_user = new_user_proof.user == Pubkey::default();
+    if is_new_user {
+        init_user_proof(new_user_proof, new_owner, bumps[0], now, registry)?;
+        new_user_proof.last_submission = old_user_proof.last_submission;
+        new_user_proof.valid_until = old_user_proof.valid_until;
+        new_user_proof.private = old_user_proof.private;
+    } else {
+        new_user_proof.valid_until = new_user_proof.valid_until.max(old_user_proof.valid_until);
     }
 
-    let diversity_bonus = base_score
-        .checked_mul(diversity_bonus_percent as u64)
-        .and_then(|s| s.checked_div(100))
+    let new_base_aggregated_score = strip_score_bonuses(new_user_proof, registry)?
+        .checked_add(recency_adjusted_score)
+        .ok_or(SolanIdError::Overflow)?;
+    require!(
+        new_user_proof.active_source_count < registry.max_active_sources,
+        SolanIdError::MaxSourcesReached
+    );
+    new_user_proof.active_source_count = new_user_proof
+        .active_source_count
+        .checked_add(1)
         .ok_or(SolanIdError::Overflow)?;
+    new_user_proof.sources_mask |= source_bit(old_individual_proof.source);
+    set_source_score(
+        new_user_proof,
+        old_individual_proof.source,
+        recency_adjusted_score,
+    )?;
+    set_source_expiry(
+        new_user_proof,
+        old_individual_proof.source,
+        old_individual_proof.expires_at,
+    )?;
+    apply_score_bonuses(
+        new_user_proof,
+        registry,
+        scoring_config,
+        new_base_aggregated_score,
+        now,
+    )?;
 
-    base_score
-        .checked_add(diversity_bonus)
-        .ok_or(SolanIdError::Overflow.into())
-}
+    new_individual_proof.user = new_owner;
+    new_individual_proof.proof_hash = old_individual_proof.proof_hash;
+    new_individual_proof.base_score = old_individual_proof.base_score;
+    new_individual_proof.weighted_score = old_individual_proof.weighted_score;
+    new_individual_proof.source = old_individual_proof.source;
+    new_individual_proof.identity_nullifier = old_individual_proof.identity_nullifier;
+    new_individual_proof.proof_data = old_individual_proof.proof_data.clone();
+    new_individual_proof.verified_at = old_individual_proof.verified_at;
+    new_individual_proof.first_verified_at = old_individual_proof.first_verified_at;
+    new_individual_proof.is_revoked = false;
+    new_individual_proof.bump = bumps[1];
+    new_individual_proof.version = ACCOUNT_VERSION;
+    new_individual_proof.migrated = old_individual_proof.migrated;
+    new_individual_proof.campaign_bonus_applied = old_individual_proof.campaign_bonus_applied;
+    new_individual_proof.revoked_at = 0;
+    new_individual_proof.contributed_score = recency_adjusted_score;
+    new_individual_proof.expiry_epoch = new_user_proof.expiry_epoch;
+    new_individual_proof.expires_at = old_individual_proof.expires_at;
+    new_individual_proof.trust_multiplier = old_individual_proof.trust_multiplier;
+    new_individual_proof.weight_override = old_individual_proof.weight_override;
 
-fn strip_diversity_bonus(
-    total_score: u64,
-    active_source_count: u8,
-    diversity_bonus_percent: u8,
-) -> Result<u64> {
-    if active_source_count <= 1 || diversity_bonus_percent == 0 {
-        return Ok(total_score);
+    identity_nullifier_registry.claimed_by = new_owner;
+
+    let close_old_user_proof = old_user_proof.active_source_count == 0;
+    if is_new_user && !close_old_user_proof {
+        registry.total_verified_users = registry
+            .total_verified_users
+            .checked_add(1)
+            .ok_or(SolanIdError::Overflow)?;
+    } else if !is_new_user && close_old_user_proof {
+        registry.total_verified_users = registry.total_verified_users.saturating_sub(1);
     }
+    if close_old_user_proof {
+        old_user_proof.close(new_owner_info)?;
+    }
+    Ok(())
+}
 
-    total_score
-        .checked_mul(100)
-        .and_then(|s| s.checked_div(100 + diversity_bonus_percent as u64))
-        .ok_or(SolanIdError::Overflow.into())
+#[allow(clippy::too_many_arguments)]
+fn build_migration_message(
+    program_id: &Pubkey,
+    registry: &Pubkey,
+    old_owner: &Pubkey,
+    new_owner: &Pubkey,
+    source: ProofSource,
+    identity_nullifier: &[u8; 32],
+    attestation_nonce: u64,
+    timestamp: i64,
+) -> Vec<u8> {
+    let mut message = Vec::with_capacity(181);
+    message.extend_from_slice(b"sidm");
+    message.extend_from_slice(program_id.as_ref());
+    message.extend_from_slice(registry.as_ref());
+    message.extend_from_slice(old_owner.as_ref());
+    message.extend_from_slice(new_owner.as_ref());
+    message.push(source as u8);
+    message.extend_from_slice(identity_nullifier);
+    message.extend_from_slice(&attestation_nonce.to_le_bytes());
+    message.extend_from_slice(&timestamp.to_le_bytes());
+    message
 }
 
-fn read_u16_le(data: &[u8], offset: usize) -> Result<u16> {
-    let end = offset
-        .checked_add(2)
-        .ok_or(SolanIdError::InvalidAttestationInstruction)?;
-    let bytes = data
-        .get(offset..end)
-        .ok_or(SolanIdError::InvalidAttestationInstruction)?;
+fn build_revocation_message(
+    program_id: &Pubkey,
+    registry: &Pubkey,
+    user: &Pubkey,
+    source: ProofSource,
+    identity_nullifier: &[u8; 32],
+    attestation_nonce: u64,
+    timestamp: i64,
+) -> Vec<u8> {
+    let mut message = Vec::with_capacity(149);
+    message.extend_from_slice(b"sidr");
+    message.extend_from_slice(program_id.as_ref());
+    message.extend_from_slice(registry.as_ref());
+    message.extend_from_slice(user.as_ref());
+    message.push(source as u8);
+    message.extend_from_slice(identity_nullifier);
+    message.extend_from_slice(&attestation_nonce.to_le_bytes());
+    message.extend_from_slice(&timestamp.to_le_bytes());
+    message
+}
 
-    Ok(u16::from_le_bytes([bytes[0], bytes[1]]))
+#[allow(clippy::too_many_arguments)]
+fn build_downgrade_message(
+    program_id: &Pubkey,
+    registry: &Pubkey,
+    user: &Pubkey,
+    source: ProofSource,
+    identity_nullifier: &[u8; 32],
+    base_score: u64,
+    attestation_nonce: u64,
+    timestamp: i64,
+) -> Vec<u8> {
+    let mut message = Vec::with_capacity(157);
+    message.extend_from_slice(b"sidd");
+    message.extend_from_slice(program_id.as_ref());
+    message.extend_from_slice(registry.as_ref());
+    message.extend_from_slice(user.as_ref());
+    message.push(source as u8);
+    message.extend_from_slice(identity_nullifier);
+    message.extend_from_slice(&base_score.to_le_bytes());
+    message.extend_from_slice(&attestation_nonce.to_le_bytes());
+    message.extend_from_slice(&timestamp.to_le_bytes());
+    message
 }
 
-fn build_attestation_message(
+fn build_soulbound_message(
     program_id: &Pubkey,
     registry: &Pubkey,
     user: &Pubkey,
-    proof_hash: &[u8; 32],
     source: ProofSource,
     identity_nullifier: &[u8; 32],
     attestation_nonce: u64,
-    base_score: u64,
     timestamp: i64,
 ) -> Vec<u8> {
-    let mut message = Vec::with_capacity(190);
-    message.extend_from_slice(b"sid1");
+    let mut message = Vec::with_capacity(149);
+    message.extend_from_slice(b"sidb");
     message.extend_from_slice(program_id.as_ref());
     message.extend_from_slice(registry.as_ref());
     message.extend_from_slice(user.as_ref());
     message.push(source as u8);
     message.extend_from_slice(identity_nullifier);
     message.extend_from_slice(&attestation_nonce.to_le_bytes());
-    message.extend_from_slice(&base_score.to_le_bytes());
     message.extend_from_slice(&timestamp.to_le_bytes());
-    message.extend_from_slice(proof_hash);
     message
 }
 
+#[allow(clippy::too_many_arguments)]
+fn build_person_link_message(
+    program_id: &Pubkey,
+    registry: &Pubkey,
+    user: &Pubkey,
+    source: ProofSource,
+    identity_nullifier: &[u8; 32],
+    person_id_hash: &[u8; 32],
+    attestation_nonce: u64,
+    timestamp: i64,
+) -> Vec<u8> {
+    let mut message = Vec::with_capacity(181);
+    message.extend_from_slice(b"sidp");
+    message.extend_from_slice(program_id.as_ref());
+    message.extend_from_slice(registry.as_ref());
+    message.extend_from_slice(user.as_ref());
+    message.push(source as u8);
+    message.extend_from_slice(identity_nullifier);
+    message.extend_from_slice(person_id_hash);
+    message.extend_from_slice(&attestation_nonce.to_le_bytes());
+    message.extend_from_slice(&timestamp.to_le_bytes());
+    message
+}
+
+fn build_wallet_age_message(
+    program_id: &Pubkey,
+    registry: &Pubkey,
+    user: &Pubkey,
+    wallet_first_seen: i64,
+    attestation_nonce: u64,
+    timestamp: i64,
+) -> Vec<u8> {
+    let mut message = Vec::with_capacity(124);
+    message.extend_from_slice(b"sidw");
+    message.extend_from_slice(program_id.as_ref());
+    message.extend_from_slice(registry.as_ref());
+    message.extend_from_slice(user.as_ref());
+    message.extend_from_slice(&wallet_first_seen.to_le_bytes());
+    message.extend_from_slice(&attestation_nonce.to_le_bytes());
+    message.extend_from_slice(&timestamp.to_le_bytes());
+    message
+}
+
+#[allow(clippy::too_many_arguments)]
 fn verify_verifier_attestation(
     instruction_sysvar: &AccountInfo,
     program_id: &Pubkey,
-    registry: Pubkey,
+    registry_key: Pubkey,
     user: Pubkey,
     proof_hash: [u8; 32],
     source: ProofSource,
@@ -794,25 +7615,24 @@ fn verify_verifier_attestation(
     attestation_nonce: u64,
     base_score: u64,
     timestamp: i64,
-    verifier_authority: Pubkey,
+    slot_binding: Option<(u64, [u8; 32])>,
+    expires_at: i64,
+    trust_multiplier: u8,
+    weight_override: u64,
+    attestation_version: u8,
+    verifier_proof: &[[u8; 32]],
+    registry: &Registry,
 ) -> Result<()> {
-    let current_index = load_current_index_checked(instruction_sysvar)
-        .map_err(|_| error!(SolanIdError::InvalidAttestationInstruction))?
-        as usize;
-
-    require!(
-        current_index > 0,
-        SolanIdError::InvalidAttestationInstruction
-    );
+    check_attestation_version(registry, attestation_version)?;
 
-    let prior_ix = load_instruction_at_checked(current_index - 1, instruction_sysvar)
-        .map_err(|_| error!(SolanIdError::InvalidAttestationInstruction))?;
+    let current_index = attested_instruction_index(instruction_sysvar)?;
 
-    validate_ed25519_instruction(
-        &prior_ix,
-        &build_attestation_message(
+    let expected_message = expected_attestation(
+        registry,
+        build_attestation_message(
+            attestation_version,
             program_id,
-            &registry,
+            &registry_key,
             &user,
             &proof_hash,
             source,
@@ -820,51 +7640,317 @@ fn verify_verifier_attestation(
             attestation_nonce,
             base_score,
             timestamp,
+            slot_binding,
+            expires_at,
+            trust_multiplier,
+            weight_override,
         ),
-        &verifier_authority,
+    );
+
+    if !verifier_proof.is_empty() {
+        return verify_allowlisted_attestation(
+            instruction_sysvar,
+            current_index,
+            &expected_message,
+            verifier_proof,
+            registry,
+        );
+    }
+
+    verify_attestation_message(
+        instruction_sysvar,
+        current_index,
+        &expected_message,
+        registry,
     )
 }
 
-fn validate_ed25519_instruction(
+/// The bytes the verifier must have signed for a submission `message`: the message
+/// itself, or its digest when the registry uses hashed attestations.
+fn expected_attestation(registry: &Registry, message: Vec<u8>) -> Vec<u8> {
+    if registry.hashed_attestations {
+        attestation_digest(&message).to_vec()
+    } else {
+        message
+    }
+}
+
+/// Accepts an attestation from any key proven to be a leaf of the registry's
+/// `verifier_merkle_root`, signed in the instruction right before this one.
+fn verify_allowlisted_attestation(
+    instruction_sysvar: &AccountInfo,
+    current_index: usize,
+    expected_message: &[u8],
+    verifier_proof: &[[u8; 32]],
+    registry: &Registry,
+) -> Result<()> {
+    check_allowlist_applies(registry)?;
+    require!(
+        verifier_proof.len() <= MAX_VERIFIER_PROOF_DEPTH,
+        SolanIdError::InvalidVerifierProof
+    );
+
+    let prior_ix = load_instruction_at_checked(current_index - 1, instruction_sysvar)
+        .map_err(|_| error!(SolanIdError::InvalidAttestationInstruction))?;
+    require!(
+        prior_ix.program_id == ed25519_program_id()?,
+        SolanIdError::InvalidAttestationInstruction
+    );
+    let signatures = parse_ed25519_signatures(&prior_ix.data)?;
+    let verifier = Pubkey::try_from(signatures[0].0)
+        .map_err(|_| error!(SolanIdError::InvalidAttestationInstruction))?;
+    require!(
+        verify_merkle_proof(
+            verifier_merkle_leaf(&verifier),
+            verifier_proof,
+            &registry.verifier_merkle_root,
+        ),
+        SolanIdError::InvalidVerifierProof
+    );
+    // The proof covers one key, so every entry must be that verifier's.
+    check_ed25519_signatures(&prior_ix.data, expected_message, &verifier)
+}
+
+/// Rejects the allowlist path unless a root is set and the registry otherwise takes
+/// a single ed25519 verifier. One allowlisted key must not stand in for an M-of-N
+/// verifier set or a secp256k1 verifier.
+pub fn check_allowlist_applies(registry: &Registry) -> Result<()> {
+    require!(
+        registry.verifier_merkle_root != [0; 32]
+            && registry.verifier_set.is_empty()
+            && registry.verifier_scheme == VerifierScheme::Ed25519,
+        SolanIdError::InvalidVerifierProof
+    );
+    Ok(())
+}
+
+/// Leaf for `verifier` in a verifier allowlist tree. Leaves and inner nodes use
+/// distinct prefixes so an inner node can never pass as a leaf.
+pub fn verifier_merkle_leaf(verifier: &Pubkey) -> [u8; 32] {
+    hashv(&[&[0u8], verifier.as_ref()]).to_bytes()
+}
+
+/// Folds `proof` into `leaf` with sorted-pair sha256 hashing and compares the result
+/// to `root`, so proofs carry no left/right path bits.
+pub fn verify_merkle_proof(leaf: [u8; 32], proof: &[[u8; 32]], root: &[u8; 32]) -> bool {
+    let computed = proof.iter().fold(leaf, |node, sibling| {
+        let (left, right) = if node <= *sibling {
+            (node, *sibling)
+        } else {
+            (*sibling, node)
+        };
+        hashv(&[&[1u8], &left, &right]).to_bytes()
+    });
+    computed == *root
+}
+
+/// During a format rollover the registry accepts every version from
+/// `min_attestation_version` up to `attestation_version`.
+fn check_attestation_version(registry: &Registry, attestation_version: u8) -> Result<()> {
+    require!(
+        (registry.min_attestation_version..=registry.attestation_version)
+            .contains(&attestation_version),
+        SolanIdError::UnsupportedAttestationVersion
+    );
+    Ok(())
+}
+
+fn resolve_slot_binding(
+    registry: &Registry,
+    slot_hashes: &AccountInfo,
+    recent_slot: u64,
+    current_slot: u64,
+) -> Result<Option<(u64, [u8; 32])>> {
+    if !registry.slot_hash_binding {
+        return Ok(None);
+    }
+
+    require!(
+        recent_slot <= current_slot && current_slot - recent_slot <= registry.max_slot_hash_age,
+        SolanIdError::StaleSlotHash
+    );
+
+    let data = slot_hashes.try_borrow_data()?;
+    require!(data.len() >= 8, SolanIdError::StaleSlotHash);
+    let entry_count = u64::from_le_bytes(data[..8].try_into().unwrap()) as usize;
+
+    for entry in data[8..].chunks_exact(40).take(entry_count) {
+        let slot = u64::from_le_bytes(entry[..8].try_into().unwrap());
+        if slot == recent_slot {
+            let mut slot_hash = [0u8; 32];
+            slot_hash.copy_from_slice(&entry[8..]);
+            return Ok(Some((slot, slot_hash)));
+        }
+        if slot < recent_slot {
+            break;
+        }
+    }
+
+    err!(SolanIdError::StaleSlotHash)
+}
+
+fn verify_attestation_message(
+    instruction_sysvar: &AccountInfo,
+    current_index: usize,
+    expected_message: &[u8],
+    registry: &Registry,
+) -> Result<()> {
+    if registry.verifier_scheme == VerifierScheme::Secp256k1 {
+        let prior_index = current_index - 1;
+        let prior_ix = load_instruction_at_checked(prior_index, instruction_sysvar)
+            .map_err(|_| error!(SolanIdError::InvalidAttestationInstruction))?;
+
+        return verify_secp256k1_attestation(
+            &prior_ix,
+            prior_index,
+            expected_message,
+            &registry.verifier_eth_address,
+        );
+    }
+
+    let (verifiers, threshold) = registry.verifier_quorum();
+    if threshold <= 1 && verifiers.len() == 1 {
+        let prior_ix = load_instruction_at_checked(current_index - 1, instruction_sysvar)
+            .map_err(|_| error!(SolanIdError::InvalidAttestationInstruction))?;
+
+        return validate_ed25519_instruction(&prior_ix, expected_message, &verifiers[0]);
+    }
+
+    let ed25519_program_id = ed25519_program_id()?;
+    let mut attestations = Vec::new();
+
+    // Only the run of signature instructions directly before this one counts, so
+    // attestations cannot be picked up from elsewhere in the transaction.
+    for index in (0..current_index).rev() {
+        let ix = load_instruction_at_checked(index, instruction_sysvar)
+            .map_err(|_| error!(SolanIdError::InvalidAttestationInstruction))?;
+        if ix.program_id != ed25519_program_id {
+            break;
+        }
+        attestations.push(ix.data);
+    }
+
+    require!(
+        !attestations.is_empty(),
+        SolanIdError::InvalidAttestationInstruction
+    );
+    require!(
+        count_quorum_signers(
+            attestations.iter().map(Vec::as_slice),
+            expected_message,
+            verifiers
+        ) >= threshold as usize,
+        SolanIdError::InvalidAttestationMessage
+    );
+
+    Ok(())
+}
+
+/// Counts the distinct `verifiers` that signed `expected_message` across a run of
+/// ed25519 instruction datas. Entries from other keys or over other messages are
+/// ignored, and so is an instruction this program can't parse, such as one whose
+/// entries point into another instruction, so an unrelated signature in the run
+/// can't sink the quorum.
+pub fn count_quorum_signers<'a>(
+    attestations: impl IntoIterator<Item = &'a [u8]>,
+    expected_message: &[u8],
+    verifiers: &[Pubkey],
+) -> usize {
+    let mut signers: Vec<&Pubkey> = Vec::with_capacity(verifiers.len());
+    for data in attestations {
+        let Ok(signatures) = parse_ed25519_signatures(data) else {
+            continue;
+        };
+        for (public_key, message) in signatures {
+            if check_signed_message(message, expected_message).is_err() {
+                continue;
+            }
+            if let Some(verifier) = verifiers.iter().find(|v| v.as_ref() == public_key) {
+                if !signers.contains(&verifier) {
+                    signers.push(verifier);
+                }
+            }
+        }
+    }
+    signers.len()
+}
+
+/// Submissions must be the only instruction of this program in the transaction that
+/// touches the user's accounts in `guarded`. Together with the attestation sitting
+/// directly before the submission, this keeps a relayer from wrapping a lifted
+/// attestation in instructions that read or change the same user's state around it.
+fn check_sole_user_instruction(
+    instruction_sysvar: &AccountInfo,
+    program_id: &Pubkey,
+    guarded: &[Pubkey],
+) -> Result<()> {
+    let current_index = load_current_index_checked(instruction_sysvar)
+        .map_err(|_| error!(SolanIdError::InvalidAttestationInstruction))?
+        as usize;
+    let mut index = 0;
+    while let Ok(ix) = load_instruction_at_checked(index, instruction_sysvar) {
+        require!(
+            index == current_index
+                || ix.program_id != *program_id
+                || !ix
+                    .accounts
+                    .iter()
+                    .any(|meta| guarded.contains(&meta.pubkey)),
+            SolanIdError::ConflictingUserInstruction
+        );
+        index += 1;
+    }
+    Ok(())
+}
+
+fn ed25519_program_id() -> Result<Pubkey> {
+    Pubkey::from_str("Ed25519SigVerify111111111111111111111111111")
+        .map_err(|_| error!(SolanIdError::InvalidAttestationInstruction))
+}
+
+fn verify_secp256k1_attestation(
     instruction: &Instruction,
+    instruction_index: usize,
     expected_message: &[u8],
-    expected_signer: &Pubkey,
+    expected_eth_address: &[u8; 20],
 ) -> Result<()> {
-    let ed25519_program_id = Pubkey::from_str("Ed25519SigVerify111111111111111111111111111")
+    let secp256k1_program_id = Pubkey::from_str("KeccakSecp256k11111111111111111111111111111")
         .map_err(|_| error!(SolanIdError::InvalidAttestationInstruction))?;
 
     require!(
-        instruction.program_id == ed25519_program_id,
+        instruction.program_id == secp256k1_program_id,
         SolanIdError::InvalidAttestationInstruction
     );
 
     let data = &instruction.data;
     require!(
-        data.len() >= 16,
+        data.len() >= 12,
         SolanIdError::InvalidAttestationInstruction
     );
     require!(data[0] == 1, SolanIdError::InvalidAttestationInstruction);
 
-    let signature_offset = read_u16_le(data, 2)? as usize;
-    let signature_instruction_index = read_u16_le(data, 4)?;
-    let public_key_offset = read_u16_le(data, 6)? as usize;
-    let public_key_instruction_index = read_u16_le(data, 8)?;
-    let message_data_offset = read_u16_le(data, 10)? as usize;
-    let message_data_size = read_u16_le(data, 12)? as usize;
-    let message_instruction_index = read_u16_le(data, 14)?;
+    let signature_offset = read_u16_le(data, 1)? as usize;
+    let signature_instruction_index = data[3];
+    let eth_address_offset = read_u16_le(data, 4)? as usize;
+    let eth_address_instruction_index = data[6];
+    let message_data_offset = read_u16_le(data, 7)? as usize;
+    let message_data_size = read_u16_le(data, 9)? as usize;
+    let message_instruction_index = data[11];
 
+    let self_index = u8::try_from(instruction_index)
+        .map_err(|_| error!(SolanIdError::InvalidAttestationInstruction))?;
     require!(
-        signature_instruction_index == u16::MAX
-            && public_key_instruction_index == u16::MAX
-            && message_instruction_index == u16::MAX,
+        signature_instruction_index == self_index
+            && eth_address_instruction_index == self_index
+            && message_instruction_index == self_index,
         SolanIdError::InvalidAttestationInstruction
     );
 
     let signature_end = signature_offset
-        .checked_add(64)
+        .checked_add(65)
         .ok_or(SolanIdError::InvalidAttestationInstruction)?;
-    let public_key_end = public_key_offset
-        .checked_add(32)
+    let eth_address_end = eth_address_offset
+        .checked_add(20)
         .ok_or(SolanIdError::InvalidAttestationInstruction)?;
     let message_end = message_data_offset
         .checked_add(message_data_size)
@@ -873,23 +7959,138 @@ fn validate_ed25519_instruction(
     let _signature = data
         .get(signature_offset..signature_end)
         .ok_or(SolanIdError::InvalidAttestationInstruction)?;
-    let public_key = data
-        .get(public_key_offset..public_key_end)
+    let eth_address = data
+        .get(eth_address_offset..eth_address_end)
         .ok_or(SolanIdError::InvalidAttestationInstruction)?;
     let message = data
         .get(message_data_offset..message_end)
         .ok_or(SolanIdError::InvalidAttestationInstruction)?;
 
     require!(
-        public_key == expected_signer.as_ref(),
+        eth_address == expected_eth_address.as_ref(),
         SolanIdError::InvalidAttestationMessage
     );
+    check_signed_message(message, expected_message)
+}
+
+fn validate_ed25519_instruction(
+    instruction: &Instruction,
+    expected_message: &[u8],
+    expected_signer: &Pubkey,
+) -> Result<()> {
+    require!(
+        instruction.program_id == ed25519_program_id()?,
+        SolanIdError::InvalidAttestationInstruction
+    );
+
+    check_ed25519_signatures(&instruction.data, expected_message, expected_signer)
+}
+
+/// Requires every signature in an ed25519 instruction's data to be `expected_signer`'s
+/// over `expected_message`, so no entry rides along unchecked.
+pub fn check_ed25519_signatures(
+    data: &[u8],
+    expected_message: &[u8],
+    expected_signer: &Pubkey,
+) -> Result<()> {
+    for (public_key, message) in parse_ed25519_signatures(data)? {
+        require!(
+            public_key == expected_signer.as_ref(),
+            SolanIdError::InvalidAttestationMessage
+        );
+        check_signed_message(message, expected_message)?;
+    }
+    Ok(())
+}
+
+/// Compares a signed message to the one this instruction expects, checking the
+/// embedded registry on its own first. A digest has no registry to pick out, but
+/// commits to it along with the rest of the message.
+fn check_signed_message(message: &[u8], expected_message: &[u8]) -> Result<()> {
+    if expected_message.len() != ATTESTATION_DIGEST_LEN {
+        let registry = expected_message
+            .get(MESSAGE_REGISTRY_OFFSET..MESSAGE_REGISTRY_OFFSET + 32)
+            .and_then(|bytes| Pubkey::try_from(bytes).ok())
+            .ok_or(SolanIdError::InvalidAttestationMessage)?;
+        check_attestation_registry(message, &registry)?;
+    }
     require!(
         message == expected_message,
         SolanIdError::InvalidAttestationMessage
     );
+    Ok(())
+}
+
+/// Rejects a signed message that embeds a registry other than `registry`. Every
+/// message layout carries the registry right after its domain tag and program id,
+/// so a verifier key shared by two registries cannot have an attestation for one
+/// accepted by the other.
+pub fn check_attestation_registry(message: &[u8], registry: &Pubkey) -> Result<()> {
+    require!(
+        message.get(MESSAGE_REGISTRY_OFFSET..MESSAGE_REGISTRY_OFFSET + 32)
+            == Some(registry.as_ref()),
+        SolanIdError::InvalidAttestationMessage
+    );
+    Ok(())
+}
+
+/// Size of one signature's offsets block in an ed25519 precompile instruction.
+const ED25519_OFFSETS_LEN: usize = 14;
+
+/// Reads every `(public_key, message)` pair out of an ed25519 precompile
+/// instruction's data, one per offsets block after the two-byte header. Each entry
+/// must point into this same instruction; the precompile itself has already checked
+/// the signatures by the time this program runs.
+pub fn parse_ed25519_signatures(data: &[u8]) -> Result<Vec<(&[u8], &[u8])>> {
+    let num_signatures = *data
+        .first()
+        .ok_or(SolanIdError::InvalidAttestationInstruction)? as usize;
+    require!(
+        num_signatures > 0 && data.len() >= 2 + num_signatures * ED25519_OFFSETS_LEN,
+        SolanIdError::InvalidAttestationInstruction
+    );
+
+    (0..num_signatures)
+        .map(|index| {
+            let offsets = 2 + index * ED25519_OFFSETS_LEN;
+            let signature_offset = read_u16_le(data, offsets)? as usize;
+            let signature_instruction_index = read_u16_le(data, offsets + 2)?;
+            let public_key_offset = read_u16_le(data, offsets + 4)? as usize;
+            let public_key_instruction_index = read_u16_le(data, offsets + 6)?;
+            let message_data_offset = read_u16_le(data, offsets + 8)? as usize;
+            let message_data_size = read_u16_le(data, offsets + 10)? as usize;
+            let message_instruction_index = read_u16_le(data, offsets + 12)?;
+
+            require!(
+                signature_instruction_index == u16::MAX
+                    && public_key_instruction_index == u16::MAX
+                    && message_instruction_index == u16::MAX,
+                SolanIdError::InvalidAttestationInstruction
+            );
+
+            let signature_end = signature_offset
+                .checked_add(64)
+                .ok_or(SolanIdError::InvalidAttestationInstruction)?;
+            let public_key_end = public_key_offset
+                .checked_add(32)
+                .ok_or(SolanIdError::InvalidAttestationInstruction)?;
+            let message_end = message_data_offset
+                .checked_add(message_data_size)
+                .ok_or(SolanIdError::InvalidAttestationInstruction)?;
 
-    Ok(())
+            let _signature = data
+                .get(signature_offset..signature_end)
+                .ok_or(SolanIdError::InvalidAttestationInstruction)?;
+            let public_key = data
+                .get(public_key_offset..public_key_end)
+                .ok_or(SolanIdError::InvalidAttestationInstruction)?;
+            let message = data
+                .get(message_data_offset..message_end)
+                .ok_or(SolanIdError::InvalidAttestationInstruction)?;
+
+            Ok((public_key, message))
+        })
+        .collect()
 }
 
 fn is_non_zero_hash(hash: &[u8; 32]) -> bool {
@@ -910,6 +8111,14 @@ fn extract_identity_nullifier(
         (ProofSource::WorldId, SourceProofData::WorldId { nullifier_hash, .. }) => {
             Ok(*nullifier_hash)
         }
+        (ProofSource::CoinbaseKyc, SourceProofData::CoinbaseKyc { account_hash, .. }) => {
+            Ok(*account_hash)
+        }
+        (ProofSource::LinkedIn, SourceProofData::LinkedIn { member_hash, .. }) => Ok(*member_hash),
+        // The fid is the Farcaster identity, so custody transfers keep the nullifier.
+        (ProofSource::Farcaster, SourceProofData::Farcaster { fid, .. }) => {
+            Ok(hashv(&[b"farcaster", &fid.to_le_bytes()]).to_bytes())
+        }
         _ => err!(SolanIdError::SourcePayloadMismatch),
     }
 }
@@ -918,8 +8127,25 @@ fn validate_source_proof_data(
     source: ProofSource,
     proof_data: &SourceProofData,
     base_score: u64,
+    timestamp: i64,
+    scoring_config: &ScoringConfig,
+    clock_skew_tolerance: i64,
     now: i64,
 ) -> Result<()> {
+    // A zero-score proof adds nothing but would still count towards the diversity
+    // bonus as an active source.
+    require!(base_score > 0, SolanIdError::ZeroScoreProof);
+    let index = source.as_index(&scoring_config.weights)?;
+    require!(
+        !scoring_config.reject_zero_weight_sources || scoring_config.weights[index] > 0,
+        SolanIdError::SourceDisabled
+    );
+    let max_base_score = scoring_config.max_base_score[index];
+    require!(
+        max_base_score == 0 || base_score <= max_base_score,
+        SolanIdError::InvalidSourceProofData
+    );
+
     match (source, proof_data) {
         (
             ProofSource::Reclaim,
@@ -943,13 +8169,14 @@ fn validate_source_proof_data(
                 SolanIdError::InvalidSourceProofData
             );
             require!(
-                *issued_at <= now + 300,
+                *issued_at <= now + clock_skew_tolerance,
                 SolanIdError::InvalidSourceProofData
             );
             require!(
                 *issued_at >= now - 86_400,
                 SolanIdError::InvalidSourceProofData
             );
+            scoring_config.check_issued_at_skew(timestamp, *issued_at)?;
         }
         (
             ProofSource::GitcoinPassport,
@@ -964,9 +8191,18 @@ fn validate_source_proof_data(
                 is_non_zero_hash(did_hash),
                 SolanIdError::InvalidSourceProofData
             );
-            require!(*stamp_count > 0, SolanIdError::InvalidSourceProofData);
-            require!(*passport_score > 0, SolanIdError::InvalidSourceProofData);
-            require!(*model_version > 0, SolanIdError::InvalidSourceProofData);
+            require!(
+                *stamp_count >= scoring_config.min_stamp_count.max(1),
+                SolanIdError::InvalidSourceProofData
+            );
+            require!(
+                *passport_score >= scoring_config.min_passport_score.max(1),
+                SolanIdError::InvalidSourceProofData
+            );
+            require!(
+                *model_version >= scoring_config.min_model_version.max(1),
+                SolanIdError::InvalidSourceProofData
+            );
             require!(
                 base_score <= *passport_score as u64,
                 SolanIdError::InvalidSourceProofData
@@ -989,7 +8225,7 @@ fn validate_source_proof_data(
                 SolanIdError::InvalidSourceProofData
             );
             require!(
-                (1..=2).contains(verification_level),
+                (scoring_config.min_world_id_level.max(1)..=2).contains(verification_level),
                 SolanIdError::InvalidSourceProofData
             );
         }
@@ -1067,6 +8303,47 @@ fn validate_source_proof_data(
                 SolanIdError::InvalidSourceProofData
             );
         }
+        (
+            ProofSource::CoinbaseKyc,
+            SourceProofData::CoinbaseKyc {
+                account_hash,
+                country_hash,
+                verified_at,
+            },
+        ) => {
+            require!(
+                is_non_zero_hash(account_hash),
+                SolanIdError::InvalidSourceProofData
+            );
+            require!(
+                is_non_zero_hash(country_hash),
+                SolanIdError::InvalidSourceProofData
+            );
+            require!(
+                *verified_at > 0 && *verified_at <= now + clock_skew_tolerance,
+                SolanIdError::InvalidSourceProofData
+            );
+        }
+        (
+            ProofSource::LinkedIn,
+            SourceProofData::LinkedIn {
+                member_hash,
+                connection_count,
+            },
+        ) => {
+            require!(
+                is_non_zero_hash(member_hash),
+                SolanIdError::InvalidSourceProofData
+            );
+            require!(*connection_count > 0, SolanIdError::InvalidSourceProofData);
+        }
+        (ProofSource::Farcaster, SourceProofData::Farcaster { fid, custody_hash }) => {
+            require!(*fid > 0, SolanIdError::InvalidSourceProofData);
+            require!(
+                is_non_zero_hash(custody_hash),
+                SolanIdError::InvalidSourceProofData
+            );
+        }
         _ => return err!(SolanIdError::SourcePayloadMismatch),
     }
 
@@ -1084,12 +8361,75 @@ pub enum ProofSource {
     Twitter = 5,
     Google = 6,
     Discord = 7,
+    CoinbaseKyc = 8,
+    LinkedIn = 9,
+    Farcaster = 10,
+}
+
+impl ProofSource {
+    /// Number of variants, which must be the last variant's discriminant plus one.
+    /// Per-source arrays in accounts are sized to `MAX_PROOF_SOURCES` instead, so
+    /// adding a variant does not move any account field.
+    pub const VARIANT_COUNT: usize = ProofSource::Farcaster as usize + 1;
+
+    pub const fn count() -> usize {
+        Self::VARIANT_COUNT
+    }
+
+    /// Index of this source into a per-source array, checked against its length so a
+    /// source the array was not sized for errors instead of panicking.
+    pub fn as_index<T>(self, array: &[T]) -> Result<usize> {
+        let index = self as usize;
+        require!(index < array.len(), SolanIdError::SourcePayloadMismatch);
+        Ok(index)
+    }
 }
 
 impl anchor_lang::Space for ProofSource {
     const INIT_SPACE: usize = 1;
 }
 
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, InitSpace, PartialEq, Eq)]
+pub enum ReputationTier {
+    None,
+    Bronze,
+    Silver,
+    Gold,
+    Platinum,
+}
+
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, InitSpace, PartialEq, Eq)]
+pub enum VerifierScheme {
+    Ed25519,
+    Secp256k1,
+}
+
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, InitSpace, PartialEq, Eq, Debug)]
+pub enum DecayMode {
+    Bracketed,
+    Linear,
+    Exponential,
+}
+
+/// How per-source contributions fold into a user's base score before the bonuses.
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, InitSpace, PartialEq, Eq, Debug)]
+pub enum ScoringMode {
+    /// Sum of every source's contribution.
+    Additive,
+    /// The single highest contribution. Breadth earns nothing, so the diversity and
+    /// cross-ecosystem bonuses are skipped.
+    Max,
+    /// Contributions averaged by source weight, so extra sources only help if they
+    /// score above the current average.
+    WeightedAverage,
+}
+
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, InitSpace, PartialEq, Eq, Debug)]
+pub enum ProofHashScope {
+    PerUser,
+    Global,
+}
+
 #[derive(AnchorSerialize, AnchorDeserialize, Clone, InitSpace, PartialEq, Eq)]
 pub enum SourceProofData {
     Reclaim {
@@ -1129,13 +8469,111 @@ pub enum SourceProofData {
         user_id_hash: [u8; 32],
         guild_id_hash: [u8; 32],
     },
+    CoinbaseKyc {
+        account_hash: [u8; 32],
+        country_hash: [u8; 32],
+        verified_at: i64,
+    },
+    LinkedIn {
+        member_hash: [u8; 32],
+        connection_count: u32,
+    },
+    Farcaster {
+        fid: u64,
+        custody_hash: [u8; 32],
+    },
 }
 
 #[derive(AnchorSerialize, AnchorDeserialize, Clone)]
+pub struct ProofSubmission {
+    pub proof_hash: [u8; 32],
+    pub source: ProofSource,
+    pub identity_nullifier: [u8; 32],
+    pub attestation_nonce: u64,
+    pub proof_data: SourceProofData,
+    pub base_score: u64,
+    pub timestamp: i64,
+    pub recent_slot: u64,
+    pub attestation_version: u8,
+    /// Verifier-attested expiry for this proof alone. Zero means it only expires with
+    /// the aggregate.
+    pub expires_at: i64,
+    /// Verifier-attested percent applied after the source weight, 100 being neutral.
+    pub trust_multiplier: u8,
+    /// Verifier-attested weight used in place of the source's configured one. Zero
+    /// keeps the configured weight.
+    pub weight_override: u64,
+}
+
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Default)]
 pub struct ProofStatus {
     pub is_verified: bool,
+    /// Stored aggregate scaled by the registry's `global_score_multiplier_bps`.
     pub aggregated_score: u64,
     pub verified_at: i64,
+    /// True when an active source has crossed a decay boundary since `last_submission`.
+    pub is_stale: bool,
+    /// `aggregated_score` recomputed against the current clock. Equals `aggregated_score`
+    /// when no `IndividualProof` accounts are passed.
+    pub live_score: u64,
+    /// True when verification only passes because of a grace period past `valid_until`.
+    pub in_grace: bool,
+    /// First check `is_verified` failed on, or `Ok` when it passed.
+    pub reason: VerificationReason,
+}
+
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, PartialEq, Eq, Debug, Default)]
+pub enum VerificationReason {
+    Ok,
+    #[default]
+    NoProof,
+    BelowThreshold,
+    Expired,
+    Banned,
+    Flagged,
+}
+
+#[derive(AnchorSerialize, AnchorDeserialize, Clone)]
+pub struct ActiveSource {
+    pub source: ProofSource,
+    /// Weighted score decayed to the current clock, before bonuses.
+    pub live_score: u64,
+}
+
+#[derive(AnchorSerialize, AnchorDeserialize, Clone)]
+pub struct ActiveSources {
+    /// `ProofSource` bits of `sources`.
+    pub sources_mask: u16,
+    pub sources: Vec<ActiveSource>,
+}
+
+#[derive(AnchorSerialize, AnchorDeserialize, Clone)]
+pub struct RegistryStats {
+    pub total_verified_users: u64,
+    pub min_score: u64,
+    /// Current `ScoringConfig` weight per `ProofSource`, indexed by its discriminant.
+    pub active_source_weights: [u64; PROOF_SOURCE_COUNT],
+    pub verifier_authority: Pubkey,
+    pub paused: bool,
+    pub proof_ttl_seconds: i64,
+}
+
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Debug, Default, PartialEq, Eq)]
+pub struct CooldownStatus {
+    pub in_cooldown: bool,
+    /// Zero once the cooldown has elapsed.
+    pub seconds_remaining: i64,
+    /// `last_submission + cooldown_period`; zero for a user that never submitted.
+    pub next_eligible_at: i64,
+}
+
+#[derive(AnchorSerialize, AnchorDeserialize, Clone)]
+pub struct NullifierStatus {
+    pub is_claimed: bool,
+    pub claimed_by: Pubkey,
+    pub source: ProofSource,
+    pub is_burned: bool,
+    pub claimed_at: i64,
 }
 
 #[event]
@@ -1146,6 +8584,37 @@ pub struct ProofSubmitted {
     pub weighted_score: u64,
     pub source: ProofSource,
     pub timestamp: i64,
+    /// The user's aggregate and active source count after this submission.
+    pub aggregated_score: u64,
+    pub active_source_count: u8,
+    /// How far the aggregate still falls short of `min_score`, 0 once it is met.
+    pub points_to_min_score: u64,
+    /// The user's lifetime submission count, this one included.
+    pub total_submissions: u64,
+}
+
+/// Emitted when a submission consumes a per-nonce registry, for monitoring verifier
+/// attestations apart from `ProofSubmitted`. `verifier` is the registry's
+/// `verifier_authority` at the time.
+#[event]
+pub struct AttestationNonceUsed {
+    pub nonce: u64,
+    pub user: Pubkey,
+    pub verifier: Pubkey,
+    pub used_at: i64,
+}
+
+#[event]
+pub struct BonusRecomputed {
+    pub user: Pubkey,
+    pub old_score: u64,
+    pub new_score: u64,
+}
+
+#[event]
+pub struct UserMarked {
+    pub user: Pubkey,
+    pub aggregated_score: u64,
 }
 
 #[event]
@@ -1153,6 +8622,100 @@ pub struct ProofRevoked {
     pub user: Pubkey,
     pub proof_hash: [u8; 32],
     pub source: ProofSource,
+    /// The user's aggregate and active source count after this revocation.
+    pub aggregated_score: u64,
+    pub active_source_count: u8,
+    /// The user's lifetime revocation count, this one included.
+    pub total_revocations: u32,
+}
+
+#[event]
+pub struct AllProofsRevoked {
+    pub user: Pubkey,
+    pub revoked_count: u8,
+}
+
+#[event]
+pub struct SourceSoftRevoked {
+    pub user: Pubkey,
+    pub proof_hash: [u8; 32],
+    pub source: ProofSource,
+    pub revoked_at: i64,
+}
+
+#[event]
+pub struct IdentityBanned {
+    pub authority: Pubkey,
+    pub nullifier: [u8; 32],
+    pub source: ProofSource,
+    pub claimed_by: Pubkey,
+    pub revoked_count: u8,
+}
+
+#[event]
+pub struct ProofRevokedByVerifier {
+    pub user: Pubkey,
+    pub proof_hash: [u8; 32],
+    pub source: ProofSource,
+    pub identity_nullifier: [u8; 32],
+    pub aggregated_score: u64,
+}
+
+#[event]
+pub struct ProofDowngraded {
+    pub user: Pubkey,
+    pub source: ProofSource,
+    pub previous_base_score: u64,
+    pub base_score: u64,
+    /// The source's new share of the aggregate.
+    pub contributed_score: u64,
+    pub aggregated_score: u64,
+}
+
+#[event]
+pub struct PersonLinked {
+    pub user: Pubkey,
+    pub source: ProofSource,
+    pub person_id_hash: [u8; 32],
+    pub aggregated_score: u64,
+}
+
+#[event]
+pub struct WalletAgeAttested {
+    pub user: Pubkey,
+    pub wallet_first_seen: i64,
+    pub aggregated_score: u64,
+}
+
+#[event]
+pub struct IdentitySoulbound {
+    pub user: Pubkey,
+    pub source: ProofSource,
+    pub identity_nullifier: [u8; 32],
+}
+
+#[event]
+pub struct IdentityFlagged {
+    pub flagger: Pubkey,
+    pub user: Pubkey,
+    pub source: ProofSource,
+    pub identity_nullifier: [u8; 32],
+}
+
+#[event]
+pub struct IdentityFlagCleared {
+    pub authority: Pubkey,
+    pub user: Pubkey,
+    pub source: ProofSource,
+    pub identity_nullifier: [u8; 32],
+}
+
+#[event]
+pub struct ProofForceRevoked {
+    pub authority: Pubkey,
+    pub user: Pubkey,
+    pub proof_hash: [u8; 32],
+    pub source: ProofSource,
 }
 
 #[event]
@@ -1161,12 +8724,37 @@ pub struct MinScoreUpdated {
     pub new_score: u64,
 }
 
+/// Emitted by `initialize_registry`, with zero old values, and `update_registry_config`.
+#[event]
+pub struct RegistryConfigUpdated {
+    pub authority: Pubkey,
+    pub old_cooldown_period: i64,
+    pub new_cooldown_period: i64,
+    pub old_diversity_bonus_percent: u8,
+    pub new_diversity_bonus_percent: u8,
+    pub old_proof_ttl_seconds: i64,
+    pub new_proof_ttl_seconds: i64,
+}
+
+#[event]
+pub struct MinScoreChangeInitiated {
+    pub current_score: u64,
+    pub pending_score: u64,
+    pub activate_at: i64,
+}
+
 #[event]
 pub struct ScoringConfigUpdated {
     pub source: ProofSource,
     pub weight: u64,
 }
 
+#[event]
+pub struct ScoringAuthorityUpdated {
+    pub old_authority: Pubkey,
+    pub new_authority: Pubkey,
+}
+
 #[event]
 pub struct VerifierRotationInitiated {
     pub current_verifier: Pubkey,
@@ -1180,6 +8768,98 @@ pub struct VerifierRotationFinalized {
     pub new_verifier: Pubkey,
 }
 
+#[event]
+pub struct VerifierRotationCancelled {
+    pub current_verifier: Pubkey,
+    pub cancelled_verifier: Pubkey,
+}
+
+#[event]
+pub struct GuardianUpdated {
+    pub old_guardian: Pubkey,
+    pub new_guardian: Pubkey,
+}
+
+#[event]
+pub struct EmergencyVerifierRotation {
+    pub guardian: Pubkey,
+    pub old_verifier: Pubkey,
+    pub new_verifier: Pubkey,
+}
+
+#[event]
+pub struct LegacyProofImported {
+    pub user: Pubkey,
+    pub source: ProofSource,
+}
+
+#[event]
+pub struct UserProofExpired {
+    pub user: Pubkey,
+    pub expired_score: u64,
+    pub expired_source_count: u8,
+}
+
+#[event]
+pub struct IdentityNullifierReclaimed {
+    pub nullifier: [u8; 32],
+    pub source: ProofSource,
+    pub previous_owner: Pubkey,
+}
+
+#[event]
+pub struct AccountMigrated {
+    pub account: Pubkey,
+    pub from_version: u8,
+    pub to_version: u8,
+}
+
+#[event]
+pub struct IdentityMigrated {
+    pub nullifier: [u8; 32],
+    pub old_owner: Pubkey,
+    pub new_owner: Pubkey,
+}
+
+#[event]
+pub struct IdentityTransferred {
+    pub nullifier: [u8; 32],
+    pub old_owner: Pubkey,
+    pub new_owner: Pubkey,
+}
+
+#[event]
+pub struct VerifierSetChangeInitiated {
+    pub verifier_count: u8,
+    pub threshold: u8,
+    pub activate_at: i64,
+}
+
+#[event]
+pub struct VerifierSetUpdated {
+    pub verifier_count: u8,
+    pub threshold: u8,
+}
+
+#[event]
+pub struct VerifierMerkleRootUpdated {
+    pub old_root: [u8; 32],
+    pub new_root: [u8; 32],
+}
+
+#[event]
+pub struct UserBanToggled {
+    pub authority: Pubkey,
+    pub user: Pubkey,
+    pub banned: bool,
+}
+
+#[event]
+pub struct RegistryPauseToggled {
+    pub authority: Pubkey,
+    pub paused: bool,
+}
+
 #[error_code]
 pub enum SolanIdError {
     #[msg("Score is below the minimum threshold")]
@@ -1220,4 +8900,92 @@ pub enum SolanIdError {
     NoVerifierRotationPending,
     #[msg("Verifier rotation delay has not elapsed")]
     VerifierRotationNotReady,
+    #[msg("Proof must be revoked before it can be closed")]
+    ProofNotRevoked,
+    #[msg("Registry is paused")]
+    RegistryPaused,
+    #[msg("Account is not a legacy proof layout")]
+    InvalidLegacyAccount,
+    #[msg("Batch size is out of range")]
+    InvalidBatchSize,
+    #[msg("Batch accounts do not match submissions")]
+    InvalidBatchAccounts,
+    #[msg("Attested slot hash is missing or too old")]
+    StaleSlotHash,
+    #[msg("Proof accounts do not match the user's active sources")]
+    InvalidProofAccounts,
+    #[msg("Source was revoked too recently to be re-claimed")]
+    ReclaimCooldownActive,
+    #[msg("Attestation nonce is still inside its retention window")]
+    NonceRetentionActive,
+    #[msg("User already holds the maximum number of active sources")]
+    MaxSourcesReached,
+    #[msg("Attestation version is not accepted by the registry")]
+    UnsupportedAttestationVersion,
+    #[msg("Attestation nonce is not the user's next sequential nonce")]
+    AttestationNonceOutOfOrder,
+    #[msg("Attestation nonce account does not match the registry's nonce mode")]
+    NonceModeMismatch,
+    #[msg("No min score change is pending")]
+    NoMinScoreChangePending,
+    #[msg("Pending min score change is not active yet")]
+    MinScoreChangeNotReady,
+    #[msg("User is banned")]
+    UserBanned,
+    #[msg("Account cannot be migrated")]
+    InvalidMigrationAccount,
+    #[msg("Account was written by a newer layout version")]
+    AccountVersionDowngrade,
+    #[msg("User proof has not expired yet")]
+    UserProofNotExpired,
+    #[msg("Fee destination is missing or does not match the registry")]
+    InvalidFeeDestination,
+    #[msg("Registry submission limit reached for this slot")]
+    RateLimited,
+    #[msg("Person id binding is disabled")]
+    PersonBindingDisabled,
+    #[msg("Person id hash cannot be zero")]
+    InvalidPersonId,
+    #[msg("Wallet first-seen time must be in the past")]
+    InvalidWalletFirstSeen,
+    #[msg("Identity is soulbound and cannot be migrated")]
+    SoulboundIdentity,
+    #[msg("Identity is flagged for review")]
+    IdentityUnderReview,
+    #[msg("Identity is not flagged")]
+    IdentityNotFlagged,
+    #[msg("Proof expiry must be in the future and within the registry TTL")]
+    InvalidProofExpiry,
+    #[msg("Account exists but was never fully initialized")]
+    PartiallyInitializedAccount,
+    #[msg("Downgrade cannot raise the base score; use submit_proof")]
+    ScoreIncreaseNotAllowed,
+    #[msg("Verifier key is not in the allowlist")]
+    InvalidVerifierProof,
+    #[msg("Attestation timestamp is too far from the source's issued_at")]
+    IssuedAtMismatch,
+    #[msg("Identity nullifier is still inside its reclaim window")]
+    NullifierReclaimWindowActive,
+    #[msg("Identity nullifier is not claimed")]
+    NullifierNotClaimed,
+    #[msg("Cooldown period must be shorter than the proof TTL")]
+    CooldownExceedsTtl,
+    #[msg("Proof base score must be greater than zero")]
+    ZeroScoreProof,
+    #[msg("Another instruction in the transaction targets the same user accounts")]
+    ConflictingUserInstruction,
+    #[msg("Source was verified too recently to be replaced")]
+    SourceDwellActive,
+    #[msg("Verifier attestation is too old to submit")]
+    AttestationExpired,
+    #[msg("Trust multiplier is zero or above the registry maximum")]
+    InvalidTrustMultiplier,
+    #[msg("Source has zero weight and is not accepted")]
+    SourceDisabled,
+    #[msg("Source is not accepted by this registry")]
+    SourceNotAllowed,
+    #[msg("Verifier rotation expired before it was finalized")]
+    VerifierRotationExpired,
+    #[msg("Weight override is above the registry maximum")]
+    InvalidWeightOverride,
 }
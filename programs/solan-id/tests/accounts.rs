@@ -0,0 +1,404 @@
+mod common;
+
+mod cpi_read {
+    use crate::common::zeroed;
+    use anchor_lang::prelude::*;
+    use solan_id::{Registry, UserProof};
+
+    /// Stand-in for a downstream program that gates an instruction on a verified user
+    /// by loading the SolanID accounts it was passed.
+    mod mock_consumer {
+        use anchor_lang::prelude::*;
+
+        pub fn gated_action<'a>(
+            user_proof: &'a AccountInfo<'a>,
+            registry: &'a AccountInfo<'a>,
+            now: i64,
+        ) -> Result<bool> {
+            let user_proof = Account::<solan_id::UserProof>::try_from(user_proof)?;
+            let registry = Account::<solan_id::Registry>::try_from(registry)?;
+            Ok(solan_id::is_verified_at(&user_proof, &registry, now))
+        }
+    }
+
+    fn account_data<T: AccountSerialize + AccountDeserialize + Space>(
+        edit: impl FnOnce(&mut T),
+    ) -> Vec<u8> {
+        let space = 8 + T::INIT_SPACE;
+        let mut account = zeroed::<T>();
+        edit(&mut account);
+
+        let mut data = Vec::with_capacity(space);
+        account.try_serialize(&mut data).unwrap();
+        data.resize(space, 0);
+        data
+    }
+
+    fn run_gate(user_proof_data: &mut [u8], registry_data: &mut [u8], now: i64) -> Result<bool> {
+        let owner = solan_id::ID;
+        let user_proof_key = Pubkey::new_unique();
+        let registry_key = Pubkey::new_unique();
+        let mut user_proof_lamports = 1;
+        let mut registry_lamports = 1;
+
+        let user_proof_info = AccountInfo::new(
+            &user_proof_key,
+            false,
+            false,
+            &mut user_proof_lamports,
+            user_proof_data,
+            &owner,
+            false,
+            0,
+        );
+        let registry_info = AccountInfo::new(
+            &registry_key,
+            false,
+            false,
+            &mut registry_lamports,
+            registry_data,
+            &owner,
+            false,
+            0,
+        );
+
+        mock_consumer::gated_action(&user_proof_info, &registry_info, now)
+    }
+
+    #[test]
+    fn mock_consumer_reads_verified_user() {
+        let now = 1_700_000_000;
+        let mut registry = account_data::<Registry>(|registry| {
+            registry.min_score = 100;
+        });
+        let mut verified = account_data::<UserProof>(|user_proof| {
+            user_proof.user = Pubkey::new_unique();
+            user_proof.aggregated_score = 150;
+            user_proof.valid_until = now + 60;
+        });
+        let mut below_threshold = account_data::<UserProof>(|user_proof| {
+            user_proof.user = Pubkey::new_unique();
+            user_proof.aggregated_score = 50;
+            user_proof.valid_until = now + 60;
+        });
+
+        assert!(run_gate(&mut verified, &mut registry, now).unwrap());
+        assert!(!run_gate(&mut verified, &mut registry, now + 61).unwrap());
+        assert!(!run_gate(&mut below_threshold, &mut registry, now).unwrap());
+    }
+
+    #[test]
+    fn mock_consumer_rejects_mismatched_account_type() {
+        let mut registry = account_data::<Registry>(|_| {});
+        let mut not_a_user_proof = registry.clone();
+
+        assert!(run_gate(&mut not_a_user_proof, &mut registry, 0).is_err());
+    }
+}
+
+mod identity_move {
+    use crate::common::zeroed;
+    use anchor_lang::prelude::*;
+    use solan_id::{
+        check_identity_move, IdentityNullifierRegistry, IndividualProof, SolanIdError, UserProof,
+    };
+
+    const NULLIFIER: [u8; 32] = [4; 32];
+
+    /// A live proof `old_owner` can hand over.
+    fn claimed(old_owner: Pubkey) -> (IdentityNullifierRegistry, UserProof, IndividualProof) {
+        let mut identity_nullifier_registry: IdentityNullifierRegistry = zeroed();
+        identity_nullifier_registry.claimed_by = old_owner;
+        identity_nullifier_registry.nullifier = NULLIFIER;
+        let mut user_proof: UserProof = zeroed();
+        user_proof.user = old_owner;
+        user_proof.expiry_epoch = 1;
+        let mut individual_proof: IndividualProof = zeroed();
+        individual_proof.user = old_owner;
+        individual_proof.identity_nullifier = NULLIFIER;
+        individual_proof.expiry_epoch = 1;
+        (identity_nullifier_registry, user_proof, individual_proof)
+    }
+
+    #[test]
+    fn live_proof_can_move() {
+        let old_owner = Pubkey::new_unique();
+        let (identity_nullifier_registry, user_proof, individual_proof) = claimed(old_owner);
+        assert!(check_identity_move(
+            &identity_nullifier_registry,
+            &user_proof,
+            &individual_proof,
+            old_owner,
+            Pubkey::new_unique(),
+        )
+        .is_ok());
+    }
+
+    #[test]
+    fn revoked_or_expired_proof_is_not_revived() {
+        let old_owner = Pubkey::new_unique();
+        let (identity_nullifier_registry, mut user_proof, mut individual_proof) =
+            claimed(old_owner);
+        let moved = |user_proof: &UserProof, individual_proof: &IndividualProof| {
+            check_identity_move(
+                &identity_nullifier_registry,
+                user_proof,
+                individual_proof,
+                old_owner,
+                Pubkey::new_unique(),
+            )
+        };
+
+        individual_proof.is_revoked = true;
+        assert_eq!(
+            moved(&user_proof, &individual_proof).unwrap_err(),
+            SolanIdError::ProofAlreadyRevoked.into()
+        );
+
+        // `expire_user_proof` bumps the epoch, zeroing every proof recorded before it.
+        individual_proof.is_revoked = false;
+        user_proof.expiry_epoch = 2;
+        assert_eq!(
+            moved(&user_proof, &individual_proof).unwrap_err(),
+            SolanIdError::ProofAlreadyRevoked.into()
+        );
+    }
+}
+
+mod individual_proof_source {
+    use crate::common::zeroed;
+    use anchor_lang::prelude::*;
+    use solan_id::{IndividualProof, ProofSource, SolanIdError};
+
+    #[test]
+    fn fresh_account_accepts_any_source() {
+        let individual_proof: IndividualProof = zeroed();
+        assert!(individual_proof.check_source(ProofSource::WorldId).is_ok());
+        assert!(individual_proof
+            .check_source(ProofSource::Farcaster)
+            .is_ok());
+    }
+
+    #[test]
+    fn existing_account_rejects_a_mismatched_source() {
+        let mut individual_proof: IndividualProof = zeroed();
+        individual_proof.user = Pubkey::new_unique();
+        individual_proof.source = ProofSource::GitcoinPassport;
+        assert!(individual_proof
+            .check_source(ProofSource::GitcoinPassport)
+            .is_ok());
+        assert_eq!(
+            individual_proof
+                .check_source(ProofSource::WorldId)
+                .unwrap_err(),
+            SolanIdError::SourcePayloadMismatch.into()
+        );
+    }
+}
+
+mod nullifier_reclaim {
+    use crate::common::zeroed;
+    use anchor_lang::prelude::*;
+    use solan_id::{IdentityNullifierRegistry, SolanIdError, NULLIFIER_RECLAIM_WINDOW_SECONDS};
+
+    const VALID_UNTIL: i64 = 1_000_000;
+
+    fn claimed_registry(owner: Pubkey) -> IdentityNullifierRegistry {
+        let mut registry = zeroed::<IdentityNullifierRegistry>();
+        registry.nullifier = [7; 32];
+        registry.claimed_by = owner;
+        registry.initialized = true;
+        registry
+    }
+
+    #[test]
+    fn reclaim_inside_window_is_rejected() {
+        let owner = Pubkey::new_unique();
+        let mut registry = claimed_registry(owner);
+        assert_eq!(
+            registry
+                .reclaim_expired(
+                    VALID_UNTIL,
+                    VALID_UNTIL + NULLIFIER_RECLAIM_WINDOW_SECONDS - 1
+                )
+                .unwrap_err(),
+            SolanIdError::NullifierReclaimWindowActive.into()
+        );
+        assert_eq!(registry.claimed_by, owner);
+    }
+
+    #[test]
+    fn burned_identity_is_never_reclaimed() {
+        let owner = Pubkey::new_unique();
+        let mut registry = claimed_registry(owner);
+        registry.is_burned = true;
+        assert_eq!(
+            registry.reclaim_expired(VALID_UNTIL, i64::MAX).unwrap_err(),
+            SolanIdError::IdentityRevokedPermanent.into()
+        );
+        assert_eq!(registry.claimed_by, owner);
+    }
+
+    #[test]
+    fn reclaim_after_window_releases_the_identity() {
+        let mut registry = claimed_registry(Pubkey::new_unique());
+        registry
+            .reclaim_expired(VALID_UNTIL, VALID_UNTIL + NULLIFIER_RECLAIM_WINDOW_SECONDS)
+            .unwrap();
+        assert_eq!(registry.claimed_by, Pubkey::default());
+        assert_eq!(registry.nullifier, [7; 32]);
+        assert!(!registry.needs_first_write().unwrap());
+
+        assert_eq!(
+            registry
+                .reclaim_expired(VALID_UNTIL, VALID_UNTIL + NULLIFIER_RECLAIM_WINDOW_SECONDS)
+                .unwrap_err(),
+            SolanIdError::NullifierNotClaimed.into()
+        );
+    }
+}
+
+mod proof_hash_uniqueness {
+    use crate::common::{registry_with, zeroed};
+    use anchor_lang::prelude::*;
+    use solan_id::{ProofHashRegistry, ProofHashScope, ProofSource, SolanIdError};
+
+    #[test]
+    fn replaying_a_proof_hash_is_rejected() {
+        let user = Pubkey::new_unique();
+        let mut proof_hash_registry: ProofHashRegistry = zeroed();
+        proof_hash_registry
+            .mark_used(user, ProofSource::GitcoinPassport, 254)
+            .unwrap();
+        assert!(proof_hash_registry.is_used);
+        assert_eq!(proof_hash_registry.user, user);
+
+        // Same user and source: a new nonce does not make the hash reusable.
+        assert_eq!(
+            proof_hash_registry
+                .mark_used(user, ProofSource::GitcoinPassport, 254)
+                .unwrap_err(),
+            SolanIdError::ProofHashAlreadyUsed.into()
+        );
+        assert_eq!(
+            proof_hash_registry
+                .mark_used(user, ProofSource::WorldId, 254)
+                .unwrap_err(),
+            SolanIdError::ProofHashAlreadyUsed.into()
+        );
+    }
+
+    #[test]
+    fn global_scope_drops_the_user_from_the_seed() {
+        let user = Pubkey::new_unique();
+        assert_eq!(registry_with(|_| {}).proof_hash_owner(user), user);
+
+        let registry = registry_with(|registry| registry.proof_hash_scope = ProofHashScope::Global);
+        assert_eq!(registry.proof_hash_owner(user), Pubkey::default());
+        assert_eq!(
+            registry.proof_hash_owner(Pubkey::new_unique()),
+            Pubkey::default()
+        );
+    }
+}
+
+mod registry_init {
+    use crate::common::zeroed;
+    use anchor_lang::prelude::*;
+    use solan_id::{AttestationNonceRegistry, IdentityNullifierRegistry};
+
+    #[test]
+    fn fresh_registries_need_their_first_write() {
+        assert!(zeroed::<IdentityNullifierRegistry>()
+            .needs_first_write()
+            .unwrap());
+        assert!(zeroed::<AttestationNonceRegistry>()
+            .needs_first_write()
+            .unwrap());
+    }
+
+    #[test]
+    fn initialized_registries_are_existing_claims() {
+        let mut nullifier: IdentityNullifierRegistry = zeroed();
+        nullifier.claimed_by = Pubkey::new_unique();
+        nullifier.initialized = true;
+        assert!(!nullifier.needs_first_write().unwrap());
+
+        let mut nonce: AttestationNonceRegistry = zeroed();
+        nonce.is_used = true;
+        nonce.initialized = true;
+        assert!(!nonce.needs_first_write().unwrap());
+    }
+
+    #[test]
+    fn pre_created_registry_without_marker_is_rejected() {
+        // Written by some other path that left `claimed_by` empty but set other state,
+        // which the old `claimed_by == default` check would have treated as fresh.
+        let mut nullifier: IdentityNullifierRegistry = zeroed();
+        nullifier.is_burned = true;
+        nullifier.claimed_at = 1;
+        assert!(nullifier.needs_first_write().is_err());
+
+        let mut claimed: IdentityNullifierRegistry = zeroed();
+        claimed.claimed_by = Pubkey::new_unique();
+        assert!(claimed.needs_first_write().is_err());
+
+        let mut nonce: AttestationNonceRegistry = zeroed();
+        nonce.user = Pubkey::new_unique();
+        assert!(nonce.needs_first_write().is_err());
+    }
+}
+
+mod source_widening {
+    use crate::common::zeroed_legacy;
+    use solan_id::{
+        DecayConfig, LegacyDecayConfig, LegacyScoringConfig, LegacyUserProof, ScoringConfig,
+        UserProof, MAX_PROOF_SOURCES,
+    };
+
+    #[test]
+    fn legacy_scoring_config_keeps_its_sources_and_defaults_the_rest() {
+        let mut legacy: LegacyScoringConfig = zeroed_legacy();
+        legacy.weights = [150, 100, 80, 100, 100, 60, 100, 40];
+        legacy.category = [0, 1, 2, 3, 4, 5, 5, 5];
+        legacy.max_base_score[2] = 500;
+        legacy.diversity_bonus_schedule = [0, 5, 10, 15, 20, 25, 30, 35];
+        legacy.max_issued_at_skew = 300;
+
+        let widened = ScoringConfig::from(legacy);
+        assert_eq!(widened.weights[..8], [150, 100, 80, 100, 100, 60, 100, 40]);
+        assert!(widened.weights[8..].iter().all(|&weight| weight == 100));
+        assert_eq!(widened.category[..8], [0, 1, 2, 3, 4, 5, 5, 5]);
+        assert_eq!(widened.category[8], 8);
+        assert_eq!(widened.max_base_score[2], 500);
+        assert!(widened.max_base_score[8..].iter().all(|&cap| cap == 0));
+        // Counts past the old schedule keep its top percent rather than dropping to zero.
+        assert_eq!(widened.diversity_bonus_percent(11, 0), 35);
+        assert_eq!(widened.max_issued_at_skew, 300);
+    }
+
+    #[test]
+    fn legacy_decay_config_leaves_new_sources_unexpired() {
+        let mut legacy: LegacyDecayConfig = zeroed_legacy();
+        legacy.source_hard_expiry[2] = 86_400;
+
+        let widened = DecayConfig::from(legacy);
+        assert_eq!(widened.source_hard_expiry[2], 86_400);
+        assert_eq!(widened.source_hard_expiry[8..], [0; MAX_PROOF_SOURCES - 8]);
+    }
+
+    #[test]
+    fn legacy_user_proof_keeps_its_source_masks() {
+        let mut legacy: LegacyUserProof = zeroed_legacy();
+        legacy.sources_mask = 0b1000_0101;
+        legacy.person_sources_mask = 0b101;
+        legacy.flagged_sources_mask = 0b1000_0000;
+        legacy.aggregated_score = 300;
+
+        let widened = UserProof::from(legacy);
+        assert_eq!(widened.sources_mask, 0b1000_0101);
+        assert_eq!(widened.person_sources_mask, 0b101);
+        assert_eq!(widened.flagged_sources_mask, 0b1000_0000);
+        assert_eq!(widened.aggregated_score, 300);
+    }
+}
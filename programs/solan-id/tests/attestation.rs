@@ -0,0 +1,479 @@
+mod common;
+
+mod attestation_message {
+    use anchor_lang::prelude::*;
+    use solan_id::attestation::{
+        attestation_domain, ATTESTATION_DOMAIN_TAG, ATTESTS_EXPIRES_AT, ATTESTS_TRUST_MULTIPLIER,
+        ATTESTS_WEIGHT_OVERRIDE,
+    };
+    use solan_id::{
+        attestation_digest, build_attestation_message, check_attestation_registry, ProofSource,
+        ATTESTATION_DIGEST_LEN, NEUTRAL_TRUST_MULTIPLIER,
+    };
+
+    // Shared with the TypeScript suite, which signs the same layout and submits it.
+    const FIXTURE: &str = include_str!("../../../tests/fixtures/attestation-message.json");
+
+    fn fixture_hex(field: &str) -> Vec<u8> {
+        let key = format!("\"{field}\": \"");
+        let start = FIXTURE.find(&key).unwrap() + key.len();
+        let hex = &FIXTURE[start..start + FIXTURE[start..].find('"').unwrap()];
+        (0..hex.len())
+            .step_by(2)
+            .map(|i| u8::from_str_radix(&hex[i..i + 2], 16).unwrap())
+            .collect()
+    }
+
+    fn registry() -> Pubkey {
+        Pubkey::find_program_address(&[b"registry"], &solan_id::ID).0
+    }
+
+    fn fixture_attestation() -> Vec<u8> {
+        build_attestation_message(
+            2,
+            &solan_id::ID,
+            &registry(),
+            &Pubkey::new_from_array([3; 32]),
+            &[5; 32],
+            ProofSource::GitcoinPassport,
+            &[4; 32],
+            7,
+            150,
+            1_700_000_000,
+            Some((9, [6; 32])),
+            1_700_086_400,
+            NEUTRAL_TRUST_MULTIPLIER,
+            0,
+        )
+    }
+
+    #[test]
+    fn helper_matches_the_shared_fixture() {
+        assert_eq!(fixture_attestation(), fixture_hex("message"));
+    }
+
+    #[test]
+    fn digest_matches_the_shared_fixture() {
+        let digest = attestation_digest(&fixture_attestation());
+        assert_eq!(digest.len(), ATTESTATION_DIGEST_LEN);
+        assert_eq!(digest.to_vec(), fixture_hex("digest"));
+    }
+
+    #[test]
+    fn helper_lays_out_documented_fields() {
+        let message = fixture_attestation();
+        assert_eq!(&message[..3], &ATTESTATION_DOMAIN_TAG);
+        assert_eq!(message[..4], attestation_domain(2));
+        assert_eq!(&message[..4], b"sid2");
+        assert_eq!(&message[4..36], solan_id::ID.as_ref());
+        check_attestation_registry(&message, &registry()).unwrap();
+        assert_eq!(message[100], ProofSource::GitcoinPassport as u8);
+        assert_eq!(message[133..141], 7u64.to_le_bytes());
+        assert_eq!(message[141..149], 150u64.to_le_bytes());
+        assert_eq!(message[149..157], 1_700_000_000i64.to_le_bytes());
+        assert_eq!(message[229], ATTESTS_EXPIRES_AT);
+        assert_eq!(message[230..], 1_700_086_400i64.to_le_bytes());
+    }
+
+    fn optional_fields(expires_at: i64, trust_multiplier: u8, weight_override: u64) -> Vec<u8> {
+        build_attestation_message(
+            1,
+            &solan_id::ID,
+            &registry(),
+            &Pubkey::new_from_array([3; 32]),
+            &[5; 32],
+            ProofSource::GitcoinPassport,
+            &[4; 32],
+            7,
+            150,
+            1_700_000_000,
+            None,
+            expires_at,
+            trust_multiplier,
+            weight_override,
+        )
+    }
+
+    #[test]
+    fn optional_fields_cannot_stand_in_for_each_other() {
+        const VALUE: i64 = 1_700_086_400;
+        assert_ne!(
+            optional_fields(VALUE, NEUTRAL_TRUST_MULTIPLIER, 0),
+            optional_fields(0, NEUTRAL_TRUST_MULTIPLIER, VALUE as u64)
+        );
+        let [first, rest @ ..] = VALUE.to_le_bytes();
+        let mut shifted = [0; 8];
+        shifted[..7].copy_from_slice(&rest);
+        assert_ne!(
+            optional_fields(0, first, u64::from_le_bytes(shifted)),
+            optional_fields(VALUE, 0, 0)
+        );
+
+        let message = optional_fields(VALUE, 150, 250);
+        assert_eq!(
+            message[157 + 32],
+            ATTESTS_EXPIRES_AT | ATTESTS_TRUST_MULTIPLIER | ATTESTS_WEIGHT_OVERRIDE
+        );
+        assert_eq!(message.len(), 157 + 32 + 1 + 8 + 1 + 8);
+    }
+}
+
+mod attestation_registry {
+    use anchor_lang::prelude::*;
+    use solan_id::{check_attestation_registry, SolanIdError};
+
+    fn signed_message(registry: &Pubkey) -> Vec<u8> {
+        let mut message = b"sid1".to_vec();
+        message.extend_from_slice(solan_id::ID.as_ref());
+        message.extend_from_slice(registry.as_ref());
+        message.extend_from_slice(Pubkey::new_unique().as_ref());
+        message
+    }
+
+    #[test]
+    fn message_for_this_registry_is_accepted() {
+        let registry = Pubkey::new_unique();
+        assert!(check_attestation_registry(&signed_message(&registry), &registry).is_ok());
+    }
+
+    #[test]
+    fn message_for_another_registry_is_rejected() {
+        let registry = Pubkey::new_unique();
+        let other_registry = Pubkey::new_unique();
+        assert_eq!(
+            check_attestation_registry(&signed_message(&other_registry), &registry).unwrap_err(),
+            SolanIdError::InvalidAttestationMessage.into()
+        );
+        assert!(check_attestation_registry(b"sid1", &registry).is_err());
+    }
+}
+
+mod ed25519_signatures {
+    use anchor_lang::prelude::*;
+    use solan_id::{
+        build_attestation_message, check_ed25519_signatures, count_quorum_signers,
+        parse_ed25519_signatures, ProofSource, SolanIdError, NEUTRAL_TRUST_MULTIPLIER,
+    };
+
+    fn attestation(registry: &Pubkey) -> Vec<u8> {
+        build_attestation_message(
+            1,
+            &solan_id::ID,
+            registry,
+            &Pubkey::new_from_array([3; 32]),
+            &[5; 32],
+            ProofSource::GitcoinPassport,
+            &[4; 32],
+            7,
+            150,
+            1_700_000_000,
+            None,
+            0,
+            NEUTRAL_TRUST_MULTIPLIER,
+            0,
+        )
+    }
+
+    /// Lays out an ed25519 precompile instruction the way the runtime's helper does: the
+    /// header and every offsets block first, then each entry's signature, key and message.
+    fn ed25519_data(entries: &[(Pubkey, &[u8])]) -> Vec<u8> {
+        let mut offsets = vec![entries.len() as u8, 0];
+        let mut payload = Vec::new();
+        let payload_start = 2 + entries.len() * 14;
+        for (public_key, message) in entries {
+            let signature_offset = payload_start + payload.len();
+            payload.extend_from_slice(&[0; 64]);
+            let public_key_offset = payload_start + payload.len();
+            payload.extend_from_slice(public_key.as_ref());
+            let message_offset = payload_start + payload.len();
+            payload.extend_from_slice(message);
+            for value in [
+                signature_offset,
+                u16::MAX as usize,
+                public_key_offset,
+                u16::MAX as usize,
+                message_offset,
+                message.len(),
+                u16::MAX as usize,
+            ] {
+                offsets.extend_from_slice(&(value as u16).to_le_bytes());
+            }
+        }
+        offsets.extend(payload);
+        offsets
+    }
+
+    #[test]
+    fn every_signature_entry_is_parsed() {
+        let registry = Pubkey::new_unique();
+        let (first, second) = (Pubkey::new_unique(), Pubkey::new_unique());
+        let message = attestation(&registry);
+        let data = ed25519_data(&[(first, &message), (second, b"other")]);
+
+        let signatures = parse_ed25519_signatures(&data).unwrap();
+        assert_eq!(signatures.len(), 2);
+        assert_eq!(signatures[0], (first.as_ref(), message.as_slice()));
+        assert_eq!(signatures[1], (second.as_ref(), b"other".as_slice()));
+    }
+
+    #[test]
+    fn every_signature_entry_is_validated() {
+        let registry = Pubkey::new_unique();
+        let verifier = Pubkey::new_unique();
+        let message = attestation(&registry);
+
+        let both_valid = ed25519_data(&[(verifier, &message), (verifier, &message)]);
+        check_ed25519_signatures(&both_valid, &message, &verifier).unwrap();
+
+        let other_signer = ed25519_data(&[(verifier, &message), (Pubkey::new_unique(), &message)]);
+        assert_eq!(
+            check_ed25519_signatures(&other_signer, &message, &verifier).unwrap_err(),
+            SolanIdError::InvalidAttestationMessage.into()
+        );
+
+        let other_message = attestation(&Pubkey::new_unique());
+        let mixed = ed25519_data(&[(verifier, &message), (verifier, &other_message)]);
+        assert_eq!(
+            check_ed25519_signatures(&mixed, &message, &verifier).unwrap_err(),
+            SolanIdError::InvalidAttestationMessage.into()
+        );
+    }
+
+    #[test]
+    fn quorum_skips_instructions_it_cannot_parse() {
+        let registry = Pubkey::new_unique();
+        let (first, second) = (Pubkey::new_unique(), Pubkey::new_unique());
+        let message = attestation(&registry);
+
+        // Points its message into another instruction, as an unrelated ed25519
+        // instruction earlier in the run may.
+        let mut foreign = ed25519_data(&[(Pubkey::new_unique(), b"unrelated")]);
+        foreign[14..16].copy_from_slice(&0u16.to_le_bytes());
+        let first_signed = ed25519_data(&[(first, &message)]);
+        let second_signed = ed25519_data(&[(second, &message), (first, &message)]);
+
+        let run = [
+            foreign.as_slice(),
+            first_signed.as_slice(),
+            second_signed.as_slice(),
+        ];
+        assert_eq!(count_quorum_signers(run, &message, &[first, second]), 2);
+        assert_eq!(
+            count_quorum_signers([foreign.as_slice()], &message, &[first, second]),
+            0
+        );
+    }
+
+    #[test]
+    fn malformed_headers_are_rejected() {
+        let registry = Pubkey::new_unique();
+        let message = attestation(&registry);
+        let data = ed25519_data(&[(Pubkey::new_unique(), &message)]);
+
+        let mut no_signatures = data.clone();
+        no_signatures[0] = 0;
+        // Claims a second offsets block that runs into the first entry's payload.
+        let mut overstated = data.clone();
+        overstated[0] = 2;
+        for data in [Vec::new(), no_signatures, overstated, data[..20].to_vec()] {
+            assert_eq!(
+                parse_ed25519_signatures(&data).unwrap_err(),
+                SolanIdError::InvalidAttestationInstruction.into()
+            );
+        }
+    }
+}
+
+mod idempotent_submission {
+    use crate::common::zeroed;
+    use anchor_lang::prelude::*;
+    use solan_id::{
+        is_recorded_submission, AttestationNonceRegistry, IndividualProof, ProofSource, UserProof,
+    };
+
+    const PROOF_HASH: [u8; 32] = [9; 32];
+    const NONCE: u64 = 7;
+
+    /// State left behind by a successful `submit_proof` of `PROOF_HASH` under `NONCE`.
+    fn recorded(user: Pubkey) -> (UserProof, IndividualProof, AttestationNonceRegistry) {
+        let mut user_proof: UserProof = zeroed();
+        user_proof.user = user;
+        let mut individual_proof: IndividualProof = zeroed();
+        individual_proof.user = user;
+        individual_proof.source = ProofSource::GitcoinPassport;
+        individual_proof.proof_hash = PROOF_HASH;
+        let mut nonce_registry: AttestationNonceRegistry = zeroed();
+        nonce_registry.nonce = NONCE;
+        nonce_registry.is_used = true;
+        nonce_registry.user = user;
+        (user_proof, individual_proof, nonce_registry)
+    }
+
+    #[test]
+    fn clean_retry_matches_the_recorded_state() {
+        let user = Pubkey::new_unique();
+        let (user_proof, individual_proof, nonce_registry) = recorded(user);
+        assert!(is_recorded_submission(
+            &user_proof,
+            &individual_proof,
+            Some(&nonce_registry),
+            user,
+            ProofSource::GitcoinPassport,
+            PROOF_HASH,
+            NONCE,
+        ));
+    }
+
+    #[test]
+    fn reused_nonce_with_another_payload_is_not_a_retry() {
+        let user = Pubkey::new_unique();
+        let (user_proof, individual_proof, nonce_registry) = recorded(user);
+        let matches = |user: Pubkey, source: ProofSource, proof_hash: [u8; 32]| {
+            is_recorded_submission(
+                &user_proof,
+                &individual_proof,
+                Some(&nonce_registry),
+                user,
+                source,
+                proof_hash,
+                NONCE,
+            )
+        };
+        assert!(!matches(user, ProofSource::GitcoinPassport, [8; 32]));
+        assert!(!matches(user, ProofSource::WorldId, PROOF_HASH));
+        assert!(!matches(
+            Pubkey::new_unique(),
+            ProofSource::GitcoinPassport,
+            PROOF_HASH
+        ));
+
+        let mut revoked = individual_proof.clone();
+        revoked.is_revoked = true;
+        assert!(!is_recorded_submission(
+            &user_proof,
+            &revoked,
+            Some(&nonce_registry),
+            user,
+            ProofSource::GitcoinPassport,
+            PROOF_HASH,
+            NONCE,
+        ));
+    }
+
+    #[test]
+    fn sequential_nonces_match_the_last_consumed_nonce() {
+        let user = Pubkey::new_unique();
+        let (mut user_proof, individual_proof, _) = recorded(user);
+        user_proof.last_nonce = NONCE;
+        let retry = |user_proof: &UserProof| {
+            is_recorded_submission(
+                user_proof,
+                &individual_proof,
+                None,
+                user,
+                ProofSource::GitcoinPassport,
+                PROOF_HASH,
+                NONCE,
+            )
+        };
+        assert!(retry(&user_proof));
+
+        user_proof.last_nonce = NONCE + 1;
+        assert!(!retry(&user_proof));
+    }
+}
+
+mod verifier_allowlist {
+    use crate::common::registry_with;
+    use anchor_lang::prelude::*;
+    use solan_id::{
+        check_allowlist_applies, verifier_merkle_leaf, verify_merkle_proof, Registry, SolanIdError,
+        VerifierScheme,
+    };
+    use solana_sha256_hasher::hashv;
+
+    fn node(a: [u8; 32], b: [u8; 32]) -> [u8; 32] {
+        let (left, right) = if a <= b { (a, b) } else { (b, a) };
+        hashv(&[&[1u8], &left, &right]).to_bytes()
+    }
+
+    fn tree(keys: &[Pubkey; 4]) -> ([u8; 32], [[u8; 32]; 4]) {
+        let leaves = keys.map(|key| verifier_merkle_leaf(&key));
+        let root = node(node(leaves[0], leaves[1]), node(leaves[2], leaves[3]));
+        (root, leaves)
+    }
+
+    #[test]
+    fn valid_leaf_is_accepted() {
+        let keys = [(); 4].map(|_| Pubkey::new_unique());
+        let (root, leaves) = tree(&keys);
+
+        let proof = [leaves[3], node(leaves[0], leaves[1])];
+        assert!(verify_merkle_proof(leaves[2], &proof, &root));
+    }
+
+    #[test]
+    fn invalid_proof_is_rejected() {
+        let keys = [(); 4].map(|_| Pubkey::new_unique());
+        let (root, leaves) = tree(&keys);
+
+        let outsider = verifier_merkle_leaf(&Pubkey::new_unique());
+        let proof = [leaves[3], node(leaves[0], leaves[1])];
+        assert!(!verify_merkle_proof(outsider, &proof, &root));
+
+        let wrong_sibling = [leaves[1], node(leaves[0], leaves[1])];
+        assert!(!verify_merkle_proof(leaves[2], &wrong_sibling, &root));
+    }
+
+    #[test]
+    fn replaced_root_revokes_old_leaves() {
+        let keys = [(); 4].map(|_| Pubkey::new_unique());
+        let (old_root, leaves) = tree(&keys);
+        let proof = [leaves[3], node(leaves[0], leaves[1])];
+        assert!(verify_merkle_proof(leaves[2], &proof, &old_root));
+
+        let mut rotated = keys;
+        rotated[2] = Pubkey::new_unique();
+        let (new_root, _) = tree(&rotated);
+        assert!(!verify_merkle_proof(leaves[2], &proof, &new_root));
+    }
+
+    fn allowlisted(registry: &mut Registry) {
+        registry.verifier_merkle_root = [7; 32];
+    }
+
+    #[test]
+    fn allowlist_needs_a_root() {
+        check_allowlist_applies(&registry_with(allowlisted)).unwrap();
+        assert_eq!(
+            check_allowlist_applies(&registry_with(|_| {})).unwrap_err(),
+            SolanIdError::InvalidVerifierProof.into()
+        );
+    }
+
+    #[test]
+    fn allowlist_cannot_bypass_a_verifier_set() {
+        let registry = registry_with(|registry| {
+            allowlisted(registry);
+            registry.verifier_set = vec![Pubkey::new_unique(), Pubkey::new_unique()];
+            registry.verifier_threshold = 2;
+        });
+        assert_eq!(
+            check_allowlist_applies(&registry).unwrap_err(),
+            SolanIdError::InvalidVerifierProof.into()
+        );
+    }
+
+    #[test]
+    fn allowlist_cannot_bypass_a_secp256k1_verifier() {
+        let registry = registry_with(|registry| {
+            allowlisted(registry);
+            registry.verifier_scheme = VerifierScheme::Secp256k1;
+            registry.verifier_eth_address = [1; 20];
+        });
+        assert_eq!(
+            check_allowlist_applies(&registry).unwrap_err(),
+            SolanIdError::InvalidVerifierProof.into()
+        );
+    }
+}
@@ -0,0 +1,20 @@
+use anchor_lang::prelude::*;
+
+/// An all-zero `T`, as the account reads right after `init` before any field is set.
+pub fn zeroed<T: AccountDeserialize + Space>() -> T {
+    T::try_deserialize_unchecked(&mut vec![0u8; 8 + T::INIT_SPACE].as_slice()).unwrap()
+}
+
+/// An all-zero legacy layout `T`, which is read without a discriminator.
+#[allow(dead_code)]
+pub fn zeroed_legacy<T: AnchorDeserialize + Space>() -> T {
+    T::deserialize(&mut vec![0u8; T::INIT_SPACE].as_slice()).unwrap()
+}
+
+/// A zeroed `Registry` with only the fields `edit` sets, for tests of checks that
+/// read a handful of config fields.
+pub fn registry_with(edit: impl FnOnce(&mut solan_id::Registry)) -> solan_id::Registry {
+    let mut registry = zeroed::<solan_id::Registry>();
+    edit(&mut registry);
+    registry
+}
@@ -0,0 +1,565 @@
+mod common;
+
+mod allowed_sources {
+    use crate::common::registry_with;
+    use solan_id::{ProofSource, SolanIdError};
+
+    #[test]
+    fn default_mask_accepts_every_source() {
+        let registry = registry_with(|registry| registry.allowed_sources = u16::MAX);
+        for source in [
+            ProofSource::Reclaim,
+            ProofSource::GitcoinPassport,
+            ProofSource::WorldId,
+            ProofSource::Farcaster,
+        ] {
+            assert!(registry.check_source_allowed(source).is_ok());
+        }
+    }
+
+    #[test]
+    fn sources_outside_the_mask_are_rejected() {
+        let registry = registry_with(|registry| {
+            registry.allowed_sources =
+                (1 << ProofSource::WorldId as u16) | (1 << ProofSource::GitcoinPassport as u16)
+        });
+        assert!(registry.check_source_allowed(ProofSource::WorldId).is_ok());
+        assert!(registry
+            .check_source_allowed(ProofSource::GitcoinPassport)
+            .is_ok());
+        assert_eq!(
+            registry
+                .check_source_allowed(ProofSource::Reclaim)
+                .unwrap_err(),
+            SolanIdError::SourceNotAllowed.into()
+        );
+    }
+}
+
+mod attestation_ttl {
+    use crate::common::registry_with;
+    use solan_id::{check_attestation_ttl_within_proof_ttl, SolanIdError};
+
+    const NOW: i64 = 1_700_000_000;
+    const PROOF_TTL: i64 = 30 * 86_400;
+
+    #[test]
+    fn attestation_expires_at_its_own_boundary() {
+        let registry = registry_with(|registry| {
+            registry.proof_ttl_seconds = PROOF_TTL;
+            registry.attestation_ttl_seconds = 600;
+        });
+        registry.check_attestation_age(NOW - 600, NOW).unwrap();
+        assert_eq!(
+            registry.check_attestation_age(NOW - 601, NOW).unwrap_err(),
+            SolanIdError::AttestationExpired.into()
+        );
+    }
+
+    #[test]
+    fn zero_ttl_leaves_only_the_proof_ttl() {
+        registry_with(|registry| registry.proof_ttl_seconds = PROOF_TTL)
+            .check_attestation_age(NOW - 29 * 86_400, NOW)
+            .unwrap();
+    }
+
+    #[test]
+    fn attestation_ttl_cannot_outlast_the_proof_ttl() {
+        check_attestation_ttl_within_proof_ttl(0, 600).unwrap();
+        check_attestation_ttl_within_proof_ttl(600, 600).unwrap();
+        for attestation_ttl_seconds in [-1, 601] {
+            assert_eq!(
+                check_attestation_ttl_within_proof_ttl(attestation_ttl_seconds, 600).unwrap_err(),
+                SolanIdError::InvalidConfig.into()
+            );
+        }
+    }
+}
+
+mod clock_skew {
+    use crate::common::registry_with;
+    use solan_id::{SolanIdError, DEFAULT_CLOCK_SKEW_TOLERANCE};
+
+    const NOW: i64 = 1_000_000;
+
+    #[test]
+    fn timestamp_at_the_tolerance_is_accepted() {
+        let registry =
+            registry_with(|registry| registry.clock_skew_tolerance = DEFAULT_CLOCK_SKEW_TOLERANCE);
+        assert!(registry.check_clock_skew(NOW, NOW).is_ok());
+        assert!(registry
+            .check_clock_skew(NOW + DEFAULT_CLOCK_SKEW_TOLERANCE, NOW)
+            .is_ok());
+    }
+
+    #[test]
+    fn timestamp_past_the_tolerance_is_rejected() {
+        let registry = registry_with(|registry| registry.clock_skew_tolerance = 60);
+        assert_eq!(
+            registry.check_clock_skew(NOW + 61, NOW).unwrap_err(),
+            SolanIdError::InvalidTimestamp.into()
+        );
+        assert!(registry.check_clock_skew(NOW + 60, NOW).is_ok());
+
+        let strict = registry_with(|registry| registry.clock_skew_tolerance = 0);
+        assert!(strict.check_clock_skew(NOW, NOW).is_ok());
+        assert!(strict.check_clock_skew(NOW + 1, NOW).is_err());
+    }
+}
+
+mod cooldown_status {
+    use crate::common::{registry_with, zeroed};
+    use anchor_lang::prelude::*;
+    use solan_id::{cooldown_status, CooldownStatus, UserProof};
+
+    const SUBMITTED_AT: i64 = 1_000_000;
+    const COOLDOWN: i64 = 3_600;
+
+    fn status_at(now: i64) -> CooldownStatus {
+        let registry = registry_with(|registry| registry.cooldown_period = COOLDOWN);
+        let mut user_proof: UserProof = zeroed();
+        user_proof.user = Pubkey::new_unique();
+        user_proof.last_submission = SUBMITTED_AT;
+        cooldown_status(&user_proof, &registry, now).unwrap()
+    }
+
+    #[test]
+    fn reports_the_time_left_just_after_a_submission() {
+        assert_eq!(
+            status_at(SUBMITTED_AT + 1),
+            CooldownStatus {
+                in_cooldown: true,
+                seconds_remaining: COOLDOWN - 1,
+                next_eligible_at: SUBMITTED_AT + COOLDOWN,
+            }
+        );
+    }
+
+    #[test]
+    fn reports_zero_once_the_cooldown_elapses() {
+        for now in [SUBMITTED_AT + COOLDOWN, SUBMITTED_AT + 2 * COOLDOWN] {
+            assert_eq!(
+                status_at(now),
+                CooldownStatus {
+                    in_cooldown: false,
+                    seconds_remaining: 0,
+                    next_eligible_at: SUBMITTED_AT + COOLDOWN,
+                }
+            );
+        }
+    }
+
+    #[test]
+    fn new_user_is_never_in_cooldown() {
+        let registry = registry_with(|registry| registry.cooldown_period = COOLDOWN);
+        assert_eq!(
+            cooldown_status(&zeroed(), &registry, SUBMITTED_AT).unwrap(),
+            CooldownStatus::default()
+        );
+    }
+}
+
+mod cooldown_ttl {
+    use solan_id::{check_cooldown_within_ttl, SolanIdError};
+
+    #[test]
+    fn cooldown_below_ttl_is_accepted() {
+        assert!(check_cooldown_within_ttl(0, 3600).is_ok());
+        assert!(check_cooldown_within_ttl(3599, 3600).is_ok());
+    }
+
+    #[test]
+    fn cooldown_equal_to_ttl_is_rejected() {
+        assert_eq!(
+            check_cooldown_within_ttl(3600, 3600).unwrap_err(),
+            SolanIdError::CooldownExceedsTtl.into()
+        );
+        assert!(check_cooldown_within_ttl(7200, 3600).is_err());
+    }
+}
+
+mod emergency_rotation {
+    use crate::common::zeroed;
+    use anchor_lang::prelude::*;
+    use solan_id::{Registry, VerifierScheme};
+
+    #[test]
+    fn leaves_the_new_key_as_the_only_verifier() {
+        let leaked = Pubkey::new_unique();
+        let replacement = Pubkey::new_unique();
+        let mut registry: Registry = zeroed();
+        registry.verifier_authority = leaked;
+        registry.verifier_set = vec![leaked, Pubkey::new_unique()];
+        registry.verifier_threshold = 2;
+        registry.verifier_scheme = VerifierScheme::Secp256k1;
+        registry.verifier_eth_address = [7; 20];
+        registry.verifier_merkle_root = [9; 32];
+        registry.pending_verifier_set = vec![leaked];
+        registry.pending_verifier_threshold = 1;
+        registry.verifier_set_change_available_at = 100;
+
+        registry.reset_verifiers(replacement);
+
+        assert_eq!(registry.verifier_authority, replacement);
+        assert!(registry.verifier_set.is_empty());
+        assert_eq!(registry.verifier_threshold, 0);
+        assert!(registry.verifier_scheme == VerifierScheme::Ed25519);
+        assert_eq!(registry.verifier_eth_address, [0; 20]);
+        assert_eq!(registry.verifier_merkle_root, [0; 32]);
+        assert!(registry.pending_verifier_set.is_empty());
+        assert_eq!(registry.verifier_set_change_available_at, 0);
+    }
+}
+
+mod min_score_cap {
+    use crate::common::registry_with;
+    use solan_id::SolanIdError;
+
+    #[test]
+    fn min_score_cannot_exceed_the_aggregate_cap() {
+        let registry = registry_with(|registry| registry.max_aggregated_score = 1_000);
+        registry.check_min_score_within_cap(1_000).unwrap();
+        assert_eq!(
+            registry.check_min_score_within_cap(1_001).unwrap_err(),
+            SolanIdError::InvalidConfig.into()
+        );
+    }
+}
+
+mod rotation_expiry {
+    use crate::common::registry_with;
+    use solan_id::{Registry, SolanIdError, DEFAULT_ROTATION_EXPIRY_SECONDS};
+
+    const AVAILABLE_AT: i64 = 1_000_000;
+
+    fn pending_rotation(registry: &mut Registry) {
+        registry.verifier_rotation_available_at = AVAILABLE_AT;
+    }
+
+    #[test]
+    fn finalize_is_allowed_up_to_the_end_of_the_window() {
+        let registry = registry_with(|registry| {
+            pending_rotation(registry);
+            registry.rotation_expiry_seconds = 3_600;
+        });
+        registry.check_rotation_not_expired(AVAILABLE_AT).unwrap();
+        registry
+            .check_rotation_not_expired(AVAILABLE_AT + 3_600)
+            .unwrap();
+        assert_eq!(
+            registry
+                .check_rotation_not_expired(AVAILABLE_AT + 3_601)
+                .unwrap_err(),
+            SolanIdError::VerifierRotationExpired.into()
+        );
+    }
+
+    #[test]
+    fn verifier_set_changes_share_the_window() {
+        let registry = registry_with(|registry| {
+            registry.rotation_expiry_seconds = 3_600;
+            registry.verifier_set_change_available_at = AVAILABLE_AT + 10_000;
+        });
+        registry
+            .check_verifier_set_change_not_expired(AVAILABLE_AT + 13_600)
+            .unwrap();
+        assert_eq!(
+            registry
+                .check_verifier_set_change_not_expired(AVAILABLE_AT + 13_601)
+                .unwrap_err(),
+            SolanIdError::VerifierRotationExpired.into()
+        );
+    }
+
+    #[test]
+    fn unset_expiry_uses_the_default_window() {
+        let registry = registry_with(pending_rotation);
+        registry
+            .check_rotation_not_expired(AVAILABLE_AT + DEFAULT_ROTATION_EXPIRY_SECONDS)
+            .unwrap();
+        assert_eq!(
+            registry
+                .check_rotation_not_expired(AVAILABLE_AT + DEFAULT_ROTATION_EXPIRY_SECONDS + 1)
+                .unwrap_err(),
+            SolanIdError::VerifierRotationExpired.into()
+        );
+    }
+}
+
+mod source_dwell {
+    use crate::common::registry_with;
+    use solan_id::SolanIdError;
+
+    #[test]
+    fn zero_dwell_allows_immediate_replacement() {
+        registry_with(|_| {})
+            .check_source_dwell(1_000, 1_000)
+            .unwrap();
+    }
+
+    #[test]
+    fn replacement_waits_out_the_dwell() {
+        let registry = registry_with(|registry| registry.min_source_dwell_seconds = 3_600);
+        assert_eq!(
+            registry.check_source_dwell(1_000, 4_599).unwrap_err(),
+            SolanIdError::SourceDwellActive.into()
+        );
+        registry.check_source_dwell(1_000, 4_600).unwrap();
+    }
+}
+
+mod strict_min_score {
+    use crate::common::registry_with;
+    use solan_id::SolanIdError;
+
+    #[test]
+    fn lenient_mode_reports_the_gap() {
+        let registry = registry_with(|registry| registry.min_score = 100);
+        assert_eq!(registry.check_min_score_gap(60).unwrap(), 40);
+        assert_eq!(registry.check_min_score_gap(100).unwrap(), 0);
+        assert_eq!(registry.check_min_score_gap(250).unwrap(), 0);
+    }
+
+    #[test]
+    fn strict_mode_rejects_any_gap() {
+        let registry = registry_with(|registry| {
+            registry.min_score = 100;
+            registry.strict_min_score = true;
+        });
+        assert_eq!(
+            registry.check_min_score_gap(99).unwrap_err(),
+            SolanIdError::ScoreBelowThreshold.into()
+        );
+        assert_eq!(registry.check_min_score_gap(100).unwrap(), 0);
+    }
+}
+
+mod timestamp_bounds {
+    use crate::common::registry_with;
+    use solan_id::{SolanIdError, MAX_PROOF_TTL_SECONDS};
+
+    const NOW: i64 = 1_000_000;
+
+    #[test]
+    fn timestamp_inside_the_ttl_is_accepted() {
+        let registry = registry_with(|registry| registry.proof_ttl_seconds = 3_600);
+        assert!(registry
+            .check_timestamp_within_ttl(NOW - 3_600, NOW)
+            .is_ok());
+        assert_eq!(
+            registry
+                .check_timestamp_within_ttl(NOW - 3_601, NOW)
+                .unwrap_err(),
+            SolanIdError::ProofExpired.into()
+        );
+    }
+
+    #[test]
+    fn extreme_ttl_fails_instead_of_wrapping() {
+        // Wrapped, `now - proof_ttl_seconds` would flip sign and let expired proofs in.
+        let registry = registry_with(|registry| registry.proof_ttl_seconds = i64::MAX);
+        assert_eq!(
+            registry.check_timestamp_within_ttl(0, -2).unwrap_err(),
+            SolanIdError::Overflow.into()
+        );
+
+        let registry = registry_with(|registry| registry.proof_ttl_seconds = i64::MIN);
+        assert_eq!(
+            registry.check_timestamp_within_ttl(NOW, NOW).unwrap_err(),
+            SolanIdError::Overflow.into()
+        );
+    }
+
+    #[test]
+    fn largest_allowed_ttl_stays_in_range() {
+        let registry = registry_with(|registry| registry.proof_ttl_seconds = MAX_PROOF_TTL_SECONDS);
+        assert!(registry
+            .check_timestamp_within_ttl(NOW - MAX_PROOF_TTL_SECONDS, NOW)
+            .is_ok());
+        assert!(registry.check_timestamp_within_ttl(0, 0).is_ok());
+    }
+
+    #[test]
+    fn extreme_skew_tolerance_fails_instead_of_saturating() {
+        let registry = registry_with(|registry| {
+            registry.proof_ttl_seconds = 3_600;
+            registry.clock_skew_tolerance = i64::MAX;
+        });
+        assert_eq!(
+            registry.check_clock_skew(i64::MAX, NOW).unwrap_err(),
+            SolanIdError::Overflow.into()
+        );
+    }
+}
+
+mod trust_multiplier {
+    use crate::common::registry_with;
+    use anchor_lang::prelude::*;
+    use solan_id::attestation::ATTESTS_TRUST_MULTIPLIER;
+    use solan_id::{
+        build_attestation_message, ProofSource, SolanIdError, NEUTRAL_TRUST_MULTIPLIER,
+    };
+
+    fn message(trust_multiplier: u8) -> Vec<u8> {
+        build_attestation_message(
+            1,
+            &solan_id::ID,
+            &Pubkey::new_unique(),
+            &Pubkey::new_from_array([3; 32]),
+            &[5; 32],
+            ProofSource::GitcoinPassport,
+            &[4; 32],
+            7,
+            150,
+            1_700_000_000,
+            None,
+            0,
+            trust_multiplier,
+            0,
+        )
+    }
+
+    #[test]
+    fn neutral_multiplier_is_always_accepted() {
+        registry_with(|_| {})
+            .check_trust_multiplier(NEUTRAL_TRUST_MULTIPLIER)
+            .unwrap();
+        registry_with(|registry| registry.max_trust_multiplier = NEUTRAL_TRUST_MULTIPLIER)
+            .check_trust_multiplier(NEUTRAL_TRUST_MULTIPLIER)
+            .unwrap();
+    }
+
+    #[test]
+    fn multiplier_is_bounded_by_the_registry() {
+        let registry = registry_with(|registry| registry.max_trust_multiplier = 150);
+        registry.check_trust_multiplier(150).unwrap();
+        registry.check_trust_multiplier(50).unwrap();
+        for trust_multiplier in [0, 151] {
+            assert_eq!(
+                registry
+                    .check_trust_multiplier(trust_multiplier)
+                    .unwrap_err(),
+                SolanIdError::InvalidTrustMultiplier.into()
+            );
+        }
+    }
+
+    #[test]
+    fn only_a_non_neutral_multiplier_is_signed() {
+        let neutral = message(NEUTRAL_TRUST_MULTIPLIER);
+        assert_eq!(neutral.len(), 157 + 32);
+        let boosted = message(150);
+        assert_eq!(boosted.len(), neutral.len() + 2);
+        assert_eq!(boosted[neutral.len()], ATTESTS_TRUST_MULTIPLIER);
+        assert_eq!(boosted[neutral.len() + 1], 150);
+    }
+}
+
+mod verification_reason {
+    use crate::common::{registry_with, zeroed};
+    use anchor_lang::prelude::*;
+    use solan_id::{verification_reason, UserProof, VerificationReason};
+
+    const NOW: i64 = 1_000_000;
+
+    fn verified_user() -> UserProof {
+        let mut user_proof: UserProof = zeroed();
+        user_proof.user = Pubkey::new_unique();
+        user_proof.aggregated_score = 150;
+        user_proof.active_source_count = 1;
+        user_proof.valid_until = NOW;
+        user_proof
+    }
+
+    fn reason(user_proof: &UserProof, banned: bool) -> VerificationReason {
+        let registry = registry_with(|registry| registry.min_score = 100);
+        verification_reason(user_proof, &registry, registry.min_score, NOW, banned)
+    }
+
+    #[test]
+    fn each_failure_reports_its_reason() {
+        let user_proof = verified_user();
+        assert_eq!(reason(&user_proof, false), VerificationReason::Ok);
+        assert_eq!(reason(&zeroed(), false), VerificationReason::NoProof);
+        assert_eq!(reason(&user_proof, true), VerificationReason::Banned);
+
+        let mut flagged = verified_user();
+        flagged.flagged_sources_mask = 1;
+        assert_eq!(reason(&flagged, false), VerificationReason::Flagged);
+
+        let mut expired = verified_user();
+        expired.valid_until = NOW - 1;
+        assert_eq!(reason(&expired, false), VerificationReason::Expired);
+
+        let mut low = verified_user();
+        low.aggregated_score = 99;
+        assert_eq!(reason(&low, false), VerificationReason::BelowThreshold);
+    }
+
+    #[test]
+    fn zero_score_is_below_threshold_even_without_a_minimum() {
+        let registry = registry_with(|_| {});
+        let mut user_proof = verified_user();
+        user_proof.aggregated_score = 0;
+        assert_eq!(
+            verification_reason(&user_proof, &registry, 0, NOW, false),
+            VerificationReason::BelowThreshold
+        );
+    }
+
+    #[test]
+    fn a_lapsed_source_expires_the_proof_before_valid_until() {
+        let mut user_proof = verified_user();
+        user_proof.valid_until = NOW + 1_000;
+        user_proof.sources_mask = 0b11;
+        user_proof.source_expires_at[1] = NOW + 1;
+        assert_eq!(reason(&user_proof, false), VerificationReason::Ok);
+
+        user_proof.source_expires_at[0] = NOW;
+        assert_eq!(user_proof.earliest_source_expiry(), NOW);
+        assert_eq!(reason(&user_proof, false), VerificationReason::Expired);
+
+        // Only counted sources are consulted.
+        user_proof.sources_mask = 0b10;
+        assert_eq!(reason(&user_proof, false), VerificationReason::Ok);
+    }
+
+    #[test]
+    fn first_failing_reason_wins() {
+        let mut user_proof = verified_user();
+        user_proof.aggregated_score = 0;
+        user_proof.valid_until = NOW - 1;
+        assert_eq!(reason(&user_proof, false), VerificationReason::Expired);
+
+        user_proof.flagged_sources_mask = 1;
+        assert_eq!(reason(&user_proof, false), VerificationReason::Flagged);
+        assert_eq!(reason(&user_proof, true), VerificationReason::Banned);
+    }
+}
+
+mod snapshot_verification {
+    use crate::common::{registry_with, zeroed};
+    use solan_id::{is_snapshot_verified, ScoreSnapshot};
+
+    fn snapshot(source_count: u8) -> ScoreSnapshot {
+        let mut score_snapshot: ScoreSnapshot = zeroed();
+        score_snapshot.aggregated_score = 150;
+        score_snapshot.source_count = source_count;
+        score_snapshot.created_at = 1_000;
+        score_snapshot.valid_until = 2_000;
+        score_snapshot
+    }
+
+    #[test]
+    fn snapshot_applies_ban_flag_and_source_count() {
+        let registry = registry_with(|registry| {
+            registry.min_score = 100;
+            registry.min_source_count = 2;
+        });
+
+        assert!(is_snapshot_verified(&snapshot(2), &registry, false, false));
+        assert!(!is_snapshot_verified(&snapshot(2), &registry, true, false));
+        assert!(!is_snapshot_verified(&snapshot(2), &registry, false, true));
+        assert!(!is_snapshot_verified(&snapshot(1), &registry, false, false));
+    }
+}
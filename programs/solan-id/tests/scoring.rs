@@ -0,0 +1,679 @@
+mod common;
+
+use solan_id::{ProofSource, PROOF_SOURCE_COUNT};
+
+const ALL_SOURCES: [ProofSource; PROOF_SOURCE_COUNT] = [
+    ProofSource::Reclaim,
+    ProofSource::GitcoinPassport,
+    ProofSource::WorldId,
+    ProofSource::BrightId,
+    ProofSource::Lens,
+    ProofSource::Twitter,
+    ProofSource::Google,
+    ProofSource::Discord,
+    ProofSource::CoinbaseKyc,
+    ProofSource::LinkedIn,
+    ProofSource::Farcaster,
+];
+
+mod continuous_decay {
+    use crate::common::zeroed;
+    use solan_id::{recency_factor, DecayConfig, DecayMode};
+
+    const DAY: i64 = 86_400;
+
+    fn decay_config(decay_mode: DecayMode) -> DecayConfig {
+        let mut config = zeroed::<DecayConfig>();
+        config.age_boundaries = [30 * DAY, 90 * DAY, 180 * DAY, i64::MAX];
+        config.factors = [100, 75, 50, 25];
+        config.decay_mode = decay_mode;
+        config
+    }
+
+    #[test]
+    fn bracketed_mode_steps_at_boundaries() {
+        let config = decay_config(DecayMode::Bracketed);
+        assert_eq!(recency_factor(30 * DAY - 1, &config), 100);
+        assert_eq!(recency_factor(30 * DAY, &config), 75);
+        assert_eq!(recency_factor(180 * DAY, &config), 25);
+    }
+
+    #[test]
+    fn default_config_uses_the_initialized_brackets() {
+        let config = DecayConfig::default();
+        assert_eq!(config.decay_mode, DecayMode::Bracketed);
+        assert_eq!(
+            config.age_boundaries,
+            decay_config(DecayMode::Bracketed).age_boundaries
+        );
+        assert_eq!(recency_factor(0, &config), 100);
+        assert_eq!(recency_factor(90 * DAY, &config), 50);
+        assert!(config.source_hard_expiry.iter().all(|&expiry| expiry == 0));
+    }
+
+    #[test]
+    fn linear_mode_glides_to_the_floor() {
+        let config = decay_config(DecayMode::Linear);
+        assert_eq!(recency_factor(-DAY, &config), 100);
+        assert_eq!(recency_factor(0, &config), 100);
+        assert_eq!(recency_factor(60 * DAY, &config), 75);
+        assert_eq!(recency_factor(90 * DAY, &config), 63);
+        assert_eq!(recency_factor(180 * DAY, &config), 25);
+        assert_eq!(recency_factor(i64::MAX, &config), 25);
+
+        let samples: Vec<u64> = (0..=200)
+            .map(|day| recency_factor(day * DAY, &config))
+            .collect();
+        for pair in samples.windows(2) {
+            assert!(pair[1] <= pair[0]);
+            assert!(pair[0] - pair[1] <= 1);
+        }
+    }
+
+    #[test]
+    fn exponential_mode_halves_towards_the_floor() {
+        let config = decay_config(DecayMode::Exponential);
+        assert_eq!(recency_factor(0, &config), 100);
+        assert_eq!(recency_factor(30 * DAY, &config), 62);
+        assert_eq!(recency_factor(60 * DAY, &config), 43);
+        assert_eq!(recency_factor(i64::MAX, &config), 25);
+
+        let samples: Vec<u64> = (0..=400)
+            .map(|day| recency_factor(day * DAY, &config))
+            .collect();
+        assert!(samples.windows(2).all(|pair| pair[1] <= pair[0]));
+    }
+}
+
+mod cross_ecosystem_bonus {
+    use crate::common::registry_with;
+    use solan_id::{
+        apply_cross_ecosystem_bonus, strip_cross_ecosystem_bonus, ProofSource, Registry,
+    };
+
+    const WEB2: u16 = 1 << ProofSource::Twitter as u16;
+    const WEB3: u16 = 1 << ProofSource::Lens as u16;
+
+    fn registry(cross_ecosystem_bonus_percent: u8) -> Registry {
+        registry_with(|registry| {
+            registry.web2_mask = WEB2;
+            registry.web3_mask = WEB3;
+            registry.cross_ecosystem_bonus_percent = cross_ecosystem_bonus_percent;
+        })
+    }
+
+    #[test]
+    fn only_both_sides_earn_the_bonus() {
+        let registry = registry(50);
+        assert_eq!(
+            apply_cross_ecosystem_bonus(100, WEB2, &registry).unwrap(),
+            (100, false)
+        );
+        assert_eq!(
+            apply_cross_ecosystem_bonus(100, WEB3, &registry).unwrap(),
+            (100, false)
+        );
+        assert_eq!(
+            apply_cross_ecosystem_bonus(100, WEB2 | WEB3, &registry).unwrap(),
+            (150, true)
+        );
+    }
+
+    #[test]
+    fn strip_inverts_the_bonus() {
+        for percent in [1, 3, 33, 50, 67, 99, 100, 255] {
+            let registry = registry(percent);
+            for base_score in (0..=1_000).chain([999_983, u64::MAX / 400]) {
+                let (total_score, bonus_applied) =
+                    apply_cross_ecosystem_bonus(base_score, WEB2 | WEB3, &registry).unwrap();
+                let stripped =
+                    strip_cross_ecosystem_bonus(total_score, percent, bonus_applied).unwrap();
+                assert_eq!(stripped, base_score, "percent {percent}, base {base_score}");
+            }
+        }
+    }
+}
+
+mod diversity_bonus {
+    use crate::common::zeroed;
+    use solan_id::{
+        apply_diversity_bonus, distinct_category_count, strip_diversity_bonus, ProofSource,
+        ScoringConfig, MAX_PROOF_SOURCES, PROOF_SOURCE_COUNT,
+    };
+
+    const SCHEDULE: [u8; MAX_PROOF_SOURCES] =
+        [0, 5, 10, 15, 20, 25, 30, 35, 40, 45, 50, 55, 60, 65, 70, 75];
+
+    fn scoring_config(schedule: [u8; MAX_PROOF_SOURCES]) -> ScoringConfig {
+        let mut scoring_config = zeroed::<ScoringConfig>();
+        scoring_config.diversity_bonus_schedule = schedule;
+        scoring_config.weights = [100; MAX_PROOF_SOURCES];
+        scoring_config.category = std::array::from_fn(|index| index as u8);
+        scoring_config
+    }
+
+    fn assert_round_trips(scoring_config: &ScoringConfig, flat_percent: u8) {
+        for count in 1..=PROOF_SOURCE_COUNT as u8 {
+            let percent = scoring_config.diversity_bonus_percent(count, flat_percent);
+            for base_score in (0..=1_000).chain([999_983, u64::MAX / 200]) {
+                let (total_score, bonus_applied) =
+                    apply_diversity_bonus(base_score, count, percent, 0).unwrap();
+                let stripped =
+                    strip_diversity_bonus(total_score, count, percent, bonus_applied).unwrap();
+                assert_eq!(stripped, base_score, "count {count}, base {base_score}");
+            }
+        }
+    }
+
+    #[test]
+    fn tiered_schedule_scales_with_source_count() {
+        let scoring_config = scoring_config(SCHEDULE);
+        for count in 1..=PROOF_SOURCE_COUNT as u8 {
+            assert_eq!(
+                scoring_config.diversity_bonus_percent(count, 20),
+                SCHEDULE[count as usize - 1]
+            );
+        }
+        assert_eq!(scoring_config.diversity_bonus_percent(0, 20), 0);
+
+        let (two, _) = apply_diversity_bonus(1_000, 2, 5, 0).unwrap();
+        let (five, _) = apply_diversity_bonus(1_000, 5, 20, 0).unwrap();
+        assert_eq!((two, five), (1_050, 1_200));
+    }
+
+    #[test]
+    fn empty_schedule_keeps_flat_bonus() {
+        let scoring_config = scoring_config([0; MAX_PROOF_SOURCES]);
+        for count in 1..=PROOF_SOURCE_COUNT as u8 {
+            assert_eq!(scoring_config.diversity_bonus_percent(count, 20), 20);
+        }
+    }
+
+    #[test]
+    fn strip_inverts_tiered_bonus_at_every_count() {
+        assert_round_trips(&scoring_config(SCHEDULE), 20);
+        assert_round_trips(&scoring_config([0; MAX_PROOF_SOURCES]), 20);
+        let mut steep = [100; MAX_PROOF_SOURCES];
+        steep[..6].copy_from_slice(&[0, 1, 33, 33, 67, 99]);
+        assert_round_trips(&scoring_config(steep), 20);
+    }
+
+    #[test]
+    fn zero_weight_sources_do_not_count_towards_diversity() {
+        let mut scoring_config = scoring_config([0; MAX_PROOF_SOURCES]);
+        let mask = (1 << ProofSource::GitcoinPassport as u16) | (1 << ProofSource::WorldId as u16);
+        assert_eq!(distinct_category_count(mask, &scoring_config), 2);
+
+        scoring_config.weights[ProofSource::WorldId as usize] = 0;
+        let distinct_sources = distinct_category_count(mask, &scoring_config);
+        assert_eq!(distinct_sources, 1);
+        assert_eq!(
+            apply_diversity_bonus(1_000, distinct_sources, 20, 0).unwrap(),
+            (1_000, false)
+        );
+    }
+
+    #[test]
+    fn strip_follows_the_applied_bonus_after_a_weight_is_zeroed() {
+        let mut scoring_config = scoring_config([0; MAX_PROOF_SOURCES]);
+        let mask = (1 << ProofSource::GitcoinPassport as u16) | (1 << ProofSource::WorldId as u16);
+        let distinct_sources = distinct_category_count(mask, &scoring_config);
+        let (total_score, bonus_applied) =
+            apply_diversity_bonus(1_000, distinct_sources, 20, 0).unwrap();
+        assert_eq!((total_score, bonus_applied), (1_200, true));
+
+        scoring_config.weights[ProofSource::WorldId as usize] = 0;
+        assert_eq!(distinct_category_count(mask, &scoring_config), 1);
+        assert_eq!(
+            strip_diversity_bonus(total_score, 2, 20, bonus_applied).unwrap(),
+            1_000
+        );
+    }
+}
+
+mod issued_at_skew {
+    use crate::common::zeroed;
+    use solan_id::{ScoringConfig, SolanIdError};
+
+    fn scoring_config(max_issued_at_skew: i64) -> ScoringConfig {
+        let mut config = zeroed::<ScoringConfig>();
+        config.max_issued_at_skew = max_issued_at_skew;
+        config
+    }
+
+    #[test]
+    fn matched_timestamp_is_accepted() {
+        let config = scoring_config(300);
+        assert!(config.check_issued_at_skew(1_000_000, 1_000_000).is_ok());
+        assert!(config.check_issued_at_skew(1_000_300, 1_000_000).is_ok());
+        assert!(config.check_issued_at_skew(1_000_000, 1_000_300).is_ok());
+    }
+
+    #[test]
+    fn mismatched_timestamp_is_rejected() {
+        let config = scoring_config(300);
+        assert_eq!(
+            config
+                .check_issued_at_skew(1_000_000 + 86_000, 1_000_000)
+                .unwrap_err(),
+            SolanIdError::IssuedAtMismatch.into()
+        );
+        assert!(config.check_issued_at_skew(1_000_000, 1_000_301).is_err());
+    }
+
+    #[test]
+    fn zero_skew_disables_the_check() {
+        let config = scoring_config(0);
+        assert!(config.check_issued_at_skew(i64::MAX, i64::MIN).is_ok());
+    }
+}
+
+mod normalized_scoring {
+    use crate::common::{registry_with, zeroed};
+    use crate::ALL_SOURCES;
+    use solan_id::{
+        apply_diversity_bonus, strip_diversity_bonus, weighted_base_score, ProofSource, Registry,
+        ScoringConfig, MAX_PROOF_SOURCES, PROOF_SOURCE_COUNT,
+    };
+
+    const WEIGHTS: [u64; PROOF_SOURCE_COUNT] = [300, 100, 150, 50, 0, 120, 100, 80, 40, 30, 30];
+    const CAPS: [u64; PROOF_SOURCE_COUNT] =
+        [1_000, 100, 500, 250, 400, 1_000, 5_000, 75, 200, 50, 10];
+
+    fn normalize(registry: &mut Registry) {
+        registry.normalized_scoring = true;
+        registry.max_total_score = 1_000;
+    }
+
+    fn per_source<T: Copy>(values: [T; PROOF_SOURCE_COUNT], reserved: T) -> [T; MAX_PROOF_SOURCES] {
+        let mut slots = [reserved; MAX_PROOF_SOURCES];
+        slots[..PROOF_SOURCE_COUNT].copy_from_slice(&values);
+        slots
+    }
+
+    fn scoring_config() -> ScoringConfig {
+        let mut scoring_config: ScoringConfig = zeroed();
+        // Reserved slots carry the default weight but must not dilute the live sources.
+        scoring_config.weights = per_source(WEIGHTS, 100);
+        scoring_config.max_base_score = per_source(CAPS, 0);
+        scoring_config
+    }
+
+    fn total(base_scores: [u64; PROOF_SOURCE_COUNT], registry: &Registry) -> u64 {
+        let scoring_config = scoring_config();
+        ALL_SOURCES
+            .iter()
+            .zip(base_scores)
+            .map(|(&source, base)| {
+                weighted_base_score(base, source, 0, registry, &scoring_config).unwrap()
+            })
+            .sum()
+    }
+
+    #[test]
+    fn raw_total_grows_with_weights_but_normalized_total_stays_bounded() {
+        let raw = total(CAPS, &registry_with(|_| {}));
+        let expected_raw: u64 = CAPS.iter().zip(WEIGHTS).map(|(c, w)| c * w / 100).sum();
+        assert_eq!(raw, expected_raw);
+
+        let normalized = total(CAPS, &registry_with(normalize));
+        assert!(normalized <= 1_000);
+        // Per-source flooring loses at most one point per weighted source.
+        assert!(normalized >= 1_000 - PROOF_SOURCE_COUNT as u64);
+
+        let doubled = {
+            let mut scoring_config = scoring_config();
+            scoring_config.weights = per_source(WEIGHTS.map(|w| w * 2), 100);
+            ALL_SOURCES
+                .iter()
+                .zip(CAPS)
+                .map(|(&source, base)| {
+                    weighted_base_score(base, source, 0, &registry_with(normalize), &scoring_config)
+                        .unwrap()
+                })
+                .sum::<u64>()
+        };
+        assert_eq!(doubled, normalized);
+    }
+
+    #[test]
+    fn normalized_score_is_the_weight_share_of_the_cap() {
+        let registry = registry_with(normalize);
+        let scoring_config = scoring_config();
+        let reclaim = |base| {
+            weighted_base_score(base, ProofSource::Reclaim, 0, &registry, &scoring_config).unwrap()
+        };
+        // Reclaim holds 300 of 1_000 total weight.
+        assert_eq!(reclaim(1_000), 300);
+        assert_eq!(reclaim(500), 150);
+        assert_eq!(reclaim(0), 0);
+        assert_eq!(
+            weighted_base_score(400, ProofSource::Lens, 0, &registry, &scoring_config).unwrap(),
+            0
+        );
+    }
+
+    #[test]
+    fn normalized_mode_requires_a_cap_on_weighted_sources() {
+        let registry = registry_with(normalize);
+        let mut scoring_config = scoring_config();
+        scoring_config.max_base_score[0] = 0;
+        assert!(
+            weighted_base_score(10, ProofSource::Reclaim, 0, &registry, &scoring_config).is_err()
+        );
+    }
+
+    #[test]
+    fn diversity_bonus_composes_with_normalized_totals() {
+        let registry = registry_with(normalize);
+        let base = total(CAPS, &registry);
+        for percent in [0, 5, 20, 100] {
+            let (with_bonus, bonus_applied) =
+                apply_diversity_bonus(base, PROOF_SOURCE_COUNT as u8, percent, 0).unwrap();
+            assert!(with_bonus <= 1_000 * (100 + percent as u64) / 100);
+            assert_eq!(
+                strip_diversity_bonus(with_bonus, PROOF_SOURCE_COUNT as u8, percent, bonus_applied)
+                    .unwrap(),
+                base
+            );
+        }
+    }
+}
+
+mod scoring_mode {
+    use solan_id::{fold_base_score, ProofSource, ScoringMode, MAX_PROOF_SOURCES};
+
+    const MODES: [ScoringMode; 3] = [
+        ScoringMode::Additive,
+        ScoringMode::Max,
+        ScoringMode::WeightedAverage,
+    ];
+
+    fn bit(source: ProofSource) -> u16 {
+        1 << source as u16
+    }
+
+    /// Gitcoin at weight 100 contributes 150, World ID at weight 200 contributes 100 from
+    /// a base of 50.
+    fn sources() -> ([u64; MAX_PROOF_SOURCES], [u64; MAX_PROOF_SOURCES], u16) {
+        let mut scores = [0; MAX_PROOF_SOURCES];
+        let mut weights = [100; MAX_PROOF_SOURCES];
+        scores[ProofSource::GitcoinPassport as usize] = 150;
+        scores[ProofSource::WorldId as usize] = 100;
+        weights[ProofSource::WorldId as usize] = 200;
+        (
+            scores,
+            weights,
+            bit(ProofSource::GitcoinPassport) | bit(ProofSource::WorldId),
+        )
+    }
+
+    #[test]
+    fn same_sources_fold_differently_per_mode() {
+        let (scores, weights, mask) = sources();
+        let folded = MODES.map(|mode| fold_base_score(mode, &scores, mask, &weights).unwrap());
+        // (150 + 100) * 100 / (100 + 200)
+        assert_eq!(folded, [250, 150, 83]);
+    }
+
+    #[test]
+    fn uncounted_slots_are_ignored() {
+        let (mut scores, weights, mask) = sources();
+        scores[ProofSource::Farcaster as usize] = 1_000;
+        assert_eq!(
+            MODES.map(|mode| fold_base_score(mode, &scores, mask, &weights).unwrap()),
+            [250, 150, 83]
+        );
+    }
+
+    #[test]
+    fn revoking_a_source_refolds_from_the_rest() {
+        let (mut scores, weights, mask) = sources();
+        scores[ProofSource::GitcoinPassport as usize] = 0;
+        let mask = mask & !bit(ProofSource::GitcoinPassport);
+        assert_eq!(
+            MODES.map(|mode| fold_base_score(mode, &scores, mask, &weights).unwrap()),
+            [100, 100, 50]
+        );
+
+        let empty = MODES.map(|mode| fold_base_score(mode, &scores, 0, &weights).unwrap());
+        assert_eq!(empty, [0, 0, 0]);
+    }
+}
+
+mod source_index {
+    use crate::common::zeroed;
+    use crate::ALL_SOURCES;
+    use solan_id::{DecayConfig, ProofSource, ScoringConfig, PROOF_SOURCE_COUNT};
+
+    #[test]
+    fn per_source_arrays_match_variant_count() {
+        assert_eq!(ProofSource::count(), ProofSource::VARIANT_COUNT);
+        assert_eq!(PROOF_SOURCE_COUNT, ProofSource::VARIANT_COUNT);
+
+        let scoring_config = zeroed::<ScoringConfig>();
+        let decay_config = zeroed::<DecayConfig>();
+        for (expected, source) in ALL_SOURCES.into_iter().enumerate() {
+            assert_eq!(source.as_index(&scoring_config.weights).unwrap(), expected);
+            assert_eq!(
+                source.as_index(&scoring_config.reclaim_cooldowns).unwrap(),
+                expected
+            );
+            assert_eq!(source.as_index(&scoring_config.category).unwrap(), expected);
+            assert_eq!(
+                source.as_index(&scoring_config.max_base_score).unwrap(),
+                expected
+            );
+            assert_eq!(
+                source.as_index(&decay_config.source_hard_expiry).unwrap(),
+                expected
+            );
+        }
+    }
+
+    #[test]
+    fn as_index_rejects_short_arrays() {
+        let short = [0u64; 4];
+        assert!(ProofSource::WorldId.as_index(&short).is_ok());
+        assert_eq!(
+            ProofSource::Lens.as_index(&short).unwrap_err(),
+            solan_id::SolanIdError::SourcePayloadMismatch.into()
+        );
+    }
+}
+
+mod weight_override {
+    use crate::common::{registry_with, zeroed};
+    use anchor_lang::prelude::*;
+    use solan_id::attestation::ATTESTS_WEIGHT_OVERRIDE;
+    use solan_id::{
+        build_attestation_message, weighted_base_score, ProofSource, Registry, ScoringConfig,
+        SolanIdError, MAX_PROOF_SOURCES, NEUTRAL_TRUST_MULTIPLIER,
+    };
+
+    fn message(weight_override: u64) -> Vec<u8> {
+        build_attestation_message(
+            1,
+            &solan_id::ID,
+            &Pubkey::new_from_array([2; 32]),
+            &Pubkey::new_from_array([3; 32]),
+            &[5; 32],
+            ProofSource::GitcoinPassport,
+            &[4; 32],
+            7,
+            150,
+            1_700_000_000,
+            None,
+            0,
+            NEUTRAL_TRUST_MULTIPLIER,
+            weight_override,
+        )
+    }
+
+    #[test]
+    fn override_replaces_the_configured_weight() {
+        let registry: Registry = zeroed();
+        let mut scoring_config: ScoringConfig = zeroed();
+        scoring_config.weights = [100; MAX_PROOF_SOURCES];
+        let weighted = |weight_override| {
+            weighted_base_score(
+                150,
+                ProofSource::WorldId,
+                weight_override,
+                &registry,
+                &scoring_config,
+            )
+            .unwrap()
+        };
+        assert_eq!(weighted(0), 150);
+        assert_eq!(weighted(200), 300);
+        assert_eq!(weighted(50), 75);
+    }
+
+    #[test]
+    fn normalized_override_cannot_exceed_the_configured_weight() {
+        let registry = registry_with(|registry| {
+            registry.normalized_scoring = true;
+            registry.max_total_score = 1_100;
+        });
+        let mut scoring_config: ScoringConfig = zeroed();
+        scoring_config.weights = [100; MAX_PROOF_SOURCES];
+        scoring_config.max_base_score = [200; MAX_PROOF_SOURCES];
+        let weighted = |weight_override| {
+            weighted_base_score(
+                200,
+                ProofSource::WorldId,
+                weight_override,
+                &registry,
+                &scoring_config,
+            )
+            .unwrap()
+        };
+        assert_eq!(weighted(0), 100);
+        assert_eq!(weighted(10_000), 100);
+        assert_eq!(weighted(50), 50);
+    }
+
+    #[test]
+    fn override_is_bounded_by_the_registry() {
+        let mut registry: Registry = zeroed();
+        registry.check_weight_override(0).unwrap();
+        assert_eq!(
+            registry.check_weight_override(1).unwrap_err(),
+            SolanIdError::InvalidWeightOverride.into()
+        );
+
+        registry.max_weight_override = 300;
+        registry.check_weight_override(300).unwrap();
+        assert_eq!(
+            registry.check_weight_override(301).unwrap_err(),
+            SolanIdError::InvalidWeightOverride.into()
+        );
+    }
+
+    #[test]
+    fn only_a_non_zero_override_is_signed() {
+        let unset = message(0);
+        let overridden = message(250);
+        assert_eq!(overridden.len(), unset.len() + 1 + 8);
+        assert_eq!(overridden[..unset.len()], unset[..]);
+        assert_eq!(overridden[unset.len()], ATTESTS_WEIGHT_OVERRIDE);
+        assert_eq!(overridden[unset.len() + 1..], 250u64.to_le_bytes());
+    }
+}
+
+mod wallet_age_bonus {
+    use crate::common::registry_with;
+    use solan_id::{apply_wallet_age_bonus, strip_wallet_age_bonus, Registry};
+
+    const NOW: i64 = 1_700_000_000;
+    const MIN_WALLET_AGE: i64 = 365 * 86_400;
+
+    fn registry(wallet_age_bonus_percent: u8) -> Registry {
+        registry_with(|registry| {
+            registry.min_wallet_age_seconds = MIN_WALLET_AGE;
+            registry.wallet_age_bonus_percent = wallet_age_bonus_percent;
+        })
+    }
+
+    #[test]
+    fn only_an_aged_wallet_earns_the_bonus() {
+        let registry = registry(10);
+        assert_eq!(
+            apply_wallet_age_bonus(100, 0, &registry, NOW).unwrap(),
+            (100, false)
+        );
+        assert_eq!(
+            apply_wallet_age_bonus(100, NOW - MIN_WALLET_AGE + 1, &registry, NOW).unwrap(),
+            (100, false)
+        );
+        assert_eq!(
+            apply_wallet_age_bonus(100, NOW - MIN_WALLET_AGE, &registry, NOW).unwrap(),
+            (110, true)
+        );
+    }
+
+    #[test]
+    fn strip_inverts_the_bonus() {
+        for percent in [1, 3, 10, 33, 50, 67, 99, 100, 255] {
+            let registry = registry(percent);
+            for base_score in (0..=1_000).chain([999_983, u64::MAX / 400]) {
+                let (total_score, bonus_applied) =
+                    apply_wallet_age_bonus(base_score, NOW - MIN_WALLET_AGE, &registry, NOW)
+                        .unwrap();
+                let stripped = strip_wallet_age_bonus(total_score, percent, bonus_applied).unwrap();
+                assert_eq!(stripped, base_score, "percent {percent}, base {base_score}");
+            }
+        }
+    }
+}
+
+mod wide_score_math {
+    use crate::common::zeroed;
+    use solan_id::{
+        apply_diversity_bonus, scale_score, strip_diversity_bonus, weighted_base_score,
+        ProofSource, Registry, ScoringConfig, SolanIdError, MAX_PROOF_SOURCES,
+    };
+
+    /// Large enough that `score * percent` overflows a `u64` for any percent above one.
+    const LARGE: u64 = u64::MAX / 2;
+
+    #[test]
+    fn scaling_only_fails_when_the_result_does_not_fit() {
+        assert!(LARGE.checked_mul(150).is_none());
+        assert_eq!(scale_score(LARGE, 150, 100).unwrap(), LARGE / 2 * 3 + 1);
+        assert_eq!(scale_score(u64::MAX, 100, 100).unwrap(), u64::MAX);
+        assert_eq!(
+            scale_score(u64::MAX, 101, 100).unwrap_err(),
+            SolanIdError::Overflow.into()
+        );
+    }
+
+    #[test]
+    fn weighted_score_survives_a_large_base() {
+        let registry: Registry = zeroed();
+        let mut scoring_config: ScoringConfig = zeroed();
+        scoring_config.weights = [150; MAX_PROOF_SOURCES];
+        assert_eq!(
+            weighted_base_score(LARGE, ProofSource::WorldId, 0, &registry, &scoring_config)
+                .unwrap(),
+            scale_score(LARGE, 150, 100).unwrap()
+        );
+    }
+
+    #[test]
+    fn diversity_bonus_round_trips_a_large_base() {
+        let (total_score, bonus_applied) = apply_diversity_bonus(LARGE, 3, 20, 0).unwrap();
+        assert!(bonus_applied);
+        assert_eq!(total_score, LARGE + LARGE / 5);
+        assert_eq!(
+            strip_diversity_bonus(total_score, 3, 20, bonus_applied).unwrap(),
+            LARGE
+        );
+
+        assert_eq!(
+            apply_diversity_bonus(u64::MAX - 1, 3, 20, 0).unwrap_err(),
+            SolanIdError::Overflow.into()
+        );
+    }
+}
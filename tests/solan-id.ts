@@ -1,6 +1,8 @@
 import * as anchor from "@coral-xyz/anchor";
 import { Program } from "@coral-xyz/anchor";
 import { expect } from "chai";
+import { createHash } from "crypto";
+import * as fs from "fs";
 import { SolanId } from "../target/types/solan_id";
 
 describe("SolanID", () => {
@@ -23,6 +25,11 @@ describe("SolanID", () => {
     program.programId
   )[0];
 
+  const decayConfigPda = anchor.web3.PublicKey.findProgramAddressSync(
+    [Buffer.from("decay_config")],
+    program.programId
+  )[0];
+
   const sourceIndex = {
     reclaim: 0,
     gitcoinPassport: 1,
@@ -32,7 +39,13 @@ describe("SolanID", () => {
     twitter: 5,
     google: 6,
     discord: 7,
+    coinbaseKyc: 8,
+    linkedIn: 9,
+    farcaster: 10,
   } as const;
+  const PROOF_SOURCE_COUNT = Object.keys(sourceIndex).length;
+  // Slots per-source config arrays reserve, including sources not added yet.
+  const MAX_PROOF_SOURCES = 16;
 
   const sourceToIndex = (source: any): number => {
     if (source.reclaim) return sourceIndex.reclaim;
@@ -43,9 +56,18 @@ describe("SolanID", () => {
     if (source.twitter) return sourceIndex.twitter;
     if (source.google) return sourceIndex.google;
     if (source.discord) return sourceIndex.discord;
+    if (source.coinbaseKyc) return sourceIndex.coinbaseKyc;
+    if (source.linkedIn) return sourceIndex.linkedIn;
+    if (source.farcaster) return sourceIndex.farcaster;
     throw new Error("Unsupported source in test helper");
   };
 
+  const slotBuf = (slot: number) => {
+    const buf = Buffer.alloc(8);
+    buf.writeBigUInt64LE(BigInt(slot));
+    return buf;
+  };
+
   const buildAttestationMessage = (
     user: anchor.web3.PublicKey,
     proofHash: Buffer,
@@ -53,7 +75,12 @@ describe("SolanID", () => {
     identityNullifier: number[],
     nonce: number,
     baseScore: anchor.BN,
-    timestamp: number
+    timestamp: number,
+    slotHash?: { slot: number; hash: Buffer },
+    version = 1,
+    expiresAt = 0,
+    trustMultiplier = 100,
+    weightOverride = 0
   ) => {
     const sourceIdx = sourceToIndex(source);
     const nonceBuf = Buffer.alloc(8);
@@ -62,9 +89,17 @@ describe("SolanID", () => {
     scoreBuf.writeBigUInt64LE(BigInt(baseScore.toString()));
     const tsBuf = Buffer.alloc(8);
     tsBuf.writeBigInt64LE(BigInt(timestamp));
+    const expiresAtBuf = Buffer.alloc(8);
+    expiresAtBuf.writeBigInt64LE(BigInt(expiresAt));
+    const weightOverrideBuf = Buffer.alloc(8);
+    weightOverrideBuf.writeBigUInt64LE(BigInt(weightOverride));
+    const presentFields =
+      (expiresAt ? 1 << 0 : 0) |
+      (trustMultiplier !== 100 ? 1 << 1 : 0) |
+      (weightOverride ? 1 << 2 : 0);
 
     return Buffer.concat([
-      Buffer.from("sid1"),
+      Buffer.from(`sid${version}`),
       program.programId.toBuffer(),
       registryPda.toBuffer(),
       user.toBuffer(),
@@ -74,6 +109,11 @@ describe("SolanID", () => {
       scoreBuf,
       tsBuf,
       proofHash,
+      ...(slotHash ? [slotBuf(slotHash.slot), slotHash.hash] : []),
+      ...(presentFields ? [Buffer.from([presentFields])] : []),
+      ...(expiresAt ? [expiresAtBuf] : []),
+      ...(trustMultiplier !== 100 ? [Buffer.from([trustMultiplier])] : []),
+      ...(weightOverride ? [weightOverrideBuf] : []),
     ]);
   };
 
@@ -84,7 +124,13 @@ describe("SolanID", () => {
   };
 
   const sourceData = (
-    kind: "reclaim" | "gitcoin" | "worldId",
+    kind:
+      | "reclaim"
+      | "gitcoin"
+      | "worldId"
+      | "coinbaseKyc"
+      | "linkedIn"
+      | "farcaster",
     now: number,
     score = 150
   ) => {
@@ -98,6 +144,32 @@ describe("SolanID", () => {
         },
       };
     }
+    if (kind === "coinbaseKyc") {
+      return {
+        coinbaseKyc: {
+          accountHash: nextHash32(),
+          countryHash: nextHash32(),
+          verifiedAt: new anchor.BN(now - 86_400),
+        },
+      };
+    }
+    if (kind === "linkedIn") {
+      return {
+        linkedIn: {
+          memberHash: nextHash32(),
+          connectionCount: 42,
+        },
+      };
+    }
+    if (kind === "farcaster") {
+      hashSeed += 1;
+      return {
+        farcaster: {
+          fid: new anchor.BN(hashSeed),
+          custodyHash: nextHash32(),
+        },
+      };
+    }
     if (kind === "gitcoin") {
       return {
         gitcoinPassport: {
@@ -123,6 +195,12 @@ describe("SolanID", () => {
       program.programId
     )[0];
 
+  const deriveUserStatusPda = (userPk: anchor.web3.PublicKey) =>
+    anchor.web3.PublicKey.findProgramAddressSync(
+      [Buffer.from("user_status"), userPk.toBuffer()],
+      program.programId
+    )[0];
+
   const deriveIndividualProofPda = (
     userPk: anchor.web3.PublicKey,
     index: number
@@ -136,15 +214,22 @@ describe("SolanID", () => {
       program.programId
     )[0];
 
-  const deriveProofHashRegistryPda = (proofHash: Buffer) =>
+  const deriveProofHashRegistryPda = (
+    userPk: anchor.web3.PublicKey,
+    proofHash: Buffer
+  ) =>
     anchor.web3.PublicKey.findProgramAddressSync(
-      [Buffer.from("proof_hash"), proofHash],
+      [Buffer.from("proof_hash"), userPk.toBuffer(), proofHash],
       program.programId
     )[0];
 
-  const deriveIdentityNullifierPda = (nullifier: number[]) =>
+  const deriveIdentityNullifierPda = (index: number, nullifier: number[]) =>
     anchor.web3.PublicKey.findProgramAddressSync(
-      [Buffer.from("identity_nullifier"), Buffer.from(nullifier)],
+      [
+        Buffer.from("identity_nullifier"),
+        Buffer.from([index]),
+        Buffer.from(nullifier),
+      ],
       program.programId
     )[0];
 
@@ -164,6 +249,17 @@ describe("SolanID", () => {
     if (proofData.reclaim) return proofData.reclaim.identityHash;
     if (proofData.gitcoinPassport) return proofData.gitcoinPassport.didHash;
     if (proofData.worldId) return proofData.worldId.nullifierHash;
+    if (proofData.coinbaseKyc) return proofData.coinbaseKyc.accountHash;
+    if (proofData.linkedIn) return proofData.linkedIn.memberHash;
+    if (proofData.farcaster) {
+      const fid = Buffer.alloc(8);
+      fid.writeBigUInt64LE(BigInt(proofData.farcaster.fid.toString()));
+      return Array.from(
+        createHash("sha256")
+          .update(Buffer.concat([Buffer.from("farcaster"), fid]))
+          .digest()
+      );
+    }
     throw new Error("Unsupported proof payload in test helper");
   };
 
@@ -175,6 +271,32 @@ describe("SolanID", () => {
     await provider.connection.confirmTransaction(sig, "confirmed");
   };
 
+  type SubmitOptions = {
+    nonce?: number;
+    slotHash?: { slot: number; hash: Buffer };
+    attestationVersion?: number;
+    // Verifier-attested expiry for this proof alone.
+    expiresAt?: number;
+    // Verifier-attested percent applied after the source weight; 100 is neutral.
+    trustMultiplier?: number;
+    // Verifier-attested weight in place of the source's configured one.
+    weightOverride?: number;
+    // Sequential-nonce registries take no per-nonce account.
+    sequentialNonce?: boolean;
+    // Pays rent and fees in place of the user.
+    relayer?: anchor.web3.Keypair;
+    // Receives the registry's submission fee, when it charges one.
+    feeDestination?: anchor.web3.PublicKey;
+    // Opted-in StatusCache PDA to refresh.
+    statusCache?: anchor.web3.PublicKey;
+    // Seeds the proof hash account; the default key under a global scope.
+    proofHashOwner?: anchor.web3.PublicKey;
+    // Merkle path for a signer allowlisted by `verifier_merkle_root`.
+    verifierProof?: number[][];
+    verifiers?: anchor.web3.Keypair[];
+    attestation?: (message: Buffer) => anchor.web3.TransactionInstruction[];
+  };
+
   const submitProof = async (
     user: anchor.web3.Keypair,
     proofHash: Buffer,
@@ -182,36 +304,47 @@ describe("SolanID", () => {
     proofData: any,
     baseScore: anchor.BN,
     timestamp: number,
-    nonceOverride?: number
+    options: SubmitOptions = {}
   ) => {
     const index = sourceToIndex(source);
     const identityNullifier = identityNullifierFromPayload(source, proofData);
-    const nonce = nonceOverride ?? attestationNonce;
-    if (nonceOverride === undefined) {
+    const nonce = options.nonce ?? attestationNonce;
+    if (options.nonce === undefined) {
       attestationNonce += 1;
     }
 
     const userProofPda = deriveUserProofPda(user.publicKey);
     const individualProofPda = deriveIndividualProofPda(user.publicKey, index);
-    const identityNullifierRegistryPda =
-      deriveIdentityNullifierPda(identityNullifier);
+    const identityNullifierRegistryPda = deriveIdentityNullifierPda(
+      index,
+      identityNullifier
+    );
     const attestationNonceRegistryPda = deriveAttestationNoncePda(nonce);
 
-    const attestationIx =
-      anchor.web3.Ed25519Program.createInstructionWithPrivateKey({
-        privateKey: verifier.secretKey,
-        message: buildAttestationMessage(
-          user.publicKey,
-          proofHash,
-          source,
-          identityNullifier,
-          nonce,
-          baseScore,
-          timestamp
-        ),
-      });
+    const message = buildAttestationMessage(
+      user.publicKey,
+      proofHash,
+      source,
+      identityNullifier,
+      nonce,
+      baseScore,
+      timestamp,
+      options.slotHash,
+      options.attestationVersion,
+      options.expiresAt,
+      options.trustMultiplier,
+      options.weightOverride
+    );
+    const attestationIxs = options.attestation
+      ? options.attestation(message)
+      : (options.verifiers ?? [verifier]).map((signer) =>
+          anchor.web3.Ed25519Program.createInstructionWithPrivateKey({
+            privateKey: signer.secretKey,
+            message,
+          })
+        );
 
-    await program.methods
+    const signature = await program.methods
       .submitProof(
         Array.from(proofHash),
         source,
@@ -219,30 +352,64 @@ describe("SolanID", () => {
         new anchor.BN(nonce),
         proofData,
         baseScore,
-        new anchor.BN(timestamp)
+        new anchor.BN(timestamp),
+        new anchor.BN(options.slotHash?.slot ?? 0),
+        options.attestationVersion ?? 1,
+        new anchor.BN(options.expiresAt ?? 0),
+        options.trustMultiplier ?? 100,
+        new anchor.BN(options.weightOverride ?? 0),
+        options.verifierProof ?? []
       )
-      .preInstructions([attestationIx])
+      .preInstructions(attestationIxs)
       .accountsStrict({
         registry: registryPda,
         userProof: userProofPda,
+        userStatus: deriveUserStatusPda(user.publicKey),
         individualProof: individualProofPda,
         identityNullifierRegistry: identityNullifierRegistryPda,
-        attestationNonceRegistry: attestationNonceRegistryPda,
+        proofHashRegistry: deriveProofHashRegistryPda(
+          options.proofHashOwner ?? user.publicKey,
+          proofHash
+        ),
+        attestationNonceRegistry: options.sequentialNonce
+          ? null
+          : attestationNonceRegistryPda,
         scoringConfig: scoringConfigPda,
+        decayConfig: decayConfigPda,
         instructionsSysvar: anchor.web3.SYSVAR_INSTRUCTIONS_PUBKEY,
+        slotHashes: anchor.web3.SYSVAR_SLOT_HASHES_PUBKEY,
+        feeDestination: options.feeDestination ?? null,
+        statusCache: options.statusCache ?? null,
         user: user.publicKey,
+        payer: (options.relayer ?? user).publicKey,
         systemProgram: anchor.web3.SystemProgram.programId,
       })
-      .signers([user])
+      .signers(options.relayer ? [user, options.relayer] : [user])
       .rpc();
 
     return {
       userProofPda,
       individualProofPda,
       identityNullifierRegistryPda,
+      attestationNonceRegistryPda,
+      nonce,
+      signature,
     };
   };
 
+  const emittedEvents = async (signature: string) => {
+    await provider.connection.confirmTransaction(signature, "confirmed");
+    const tx = await provider.connection.getTransaction(signature, {
+      commitment: "confirmed",
+      maxSupportedTransactionVersion: 0,
+    });
+    const parser = new anchor.EventParser(
+      program.programId,
+      new anchor.BorshCoder(program.idl)
+    );
+    return [...parser.parseLogs(tx?.meta?.logMessages ?? [])];
+  };
+
   describe("Initialization", () => {
     it("should initialize registry", async () => {
       await program.methods
@@ -251,7 +418,8 @@ describe("SolanID", () => {
           new anchor.BN(0),
           10,
           new anchor.BN(3600),
-          payer
+          payer,
+          anchor.web3.PublicKey.default
         )
         .accountsStrict({
           registry: registryPda,
@@ -267,6 +435,9 @@ describe("SolanID", () => {
       expect(registry.cooldownPeriod.toNumber()).to.equal(0);
       expect(registry.proofTtlSeconds.toNumber()).to.equal(3600);
       expect(registry.totalVerifiedUsers.toNumber()).to.equal(0);
+      expect(registry.guardian.toString()).to.equal(
+        anchor.web3.PublicKey.default.toString()
+      );
     });
 
     it("should initialize scoring config", async () => {
@@ -288,6 +459,50 @@ describe("SolanID", () => {
         true
       );
     });
+
+    it("should reject a decay config initialized by a non-authority", async () => {
+      const attacker = anchor.web3.Keypair.generate();
+      await airdrop(attacker.publicKey);
+
+      try {
+        await program.methods
+          .initializeDecayConfig()
+          .accountsStrict({
+            registry: registryPda,
+            decayConfig: decayConfigPda,
+            authority: attacker.publicKey,
+            systemProgram: anchor.web3.SystemProgram.programId,
+          })
+          .signers([attacker])
+          .rpc();
+        expect.fail("should have thrown error");
+      } catch (error: any) {
+        expect(error.error.errorCode.code).to.equal("Unauthorized");
+      }
+    });
+
+    it("should initialize decay config with the default brackets", async () => {
+      await program.methods
+        .initializeDecayConfig()
+        .accountsStrict({
+          registry: registryPda,
+          decayConfig: decayConfigPda,
+          authority: payer,
+          systemProgram: anchor.web3.SystemProgram.programId,
+        })
+        .rpc();
+
+      const decayConfig = await program.account.decayConfig.fetch(
+        decayConfigPda
+      );
+
+      expect(
+        decayConfig.ageBoundaries.slice(0, 3).map((b) => b.toNumber())
+      ).to.deep.equal([2592000, 7776000, 15552000]);
+      expect(decayConfig.factors.map((f) => f.toNumber())).to.deep.equal([
+        100, 75, 50, 25,
+      ]);
+    });
   });
 
   describe("Proof Submission", () => {
@@ -316,7 +531,7 @@ describe("SolanID", () => {
       expect(userProof.validUntil.toNumber()).to.be.greaterThan(now);
     });
 
-    it("should allow duplicate proof hash with unique nonce", async () => {
+    it("should reject a replayed proof hash with a new nonce", async () => {
       const user = anchor.web3.Keypair.generate();
       await airdrop(user.publicKey);
 
@@ -334,13 +549,25 @@ describe("SolanID", () => {
         now
       );
 
-      await submitProof(
-        user,
-        proofHash,
-        source,
-        payload,
-        new anchor.BN(200),
-        now
+      try {
+        await submitProof(
+          user,
+          proofHash,
+          source,
+          payload,
+          new anchor.BN(200),
+          now
+        );
+        expect.fail("should have thrown error");
+      } catch (error: any) {
+        expect(error.error.errorCode.code).to.equal("ProofHashAlreadyUsed");
+      }
+      const proofHashRegistry = await program.account.proofHashRegistry.fetch(
+        deriveProofHashRegistryPda(user.publicKey, proofHash)
+      );
+      expect(proofHashRegistry.isUsed).to.equal(true);
+      expect(proofHashRegistry.user.toBase58()).to.equal(
+        user.publicKey.toBase58()
       );
     });
 
@@ -389,732 +616,7807 @@ describe("SolanID", () => {
       }
     });
 
-    it("should reject unsupported source payload mappings", async () => {
+    it("should tie the attestation timestamp to reclaim issued_at", async () => {
       const user = anchor.web3.Keypair.generate();
       await airdrop(user.publicKey);
+
       const now = Math.floor(Date.now() / 1000);
-      const reclaimPayload = sourceData("reclaim", now);
 
-      const unsupportedSources = [
-        { brightId: {} },
-        { lens: {} },
-        { twitter: {} },
-        { google: {} },
-        { discord: {} },
-      ];
+      try {
+        await submitProof(
+          user,
+          Buffer.alloc(32, "skew1"),
+          { reclaim: {} },
+          sourceData("reclaim", now - 3600),
+          new anchor.BN(150),
+          now
+        );
+        expect.fail("should have thrown error");
+      } catch (error: any) {
+        expect(error.error.errorCode.code).to.equal("IssuedAtMismatch");
+      }
 
-      for (let i = 0; i < unsupportedSources.length; i += 1) {
+      const { userProofPda } = await submitProof(
+        user,
+        Buffer.alloc(32, "skew2"),
+        { reclaim: {} },
+        sourceData("reclaim", now - 120),
+        new anchor.BN(150),
+        now
+      );
+      const userProof = await program.account.userProof.fetch(userProofPda);
+      expect(userProof.activeSourceCount).to.equal(1);
+    });
+
+    it("should apply the registry's clock skew tolerance", async () => {
+      const setTolerance = (seconds: number) =>
+        program.methods
+          .setClockSkewTolerance(new anchor.BN(seconds))
+          .accountsStrict({
+            registry: registryPda,
+            authority: payer,
+          })
+          .rpc();
+
+      for (const seconds of [-1, 3601]) {
         try {
-          await submitProof(
-            user,
-            Buffer.from(Uint8Array.from([40 + i, ...Array(31).fill(0)])),
-            unsupportedSources[i],
-            reclaimPayload,
-            new anchor.BN(140),
-            now
-          );
+          await setTolerance(seconds);
           expect.fail("should have thrown error");
         } catch (error: any) {
-          expect(error.error.errorCode.code).to.equal("SourcePayloadMismatch");
+          expect(error.error.errorCode.code).to.equal("InvalidConfig");
         }
       }
-    });
 
-    it("should reject when ed25519 pre-instruction is missing", async () => {
+      await setTolerance(60);
       const user = anchor.web3.Keypair.generate();
       await airdrop(user.publicKey);
       const now = Math.floor(Date.now() / 1000);
-      const source = { reclaim: {} };
-      const payload = sourceData("reclaim", now);
-      const identityNullifier = identityNullifierFromPayload(source, payload);
-      const nonce = 818181;
-
-      const userProofPda = deriveUserProofPda(user.publicKey);
-      const individualProofPda = deriveIndividualProofPda(
-        user.publicKey,
-        sourceIndex.reclaim
-      );
-      const identityNullifierRegistryPda =
-        deriveIdentityNullifierPda(identityNullifier);
-      const attestationNonceRegistryPda = deriveAttestationNoncePda(nonce);
 
       try {
-        await program.methods
-          .submitProof(
-            Array.from(Buffer.alloc(32, "nosig")),
-            source,
-            identityNullifier,
-            new anchor.BN(nonce),
-            payload as any,
-            new anchor.BN(150),
-            new anchor.BN(now)
-          )
-          .accountsStrict({
-            registry: registryPda,
-            userProof: userProofPda,
-            individualProof: individualProofPda,
-            identityNullifierRegistry: identityNullifierRegistryPda,
-            attestationNonceRegistry: attestationNonceRegistryPda,
-            scoringConfig: scoringConfigPda,
-            instructionsSysvar: anchor.web3.SYSVAR_INSTRUCTIONS_PUBKEY,
-            user: user.publicKey,
-            systemProgram: anchor.web3.SystemProgram.programId,
-          })
-          .signers([user])
-          .rpc();
-
+        await submitProof(
+          user,
+          Buffer.alloc(32, "skewfar"),
+          { worldId: {} },
+          sourceData("worldId", now + 600),
+          new anchor.BN(150),
+          now + 600
+        );
         expect.fail("should have thrown error");
       } catch (error: any) {
-        expect(error.error.errorCode.code).to.equal(
-          "InvalidAttestationInstruction"
-        );
+        expect(error.error.errorCode.code).to.equal("InvalidTimestamp");
       }
-    });
 
-    it("should reject duplicate identity nullifier across wallets", async () => {
-      const userA = anchor.web3.Keypair.generate();
-      const userB = anchor.web3.Keypair.generate();
-      await airdrop(userA.publicKey);
-      await airdrop(userB.publicKey);
+      const { userProofPda } = await submitProof(
+        user,
+        Buffer.alloc(32, "skewnear"),
+        { worldId: {} },
+        sourceData("worldId", now + 30),
+        new anchor.BN(150),
+        now + 30
+      );
+      const userProof = await program.account.userProof.fetch(userProofPda);
+      expect(userProof.activeSourceCount).to.equal(1);
 
-      const now = Math.floor(Date.now() / 1000);
-      const source = { worldId: {} };
-      const sharedPayload = sourceData("worldId", now);
+      await setTolerance(300);
+      const registry = await program.account.registry.fetch(registryPda);
+      expect(registry.clockSkewTolerance.toNumber()).to.equal(300);
+    });
 
-      await submitProof(
-        userA,
-        Buffer.alloc(32, "proof3c"),
-        source,
-        sharedPayload,
-        new anchor.BN(160),
-        now
-      );
+    it("should reject a zero-score proof", async () => {
+      const user = anchor.web3.Keypair.generate();
+      await airdrop(user.publicKey);
 
+      const now = Math.floor(Date.now() / 1000);
       try {
         await submitProof(
-          userB,
-          Buffer.alloc(32, "proof3d"),
-          source,
-          sharedPayload,
-          new anchor.BN(160),
+          user,
+          Buffer.alloc(32, "zeroscore"),
+          { reclaim: {} },
+          sourceData("reclaim", now),
+          new anchor.BN(0),
           now
         );
         expect.fail("should have thrown error");
       } catch (error: any) {
-        expect(error.error.errorCode.code).to.equal("DuplicateIdentityClaim");
+        expect(error.error.errorCode.code).to.equal("ZeroScoreProof");
       }
+      expect(
+        await program.account.userProof.fetchNullable(
+          deriveUserProofPda(user.publicKey)
+        )
+      ).to.equal(null);
     });
 
-    it("should reject reused attestation nonce", async () => {
+    it("should reject a below-threshold proof only in strict mode", async () => {
+      const setStrictMinScore = (enabled: boolean) =>
+        program.methods
+          .setStrictMinScore(enabled)
+          .accountsStrict({
+            registry: registryPda,
+            authority: payer,
+          })
+          .rpc();
+
       const user = anchor.web3.Keypair.generate();
       await airdrop(user.publicKey);
-
       const now = Math.floor(Date.now() / 1000);
-      const nonce = 999999;
-
-      await submitProof(
-        user,
-        Buffer.alloc(32, "proof3e"),
-        { reclaim: {} },
-        sourceData("reclaim", now),
-        new anchor.BN(170),
-        now,
-        nonce
-      );
+      const { minScore } = await program.account.registry.fetch(registryPda);
 
+      await setStrictMinScore(true);
       try {
         await submitProof(
           user,
-          Buffer.alloc(32, "proof3f"),
+          Buffer.alloc(32, "strictlow"),
           { reclaim: {} },
           sourceData("reclaim", now),
-          new anchor.BN(170),
-          now,
-          nonce
+          new anchor.BN(60),
+          now
         );
         expect.fail("should have thrown error");
       } catch (error: any) {
-        expect(error.error.errorCode.code).to.equal(
-          "AttestationNonceAlreadyUsed"
-        );
+        expect(error.error.errorCode.code).to.equal("ScoreBelowThreshold");
+      } finally {
+        await setStrictMinScore(false);
       }
-    });
-  });
+      expect(
+        await program.account.userProof.fetchNullable(
+          deriveUserProofPda(user.publicKey)
+        )
+      ).to.equal(null);
 
-  describe("Proof Verification", () => {
-    it("should verify proof successfully", async () => {
-      const user = anchor.web3.Keypair.generate();
+      // Lenient mode records the proof and reports the remaining gap.
+      const { userProofPda, signature } = await submitProof(
+        user,
+        Buffer.alloc(32, "lenientlow"),
+        { reclaim: {} },
+        sourceData("reclaim", now),
+        new anchor.BN(60),
+        now
+      );
+      const { aggregatedScore } = await program.account.userProof.fetch(
+        userProofPda
+      );
+      expect(aggregatedScore.toNumber()).to.be.lessThan(minScore.toNumber());
+      const [submitted] = (await emittedEvents(signature)).filter(
+        (event) => event.name === "proofSubmitted"
+      );
+      expect(submitted.data.pointsToMinScore.toNumber()).to.equal(
+        minScore.toNumber() - aggregatedScore.toNumber()
+      );
+    });
+
+    it("should hold a source's identity for the dwell window", async () => {
+      const setMinSourceDwell = (seconds: number) =>
+        program.methods
+          .setMinSourceDwell(new anchor.BN(seconds))
+          .accountsStrict({
+            registry: registryPda,
+            authority: payer,
+          })
+          .rpc();
+
+      const user = anchor.web3.Keypair.generate();
+      await airdrop(user.publicKey);
+      const verifiedAt = Math.floor(Date.now() / 1000) - 120;
+      const original = sourceData("worldId", verifiedAt);
+      await submitProof(
+        user,
+        Buffer.alloc(32, "dwell0"),
+        { worldId: {} },
+        original,
+        new anchor.BN(150),
+        verifiedAt
+      );
+
+      await setMinSourceDwell(3600);
+      try {
+        try {
+          await submitProof(
+            user,
+            Buffer.alloc(32, "dwell1"),
+            { worldId: {} },
+            sourceData("worldId", verifiedAt),
+            new anchor.BN(150),
+            verifiedAt
+          );
+          expect.fail("should have thrown error");
+        } catch (error: any) {
+          expect(error.error.errorCode.code).to.equal("SourceDwellActive");
+        }
+        // Refreshing the same identity is not a replacement.
+        await submitProof(
+          user,
+          Buffer.alloc(32, "dwell2"),
+          { worldId: {} },
+          original,
+          new anchor.BN(150),
+          verifiedAt
+        );
+
+        await setMinSourceDwell(60);
+        const replacement = sourceData("worldId", verifiedAt);
+        const { individualProofPda } = await submitProof(
+          user,
+          Buffer.alloc(32, "dwell3"),
+          { worldId: {} },
+          replacement,
+          new anchor.BN(150),
+          verifiedAt
+        );
+        const individualProof = await program.account.individualProof.fetch(
+          individualProofPda
+        );
+        expect(individualProof.identityNullifier).to.deep.equal(
+          identityNullifierFromPayload({ worldId: {} }, replacement)
+        );
+      } finally {
+        await setMinSourceDwell(0);
+      }
+    });
+
+    it("should expire attestations before the proofs they back", async () => {
+      const setAttestationTtl = (seconds: number) =>
+        program.methods
+          .setAttestationTtl(new anchor.BN(seconds))
+          .accountsStrict({
+            registry: registryPda,
+            authority: payer,
+          })
+          .rpc();
+
+      const user = anchor.web3.Keypair.generate();
+      await airdrop(user.publicKey);
+      const now = Math.floor(Date.now() / 1000);
+      const { proofTtlSeconds } = await program.account.registry.fetch(
+        registryPda
+      );
+
+      await setAttestationTtl(600);
+      try {
+        // Well inside the proof TTL, but past the attestation TTL.
+        const staleAt = now - 900;
+        expect(staleAt).to.be.greaterThan(now - proofTtlSeconds.toNumber());
+        try {
+          await submitProof(
+            user,
+            Buffer.alloc(32, "attttl0"),
+            { worldId: {} },
+            sourceData("worldId", staleAt),
+            new anchor.BN(150),
+            staleAt
+          );
+          expect.fail("should have thrown error");
+        } catch (error: any) {
+          expect(error.error.errorCode.code).to.equal("AttestationExpired");
+        }
+
+        const freshAt = now - 300;
+        const { userProofPda } = await submitProof(
+          user,
+          Buffer.alloc(32, "attttl1"),
+          { worldId: {} },
+          sourceData("worldId", freshAt),
+          new anchor.BN(150),
+          freshAt
+        );
+        // The proof itself still lives for the full proof TTL.
+        const userProof = await program.account.userProof.fetch(userProofPda);
+        expect(userProof.validUntil.toNumber()).to.be.at.least(
+          now + proofTtlSeconds.toNumber() - 60
+        );
+
+        // Nor can the proof TTL be lowered beneath the attestation TTL.
+        try {
+          await program.methods
+            .updateRegistryConfig(
+              new anchor.BN(0),
+              10,
+              new anchor.BN(599),
+              new anchor.BN(0),
+              1
+            )
+            .accountsStrict({
+              registry: registryPda,
+              authority: payer,
+            })
+            .rpc();
+          expect.fail("should have thrown error");
+        } catch (error: any) {
+          expect(error.error.errorCode.code).to.equal("InvalidConfig");
+        }
+      } finally {
+        await setAttestationTtl(0);
+      }
+    });
+
+    it("should accept proofs from the newly added sources", async () => {
+      const user = anchor.web3.Keypair.generate();
+      await airdrop(user.publicKey);
+      const now = Math.floor(Date.now() / 1000);
+
+      let userProofPda: anchor.web3.PublicKey | undefined;
+      for (const [kind, source] of [
+        ["coinbaseKyc", { coinbaseKyc: {} }],
+        ["linkedIn", { linkedIn: {} }],
+        ["farcaster", { farcaster: {} }],
+      ] as const) {
+        ({ userProofPda } = await submitProof(
+          user,
+          Buffer.alloc(32, `new-${kind}`),
+          source,
+          sourceData(kind, now),
+          new anchor.BN(120),
+          now
+        ));
+        const individualProof = await program.account.individualProof.fetch(
+          deriveIndividualProofPda(user.publicKey, sourceToIndex(source))
+        );
+        expect(individualProof.weightedScore.toNumber()).to.equal(120);
+      }
+
+      const userProof = await program.account.userProof.fetch(userProofPda!);
+      expect(userProof.activeSourceCount).to.equal(3);
+      expect(userProof.sourcesMask).to.equal(0b111 << sourceIndex.coinbaseKyc);
+
+      try {
+        await submitProof(
+          user,
+          Buffer.alloc(32, "new-bad"),
+          { linkedIn: {} },
+          { linkedIn: { memberHash: nextHash32(), connectionCount: 0 } },
+          new anchor.BN(120),
+          now
+        );
+        expect.fail("should have thrown error");
+      } catch (error: any) {
+        expect(error.error.errorCode.code).to.equal("InvalidSourceProofData");
+      }
+    });
+
+    it("should reject unsupported source payload mappings", async () => {
+      const user = anchor.web3.Keypair.generate();
+      await airdrop(user.publicKey);
+      const now = Math.floor(Date.now() / 1000);
+      const reclaimPayload = sourceData("reclaim", now);
+
+      const unsupportedSources = [
+        { brightId: {} },
+        { lens: {} },
+        { twitter: {} },
+        { google: {} },
+        { discord: {} },
+      ];
+
+      for (let i = 0; i < unsupportedSources.length; i += 1) {
+        try {
+          await submitProof(
+            user,
+            Buffer.from(Uint8Array.from([40 + i, ...Array(31).fill(0)])),
+            unsupportedSources[i],
+            reclaimPayload,
+            new anchor.BN(140),
+            now
+          );
+          expect.fail("should have thrown error");
+        } catch (error: any) {
+          expect(error.error.errorCode.code).to.equal("SourcePayloadMismatch");
+        }
+      }
+    });
+
+    it("should enforce configured gitcoin passport minimums", async () => {
+      const setRequirements = (stamps: number, score: number, model: number) =>
+        program.methods
+          .updateGitcoinRequirements(stamps, score, model)
+          .accountsStrict({
+            scoringConfig: scoringConfigPda,
+            authority: payer,
+          })
+          .rpc();
+
+      const user = anchor.web3.Keypair.generate();
+      await airdrop(user.publicKey);
+      const now = Math.floor(Date.now() / 1000);
+      const passport = (stampCount: number, modelVersion: number) => ({
+        gitcoinPassport: {
+          didHash: nextHash32(),
+          stampCount,
+          passportScore: 300,
+          modelVersion,
+        },
+      });
+
+      await setRequirements(5, 20, 2);
+
+      // Both passports clear the baseline non-zero checks.
+      for (const payload of [passport(3, 2), passport(5, 1)]) {
+        try {
+          await submitProof(
+            user,
+            Buffer.alloc(32, "gitcoinMin1"),
+            { gitcoinPassport: {} },
+            payload,
+            new anchor.BN(150),
+            now
+          );
+          expect.fail("should have thrown error");
+        } catch (error: any) {
+          expect(error.error.errorCode.code).to.equal("InvalidSourceProofData");
+        }
+      }
+
+      await submitProof(
+        user,
+        Buffer.alloc(32, "gitcoinMin2"),
+        { gitcoinPassport: {} },
+        passport(5, 2),
+        new anchor.BN(150),
+        now
+      );
+
+      await setRequirements(0, 0, 0);
+    });
+
+    it("should reject device-level world id below the minimum", async () => {
+      const setMinLevel = (level: number) =>
+        program.methods
+          .updateMinWorldIdLevel(level)
+          .accountsStrict({
+            scoringConfig: scoringConfigPda,
+            authority: payer,
+          })
+          .rpc();
+
+      const user = anchor.web3.Keypair.generate();
+      await airdrop(user.publicKey);
+      const now = Math.floor(Date.now() / 1000);
+      const worldId = (verificationLevel: number) => ({
+        worldId: {
+          nullifierHash: nextHash32(),
+          merkleRoot: nextHash32(),
+          verificationLevel,
+        },
+      });
+
+      await setMinLevel(2);
+
+      try {
+        await submitProof(
+          user,
+          Buffer.alloc(32, "orbOnly1"),
+          { worldId: {} },
+          worldId(1),
+          new anchor.BN(150),
+          now
+        );
+        expect.fail("should have thrown error");
+      } catch (error: any) {
+        expect(error.error.errorCode.code).to.equal("InvalidSourceProofData");
+      }
+
+      await submitProof(
+        user,
+        Buffer.alloc(32, "orbOnly2"),
+        { worldId: {} },
+        worldId(2),
+        new anchor.BN(150),
+        now
+      );
+
+      await setMinLevel(1);
+    });
+
+    it("should reject a base score above the source cap", async () => {
+      const setMaxScore = (maxScore: number) =>
+        program.methods
+          .updateSourceMaxScore({ reclaim: {} }, new anchor.BN(maxScore))
+          .accountsStrict({
+            scoringConfig: scoringConfigPda,
+            authority: payer,
+          })
+          .rpc();
+
+      const user = anchor.web3.Keypair.generate();
+      await airdrop(user.publicKey);
+      const now = Math.floor(Date.now() / 1000);
+      const payload = sourceData("reclaim", now);
+
+      await setMaxScore(200);
+
+      try {
+        await submitProof(
+          user,
+          Buffer.alloc(32, "maxScore1"),
+          { reclaim: {} },
+          payload,
+          new anchor.BN(201),
+          now
+        );
+        expect.fail("should have thrown error");
+      } catch (error: any) {
+        expect(error.error.errorCode.code).to.equal("InvalidSourceProofData");
+      }
+
+      await submitProof(
+        user,
+        Buffer.alloc(32, "maxScore2"),
+        { reclaim: {} },
+        payload,
+        new anchor.BN(200),
+        now
+      );
+
+      await setMaxScore(0);
+    });
+
+    it("should bound weighted scores in normalized mode", async () => {
+      const setMaxScore = (maxScore: number) =>
+        program.methods
+          .updateSourceMaxScore({ reclaim: {} }, new anchor.BN(maxScore))
+          .accountsStrict({
+            scoringConfig: scoringConfigPda,
+            authority: payer,
+          })
+          .rpc();
+      const setNormalized = (enabled: boolean, maxTotalScore: number) =>
+        program.methods
+          .setNormalizedScoring(enabled, new anchor.BN(maxTotalScore))
+          .accountsStrict({
+            registry: registryPda,
+            authority: payer,
+          })
+          .rpc();
+      const submitFor = async (label: string) => {
+        const user = anchor.web3.Keypair.generate();
+        await airdrop(user.publicKey);
+        const now = Math.floor(Date.now() / 1000);
+        const { individualProofPda } = await submitProof(
+          user,
+          Buffer.alloc(32, label),
+          { reclaim: {} },
+          sourceData("reclaim", now),
+          new anchor.BN(200),
+          now
+        );
+        return (
+          await program.account.individualProof.fetch(individualProofPda)
+        ).weightedScore.toNumber();
+      };
+
+      try {
+        await setNormalized(true, 0);
+        expect.fail("should have thrown error");
+      } catch (error: any) {
+        expect(error.error.errorCode.code).to.equal("InvalidConfig");
+      }
+
+      const { weights } = await program.account.scoringConfig.fetch(
+        scoringConfigPda
+      );
+      const weight = weights[sourceIndex.reclaim].toNumber();
+      const totalWeight = weights.reduce((sum, w) => sum + w.toNumber(), 0);
+
+      await setMaxScore(400);
+      const raw = await submitFor("normalized1");
+      await setNormalized(true, 1000);
+      const normalized = await submitFor("normalized2");
+      await setNormalized(false, 0);
+      await setMaxScore(0);
+
+      // Same 200-of-400 input: raw uses weight / 100, normalized its share.
+      expect(raw).to.equal(Math.floor((200 * weight) / 100));
+      expect(normalized).to.equal(
+        Math.floor((200 * weight * 1000) / (400 * totalWeight))
+      );
+    });
+
+    it("should reject when ed25519 pre-instruction is missing", async () => {
+      const user = anchor.web3.Keypair.generate();
+      await airdrop(user.publicKey);
+      const now = Math.floor(Date.now() / 1000);
+      const source = { reclaim: {} };
+      const payload = sourceData("reclaim", now);
+      const identityNullifier = identityNullifierFromPayload(source, payload);
+      const nonce = 818181;
+
+      const userProofPda = deriveUserProofPda(user.publicKey);
+      const individualProofPda = deriveIndividualProofPda(
+        user.publicKey,
+        sourceIndex.reclaim
+      );
+      const identityNullifierRegistryPda = deriveIdentityNullifierPda(
+        sourceIndex.reclaim,
+        identityNullifier
+      );
+      const attestationNonceRegistryPda = deriveAttestationNoncePda(nonce);
+
+      try {
+        await program.methods
+          .submitProof(
+            Array.from(Buffer.alloc(32, "nosig")),
+            source,
+            identityNullifier,
+            new anchor.BN(nonce),
+            payload as any,
+            new anchor.BN(150),
+            new anchor.BN(now),
+            new anchor.BN(0),
+            1,
+            new anchor.BN(0),
+            100,
+            new anchor.BN(0),
+            []
+          )
+          .accountsStrict({
+            registry: registryPda,
+            userProof: userProofPda,
+            userStatus: deriveUserStatusPda(user.publicKey),
+            individualProof: individualProofPda,
+            identityNullifierRegistry: identityNullifierRegistryPda,
+            proofHashRegistry: deriveProofHashRegistryPda(
+              user.publicKey,
+              Buffer.alloc(32, "nosig")
+            ),
+            attestationNonceRegistry: attestationNonceRegistryPda,
+            scoringConfig: scoringConfigPda,
+            decayConfig: decayConfigPda,
+            instructionsSysvar: anchor.web3.SYSVAR_INSTRUCTIONS_PUBKEY,
+            slotHashes: anchor.web3.SYSVAR_SLOT_HASHES_PUBKEY,
+            feeDestination: null,
+            statusCache: null,
+            user: user.publicKey,
+            payer: user.publicKey,
+            systemProgram: anchor.web3.SystemProgram.programId,
+          })
+          .signers([user])
+          .rpc();
+
+        expect.fail("should have thrown error");
+      } catch (error: any) {
+        expect(error.error.errorCode.code).to.equal(
+          "InvalidAttestationInstruction"
+        );
+      }
+    });
+
+    it("should reject duplicate identity nullifier across wallets", async () => {
+      const userA = anchor.web3.Keypair.generate();
+      const userB = anchor.web3.Keypair.generate();
+      await airdrop(userA.publicKey);
+      await airdrop(userB.publicKey);
+
+      const now = Math.floor(Date.now() / 1000);
+      const source = { worldId: {} };
+      const sharedPayload = sourceData("worldId", now);
+
+      await submitProof(
+        userA,
+        Buffer.alloc(32, "proof3c"),
+        source,
+        sharedPayload,
+        new anchor.BN(160),
+        now
+      );
+
+      try {
+        await submitProof(
+          userB,
+          Buffer.alloc(32, "proof3d"),
+          source,
+          sharedPayload,
+          new anchor.BN(160),
+          now
+        );
+        expect.fail("should have thrown error");
+      } catch (error: any) {
+        expect(error.error.errorCode.code).to.equal("DuplicateIdentityClaim");
+      }
+    });
+
+    it("should namespace identity nullifiers by source", async () => {
+      const user = anchor.web3.Keypair.generate();
+      await airdrop(user.publicKey);
+
+      const now = Math.floor(Date.now() / 1000);
+      const sharedNullifier = nextHash32();
+      const reclaimPayload = sourceData("reclaim", now);
+      reclaimPayload.reclaim.identityHash = sharedNullifier;
+      const worldIdPayload = sourceData("worldId", now);
+      worldIdPayload.worldId.nullifierHash = sharedNullifier;
+
+      const reclaim = await submitProof(
+        user,
+        Buffer.alloc(32, "ns1"),
+        { reclaim: {} },
+        reclaimPayload,
+        new anchor.BN(150),
+        now
+      );
+      const worldId = await submitProof(
+        user,
+        Buffer.alloc(32, "ns2"),
+        { worldId: {} },
+        worldIdPayload,
+        new anchor.BN(150),
+        now
+      );
+
+      expect(reclaim.identityNullifierRegistryPda.toString()).to.not.equal(
+        worldId.identityNullifierRegistryPda.toString()
+      );
+
+      const reclaimRegistry =
+        await program.account.identityNullifierRegistry.fetch(
+          reclaim.identityNullifierRegistryPda
+        );
+      const worldIdRegistry =
+        await program.account.identityNullifierRegistry.fetch(
+          worldId.identityNullifierRegistryPda
+        );
+      expect(reclaimRegistry.source).to.deep.equal({ reclaim: {} });
+      expect(worldIdRegistry.source).to.deep.equal({ worldId: {} });
+      expect(reclaimRegistry.nullifier).to.deep.equal(sharedNullifier);
+      expect(worldIdRegistry.nullifier).to.deep.equal(sharedNullifier);
+      expect(reclaimRegistry.initialized).to.equal(true);
+      expect(worldIdRegistry.initialized).to.equal(true);
+      const nonceRegistry =
+        await program.account.attestationNonceRegistry.fetch(
+          worldId.attestationNonceRegistryPda
+        );
+      expect(nonceRegistry.initialized).to.equal(true);
+    });
+
+    it("should scope proof hash uniqueness per user or globally", async () => {
+      const setProofHashScope = (scope: object) =>
+        program.methods
+          .setProofHashScope(scope as any)
+          .accountsStrict({
+            registry: registryPda,
+            authority: payer,
+          })
+          .rpc();
+
+      const first = anchor.web3.Keypair.generate();
+      const second = anchor.web3.Keypair.generate();
+      await airdrop(first.publicKey);
+      await airdrop(second.publicKey);
+      const now = Math.floor(Date.now() / 1000);
+
+      // Per user: another wallet may use the same hash, the same wallet may not
+      // reuse it for a different source.
+      const perUserHash = Buffer.alloc(32, "samehash");
+      await submitProof(
+        first,
+        perUserHash,
+        { reclaim: {} },
+        sourceData("reclaim", now),
+        new anchor.BN(150),
+        now
+      );
+      await submitProof(
+        second,
+        perUserHash,
+        { reclaim: {} },
+        sourceData("reclaim", now),
+        new anchor.BN(150),
+        now
+      );
+      try {
+        await submitProof(
+          first,
+          perUserHash,
+          { worldId: {} },
+          sourceData("worldId", now),
+          new anchor.BN(150),
+          now
+        );
+        expect.fail("should have thrown error");
+      } catch (error: any) {
+        expect(error.error.errorCode.code).to.equal("ProofHashAlreadyUsed");
+      }
+
+      // Globally: once any wallet uses a hash, no other wallet can.
+      await setProofHashScope({ global: {} });
+      try {
+        const globalHash = Buffer.alloc(32, "globalhash");
+        const globalOwner = { proofHashOwner: anchor.web3.PublicKey.default };
+        await submitProof(
+          first,
+          globalHash,
+          { gitcoinPassport: {} },
+          sourceData("gitcoin", now),
+          new anchor.BN(150),
+          now,
+          globalOwner
+        );
+        try {
+          await submitProof(
+            second,
+            globalHash,
+            { gitcoinPassport: {} },
+            sourceData("gitcoin", now),
+            new anchor.BN(150),
+            now,
+            globalOwner
+          );
+          expect.fail("should have thrown error");
+        } catch (error: any) {
+          expect(error.error.errorCode.code).to.equal("ProofHashAlreadyUsed");
+        }
+      } finally {
+        await setProofHashScope({ perUser: {} });
+      }
+    });
+
+    it("should reject reused attestation nonce", async () => {
+      const user = anchor.web3.Keypair.generate();
+      await airdrop(user.publicKey);
+
+      const now = Math.floor(Date.now() / 1000);
+      const nonce = 999999;
+
+      await submitProof(
+        user,
+        Buffer.alloc(32, "proof3e"),
+        { reclaim: {} },
+        sourceData("reclaim", now),
+        new anchor.BN(170),
+        now,
+        { nonce }
+      );
+
+      try {
+        await submitProof(
+          user,
+          Buffer.alloc(32, "proof3f"),
+          { reclaim: {} },
+          sourceData("reclaim", now),
+          new anchor.BN(170),
+          now,
+          { nonce }
+        );
+        expect.fail("should have thrown error");
+      } catch (error: any) {
+        expect(error.error.errorCode.code).to.equal(
+          "AttestationNonceAlreadyUsed"
+        );
+      }
+    });
+
+    it("should treat a retried submission as a no-op", async () => {
+      const user = anchor.web3.Keypair.generate();
+      const relayer = anchor.web3.Keypair.generate();
+      await airdrop(user.publicKey);
+      await airdrop(relayer.publicKey);
+      const now = Math.floor(Date.now() / 1000);
+      const source = { gitcoinPassport: {} };
+      const payload = sourceData("gitcoin", now);
+      const proofHash = Buffer.from("retry".padEnd(32, "x"));
+
+      const first = await submitProof(
+        user,
+        proofHash,
+        source,
+        payload,
+        new anchor.BN(150),
+        now
+      );
+      const before = await program.account.userProof.fetch(
+        first.userProofPda
+      );
+
+      // The same attestation sent again, here through a relayer so the
+      // transaction differs, as a client would after an RPC timeout.
+      const retry = await submitProof(
+        user,
+        proofHash,
+        source,
+        payload,
+        new anchor.BN(150),
+        now,
+        { nonce: first.nonce, relayer }
+      );
+      const after = await program.account.userProof.fetch(first.userProofPda);
+      expect(after.aggregatedScore.toNumber()).to.equal(
+        before.aggregatedScore.toNumber()
+      );
+      expect(after.lastSubmission.toNumber()).to.equal(
+        before.lastSubmission.toNumber()
+      );
+      expect(after.activeSourceCount).to.equal(before.activeSourceCount);
+      const events = await emittedEvents(retry.signature);
+      expect(events.filter((event) => event.name === "proofSubmitted")).to.be
+        .empty;
+
+      // A different proof under the consumed nonce is still a replay.
+      try {
+        await submitProof(
+          user,
+          Buffer.from("retry-forged".padEnd(32, "x")),
+          source,
+          payload,
+          new anchor.BN(150),
+          now,
+          { nonce: first.nonce, relayer }
+        );
+        expect.fail("should have thrown error");
+      } catch (error: any) {
+        expect(error.error.errorCode.code).to.equal(
+          "AttestationNonceAlreadyUsed"
+        );
+      }
+    });
+
+    it("should let a relayer submit a proof the user owns", async () => {
+      const user = anchor.web3.Keypair.generate();
+      const relayer = anchor.web3.Keypair.generate();
+      await airdrop(relayer.publicKey);
+
+      const now = Math.floor(Date.now() / 1000);
+      const { userProofPda, individualProofPda } = await submitProof(
+        user,
+        Buffer.alloc(32, "relayed1"),
+        { worldId: {} },
+        sourceData("worldId", now),
+        new anchor.BN(150),
+        now,
+        { relayer }
+      );
+
+      const userProof = await program.account.userProof.fetch(userProofPda);
+      const individualProof = await program.account.individualProof.fetch(
+        individualProofPda
+      );
+      expect(userProof.user.toBase58()).to.equal(user.publicKey.toBase58());
+      expect(individualProof.user.toBase58()).to.equal(
+        user.publicKey.toBase58()
+      );
+      // The relayer covered rent, so the unfunded user still holds nothing.
+      expect(await provider.connection.getBalance(user.publicKey)).to.equal(0);
+    });
+  });
+
+  describe("Proof Verification", () => {
+    it("should verify proof successfully", async () => {
+      const user = anchor.web3.Keypair.generate();
+      await airdrop(user.publicKey);
+
+      const now = Math.floor(Date.now() / 1000);
+      const source = { reclaim: {} };
+      const payload = sourceData("reclaim", now);
+
+      const { userProofPda } = await submitProof(
+        user,
+        Buffer.alloc(32, "proof4"),
+        source,
+        payload,
+        new anchor.BN(200),
+        now
+      );
+
+      const result = await program.methods
+        .verifyProof()
+        .accountsStrict({
+          userProof: userProofPda,
+          userStatus: deriveUserStatusPda(user.publicKey),
+          registry: registryPda,
+          scoringConfig: scoringConfigPda,
+          decayConfig: decayConfigPda,
+          user: user.publicKey,
+        })
+        .view();
+
+      expect(result.isVerified).to.equal(true);
+    });
+
+    it("should report an unregistered wallet as unverified", async () => {
+      const stranger = anchor.web3.Keypair.generate().publicKey;
+      const accounts = {
+        userProof: deriveUserProofPda(stranger),
+        userStatus: deriveUserStatusPda(stranger),
+        registry: registryPda,
+        scoringConfig: scoringConfigPda,
+        decayConfig: decayConfigPda,
+        user: stranger,
+      };
+
+      const status = await program.methods
+        .verifyProof()
+        .accountsStrict(accounts)
+        .view();
+      expect(status.isVerified).to.equal(false);
+      expect(status.aggregatedScore.toNumber()).to.equal(0);
+      expect(status.verifiedAt.toNumber()).to.equal(0);
+      expect(status.liveScore.toNumber()).to.equal(0);
+      expect(status.reason).to.deep.equal({ noProof: {} });
+
+      const tier = await program.methods
+        .getTier()
+        .accountsStrict(accounts)
+        .view();
+      expect(tier).to.deep.equal({ none: {} });
+      const active = await program.methods
+        .getActiveSources()
+        .accountsStrict(accounts)
+        .view();
+      expect(active.sources).to.have.length(0);
+    });
+
+    it("should mark a user only when verification passes", async () => {
+      const verifyAndMark = async (
+        user: anchor.web3.Keypair,
+        score: number
+      ) => {
+        const now = Math.floor(Date.now() / 1000);
+        const { userProofPda } = await submitProof(
+          user,
+          Buffer.alloc(32, `mark${score}`),
+          { reclaim: {} },
+          sourceData("reclaim", now),
+          new anchor.BN(score),
+          now
+        );
+        const accounts = {
+          userProof: userProofPda,
+          userStatus: deriveUserStatusPda(user.publicKey),
+          registry: registryPda,
+          decayConfig: decayConfigPda,
+          scoringConfig: scoringConfigPda,
+          user: user.publicKey,
+        };
+        await program.methods
+          .verifyAndMark()
+          .accountsStrict(accounts)
+          .signers([user])
+          .rpc();
+        const status = await program.methods
+          .verifyProof()
+          .accountsStrict(accounts)
+          .view();
+        const { marked } = await program.account.userProof.fetch(userProofPda);
+        return { status, marked };
+      };
+
+      const passing = anchor.web3.Keypair.generate();
+      const failing = anchor.web3.Keypair.generate();
+      await airdrop(passing.publicKey);
+      await airdrop(failing.publicKey);
+
+      const passed = await verifyAndMark(passing, 200);
+      expect(passed.status.isVerified).to.equal(true);
+      expect(passed.marked).to.equal(true);
+
+      const failed = await verifyAndMark(failing, 20);
+      expect(failed.status.isVerified).to.equal(false);
+      expect(failed.marked).to.equal(false);
+    });
+
+    it("should hide a private user's score but still verify", async () => {
+      const user = anchor.web3.Keypair.generate();
+      await airdrop(user.publicKey);
+
+      const now = Math.floor(Date.now() / 1000);
+      const { userProofPda } = await submitProof(
+        user,
+        Buffer.alloc(32, "private1"),
+        { reclaim: {} },
+        sourceData("reclaim", now),
+        new anchor.BN(200),
+        now
+      );
+      const setPrivate = (value: boolean) =>
+        program.methods
+          .setPrivate(value)
+          .accountsStrict({ userProof: userProofPda, user: user.publicKey })
+          .signers([user])
+          .rpc();
+      const verify = () =>
+        program.methods
+          .verifyProof()
+          .accountsStrict({
+            userProof: userProofPda,
+            userStatus: deriveUserStatusPda(user.publicKey),
+            registry: registryPda,
+            scoringConfig: scoringConfigPda,
+            decayConfig: decayConfigPda,
+            user: user.publicKey,
+          })
+          .view();
+
+      await setPrivate(true);
+      const hidden = await verify();
+      expect(hidden.isVerified).to.equal(true);
+      expect(hidden.aggregatedScore.toNumber()).to.equal(0);
+      expect(hidden.verifiedAt.toNumber()).to.equal(0);
+
+      await setPrivate(false);
+      const visible = await verify();
+      expect(visible.isVerified).to.equal(true);
+      expect(visible.aggregatedScore.toNumber()).to.be.greaterThan(0);
+    });
+
+    it("should require renewal after ttl expiry", async () => {
+      await program.methods
+        .updateRegistryConfig(
+          new anchor.BN(0),
+          10,
+          new anchor.BN(1),
+          new anchor.BN(0),
+          1
+        )
+        .accountsStrict({
+          registry: registryPda,
+          authority: payer,
+        })
+        .rpc();
+
+      const user = anchor.web3.Keypair.generate();
+      await airdrop(user.publicKey);
+      const now = Math.floor(Date.now() / 1000);
+
+      const { userProofPda } = await submitProof(
+        user,
+        Buffer.alloc(32, "proof4b"),
+        { reclaim: {} },
+        sourceData("reclaim", now),
+        new anchor.BN(250),
+        now
+      );
+
+      await new Promise((resolve) => setTimeout(resolve, 2200));
+
+      const result = await program.methods
+        .verifyProof()
+        .accountsStrict({
+          userProof: userProofPda,
+          userStatus: deriveUserStatusPda(user.publicKey),
+          registry: registryPda,
+          scoringConfig: scoringConfigPda,
+          decayConfig: decayConfigPda,
+          user: user.publicKey,
+        })
+        .view();
+
+      expect(result.isVerified).to.equal(false);
+
+      await program.methods
+        .updateRegistryConfig(
+          new anchor.BN(0),
+          10,
+          new anchor.BN(3600),
+          new anchor.BN(0),
+          1
+        )
+        .accountsStrict({
+          registry: registryPda,
+          authority: payer,
+        })
+        .rpc();
+    });
+
+    it("should report the remaining submission cooldown", async () => {
+      const setCooldown = (cooldown: number) =>
+        program.methods
+          .updateRegistryConfig(
+            new anchor.BN(cooldown),
+            10,
+            new anchor.BN(3600),
+            new anchor.BN(0),
+            1
+          )
+          .accountsStrict({
+            registry: registryPda,
+            authority: payer,
+          })
+          .rpc();
+
+      const user = anchor.web3.Keypair.generate();
+      await airdrop(user.publicKey);
+      const now = Math.floor(Date.now() / 1000);
+      const cooldownStatus = () =>
+        program.methods
+          .getCooldownStatus()
+          .accountsStrict({
+            userProof: deriveUserProofPda(user.publicKey),
+            registry: registryPda,
+            user: user.publicKey,
+          })
+          .view();
+
+      const fresh = await cooldownStatus();
+      expect(fresh.inCooldown).to.equal(false);
+      expect(fresh.secondsRemaining.toNumber()).to.equal(0);
+
+      await setCooldown(2);
+      try {
+        const { userProofPda } = await submitProof(
+          user,
+          Buffer.alloc(32, "cooldownStatus1"),
+          { reclaim: {} },
+          sourceData("reclaim", now),
+          new anchor.BN(100),
+          now
+        );
+        const userProof = await program.account.userProof.fetch(userProofPda);
+
+        const cooling = await cooldownStatus();
+        expect(cooling.inCooldown).to.equal(true);
+        expect(cooling.secondsRemaining.toNumber()).to.be.within(1, 2);
+        expect(cooling.nextEligibleAt.toNumber()).to.equal(
+          userProof.lastSubmission.toNumber() + 2
+        );
+
+        await new Promise((resolve) => setTimeout(resolve, 3200));
+
+        const elapsed = await cooldownStatus();
+        expect(elapsed.inCooldown).to.equal(false);
+        expect(elapsed.secondsRemaining.toNumber()).to.equal(0);
+      } finally {
+        await setCooldown(0);
+      }
+    });
+
+    it("should reject a cooldown that reaches the proof TTL", async () => {
+      const setCooldownAndTtl = (cooldown: number, ttl: number) =>
+        program.methods
+          .updateRegistryConfig(
+            new anchor.BN(cooldown),
+            10,
+            new anchor.BN(ttl),
+            new anchor.BN(0),
+            1
+          )
+          .accountsStrict({
+            registry: registryPda,
+            authority: payer,
+          })
+          .rpc();
+
+      for (const cooldown of [3600, 7200]) {
+        try {
+          await setCooldownAndTtl(cooldown, 3600);
+          expect.fail("should have thrown error");
+        } catch (error: any) {
+          expect(error.error.errorCode.code).to.equal("CooldownExceedsTtl");
+        }
+      }
+
+      await setCooldownAndTtl(3599, 3600);
+      const registry = await program.account.registry.fetch(registryPda);
+      expect(registry.cooldownPeriod.toNumber()).to.equal(3599);
+
+      await setCooldownAndTtl(0, 3600);
+    });
+
+    it("should reject a proof TTL past the maximum", async () => {
+      for (const ttl of [
+        new anchor.BN(10 * 365 * 86_400 + 1),
+        new anchor.BN("9223372036854775807"),
+      ]) {
+        try {
+          await program.methods
+            .updateRegistryConfig(new anchor.BN(0), 10, ttl, new anchor.BN(0), 1)
+            .accountsStrict({
+              registry: registryPda,
+              authority: payer,
+            })
+            .rpc();
+          expect.fail("should have thrown error");
+        } catch (error: any) {
+          expect(error.error.errorCode.code).to.equal("InvalidConfig");
+        }
+      }
+
+      const registry = await program.account.registry.fetch(registryPda);
+      expect(registry.proofTtlSeconds.toNumber()).to.equal(3600);
+    });
+
+    it("should keep verification through the expiry grace period", async () => {
+      const setTtlAndGrace = (ttl: number, grace: number) =>
+        program.methods
+          .updateRegistryConfig(
+            new anchor.BN(0),
+            10,
+            new anchor.BN(ttl),
+            new anchor.BN(grace),
+            1
+          )
+          .accountsStrict({
+            registry: registryPda,
+            authority: payer,
+          })
+          .rpc();
+
+      try {
+        await setTtlAndGrace(3600, -1);
+        expect.fail("should have thrown error");
+      } catch (error: any) {
+        expect(error.error.errorCode.code).to.equal("InvalidConfig");
+      }
+
+      await setTtlAndGrace(3, 4);
+
+      const user = anchor.web3.Keypair.generate();
+      await airdrop(user.publicKey);
+      const now = Math.floor(Date.now() / 1000);
+      const { userProofPda } = await submitProof(
+        user,
+        Buffer.alloc(32, "grace1"),
+        { reclaim: {} },
+        sourceData("reclaim", now),
+        new anchor.BN(250),
+        now
+      );
+      const verify = () =>
+        program.methods
+          .verifyProof()
+          .accountsStrict({
+            userProof: userProofPda,
+            userStatus: deriveUserStatusPda(user.publicKey),
+            registry: registryPda,
+            scoringConfig: scoringConfigPda,
+            decayConfig: decayConfigPda,
+            user: user.publicKey,
+          })
+          .view();
+
+      const beforeExpiry = await verify();
+      expect(beforeExpiry.isVerified).to.equal(true);
+      expect(beforeExpiry.inGrace).to.equal(false);
+
+      await new Promise((resolve) => setTimeout(resolve, 4500));
+      const inGrace = await verify();
+      expect(inGrace.isVerified).to.equal(true);
+      expect(inGrace.inGrace).to.equal(true);
+
+      await new Promise((resolve) => setTimeout(resolve, 4000));
+      const afterGrace = await verify();
+      expect(afterGrace.isVerified).to.equal(false);
+      expect(afterGrace.inGrace).to.equal(false);
+      expect(afterGrace.reason).to.deep.equal({ expired: {} });
+
+      await setTtlAndGrace(3600, 0);
+    });
+
+    it("should zero an expired proof and rebuild it on resubmit", async () => {
+      const setTtl = (ttl: number) =>
+        program.methods
+          .updateRegistryConfig(
+            new anchor.BN(0),
+            10,
+            new anchor.BN(ttl),
+            new anchor.BN(0),
+            1,
+            new anchor.BN(0),
+            []
+          )
+          .accountsStrict({
+            registry: registryPda,
+            authority: payer,
+          })
+          .rpc();
+      await setTtl(2);
+
+      const user = anchor.web3.Keypair.generate();
+      await airdrop(user.publicKey);
+      const now = Math.floor(Date.now() / 1000);
+      const reclaimPayload = sourceData("reclaim", now);
+      const gitcoinPayload = sourceData("gitcoin", now);
+      const { userProofPda, individualProofPda: reclaimProofPda } =
+        await submitProof(
+          user,
+          Buffer.alloc(32, "expire1"),
+          { reclaim: {} },
+          reclaimPayload,
+          new anchor.BN(250),
+          now
+        );
+      const {
+        individualProofPda: gitcoinProofPda,
+        identityNullifierRegistryPda: gitcoinNullifierPda,
+      } = await submitProof(
+        user,
+        Buffer.alloc(32, "expire2"),
+        { gitcoinPassport: {} },
+        gitcoinPayload,
+        new anchor.BN(100),
+        now
+      );
+
+      const expire = () =>
+        program.methods
+          .expireUserProof()
+          .accountsStrict({
+            registry: registryPda,
+            userProof: userProofPda,
+            user: user.publicKey,
+          })
+          .rpc();
+
+      try {
+        await expire();
+        expect.fail("should have thrown error");
+      } catch (error: any) {
+        expect(error.error.errorCode.code).to.equal("UserProofNotExpired");
+      }
+
+      await new Promise((resolve) => setTimeout(resolve, 3500));
+      await expire();
+      const expired = await program.account.userProof.fetch(userProofPda);
+      expect(expired.aggregatedScore.toNumber()).to.equal(0);
+      expect(expired.activeSourceCount).to.equal(0);
+      expect(expired.sourcesMask).to.equal(0);
+      expect(expired.expiryEpoch).to.equal(1);
+
+      await setTtl(3600);
+      const later = Math.floor(Date.now() / 1000);
+      await submitProof(
+        user,
+        Buffer.alloc(32, "expire3"),
+        { reclaim: {} },
+        reclaimPayload,
+        new anchor.BN(250),
+        later
+      );
+      const rebuilt = await program.account.userProof.fetch(userProofPda);
+      const reclaimProof = await program.account.individualProof.fetch(
+        reclaimProofPda
+      );
+      // Only the resubmitted source counts; the expired Gitcoin proof does not.
+      expect(rebuilt.activeSourceCount).to.equal(1);
+      expect(rebuilt.sourcesMask).to.equal(0b001);
+      expect(rebuilt.aggregatedScore.toNumber()).to.equal(
+        reclaimProof.contributedScore.toNumber()
+      );
+
+      // Revoking the expired proof leaves the rebuilt aggregate alone.
+      await program.methods
+        .revokeProof({ gitcoinPassport: {} })
+        .accountsStrict({
+          registry: registryPda,
+          userProof: userProofPda,
+          individualProof: gitcoinProofPda,
+          identityNullifierRegistry: gitcoinNullifierPda,
+          scoringConfig: scoringConfigPda,
+          decayConfig: decayConfigPda,
+          userStatus: deriveUserStatusPda(user.publicKey),
+          statusCache: null,
+          user: user.publicKey,
+        })
+        .signers([user])
+        .rpc();
+      const afterRevoke = await program.account.userProof.fetch(userProofPda);
+      expect(afterRevoke.activeSourceCount).to.equal(1);
+      expect(afterRevoke.aggregatedScore.toNumber()).to.equal(
+        rebuilt.aggregatedScore.toNumber()
+      );
+    });
+
+    it("should clamp the aggregate to the registry cap", async () => {
+      const setCap = (cap: anchor.BN) =>
+        program.methods
+          .setMaxAggregatedScore(cap)
+          .accountsStrict({
+            registry: registryPda,
+            authority: payer,
+          })
+          .rpc();
+
+      try {
+        await setCap(new anchor.BN(0));
+        expect.fail("should have thrown error");
+      } catch (error: any) {
+        expect(error.error.errorCode.code).to.equal("InvalidConfig");
+      }
+      await setCap(new anchor.BN(200));
+      for (const raiseMinScore of [
+        program.methods.updateMinScore(new anchor.BN(201)),
+        program.methods.initiateMinScoreChange(
+          new anchor.BN(201),
+          new anchor.BN(1)
+        ),
+      ]) {
+        try {
+          await raiseMinScore
+            .accountsStrict({
+              registry: registryPda,
+              authority: payer,
+            })
+            .rpc();
+          expect.fail("should have thrown error");
+        } catch (error: any) {
+          expect(error.error.errorCode.code).to.equal("InvalidConfig");
+        }
+      }
+
+      const user = anchor.web3.Keypair.generate();
+      await airdrop(user.publicKey);
+      const now = Math.floor(Date.now() / 1000);
+      const worldId = await submitProof(
+        user,
+        Buffer.alloc(32, "cap1"),
+        { worldId: {} },
+        sourceData("worldId", now),
+        new anchor.BN(180),
+        now
+      );
+      const reclaim = await submitProof(
+        user,
+        Buffer.alloc(32, "cap2"),
+        { reclaim: {} },
+        sourceData("reclaim", now),
+        new anchor.BN(150),
+        now
+      );
+
+      const capped = await program.account.userProof.fetch(
+        worldId.userProofPda
+      );
+      expect(capped.aggregatedScore.toNumber()).to.equal(200);
+      expect(capped.preCapScore.toNumber()).to.be.greaterThan(180 + 150);
+
+      // Revoking strips the bonus from the unclamped total, not from the cap.
+      await program.methods
+        .revokeProof({ reclaim: {} })
+        .accountsStrict({
+          registry: registryPda,
+          userProof: reclaim.userProofPda,
+          individualProof: reclaim.individualProofPda,
+          identityNullifierRegistry: reclaim.identityNullifierRegistryPda,
+          scoringConfig: scoringConfigPda,
+          decayConfig: decayConfigPda,
+          userStatus: deriveUserStatusPda(user.publicKey),
+          statusCache: null,
+          user: user.publicKey,
+        })
+        .signers([user])
+        .rpc();
+
+      const remaining = await program.account.individualProof.fetch(
+        worldId.individualProofPda
+      );
+      const afterRevoke = await program.account.userProof.fetch(
+        worldId.userProofPda
+      );
+      expect(afterRevoke.aggregatedScore.toNumber()).to.equal(
+        remaining.contributedScore.toNumber()
+      );
+      expect(afterRevoke.preCapScore.toNumber()).to.equal(0);
+
+      await setCap(new anchor.BN("18446744073709551615"));
+    });
+
+    it("should list only a user's active sources", async () => {
+      const user = anchor.web3.Keypair.generate();
+      await airdrop(user.publicKey);
+
+      const now = Math.floor(Date.now() / 1000);
+      await submitProof(
+        user,
+        Buffer.alloc(32, "active1"),
+        { worldId: {} },
+        sourceData("worldId", now),
+        new anchor.BN(180),
+        now
+      );
+      await submitProof(
+        user,
+        Buffer.alloc(32, "active2"),
+        { gitcoinPassport: {} },
+        sourceData("gitcoin", now),
+        new anchor.BN(120),
+        now
+      );
+      const reclaim = await submitProof(
+        user,
+        Buffer.alloc(32, "active3"),
+        { reclaim: {} },
+        sourceData("reclaim", now),
+        new anchor.BN(150),
+        now
+      );
+      await program.methods
+        .revokeProof({ reclaim: {} })
+        .accountsStrict({
+          registry: registryPda,
+          userProof: reclaim.userProofPda,
+          individualProof: reclaim.individualProofPda,
+          identityNullifierRegistry: reclaim.identityNullifierRegistryPda,
+          scoringConfig: scoringConfigPda,
+          decayConfig: decayConfigPda,
+          userStatus: deriveUserStatusPda(user.publicKey),
+          statusCache: null,
+          user: user.publicKey,
+        })
+        .signers([user])
+        .rpc();
+
+      const active = await program.methods
+        .getActiveSources()
+        .accountsStrict({
+          userProof: reclaim.userProofPda,
+          userStatus: deriveUserStatusPda(user.publicKey),
+          registry: registryPda,
+          scoringConfig: scoringConfigPda,
+          decayConfig: decayConfigPda,
+          user: user.publicKey,
+        })
+        .remainingAccounts(
+          Array.from({ length: PROOF_SOURCE_COUNT }, (_, index) => ({
+            pubkey: deriveIndividualProofPda(user.publicKey, index),
+            isSigner: false,
+            isWritable: false,
+          }))
+        )
+        .view();
+
+      expect(active.sourcesMask).to.equal(
+        (1 << sourceIndex.gitcoinPassport) | (1 << sourceIndex.worldId)
+      );
+      expect(active.sources.map((entry: any) => entry.source)).to.deep.equal([
+        { gitcoinPassport: {} },
+        { worldId: {} },
+      ]);
+      for (const entry of active.sources) {
+        expect(entry.liveScore.toNumber()).to.be.greaterThan(0);
+      }
+    });
+
+    it("should extend expiry during a pending verifier rotation", async () => {
+      await program.methods
+        .updateRegistryConfig(
+          new anchor.BN(0),
+          10,
+          new anchor.BN(1),
+          new anchor.BN(0),
+          1
+        )
+        .accountsStrict({
+          registry: registryPda,
+          authority: payer,
+        })
+        .rpc();
+      await program.methods
+        .updateRotationGrace(new anchor.BN(3600))
+        .accountsStrict({
+          registry: registryPda,
+          authority: payer,
+        })
+        .rpc();
+
+      const user = anchor.web3.Keypair.generate();
+      await airdrop(user.publicKey);
+      const now = Math.floor(Date.now() / 1000);
+
+      const { userProofPda } = await submitProof(
+        user,
+        Buffer.alloc(32, "grace1"),
+        { reclaim: {} },
+        sourceData("reclaim", now),
+        new anchor.BN(250),
+        now
+      );
+
+      await new Promise((resolve) => setTimeout(resolve, 2200));
+
+      const verify = () =>
+        program.methods
+          .verifyProof()
+          .accountsStrict({
+            userProof: userProofPda,
+            userStatus: deriveUserStatusPda(user.publicKey),
+            registry: registryPda,
+            scoringConfig: scoringConfigPda,
+            decayConfig: decayConfigPda,
+            user: user.publicKey,
+          })
+          .view();
+
+      expect((await verify()).isVerified).to.equal(false);
+
+      await program.methods
+        .initiateVerifierRotation(payer, new anchor.BN(1))
+        .accountsStrict({
+          registry: registryPda,
+          authority: payer,
+        })
+        .rpc();
+
+      expect((await verify()).isVerified).to.equal(true);
+
+      await new Promise((resolve) => setTimeout(resolve, 2500));
+
+      await program.methods
+        .finalizeVerifierRotation()
+        .accountsStrict({
+          registry: registryPda,
+          authority: payer,
+        })
+        .rpc();
+
+      expect((await verify()).isVerified).to.equal(false);
+
+      await program.methods
+        .updateRotationGrace(new anchor.BN(0))
+        .accountsStrict({
+          registry: registryPda,
+          authority: payer,
+        })
+        .rpc();
+      await program.methods
+        .updateRegistryConfig(
+          new anchor.BN(0),
+          10,
+          new anchor.BN(3600),
+          new anchor.BN(0),
+          1
+        )
+        .accountsStrict({
+          registry: registryPda,
+          authority: payer,
+        })
+        .rpc();
+    });
+
+    it("should require the minimum number of active sources", async () => {
+      const setMinSources = (minSources: number) =>
+        program.methods
+          .updateRegistryConfig(
+            new anchor.BN(0),
+            10,
+            new anchor.BN(3600),
+            new anchor.BN(0),
+            minSources
+          )
+          .accountsStrict({
+            registry: registryPda,
+            authority: payer,
+          })
+          .rpc();
+
+      try {
+        await setMinSources(0);
+        expect.fail("should have thrown error");
+      } catch (error: any) {
+        expect(error.error.errorCode.code).to.equal("InvalidConfig");
+      }
+      await setMinSources(2);
+
+      const user = anchor.web3.Keypair.generate();
+      await airdrop(user.publicKey);
+      const now = Math.floor(Date.now() / 1000);
+      const { userProofPda } = await submitProof(
+        user,
+        Buffer.alloc(32, "minsrc1"),
+        { reclaim: {} },
+        sourceData("reclaim", now),
+        new anchor.BN(1000),
+        now
+      );
+      const verify = () =>
+        program.methods
+          .verifyProof()
+          .accountsStrict({
+            userProof: userProofPda,
+            userStatus: deriveUserStatusPda(user.publicKey),
+            registry: registryPda,
+            scoringConfig: scoringConfigPda,
+            decayConfig: decayConfigPda,
+            user: user.publicKey,
+          })
+          .view();
+
+      // A single source far above min_score is not enough on its own.
+      const single = await verify();
+      expect(single.aggregatedScore.toNumber()).to.be.greaterThan(250);
+      expect(single.isVerified).to.equal(false);
+
+      await submitProof(
+        user,
+        Buffer.alloc(32, "minsrc2"),
+        { gitcoinPassport: {} },
+        sourceData("gitcoin", now),
+        new anchor.BN(100),
+        now
+      );
+      expect((await verify()).isVerified).to.equal(true);
+
+      await setMinSources(1);
+    });
+
+    it("should charge the submission fee to the user", async () => {
+      const fee = 0.01 * anchor.web3.LAMPORTS_PER_SOL;
+      const feeDestination = anchor.web3.Keypair.generate().publicKey;
+      const setFee = (lamports: number, destination: anchor.web3.PublicKey) =>
+        program.methods
+          .updateSubmissionFee(new anchor.BN(lamports), destination)
+          .accountsStrict({
+            registry: registryPda,
+            authority: payer,
+          })
+          .rpc();
+      const balance = (pubkey: anchor.web3.PublicKey) =>
+        provider.connection.getBalance(pubkey, "confirmed");
+
+      // Free while the fee is zero, with no destination account.
+      const user = anchor.web3.Keypair.generate();
+      await airdrop(user.publicKey);
+      const now = Math.floor(Date.now() / 1000);
+      await submitProof(
+        user,
+        Buffer.alloc(32, "fee1"),
+        { reclaim: {} },
+        sourceData("reclaim", now),
+        new anchor.BN(250),
+        now
+      );
+
+      try {
+        await setFee(fee, anchor.web3.PublicKey.default);
+        expect.fail("should have thrown error");
+      } catch (error: any) {
+        expect(error.error.errorCode.code).to.equal("InvalidConfig");
+      }
+      await setFee(fee, feeDestination);
+
+      const gitcoinPayload = sourceData("gitcoin", now);
+      try {
+        await submitProof(
+          user,
+          Buffer.alloc(32, "fee2"),
+          { gitcoinPassport: {} },
+          gitcoinPayload,
+          new anchor.BN(100),
+          now
+        );
+        expect.fail("should have thrown error");
+      } catch (error: any) {
+        expect(error.error.errorCode.code).to.equal("InvalidFeeDestination");
+      }
+
+      // A proof that fails verification is not charged.
+      try {
+        await submitProof(
+          user,
+          Buffer.alloc(32, "fee2"),
+          { gitcoinPassport: {} },
+          gitcoinPayload,
+          new anchor.BN(100),
+          now,
+          { feeDestination, verifiers: [anchor.web3.Keypair.generate()] }
+        );
+        expect.fail("should have thrown error");
+      } catch (error: any) {
+        expect(error.error.errorCode.code).to.equal(
+          "InvalidAttestationMessage"
+        );
+      }
+      expect(await balance(feeDestination)).to.equal(0);
+
+      await submitProof(
+        user,
+        Buffer.alloc(32, "fee2"),
+        { gitcoinPassport: {} },
+        gitcoinPayload,
+        new anchor.BN(100),
+        now,
+        { feeDestination }
+      );
+      expect(await balance(feeDestination)).to.equal(fee);
+
+      await setFee(1000 * anchor.web3.LAMPORTS_PER_SOL, feeDestination);
+      try {
+        await submitProof(
+          user,
+          Buffer.alloc(32, "fee3"),
+          { worldId: {} },
+          sourceData("worldId", now),
+          new anchor.BN(100),
+          now,
+          { feeDestination }
+        );
+        expect.fail("should have thrown error");
+      } catch (error: any) {
+        expect((error.logs ?? []).join("\n")).to.include(
+          "insufficient lamports"
+        );
+      }
+      expect(await balance(feeDestination)).to.equal(fee);
+
+      await setFee(0, anchor.web3.PublicKey.default);
+    });
+
+    it("should keep the status cache in step with verify_proof", async () => {
+      const user = anchor.web3.Keypair.generate();
+      await airdrop(user.publicKey);
+      const now = Math.floor(Date.now() / 1000);
+      const [statusCachePda] = anchor.web3.PublicKey.findProgramAddressSync(
+        [Buffer.from("status"), user.publicKey.toBuffer()],
+        program.programId
+      );
+      const { userProofPda } = await submitProof(
+        user,
+        Buffer.alloc(32, "statusCache1"),
+        { reclaim: {} },
+        sourceData("reclaim", now),
+        new anchor.BN(150),
+        now
+      );
+      const accounts = {
+        userProof: userProofPda,
+        userStatus: deriveUserStatusPda(user.publicKey),
+        registry: registryPda,
+        scoringConfig: scoringConfigPda,
+        decayConfig: decayConfigPda,
+        user: user.publicKey,
+      };
+      const expectCacheMatches = async () => {
+        const cache = await program.account.statusCache.fetch(statusCachePda);
+        const status = await program.methods
+          .verifyProof()
+          .accountsStrict(accounts)
+          .view();
+        const tier = await program.methods
+          .getTier()
+          .accountsStrict(accounts)
+          .view();
+        expect(cache.isVerified).to.equal(status.isVerified);
+        expect(cache.liveScore.toNumber()).to.equal(
+          status.liveScore.toNumber()
+        );
+        expect(cache.tier).to.deep.equal(tier);
+        return cache;
+      };
+
+      await program.methods
+        .enableStatusCache()
+        .accountsStrict({
+          statusCache: statusCachePda,
+          ...accounts,
+          systemProgram: anchor.web3.SystemProgram.programId,
+        })
+        .signers([user])
+        .rpc();
+      const enabled = await expectCacheMatches();
+      expect(enabled.isVerified).to.equal(true);
+
+      const worldId = await submitProof(
+        user,
+        Buffer.alloc(32, "statusCache2"),
+        { worldId: {} },
+        sourceData("worldId", now),
+        new anchor.BN(200),
+        now,
+        { statusCache: statusCachePda }
+      );
+      const submitted = await expectCacheMatches();
+      expect(submitted.liveScore.toNumber()).to.be.greaterThan(
+        enabled.liveScore.toNumber()
+      );
+
+      await program.methods
+        .revokeProof({ worldId: {} })
+        .accountsStrict({
+          registry: registryPda,
+          userProof: userProofPda,
+          individualProof: worldId.individualProofPda,
+          identityNullifierRegistry: worldId.identityNullifierRegistryPda,
+          scoringConfig: scoringConfigPda,
+          decayConfig: decayConfigPda,
+          userStatus: deriveUserStatusPda(user.publicKey),
+          statusCache: statusCachePda,
+          user: user.publicKey,
+        })
+        .signers([user])
+        .rpc();
+      const revoked = await expectCacheMatches();
+      expect(revoked.liveScore.toNumber()).to.be.lessThan(
+        submitted.liveScore.toNumber()
+      );
+
+      // Anyone can refresh; the provider wallet signs only as fee payer.
+      await program.methods
+        .refreshScore()
+        .accountsStrict({ statusCache: statusCachePda, ...accounts })
+        .rpc();
+      const refreshed = await expectCacheMatches();
+      expect(refreshed.computedAt.toNumber()).to.be.at.least(
+        revoked.computedAt.toNumber()
+      );
+    });
+
+    it("should verify one user against caller thresholds", async () => {
+      const user = anchor.web3.Keypair.generate();
+      await airdrop(user.publicKey);
+      const now = Math.floor(Date.now() / 1000);
+      const { userProofPda } = await submitProof(
+        user,
+        Buffer.alloc(32, "threshold1"),
+        { reclaim: {} },
+        sourceData("reclaim", now),
+        new anchor.BN(250),
+        now
+      );
+      const accounts = {
+        userProof: userProofPda,
+        userStatus: deriveUserStatusPda(user.publicKey),
+        registry: registryPda,
+        scoringConfig: scoringConfigPda,
+        decayConfig: decayConfigPda,
+        user: user.publicKey,
+      };
+      const withThreshold = (minScore: number) =>
+        program.methods
+          .verifyProofWithThreshold(new anchor.BN(minScore))
+          .accountsStrict(accounts)
+          .view();
+
+      const status = await program.methods
+        .verifyProof()
+        .accountsStrict(accounts)
+        .view();
+      const score = status.aggregatedScore.toNumber();
+      expect(status.isVerified).to.equal(true);
+      expect(status.reason).to.deep.equal({ ok: {} });
+
+      // A low-bar faucet and a high-bar governance gate on the same registry.
+      expect((await withThreshold(score)).isVerified).to.equal(true);
+      const gated = await withThreshold(score + 1);
+      expect(gated.isVerified).to.equal(false);
+      expect(gated.reason).to.deep.equal({ belowThreshold: {} });
+      const faucet = await withThreshold(1);
+      expect(faucet.isVerified).to.equal(true);
+      expect(faucet.aggregatedScore.toNumber()).to.equal(score);
+    });
+
+    it("should drop a source past its own expiry", async () => {
+      const user = anchor.web3.Keypair.generate();
+      await airdrop(user.publicKey);
+      const now = Math.floor(Date.now() / 1000);
+      const registry = await program.account.registry.fetch(registryPda);
+
+      try {
+        await submitProof(
+          user,
+          Buffer.alloc(32, "expiresAt0"),
+          { reclaim: {} },
+          sourceData("reclaim", now),
+          new anchor.BN(100),
+          now,
+          { expiresAt: now + registry.proofTtlSeconds.toNumber() + 1 }
+        );
+        expect.fail("should have thrown error");
+      } catch (error: any) {
+        expect(error.error.errorCode.code).to.equal("InvalidProofExpiry");
+      }
+
+      const shortLived = await submitProof(
+        user,
+        Buffer.alloc(32, "expiresAt1"),
+        { reclaim: {} },
+        sourceData("reclaim", now),
+        new anchor.BN(100),
+        now,
+        { expiresAt: now + 4 }
+      );
+      const lasting = await submitProof(
+        user,
+        Buffer.alloc(32, "expiresAt2"),
+        { worldId: {} },
+        sourceData("worldId", now),
+        new anchor.BN(300),
+        now
+      );
+      const stored = await program.account.individualProof.fetch(
+        shortLived.individualProofPda
+      );
+      expect(stored.expiresAt.toNumber()).to.equal(now + 4);
+      const verify = (minScore: number) =>
+        program.methods
+          .verifyProofWithThreshold(new anchor.BN(minScore))
+          .accountsStrict({
+            userProof: lasting.userProofPda,
+            userStatus: deriveUserStatusPda(user.publicKey),
+            registry: registryPda,
+            scoringConfig: scoringConfigPda,
+            decayConfig: decayConfigPda,
+            user: user.publicKey,
+          })
+          .remainingAccounts(
+            [shortLived, lasting].map(({ individualProofPda }) => ({
+              pubkey: individualProofPda,
+              isSigner: false,
+              isWritable: false,
+            }))
+          )
+          .view();
+
+      const verifyWithoutProofs = () =>
+        program.methods
+          .verifyProofWithThreshold(new anchor.BN(1))
+          .accountsStrict({
+            userProof: lasting.userProofPda,
+            userStatus: deriveUserStatusPda(user.publicKey),
+            registry: registryPda,
+            scoringConfig: scoringConfigPda,
+            decayConfig: decayConfigPda,
+            user: user.publicKey,
+          })
+          .view();
+
+      const before = await verify(1);
+      const minScore = before.liveScore.toNumber();
+      expect((await verify(minScore)).isVerified).to.equal(true);
+      expect((await verifyWithoutProofs()).isVerified).to.equal(true);
+
+      await new Promise((resolve) => setTimeout(resolve, 6000));
+
+      // Only the World ID source still counts once reclaim's own expiry passes.
+      const after = await verify(minScore);
+      expect(after.isVerified).to.equal(false);
+      expect(after.liveScore.toNumber()).to.be.lessThan(minScore);
+      expect((await verify(1)).isVerified).to.equal(true);
+
+      // Without the proof accounts the lapsed source can't be dropped, so the
+      // stored aggregate is no longer trusted.
+      const unchecked = await verifyWithoutProofs();
+      expect(unchecked.isVerified).to.equal(false);
+      expect(unchecked.reason).to.deep.equal({ expired: {} });
+    });
+
+    it("should scale verification by the global multiplier", async () => {
+      const setMultiplier = (bps: number) =>
+        program.methods
+          .setGlobalScoreMultiplier(bps)
+          .accountsStrict({
+            registry: registryPda,
+            authority: payer,
+          })
+          .rpc();
+
+      const user = anchor.web3.Keypair.generate();
+      await airdrop(user.publicKey);
+      const { minScore } = await program.account.registry.fetch(registryPda);
+      const now = Math.floor(Date.now() / 1000);
+      const { userProofPda } = await submitProof(
+        user,
+        Buffer.alloc(32, "multiplier1"),
+        { worldId: {} },
+        sourceData("worldId", now),
+        new anchor.BN(Math.floor(minScore.toNumber() * 0.9)),
+        now
+      );
+      const { aggregatedScore } = await program.account.userProof.fetch(
+        userProofPda
+      );
+      const verify = () =>
+        program.methods
+          .verifyProof()
+          .accountsStrict({
+            userProof: userProofPda,
+            userStatus: deriveUserStatusPda(user.publicKey),
+            registry: registryPda,
+            scoringConfig: scoringConfigPda,
+            decayConfig: decayConfigPda,
+            user: user.publicKey,
+          })
+          .view();
+
+      expect((await verify()).isVerified).to.equal(false);
+
+      await setMultiplier(12000);
+      const scaled = await verify();
+      expect(scaled.isVerified).to.equal(true);
+      expect(scaled.aggregatedScore.toNumber()).to.equal(
+        Math.floor((aggregatedScore.toNumber() * 12000) / 10000)
+      );
+
+      await setMultiplier(10000);
+      expect((await verify()).isVerified).to.equal(false);
+    });
+
+    it("should verify a mixed batch of users in one call", async () => {
+      const now = Math.floor(Date.now() / 1000);
+      const submitted = async (seed: string, score: number) => {
+        const user = anchor.web3.Keypair.generate();
+        await airdrop(user.publicKey);
+        await submitProof(
+          user,
+          Buffer.alloc(32, seed),
+          { reclaim: {} },
+          sourceData("reclaim", now),
+          new anchor.BN(score),
+          now
+        );
+        return user.publicKey;
+      };
+
+      const passing = await submitted("batchVerify1", 300);
+      const failing = await submitted("batchVerify2", 50);
+      const banned = await submitted("batchVerify3", 300);
+      await program.methods
+        .banUser()
+        .accountsStrict({
+          registry: registryPda,
+          userStatus: deriveUserStatusPda(banned),
+          user: banned,
+          authority: payer,
+          systemProgram: anchor.web3.SystemProgram.programId,
+        })
+        .rpc();
+      const absent = anchor.web3.Keypair.generate().publicKey;
+
+      const wallets = [passing, failing, absent, banned, passing];
+      const mask = await program.methods
+        .verifyProofsBatch()
+        .accountsStrict({ registry: registryPda })
+        .remainingAccounts(
+          wallets.flatMap((wallet) => [
+            {
+              pubkey: deriveUserProofPda(wallet),
+              isSigner: false,
+              isWritable: false,
+            },
+            {
+              pubkey: deriveUserStatusPda(wallet),
+              isSigner: false,
+              isWritable: false,
+            },
+          ])
+        )
+        .view();
+      expect(mask).to.equal(0b10001);
+    });
+  });
+
+  describe("Proof Revocation", () => {
+    it("should not let a relayer revoke a proof it submitted", async () => {
+      const user = anchor.web3.Keypair.generate();
+      const relayer = anchor.web3.Keypair.generate();
+      await airdrop(relayer.publicKey);
+
+      const now = Math.floor(Date.now() / 1000);
+      const { userProofPda, individualProofPda, identityNullifierRegistryPda } =
+        await submitProof(
+          user,
+          Buffer.alloc(32, "relayed2"),
+          { worldId: {} },
+          sourceData("worldId", now),
+          new anchor.BN(150),
+          now,
+          { relayer }
+        );
+
+      try {
+        await program.methods
+          .revokeProof({ worldId: {} })
+          .accountsStrict({
+            registry: registryPda,
+            userProof: userProofPda,
+            individualProof: individualProofPda,
+            identityNullifierRegistry: identityNullifierRegistryPda,
+            scoringConfig: scoringConfigPda,
+            decayConfig: decayConfigPda,
+            userStatus: deriveUserStatusPda(relayer.publicKey),
+            statusCache: null,
+            user: relayer.publicKey,
+          })
+          .signers([relayer])
+          .rpc();
+        expect.fail("should have thrown error");
+      } catch (error: any) {
+        expect(error.error.errorCode.code).to.equal("ConstraintSeeds");
+      }
+
+      const individualProof = await program.account.individualProof.fetch(
+        individualProofPda
+      );
+      expect(individualProof.isRevoked).to.equal(false);
+    });
+
+    it("should emit the resulting aggregate on submit and revoke", async () => {
+      const user = anchor.web3.Keypair.generate();
+      await airdrop(user.publicKey);
+      const now = Math.floor(Date.now() / 1000);
+
+      await submitProof(
+        user,
+        Buffer.alloc(32, "eventAgg1"),
+        { reclaim: {} },
+        sourceData("reclaim", now),
+        new anchor.BN(100),
+        now
+      );
+      const {
+        userProofPda,
+        individualProofPda,
+        identityNullifierRegistryPda,
+        signature,
+      } = await submitProof(
+        user,
+        Buffer.alloc(32, "eventAgg2"),
+        { worldId: {} },
+        sourceData("worldId", now),
+        new anchor.BN(100),
+        now
+      );
+
+      const submitted = (await emittedEvents(signature)).find(
+        (event) => event.name === "proofSubmitted"
+      );
+      let userProof = await program.account.userProof.fetch(userProofPda);
+      expect(submitted.data.aggregatedScore.toNumber()).to.equal(
+        userProof.aggregatedScore.toNumber()
+      );
+      expect(submitted.data.activeSourceCount).to.equal(2);
+
+      const revokeSignature = await program.methods
+        .revokeProof({ worldId: {} })
+        .accountsStrict({
+          registry: registryPda,
+          userProof: userProofPda,
+          individualProof: individualProofPda,
+          identityNullifierRegistry: identityNullifierRegistryPda,
+          scoringConfig: scoringConfigPda,
+          decayConfig: decayConfigPda,
+          userStatus: deriveUserStatusPda(user.publicKey),
+          statusCache: null,
+          user: user.publicKey,
+        })
+        .signers([user])
+        .rpc();
+
+      const revoked = (await emittedEvents(revokeSignature)).find(
+        (event) => event.name === "proofRevoked"
+      );
+      userProof = await program.account.userProof.fetch(userProofPda);
+      expect(revoked.data.aggregatedScore.toNumber()).to.equal(
+        userProof.aggregatedScore.toNumber()
+      );
+      expect(revoked.data.activeSourceCount).to.equal(1);
+    });
+
+    it("should emit one AttestationNonceUsed per submission", async () => {
+      const user = anchor.web3.Keypair.generate();
+      await airdrop(user.publicKey);
+      const now = Math.floor(Date.now() / 1000);
+      const { nonce, signature } = await submitProof(
+        user,
+        Buffer.alloc(32, "nonceEvent1"),
+        { reclaim: {} },
+        sourceData("reclaim", now),
+        new anchor.BN(100),
+        now
+      );
+
+      const used = (await emittedEvents(signature)).filter(
+        (event) => event.name === "attestationNonceUsed"
+      );
+      const { verifierAuthority } = await program.account.registry.fetch(
+        registryPda
+      );
+      expect(used).to.have.length(1);
+      expect(used[0].data.nonce.toNumber()).to.equal(nonce);
+      expect(used[0].data.user.toString()).to.equal(user.publicKey.toString());
+      expect(used[0].data.verifier.toString()).to.equal(
+        verifierAuthority.toString()
+      );
+    });
+
+    it("should lower a source's score on a verifier downgrade", async () => {
+      const user = anchor.web3.Keypair.generate();
+      await airdrop(user.publicKey);
+      const now = Math.floor(Date.now() / 1000);
+      const source = { worldId: {} };
+      const payload = sourceData("worldId", now);
+      const identityNullifier = identityNullifierFromPayload(source, payload);
+      const { userProofPda, individualProofPda, identityNullifierRegistryPda } =
+        await submitProof(
+          user,
+          Buffer.alloc(32, "downgrade1"),
+          source,
+          payload,
+          new anchor.BN(200),
+          now
+        );
+      const downgrade = (baseScore: number) => {
+        const nonce = attestationNonce++;
+        const scoreBuf = Buffer.alloc(8);
+        scoreBuf.writeBigUInt64LE(BigInt(baseScore));
+        const nonceBuf = Buffer.alloc(8);
+        nonceBuf.writeBigUInt64LE(BigInt(nonce));
+        const tsBuf = Buffer.alloc(8);
+        tsBuf.writeBigInt64LE(BigInt(now));
+        const message = Buffer.concat([
+          Buffer.from("sidd"),
+          program.programId.toBuffer(),
+          registryPda.toBuffer(),
+          user.publicKey.toBuffer(),
+          Buffer.from([sourceIndex.worldId]),
+          Buffer.from(identityNullifier),
+          scoreBuf,
+          nonceBuf,
+          tsBuf,
+        ]);
+        return program.methods
+          .downgradeProof(
+            source,
+            new anchor.BN(baseScore),
+            new anchor.BN(nonce),
+            new anchor.BN(now)
+          )
+          .preInstructions([
+            anchor.web3.Ed25519Program.createInstructionWithPrivateKey({
+              privateKey: verifier.secretKey,
+              message,
+            }),
+          ])
+          .accountsStrict({
+            registry: registryPda,
+            userProof: userProofPda,
+            individualProof: individualProofPda,
+            attestationNonceRegistry: deriveAttestationNoncePda(nonce),
+            scoringConfig: scoringConfigPda,
+            user: user.publicKey,
+            instructionsSysvar: anchor.web3.SYSVAR_INSTRUCTIONS_PUBKEY,
+            payer,
+            systemProgram: anchor.web3.SystemProgram.programId,
+          })
+          .rpc();
+      };
+
+      const before = await program.account.userProof.fetch(userProofPda);
+      const proofBefore = await program.account.individualProof.fetch(
+        individualProofPda
+      );
+      await downgrade(120);
+      const after = await program.account.userProof.fetch(userProofPda);
+      const proofAfter = await program.account.individualProof.fetch(
+        individualProofPda
+      );
+
+      expect(proofAfter.baseScore.toNumber()).to.equal(120);
+      expect(proofAfter.contributedScore.toNumber()).to.be.lessThan(
+        proofBefore.contributedScore.toNumber()
+      );
+      expect(
+        before.aggregatedScore.toNumber() - after.aggregatedScore.toNumber()
+      ).to.equal(
+        proofBefore.contributedScore.toNumber() -
+          proofAfter.contributedScore.toNumber()
+      );
+      expect(after.activeSourceCount).to.equal(before.activeSourceCount);
+      const nullifier = await program.account.identityNullifierRegistry.fetch(
+        identityNullifierRegistryPda
+      );
+      expect(nullifier.isBurned).to.equal(false);
+
+      try {
+        await downgrade(150);
+        expect.fail("should have thrown error");
+      } catch (error: any) {
+        expect(error.error.errorCode.code).to.equal("ScoreIncreaseNotAllowed");
+      }
+    });
+
+    it("should let only the verifier force a revocation", async () => {
+      const user = anchor.web3.Keypair.generate();
+      await airdrop(user.publicKey);
+      const now = Math.floor(Date.now() / 1000);
+      const source = { worldId: {} };
+      const payload = sourceData("worldId", now);
+      const identityNullifier = identityNullifierFromPayload(source, payload);
+
+      const { userProofPda, individualProofPda, identityNullifierRegistryPda } =
+        await submitProof(
+          user,
+          Buffer.alloc(32, "verifierRevoke1"),
+          source,
+          payload,
+          new anchor.BN(150),
+          now
+        );
+
+      const nonce = attestationNonce++;
+      const nonceBuf = Buffer.alloc(8);
+      nonceBuf.writeBigUInt64LE(BigInt(nonce));
+      const tsBuf = Buffer.alloc(8);
+      tsBuf.writeBigInt64LE(BigInt(now));
+      const message = Buffer.concat([
+        Buffer.from("sidr"),
+        program.programId.toBuffer(),
+        registryPda.toBuffer(),
+        user.publicKey.toBuffer(),
+        Buffer.from([sourceIndex.worldId]),
+        Buffer.from(identityNullifier),
+        nonceBuf,
+        tsBuf,
+      ]);
+      const revokeSignedBy = (signer: anchor.web3.Keypair) =>
+        program.methods
+          .revokeProofByVerifier(
+            source,
+            new anchor.BN(nonce),
+            new anchor.BN(now)
+          )
+          .preInstructions([
+            anchor.web3.Ed25519Program.createInstructionWithPrivateKey({
+              privateKey: signer.secretKey,
+              message,
+            }),
+          ])
+          .accountsStrict({
+            registry: registryPda,
+            userProof: userProofPda,
+            individualProof: individualProofPda,
+            identityNullifierRegistry: identityNullifierRegistryPda,
+            attestationNonceRegistry: deriveAttestationNoncePda(nonce),
+            scoringConfig: scoringConfigPda,
+            user: user.publicKey,
+            instructionsSysvar: anchor.web3.SYSVAR_INSTRUCTIONS_PUBKEY,
+            payer,
+            systemProgram: anchor.web3.SystemProgram.programId,
+          })
+          .rpc();
+
+      try {
+        await revokeSignedBy(anchor.web3.Keypair.generate());
+        expect.fail("should have thrown error");
+      } catch (error: any) {
+        expect(error.error.errorCode.code).to.equal(
+          "InvalidAttestationMessage"
+        );
+      }
+
+      await revokeSignedBy(verifier);
+
+      const individualProof = await program.account.individualProof.fetch(
+        individualProofPda
+      );
+      expect(individualProof.isRevoked).to.equal(true);
+      const nullifier = await program.account.identityNullifierRegistry.fetch(
+        identityNullifierRegistryPda
+      );
+      expect(nullifier.isBurned).to.equal(true);
+      const userProof = await program.account.userProof.fetch(userProofPda);
+      expect(userProof.activeSourceCount).to.equal(0);
+    });
+
+    it("should revoke proof successfully", async () => {
+      const user = anchor.web3.Keypair.generate();
+      await airdrop(user.publicKey);
+
+      const now = Math.floor(Date.now() / 1000);
+      const source = { gitcoinPassport: {} };
+      const payload = sourceData("gitcoin", now, 200);
+
+      const { userProofPda, individualProofPda, identityNullifierRegistryPda } =
+        await submitProof(
+          user,
+          Buffer.alloc(32, "proof5"),
+          source,
+          payload,
+          new anchor.BN(200),
+          now
+        );
+
+      const before = await program.account.userProof.fetch(userProofPda);
+
+      await program.methods
+        .revokeProof(source)
+        .accountsStrict({
+          registry: registryPda,
+          userProof: userProofPda,
+          individualProof: individualProofPda,
+          identityNullifierRegistry: identityNullifierRegistryPda,
+          scoringConfig: scoringConfigPda,
+          decayConfig: decayConfigPda,
+          userStatus: deriveUserStatusPda(user.publicKey),
+          statusCache: null,
+          user: user.publicKey,
+        })
+        .signers([user])
+        .rpc();
+
+      const after = await program.account.userProof.fetch(userProofPda);
+      expect(after.aggregatedScore.toNumber()).to.be.lessThan(
+        before.aggregatedScore.toNumber()
+      );
+      expect(after.activeSourceCount).to.equal(0);
+    });
+
+    it("should count lifetime submissions and revocations", async () => {
+      const user = anchor.web3.Keypair.generate();
+      await airdrop(user.publicKey);
+      const now = Math.floor(Date.now() / 1000);
+      const gitcoinPayload = sourceData("gitcoin", now);
+
+      const submit = async (label: string, source: any, payload: any) => {
+        const submitted = await submitProof(
+          user,
+          Buffer.from(`lifetime-${label}`.padEnd(32, "x")),
+          source,
+          payload,
+          new anchor.BN(150),
+          now
+        );
+        const [event] = (await emittedEvents(submitted.signature)).filter(
+          (event) => event.name === "proofSubmitted"
+        );
+        return { submitted, event };
+      };
+      const revoke = async (
+        source: any,
+        submitted: Awaited<ReturnType<typeof submitProof>>
+      ) => {
+        const signature = await program.methods
+          .revokeProof(source)
+          .accountsStrict({
+            registry: registryPda,
+            userProof: submitted.userProofPda,
+            individualProof: submitted.individualProofPda,
+            identityNullifierRegistry: submitted.identityNullifierRegistryPda,
+            scoringConfig: scoringConfigPda,
+            decayConfig: decayConfigPda,
+            userStatus: deriveUserStatusPda(user.publicKey),
+            statusCache: null,
+            user: user.publicKey,
+          })
+          .signers([user])
+          .rpc();
+        const [event] = (await emittedEvents(signature)).filter(
+          (event) => event.name === "proofRevoked"
+        );
+        return event;
+      };
+      const counters = async () => {
+        const userProof = await program.account.userProof.fetch(
+          deriveUserProofPda(user.publicKey)
+        );
+        return [
+          userProof.totalSubmissions.toNumber(),
+          userProof.totalRevocations,
+        ];
+      };
+
+      const gitcoin = { gitcoinPassport: {} };
+      const first = await submit("first", gitcoin, gitcoinPayload);
+      expect(first.event.data.totalSubmissions.toNumber()).to.equal(1);
+      // Refreshing the active source counts as another submission.
+      const refreshed = await submit("refresh", gitcoin, gitcoinPayload);
+      expect(refreshed.event.data.totalSubmissions.toNumber()).to.equal(2);
+      expect(await counters()).to.deep.equal([2, 0]);
+
+      const revoked = await revoke(gitcoin, refreshed.submitted);
+      expect(revoked.data.totalRevocations).to.equal(1);
+      expect(await counters()).to.deep.equal([2, 1]);
+
+      // The same source again, under a fresh identity, after its revocation.
+      await submit("again", gitcoin, sourceData("gitcoin", now));
+      const worldId = await submit(
+        "worldid",
+        { worldId: {} },
+        sourceData("worldId", now)
+      );
+      expect(worldId.event.data.totalSubmissions.toNumber()).to.equal(4);
+      await revoke({ worldId: {} }, worldId.submitted);
+      expect(await counters()).to.deep.equal([4, 2]);
+    });
+
+    it("should revoke all of a user's sources atomically", async () => {
+      const user = anchor.web3.Keypair.generate();
+      const other = anchor.web3.Keypair.generate();
+      await airdrop(user.publicKey);
+      await airdrop(other.publicKey);
+      const now = Math.floor(Date.now() / 1000);
+
+      const submitted: Awaited<ReturnType<typeof submitProof>>[] = [];
+      for (const [source, kind] of [
+        [{ gitcoinPassport: {} }, "gitcoin"],
+        [{ worldId: {} }, "worldId"],
+        [{ reclaim: {} }, "reclaim"],
+      ] as const) {
+        submitted.push(
+          await submitProof(
+            user,
+            Buffer.from(`revokeall-${kind}`.padEnd(32, "x")),
+            source,
+            sourceData(kind, now),
+            new anchor.BN(150),
+            now
+          )
+        );
+      }
+      const otherProof = await submitProof(
+        other,
+        Buffer.from("revokeall-other".padEnd(32, "x")),
+        { gitcoinPassport: {} },
+        sourceData("gitcoin", now),
+        new anchor.BN(150),
+        now
+      );
+      const { userProofPda } = submitted[0];
+      const revokeAll = (proofs: typeof submitted) =>
+        program.methods
+          .revokeAll()
+          .accountsStrict({
+            registry: registryPda,
+            userProof: userProofPda,
+            scoringConfig: scoringConfigPda,
+            decayConfig: decayConfigPda,
+            userStatus: deriveUserStatusPda(user.publicKey),
+            statusCache: null,
+            user: user.publicKey,
+          })
+          .remainingAccounts(
+            proofs.flatMap((proof) => [
+              {
+                pubkey: proof.individualProofPda,
+                isSigner: false,
+                isWritable: true,
+              },
+              {
+                pubkey: proof.identityNullifierRegistryPda,
+                isSigner: false,
+                isWritable: true,
+              },
+            ])
+          )
+          .signers([user])
+          .rpc();
+
+      for (const proofs of [
+        submitted.slice(0, 2),
+        [...submitted.slice(0, 2), otherProof],
+      ]) {
+        try {
+          await revokeAll(proofs);
+          expect.fail("should have thrown error");
+        } catch (error: any) {
+          expect(error.error.errorCode.code).to.equal("InvalidProofAccounts");
+        }
+      }
+      const untouched = await program.account.userProof.fetch(userProofPda);
+      expect(untouched.activeSourceCount).to.equal(3);
+
+      const signature = await revokeAll(submitted);
+      const after = await program.account.userProof.fetch(userProofPda);
+      expect(after.aggregatedScore.toNumber()).to.equal(0);
+      expect(after.activeSourceCount).to.equal(0);
+      expect(after.sourcesMask).to.equal(0);
+      for (const proof of submitted) {
+        const individual = await program.account.individualProof.fetch(
+          proof.individualProofPda
+        );
+        expect(individual.isRevoked).to.equal(true);
+        const nullifier =
+          await program.account.identityNullifierRegistry.fetch(
+            proof.identityNullifierRegistryPda
+          );
+        expect(nullifier.isBurned).to.equal(true);
+      }
+      const events = await emittedEvents(signature);
+      const revoked = events.filter(
+        (event) => event.name === "allProofsRevoked"
+      );
+      expect(revoked).to.have.length(1);
+      expect(revoked[0].data.revokedCount).to.equal(3);
+    });
+
+    it("should fold the same sources differently per scoring mode", async () => {
+      const setScoringMode = (scoringMode: any) =>
+        program.methods
+          .updateScoringMode(scoringMode)
+          .accountsStrict({
+            scoringConfig: scoringConfigPda,
+            authority: payer,
+          })
+          .rpc();
+
+      const aggregates: Record<string, number> = {};
+      try {
+        for (const [mode, scoringMode] of [
+          ["additive", { additive: {} }],
+          ["max", { max: {} }],
+          ["weightedAverage", { weightedAverage: {} }],
+        ] as const) {
+          await setScoringMode(scoringMode);
+          const user = anchor.web3.Keypair.generate();
+          await airdrop(user.publicKey);
+          const now = Math.floor(Date.now() / 1000);
+
+          await submitProof(
+            user,
+            Buffer.from(`mode-${mode}-gitcoin`.padEnd(32, "x")),
+            { gitcoinPassport: {} },
+            sourceData("gitcoin", now, 200),
+            new anchor.BN(200),
+            now
+          );
+          const worldId = await submitProof(
+            user,
+            Buffer.from(`mode-${mode}-worldid`.padEnd(32, "x")),
+            { worldId: {} },
+            sourceData("worldId", now, 100),
+            new anchor.BN(100),
+            now
+          );
+          const folded = await program.account.userProof.fetch(
+            worldId.userProofPda
+          );
+          expect(folded.scoringMode).to.deep.equal(scoringMode);
+          aggregates[mode] = folded.aggregatedScore.toNumber();
+
+          await program.methods
+            .revokeProof({ worldId: {} })
+            .accountsStrict({
+              registry: registryPda,
+              userProof: worldId.userProofPda,
+              individualProof: worldId.individualProofPda,
+              identityNullifierRegistry: worldId.identityNullifierRegistryPda,
+              scoringConfig: scoringConfigPda,
+              decayConfig: decayConfigPda,
+              userStatus: deriveUserStatusPda(user.publicKey),
+              statusCache: null,
+              user: user.publicKey,
+            })
+            .signers([user])
+            .rpc();
+
+          // Only Gitcoin is left, which every mode folds to its own score.
+          const revoked = await program.account.userProof.fetch(
+            worldId.userProofPda
+          );
+          expect(revoked.aggregatedScore.toNumber()).to.equal(200);
+          expect(revoked.sourceScores[2].toNumber()).to.equal(0);
+        }
+      } finally {
+        await setScoringMode({ additive: {} });
+      }
+
+      // Max skips the breadth bonuses; the average gets the same bonus as the sum.
+      expect(aggregates.max).to.equal(200);
+      expect(aggregates.additive).to.be.greaterThan(300);
+      expect(aggregates.weightedAverage).to.be.closeTo(
+        aggregates.additive / 2,
+        1
+      );
+    });
+
+    it("should reject revoke from mismatched signer", async () => {
+      const owner = anchor.web3.Keypair.generate();
+      const attacker = anchor.web3.Keypair.generate();
+      await airdrop(owner.publicKey);
+
+      const now = Math.floor(Date.now() / 1000);
+
+      const { userProofPda, individualProofPda, identityNullifierRegistryPda } =
+        await submitProof(
+          owner,
+          Buffer.alloc(32, "proof6"),
+          { worldId: {} },
+          sourceData("worldId", now),
+          new anchor.BN(150),
+          now
+        );
+
+      try {
+        await program.methods
+          .revokeProof({ worldId: {} })
+          .accountsStrict({
+            registry: registryPda,
+            userProof: userProofPda,
+            individualProof: individualProofPda,
+            identityNullifierRegistry: identityNullifierRegistryPda,
+            scoringConfig: scoringConfigPda,
+            decayConfig: decayConfigPda,
+            userStatus: deriveUserStatusPda(attacker.publicKey),
+            statusCache: null,
+            user: attacker.publicKey,
+          })
+          .signers([attacker])
+          .rpc();
+        expect.fail("should have thrown error");
+      } catch (error: any) {
+        expect(error.error.errorCode.code).to.equal("ConstraintSeeds");
+      }
+    });
+
+    it("should permanently burn identity after revoke", async () => {
+      const user = anchor.web3.Keypair.generate();
+      await airdrop(user.publicKey);
+
+      const now = Math.floor(Date.now() / 1000);
+      const source = { worldId: {} };
+      const payload = sourceData("worldId", now);
+
+      const { individualProofPda, userProofPda, identityNullifierRegistryPda } =
+        await submitProof(
+          user,
+          Buffer.alloc(32, "burn1"),
+          source,
+          payload,
+          new anchor.BN(180),
+          now
+        );
+
+      await program.methods
+        .revokeProof(source)
+        .accountsStrict({
+          registry: registryPda,
+          userProof: userProofPda,
+          individualProof: individualProofPda,
+          identityNullifierRegistry: identityNullifierRegistryPda,
+          scoringConfig: scoringConfigPda,
+          decayConfig: decayConfigPda,
+          userStatus: deriveUserStatusPda(user.publicKey),
+          statusCache: null,
+          user: user.publicKey,
+        })
+        .signers([user])
+        .rpc();
+
+      try {
+        await submitProof(
+          user,
+          Buffer.alloc(32, "burn2"),
+          source,
+          payload,
+          new anchor.BN(180),
+          now
+        );
+        expect.fail("should have thrown error");
+      } catch (error: any) {
+        expect(error.error.errorCode.code).to.equal("IdentityRevokedPermanent");
+      }
+    });
+
+    it("should enforce the reclaim cooldown after a soft revoke", async () => {
+      const setReclaimCooldown = (seconds: number) =>
+        program.methods
+          .updateReclaimCooldown({ worldId: {} }, new anchor.BN(seconds))
+          .accountsStrict({
+            scoringConfig: scoringConfigPda,
+            authority: payer,
+          })
+          .rpc();
+
+      await setReclaimCooldown(3);
+
+      const user = anchor.web3.Keypair.generate();
+      await airdrop(user.publicKey);
+
+      const now = Math.floor(Date.now() / 1000);
+      const source = { worldId: {} };
+      const payload = sourceData("worldId", now);
+
+      const { individualProofPda, userProofPda, identityNullifierRegistryPda } =
+        await submitProof(
+          user,
+          Buffer.alloc(32, "soft1"),
+          source,
+          payload,
+          new anchor.BN(180),
+          now
+        );
+
+      await program.methods
+        .softRevoke(source)
+        .accountsStrict({
+          registry: registryPda,
+          userProof: userProofPda,
+          individualProof: individualProofPda,
+          identityNullifierRegistry: identityNullifierRegistryPda,
+          scoringConfig: scoringConfigPda,
+          decayConfig: decayConfigPda,
+          userStatus: deriveUserStatusPda(user.publicKey),
+          statusCache: null,
+          user: user.publicKey,
+        })
+        .signers([user])
+        .rpc();
+
+      const nullifier = await program.account.identityNullifierRegistry.fetch(
+        identityNullifierRegistryPda
+      );
+      expect(nullifier.isBurned).to.equal(false);
+
+      try {
+        await submitProof(
+          user,
+          Buffer.alloc(32, "soft2"),
+          source,
+          payload,
+          new anchor.BN(180),
+          now
+        );
+        expect.fail("should have thrown error");
+      } catch (error: any) {
+        expect(error.error.errorCode.code).to.equal("ReclaimCooldownActive");
+      }
+
+      await new Promise((resolve) => setTimeout(resolve, 4000));
+      await submitProof(
+        user,
+        Buffer.alloc(32, "soft3"),
+        source,
+        payload,
+        new anchor.BN(180),
+        now
+      );
+      const userProof = await program.account.userProof.fetch(userProofPda);
+      expect(userProof.activeSourceCount).to.equal(1);
+
+      await setReclaimCooldown(0);
+    });
+
+    it("should reject closing an active proof", async () => {
+      const user = anchor.web3.Keypair.generate();
+      await airdrop(user.publicKey);
+      const now = Math.floor(Date.now() / 1000);
+
+      const { individualProofPda } = await submitProof(
+        user,
+        Buffer.alloc(32, "close0"),
+        { reclaim: {} },
+        sourceData("reclaim", now),
+        new anchor.BN(150),
+        now
+      );
+
+      try {
+        await program.methods
+          .closeRevokedProof({ reclaim: {} })
+          .accountsStrict({
+            individualProof: individualProofPda,
+            scoringConfig: scoringConfigPda,
+            user: user.publicKey,
+          })
+          .signers([user])
+          .rpc();
+        expect.fail("should have thrown error");
+      } catch (error: any) {
+        expect(error.error.errorCode.code).to.equal("ProofNotRevoked");
+      }
+    });
+
+    it("should close revoked proof and keep identity burned", async () => {
+      const user = anchor.web3.Keypair.generate();
+      await airdrop(user.publicKey);
+
+      const now = Math.floor(Date.now() / 1000);
+      const source = { worldId: {} };
+      const payload = sourceData("worldId", now);
+
+      const { individualProofPda, userProofPda, identityNullifierRegistryPda } =
+        await submitProof(
+          user,
+          Buffer.alloc(32, "close1"),
+          source,
+          payload,
+          new anchor.BN(180),
+          now
+        );
+
+      await program.methods
+        .revokeProof(source)
+        .accountsStrict({
+          registry: registryPda,
+          userProof: userProofPda,
+          individualProof: individualProofPda,
+          identityNullifierRegistry: identityNullifierRegistryPda,
+          scoringConfig: scoringConfigPda,
+          decayConfig: decayConfigPda,
+          userStatus: deriveUserStatusPda(user.publicKey),
+          statusCache: null,
+          user: user.publicKey,
+        })
+        .signers([user])
+        .rpc();
+
+      const rent = await provider.connection.getBalance(individualProofPda);
+      const balanceBefore = await provider.connection.getBalance(
+        user.publicKey
+      );
+
+      await program.methods
+        .closeRevokedProof(source)
+        .accountsStrict({
+          individualProof: individualProofPda,
+          scoringConfig: scoringConfigPda,
+          user: user.publicKey,
+        })
+        .signers([user])
+        .rpc();
+
+      const balanceAfter = await provider.connection.getBalance(user.publicKey);
+      expect(
+        await program.account.individualProof.fetchNullable(individualProofPda)
+      ).to.equal(null);
+      expect(balanceAfter).to.be.greaterThan(balanceBefore + rent / 2);
+
+      const nullifier = await program.account.identityNullifierRegistry.fetch(
+        identityNullifierRegistryPda
+      );
+      expect(nullifier.isBurned).to.equal(true);
+
+      try {
+        await submitProof(
+          user,
+          Buffer.alloc(32, "close2"),
+          source,
+          payload,
+          new anchor.BN(180),
+          now
+        );
+        expect.fail("should have thrown error");
+      } catch (error: any) {
+        expect(error.error.errorCode.code).to.equal("IdentityRevokedPermanent");
+      }
+    });
+
+    it("should let the authority force-revoke a proof", async () => {
+      const user = anchor.web3.Keypair.generate();
+      const attacker = anchor.web3.Keypair.generate();
+      await airdrop(user.publicKey);
+      await airdrop(attacker.publicKey);
+
+      const now = Math.floor(Date.now() / 1000);
+      const source = { worldId: {} };
+
+      const { individualProofPda, userProofPda, identityNullifierRegistryPda } =
+        await submitProof(
+          user,
+          Buffer.alloc(32, "force1"),
+          source,
+          sourceData("worldId", now),
+          new anchor.BN(180),
+          now
+        );
+
+      const adminRevoke = (authority: anchor.web3.Keypair) =>
+        program.methods
+          .adminRevokeProof(source)
+          .accountsStrict({
+            registry: registryPda,
+            userProof: userProofPda,
+            individualProof: individualProofPda,
+            identityNullifierRegistry: identityNullifierRegistryPda,
+            scoringConfig: scoringConfigPda,
+            user: user.publicKey,
+            authority: authority.publicKey,
+          })
+          .signers([authority])
+          .rpc();
+
+      try {
+        await adminRevoke(attacker);
+        expect.fail("should have thrown error");
+      } catch (error: any) {
+        expect(error.error.errorCode.code).to.equal("Unauthorized");
+      }
+
+      await adminRevoke(verifier);
+
+      const proof = await program.account.individualProof.fetch(
+        individualProofPda
+      );
+      const nullifier = await program.account.identityNullifierRegistry.fetch(
+        identityNullifierRegistryPda
+      );
+      const userProof = await program.account.userProof.fetch(userProofPda);
+      expect(proof.isRevoked).to.equal(true);
+      expect(nullifier.isBurned).to.equal(true);
+      expect(userProof.activeSourceCount).to.equal(0);
+    });
+
+    it("should drop a banned identity's score immediately", async () => {
+      const user = anchor.web3.Keypair.generate();
+      await airdrop(user.publicKey);
+
+      const now = Math.floor(Date.now() / 1000);
+      const payload = sourceData("worldId", now);
+      const banned = await submitProof(
+        user,
+        Buffer.alloc(32, "ban1"),
+        { worldId: {} },
+        payload,
+        new anchor.BN(180),
+        now
+      );
+      const kept = await submitProof(
+        user,
+        Buffer.alloc(32, "ban2"),
+        { reclaim: {} },
+        sourceData("reclaim", now),
+        new anchor.BN(150),
+        now
+      );
+
+      await program.methods
+        .banAndRevoke({ worldId: {} }, payload.worldId.nullifierHash)
+        .accountsStrict({
+          registry: registryPda,
+          identityNullifierRegistry: banned.identityNullifierRegistryPda,
+          userProof: banned.userProofPda,
+          scoringConfig: scoringConfigPda,
+          authority: payer,
+        })
+        .remainingAccounts(
+          [banned.individualProofPda, kept.individualProofPda].map(
+            (pubkey) => ({ pubkey, isSigner: false, isWritable: true })
+          )
+        )
+        .rpc();
+
+      const bannedProof = await program.account.individualProof.fetch(
+        banned.individualProofPda
+      );
+      const keptProof = await program.account.individualProof.fetch(
+        kept.individualProofPda
+      );
+      const nullifier = await program.account.identityNullifierRegistry.fetch(
+        banned.identityNullifierRegistryPda
+      );
+      const userProof = await program.account.userProof.fetch(
+        banned.userProofPda
+      );
+      expect(bannedProof.isRevoked).to.equal(true);
+      expect(keptProof.isRevoked).to.equal(false);
+      expect(nullifier.isBurned).to.equal(true);
+      expect(userProof.activeSourceCount).to.equal(1);
+      expect(userProof.aggregatedScore.toNumber()).to.equal(
+        keptProof.weightedScore.toNumber()
+      );
+    });
+
+    it("should only reclaim an abandoned identity after its window", async () => {
+      const user = anchor.web3.Keypair.generate();
+      await airdrop(user.publicKey);
+
+      const now = Math.floor(Date.now() / 1000);
+      const payload = sourceData("worldId", now);
+      const { userProofPda, identityNullifierRegistryPda } = await submitProof(
+        user,
+        Buffer.alloc(32, "reclaimnf1"),
+        { worldId: {} },
+        payload,
+        new anchor.BN(180),
+        now
+      );
+
+      const reclaim = () =>
+        program.methods
+          .reclaimExpiredNullifier(
+            { worldId: {} },
+            payload.worldId.nullifierHash
+          )
+          .accountsStrict({
+            identityNullifierRegistry: identityNullifierRegistryPda,
+            userProof: userProofPda,
+          })
+          .rpc();
+
+      try {
+        await reclaim();
+        expect.fail("should have thrown error");
+      } catch (error: any) {
+        expect(error.error.errorCode.code).to.equal(
+          "NullifierReclaimWindowActive"
+        );
+      }
+
+      await program.methods
+        .banAndRevoke({ worldId: {} }, payload.worldId.nullifierHash)
+        .accountsStrict({
+          registry: registryPda,
+          identityNullifierRegistry: identityNullifierRegistryPda,
+          userProof: userProofPda,
+          scoringConfig: scoringConfigPda,
+          authority: payer,
+        })
+        .rpc();
+
+      try {
+        await reclaim();
+        expect.fail("should have thrown error");
+      } catch (error: any) {
+        expect(error.error.errorCode.code).to.equal(
+          "IdentityRevokedPermanent"
+        );
+      }
+
+      const nullifier = await program.account.identityNullifierRegistry.fetch(
+        identityNullifierRegistryPda
+      );
+      expect(nullifier.claimedBy.toBase58()).to.equal(
+        user.publicKey.toBase58()
+      );
+    });
+
+    it("should migrate identity to a new wallet", async () => {
+      const oldOwner = anchor.web3.Keypair.generate();
+      const newOwner = anchor.web3.Keypair.generate();
+      await airdrop(oldOwner.publicKey);
+      await airdrop(newOwner.publicKey);
+
+      const now = Math.floor(Date.now() / 1000);
+      const source = { worldId: {} };
+      const payload = sourceData("worldId", now);
+      const identityNullifier = identityNullifierFromPayload(source, payload);
+
+      const { individualProofPda, userProofPda, identityNullifierRegistryPda } =
+        await submitProof(
+          oldOwner,
+          Buffer.alloc(32, "migrate1"),
+          source,
+          payload,
+          new anchor.BN(180),
+          now
+        );
+
+      const nonce = attestationNonce++;
+      const nonceBuf = Buffer.alloc(8);
+      nonceBuf.writeBigUInt64LE(BigInt(nonce));
+      const tsBuf = Buffer.alloc(8);
+      tsBuf.writeBigInt64LE(BigInt(now));
+      const message = Buffer.concat([
+        Buffer.from("sidm"),
+        program.programId.toBuffer(),
+        registryPda.toBuffer(),
+        oldOwner.publicKey.toBuffer(),
+        newOwner.publicKey.toBuffer(),
+        Buffer.from([sourceIndex.worldId]),
+        Buffer.from(identityNullifier),
+        nonceBuf,
+        tsBuf,
+      ]);
+
+      const newUserProofPda = deriveUserProofPda(newOwner.publicKey);
+      const newIndividualProofPda = deriveIndividualProofPda(
+        newOwner.publicKey,
+        sourceIndex.worldId
+      );
+
+      await program.methods
+        .migrateIdentity(source, new anchor.BN(nonce), new anchor.BN(now))
+        .preInstructions([
+          anchor.web3.Ed25519Program.createInstructionWithPrivateKey({
+            privateKey: verifier.secretKey,
+            message,
+          }),
+        ])
+        .accountsStrict({
+          registry: registryPda,
+          identityNullifierRegistry: identityNullifierRegistryPda,
+          oldUserProof: userProofPda,
+          oldIndividualProof: individualProofPda,
+          newUserProof: newUserProofPda,
+          newIndividualProof: newIndividualProofPda,
+          attestationNonceRegistry: deriveAttestationNoncePda(nonce),
+          scoringConfig: scoringConfigPda,
+          decayConfig: decayConfigPda,
+          oldOwner: oldOwner.publicKey,
+          instructionsSysvar: anchor.web3.SYSVAR_INSTRUCTIONS_PUBKEY,
+          newOwner: newOwner.publicKey,
+          systemProgram: anchor.web3.SystemProgram.programId,
+        })
+        .signers([newOwner])
+        .rpc();
+
+      const nullifier = await program.account.identityNullifierRegistry.fetch(
+        identityNullifierRegistryPda
+      );
+      expect(nullifier.claimedBy.toString()).to.equal(
+        newOwner.publicKey.toString()
+      );
+      expect(
+        await program.account.userProof.fetchNullable(userProofPda)
+      ).to.equal(null);
+      const migrated = await program.account.userProof.fetch(newUserProofPda);
+      expect(migrated.activeSourceCount).to.equal(1);
+      expect(migrated.aggregatedScore.toNumber()).to.be.greaterThan(0);
+
+      try {
+        await program.methods
+          .revokeProof(source)
+          .accountsStrict({
+            registry: registryPda,
+            userProof: userProofPda,
+            individualProof: individualProofPda,
+            identityNullifierRegistry: identityNullifierRegistryPda,
+            scoringConfig: scoringConfigPda,
+            decayConfig: decayConfigPda,
+            userStatus: deriveUserStatusPda(oldOwner.publicKey),
+            statusCache: null,
+            user: oldOwner.publicKey,
+          })
+          .signers([oldOwner])
+          .rpc();
+        expect.fail("should have thrown error");
+      } catch (error: any) {
+        expect(error.error.errorCode.code).to.equal("AccountNotInitialized");
+      }
+    });
+
+    it("should transfer an identity only when both wallets sign", async () => {
+      const oldOwner = anchor.web3.Keypair.generate();
+      const newOwner = anchor.web3.Keypair.generate();
+      await airdrop(oldOwner.publicKey);
+      await airdrop(newOwner.publicKey);
+
+      const now = Math.floor(Date.now() / 1000);
+      const source = { worldId: {} };
+      const { individualProofPda, userProofPda, identityNullifierRegistryPda } =
+        await submitProof(
+          oldOwner,
+          Buffer.alloc(32, "transfer1"),
+          source,
+          sourceData("worldId", now),
+          new anchor.BN(180),
+          now
+        );
+
+      const newUserProofPda = deriveUserProofPda(newOwner.publicKey);
+      const transfer = (signers: anchor.web3.Keypair[]) =>
+        program.methods
+          .transferIdentity(source)
+          .accountsStrict({
+            registry: registryPda,
+            identityNullifierRegistry: identityNullifierRegistryPda,
+            oldUserProof: userProofPda,
+            oldIndividualProof: individualProofPda,
+            newUserProof: newUserProofPda,
+            newIndividualProof: deriveIndividualProofPda(
+              newOwner.publicKey,
+              sourceIndex.worldId
+            ),
+            scoringConfig: scoringConfigPda,
+            decayConfig: decayConfigPda,
+            oldOwner: oldOwner.publicKey,
+            newOwner: newOwner.publicKey,
+            systemProgram: anchor.web3.SystemProgram.programId,
+          })
+          .signers(signers)
+          .rpc();
+
+      for (const signer of [oldOwner, newOwner]) {
+        try {
+          await transfer([signer]);
+          expect.fail("should have thrown error");
+        } catch (error: any) {
+          expect(String(error)).to.match(/Missing signature|unknown signer/i);
+        }
+      }
+      let nullifier = await program.account.identityNullifierRegistry.fetch(
+        identityNullifierRegistryPda
+      );
+      expect(nullifier.claimedBy.toString()).to.equal(
+        oldOwner.publicKey.toString()
+      );
+
+      const signature = await transfer([oldOwner, newOwner]);
+
+      nullifier = await program.account.identityNullifierRegistry.fetch(
+        identityNullifierRegistryPda
+      );
+      expect(nullifier.claimedBy.toString()).to.equal(
+        newOwner.publicKey.toString()
+      );
+      expect(
+        await program.account.userProof.fetchNullable(userProofPda)
+      ).to.equal(null);
+      const transferred = await program.account.userProof.fetch(
+        newUserProofPda
+      );
+      expect(transferred.activeSourceCount).to.equal(1);
+
+      const transferredEvent = (await emittedEvents(signature)).find(
+        (event) => event.name === "identityTransferred"
+      );
+      expect(transferredEvent.data.newOwner.toString()).to.equal(
+        newOwner.publicKey.toString()
+      );
+    });
+
+    it("should refuse to migrate a soulbound identity", async () => {
+      const attestationBuffers = (nonce: number, now: number) => {
+        const nonceBuf = Buffer.alloc(8);
+        nonceBuf.writeBigUInt64LE(BigInt(nonce));
+        const tsBuf = Buffer.alloc(8);
+        tsBuf.writeBigInt64LE(BigInt(now));
+        return [nonceBuf, tsBuf];
+      };
+      const submitWorldId = async (tag: string) => {
+        const owner = anchor.web3.Keypair.generate();
+        await airdrop(owner.publicKey);
+        const now = Math.floor(Date.now() / 1000);
+        const source = { worldId: {} };
+        const payload = sourceData("worldId", now);
+        const submitted = await submitProof(
+          owner,
+          Buffer.alloc(32, tag),
+          source,
+          payload,
+          new anchor.BN(180),
+          now
+        );
+        const identityNullifier = identityNullifierFromPayload(source, payload);
+        return { owner, identityNullifier, ...submitted };
+      };
+      const markSoulbound = async (
+        identity: Awaited<ReturnType<typeof submitWorldId>>
+      ) => {
+        const now = Math.floor(Date.now() / 1000);
+        const nonce = attestationNonce++;
+        const message = Buffer.concat([
+          Buffer.from("sidb"),
+          program.programId.toBuffer(),
+          registryPda.toBuffer(),
+          identity.owner.publicKey.toBuffer(),
+          Buffer.from([sourceIndex.worldId]),
+          Buffer.from(identity.identityNullifier),
+          ...attestationBuffers(nonce, now),
+        ]);
+        await program.methods
+          .markIdentitySoulbound(
+            { worldId: {} },
+            new anchor.BN(nonce),
+            new anchor.BN(now)
+          )
+          .preInstructions([
+            anchor.web3.Ed25519Program.createInstructionWithPrivateKey({
+              privateKey: verifier.secretKey,
+              message,
+            }),
+          ])
+          .accountsStrict({
+            registry: registryPda,
+            userProof: identity.userProofPda,
+            individualProof: identity.individualProofPda,
+            identityNullifierRegistry: identity.identityNullifierRegistryPda,
+            attestationNonceRegistry: deriveAttestationNoncePda(nonce),
+            user: identity.owner.publicKey,
+            instructionsSysvar: anchor.web3.SYSVAR_INSTRUCTIONS_PUBKEY,
+            payer,
+            systemProgram: anchor.web3.SystemProgram.programId,
+          })
+          .rpc();
+      };
+      const migrate = async (
+        identity: Awaited<ReturnType<typeof submitWorldId>>
+      ) => {
+        const newOwner = anchor.web3.Keypair.generate();
+        await airdrop(newOwner.publicKey);
+        const now = Math.floor(Date.now() / 1000);
+        const nonce = attestationNonce++;
+        const message = Buffer.concat([
+          Buffer.from("sidm"),
+          program.programId.toBuffer(),
+          registryPda.toBuffer(),
+          identity.owner.publicKey.toBuffer(),
+          newOwner.publicKey.toBuffer(),
+          Buffer.from([sourceIndex.worldId]),
+          Buffer.from(identity.identityNullifier),
+          ...attestationBuffers(nonce, now),
+        ]);
+        await program.methods
+          .migrateIdentity(
+            { worldId: {} },
+            new anchor.BN(nonce),
+            new anchor.BN(now)
+          )
+          .preInstructions([
+            anchor.web3.Ed25519Program.createInstructionWithPrivateKey({
+              privateKey: verifier.secretKey,
+              message,
+            }),
+          ])
+          .accountsStrict({
+            registry: registryPda,
+            identityNullifierRegistry: identity.identityNullifierRegistryPda,
+            oldUserProof: identity.userProofPda,
+            oldIndividualProof: identity.individualProofPda,
+            newUserProof: deriveUserProofPda(newOwner.publicKey),
+            newIndividualProof: deriveIndividualProofPda(
+              newOwner.publicKey,
+              sourceIndex.worldId
+            ),
+            attestationNonceRegistry: deriveAttestationNoncePda(nonce),
+            scoringConfig: scoringConfigPda,
+            decayConfig: decayConfigPda,
+            oldOwner: identity.owner.publicKey,
+            instructionsSysvar: anchor.web3.SYSVAR_INSTRUCTIONS_PUBKEY,
+            newOwner: newOwner.publicKey,
+            systemProgram: anchor.web3.SystemProgram.programId,
+          })
+          .signers([newOwner])
+          .rpc();
+        return newOwner;
+      };
+
+      const bound = await submitWorldId("soulbound1");
+      await markSoulbound(bound);
+      const nullifier = await program.account.identityNullifierRegistry.fetch(
+        bound.identityNullifierRegistryPda
+      );
+      expect(nullifier.soulbound).to.equal(true);
+      try {
+        await migrate(bound);
+        expect.fail("should have thrown error");
+      } catch (error: any) {
+        expect(error.error.errorCode.code).to.equal("SoulboundIdentity");
+      }
+
+      const transferable = await submitWorldId("soulbound2");
+      const newOwner = await migrate(transferable);
+      const migrated = await program.account.identityNullifierRegistry.fetch(
+        transferable.identityNullifierRegistryPda
+      );
+      expect(migrated.soulbound).to.equal(false);
+      expect(migrated.claimedBy.toBase58()).to.equal(
+        newOwner.publicKey.toBase58()
+      );
+    });
+  });
+
+  describe("Admin Functions", () => {
+    it("should update min score", async () => {
+      await program.methods
+        .updateMinScore(new anchor.BN(250))
+        .accountsStrict({
+          registry: registryPda,
+          authority: payer,
+        })
+        .rpc();
+
+      const registry = await program.account.registry.fetch(registryPda);
+      expect(registry.minScore.toNumber()).to.equal(250);
+    });
+
+    it("should update scoring config", async () => {
+      await program.methods
+        .updateScoringConfig({ reclaim: {} }, new anchor.BN(150))
+        .accountsStrict({
+          scoringConfig: scoringConfigPda,
+          authority: payer,
+        })
+        .rpc();
+
+      const scoringConfig = await program.account.scoringConfig.fetch(
+        scoringConfigPda
+      );
+      expect(scoringConfig.weights[0].toNumber()).to.equal(150);
+    });
+
+    it("should reject increasing decay factors", async () => {
+      try {
+        await program.methods
+          .updateDecayConfig(
+            [
+              new anchor.BN(2592000),
+              new anchor.BN(7776000),
+              new anchor.BN(15552000),
+              new anchor.BN("9223372036854775807"),
+            ],
+            [
+              new anchor.BN(100),
+              new anchor.BN(50),
+              new anchor.BN(75),
+              new anchor.BN(25),
+            ]
+          )
+          .accountsStrict({
+            decayConfig: decayConfigPda,
+            authority: payer,
+          })
+          .rpc();
+        expect.fail("should have thrown error");
+      } catch (error: any) {
+        expect(error.error.errorCode.code).to.equal("InvalidConfig");
+      }
+    });
+
+    it("should update registry config", async () => {
+      const before = await program.account.registry.fetch(registryPda);
+      const signature = await program.methods
+        .updateRegistryConfig(
+          new anchor.BN(0),
+          20,
+          new anchor.BN(3600),
+          new anchor.BN(0),
+          1
+        )
+        .accountsStrict({
+          registry: registryPda,
+          authority: payer,
+        })
+        .rpc();
+
+      const registry = await program.account.registry.fetch(registryPda);
+      expect(registry.cooldownPeriod.toNumber()).to.equal(0);
+      expect(registry.diversityBonusPercent).to.equal(20);
+      expect(registry.proofTtlSeconds.toNumber()).to.equal(3600);
+      expect(registry.verifierAuthority.toString()).to.equal(payer.toString());
+
+      const updated = (await emittedEvents(signature)).find(
+        (event) => event.name === "registryConfigUpdated"
+      );
+      expect(updated?.data.authority.toString()).to.equal(
+        registry.authority.toString()
+      );
+      expect(updated?.data.oldCooldownPeriod.toNumber()).to.equal(
+        before.cooldownPeriod.toNumber()
+      );
+      expect(updated?.data.newCooldownPeriod.toNumber()).to.equal(
+        registry.cooldownPeriod.toNumber()
+      );
+      expect(updated?.data.oldDiversityBonusPercent).to.equal(
+        before.diversityBonusPercent
+      );
+      expect(updated?.data.newDiversityBonusPercent).to.equal(
+        registry.diversityBonusPercent
+      );
+      expect(updated?.data.oldProofTtlSeconds.toNumber()).to.equal(
+        before.proofTtlSeconds.toNumber()
+      );
+      expect(updated?.data.newProofTtlSeconds.toNumber()).to.equal(
+        registry.proofTtlSeconds.toNumber()
+      );
+    });
+
+    it("should snapshot config unaffected by later updates", async () => {
+      const snapshotId = new anchor.BN(1);
+      const configSnapshotPda = anchor.web3.PublicKey.findProgramAddressSync(
+        [
+          Buffer.from("config_snapshot"),
+          snapshotId.toArrayLike(Buffer, "le", 8),
+        ],
+        program.programId
+      )[0];
+
+      await program.methods
+        .snapshotConfig(snapshotId)
+        .accountsStrict({
+          configSnapshot: configSnapshotPda,
+          registry: registryPda,
+          scoringConfig: scoringConfigPda,
+          authority: payer,
+          systemProgram: anchor.web3.SystemProgram.programId,
+        })
+        .rpc();
+
+      const registry = await program.account.registry.fetch(registryPda);
+      const scoringConfig = await program.account.scoringConfig.fetch(
+        scoringConfigPda
+      );
+      let snapshot = await program.account.configSnapshot.fetch(
+        configSnapshotPda
+      );
+      expect(snapshot.snapshotId.toNumber()).to.equal(1);
+      expect(snapshot.registry.minScore.toNumber()).to.equal(
+        registry.minScore.toNumber()
+      );
+      expect(snapshot.registry.diversityBonusPercent).to.equal(
+        registry.diversityBonusPercent
+      );
+      expect(snapshot.registry.verifierAuthority.toString()).to.equal(
+        registry.verifierAuthority.toString()
+      );
+      expect(
+        snapshot.scoringConfig.weights.map((w) => w.toNumber())
+      ).to.deep.equal(scoringConfig.weights.map((w) => w.toNumber()));
+
+      await program.methods
+        .updateMinScore(new anchor.BN(260))
+        .accountsStrict({
+          registry: registryPda,
+          authority: payer,
+        })
+        .rpc();
+
+      snapshot = await program.account.configSnapshot.fetch(configSnapshotPda);
+      expect(snapshot.registry.minScore.toNumber()).to.equal(
+        registry.minScore.toNumber()
+      );
+
+      try {
+        await program.methods
+          .snapshotConfig(snapshotId)
+          .accountsStrict({
+            configSnapshot: configSnapshotPda,
+            registry: registryPda,
+            scoringConfig: scoringConfigPda,
+            authority: payer,
+            systemProgram: anchor.web3.SystemProgram.programId,
+          })
+          .rpc();
+        expect.fail("should have thrown error");
+      } catch (error: any) {
+        expect(error).to.exist;
+      }
+
+      await program.methods
+        .updateMinScore(registry.minScore)
+        .accountsStrict({
+          registry: registryPda,
+          authority: payer,
+        })
+        .rpc();
+    });
+
+    it("should verify against a frozen score snapshot", async () => {
+      const snapshotId = new anchor.BN(2);
+      const idBytes = snapshotId.toArrayLike(Buffer, "le", 8);
+      const configSnapshotPda = anchor.web3.PublicKey.findProgramAddressSync(
+        [Buffer.from("config_snapshot"), idBytes],
+        program.programId
+      )[0];
+      const user = anchor.web3.Keypair.generate();
+      await airdrop(user.publicKey);
+      const scoreSnapshotPda = anchor.web3.PublicKey.findProgramAddressSync(
+        [Buffer.from("snapshot"), user.publicKey.toBuffer(), idBytes],
+        program.programId
+      )[0];
+
+      const { minScore } = await program.account.registry.fetch(registryPda);
+      const now = Math.floor(Date.now() / 1000);
+      const { userProofPda, individualProofPda } = await submitProof(
+        user,
+        Buffer.alloc(32, "scoreSnapshot1"),
+        { worldId: {} },
+        sourceData("worldId", now),
+        new anchor.BN(minScore.toNumber() - 50),
+        now
+      );
+
+      await program.methods
+        .snapshotConfig(snapshotId)
+        .accountsStrict({
+          configSnapshot: configSnapshotPda,
+          registry: registryPda,
+          scoringConfig: scoringConfigPda,
+          authority: payer,
+          systemProgram: anchor.web3.SystemProgram.programId,
+        })
+        .rpc();
+      await program.methods
+        .snapshotScore(snapshotId)
+        .accountsStrict({
+          scoreSnapshot: scoreSnapshotPda,
+          configSnapshot: configSnapshotPda,
+          userProof: userProofPda,
+          decayConfig: decayConfigPda,
+          user: user.publicKey,
+          payer,
+          systemProgram: anchor.web3.SystemProgram.programId,
+        })
+        .remainingAccounts([
+          { pubkey: individualProofPda, isWritable: false, isSigner: false },
+        ])
+        .rpc();
+
+      await submitProof(
+        user,
+        Buffer.alloc(32, "scoreSnapshot2"),
+        { reclaim: {} },
+        sourceData("reclaim", now),
+        new anchor.BN(150),
+        now
+      );
+
+      const live = await program.methods
+        .verifyProof()
+        .accountsStrict({
+          userProof: userProofPda,
+          userStatus: deriveUserStatusPda(user.publicKey),
+          registry: registryPda,
+          scoringConfig: scoringConfigPda,
+          decayConfig: decayConfigPda,
+          user: user.publicKey,
+        })
+        .view();
+      expect(live.isVerified).to.equal(true);
+
+      const atSnapshot = await program.methods
+        .verifyProofAtSnapshot(snapshotId)
+        .accountsStrict({
+          scoreSnapshot: scoreSnapshotPda,
+          configSnapshot: configSnapshotPda,
+          userProof: userProofPda,
+          userStatus: deriveUserStatusPda(user.publicKey),
+          user: user.publicKey,
+        })
+        .view();
+      expect(atSnapshot).to.equal(false);
+    });
+
+    it("should keep one immutable score snapshot per campaign", async () => {
+      const user = anchor.web3.Keypair.generate();
+      await airdrop(user.publicKey);
+      const now = Math.floor(Date.now() / 1000);
+      const { userProofPda, individualProofPda } = await submitProof(
+        user,
+        Buffer.alloc(32, "campaignSnapshot1"),
+        { worldId: {} },
+        sourceData("worldId", now),
+        new anchor.BN(150),
+        now
+      );
+
+      const snapshot = async (campaignId: number) => {
+        const idBytes = new anchor.BN(campaignId).toArrayLike(Buffer, "le", 8);
+        const configSnapshotPda = anchor.web3.PublicKey.findProgramAddressSync(
+          [Buffer.from("config_snapshot"), idBytes],
+          program.programId
+        )[0];
+        const scoreSnapshotPda = anchor.web3.PublicKey.findProgramAddressSync(
+          [Buffer.from("snapshot"), user.publicKey.toBuffer(), idBytes],
+          program.programId
+        )[0];
+        if (!(await provider.connection.getAccountInfo(configSnapshotPda))) {
+          await program.methods
+            .snapshotConfig(new anchor.BN(campaignId))
+            .accountsStrict({
+              configSnapshot: configSnapshotPda,
+              registry: registryPda,
+              scoringConfig: scoringConfigPda,
+              authority: payer,
+              systemProgram: anchor.web3.SystemProgram.programId,
+            })
+            .rpc();
+        }
+        await program.methods
+          .snapshotScore(new anchor.BN(campaignId))
+          .accountsStrict({
+            scoreSnapshot: scoreSnapshotPda,
+            configSnapshot: configSnapshotPda,
+            userProof: userProofPda,
+            decayConfig: decayConfigPda,
+            user: user.publicKey,
+            payer,
+            systemProgram: anchor.web3.SystemProgram.programId,
+          })
+          .remainingAccounts([
+            { pubkey: individualProofPda, isWritable: false, isSigner: false },
+          ])
+          .rpc();
+        return program.account.scoreSnapshot.fetch(scoreSnapshotPda);
+      };
+
+      const first = await snapshot(30);
+      expect(first.aggregatedScore.toNumber()).to.equal(150);
+      expect(first.sourceCount).to.equal(1);
+      expect(first.computedAtSlot.toNumber()).to.be.greaterThan(0);
+
+      try {
+        await snapshot(30);
+        expect.fail("should have thrown error");
+      } catch (error: any) {
+        expect(error).to.exist;
+      }
+
+      const second = await snapshot(31);
+      expect(second.snapshotId.toNumber()).to.equal(31);
+      expect(second.aggregatedScore.toNumber()).to.equal(150);
+    });
+
+    it("should block submissions while paused", async () => {
+      const existing = anchor.web3.Keypair.generate();
+      const user = anchor.web3.Keypair.generate();
+      await airdrop(existing.publicKey);
+      await airdrop(user.publicKey);
+      const now = Math.floor(Date.now() / 1000);
+
+      const { userProofPda } = await submitProof(
+        existing,
+        Buffer.alloc(32, "pause0"),
+        { reclaim: {} },
+        sourceData("reclaim", now),
+        new anchor.BN(200),
+        now
+      );
+
+      await program.methods
+        .setPaused(true)
+        .accountsStrict({
+          registry: registryPda,
+          authority: payer,
+        })
+        .rpc();
+
+      const registry = await program.account.registry.fetch(registryPda);
+      expect(registry.paused).to.equal(true);
+
+      try {
+        await submitProof(
+          user,
+          Buffer.alloc(32, "pause1"),
+          { reclaim: {} },
+          sourceData("reclaim", now),
+          new anchor.BN(200),
+          now
+        );
+        expect.fail("should have thrown error");
+      } catch (error: any) {
+        expect(error.error.errorCode.code).to.equal("RegistryPaused");
+      }
+
+      const status = await program.methods
+        .verifyProof()
+        .accountsStrict({
+          userProof: userProofPda,
+          userStatus: deriveUserStatusPda(existing.publicKey),
+          registry: registryPda,
+          scoringConfig: scoringConfigPda,
+          decayConfig: decayConfigPda,
+          user: existing.publicKey,
+        })
+        .view();
+      expect(status.isVerified).to.equal(true);
+
+      await program.methods
+        .setPaused(false)
+        .accountsStrict({
+          registry: registryPda,
+          authority: payer,
+        })
+        .rpc();
+
+      await submitProof(
+        user,
+        Buffer.alloc(32, "pause2"),
+        { reclaim: {} },
+        sourceData("reclaim", now),
+        new anchor.BN(200),
+        now
+      );
+    });
+
+    it("should reject finalize when no verifier rotation pending", async () => {
+      try {
+        await program.methods
+          .finalizeVerifierRotation()
+          .accountsStrict({
+            registry: registryPda,
+            authority: payer,
+          })
+          .rpc();
+        expect.fail("should have thrown error");
+      } catch (error: any) {
+        expect(error.error.errorCode.code).to.equal(
+          "NoVerifierRotationPending"
+        );
+      }
+    });
+
+    it("should reject invalid verifier rotation params", async () => {
+      try {
+        await program.methods
+          .initiateVerifierRotation(
+            anchor.web3.PublicKey.default,
+            new anchor.BN(1)
+          )
+          .accountsStrict({
+            registry: registryPda,
+            authority: payer,
+          })
+          .rpc();
+        expect.fail("should have thrown error");
+      } catch (error: any) {
+        expect(error.error.errorCode.code).to.equal("InvalidConfig");
+      }
+
+      try {
+        await program.methods
+          .initiateVerifierRotation(payer, new anchor.BN(0))
+          .accountsStrict({
+            registry: registryPda,
+            authority: payer,
+          })
+          .rpc();
+        expect.fail("should have thrown error");
+      } catch (error: any) {
+        expect(error.error.errorCode.code).to.equal("InvalidConfig");
+      }
+    });
+
+    it("should let the scoring authority tune weights only", async () => {
+      const riskTeam = anchor.web3.Keypair.generate();
+      await airdrop(riskTeam.publicKey);
+      const setScoringAuthority = (
+        authority: anchor.web3.Keypair | null,
+        newAuthority: anchor.web3.PublicKey
+      ) => {
+        const builder = program.methods
+          .setScoringAuthority(newAuthority)
+          .accountsStrict({
+            scoringConfig: scoringConfigPda,
+            authority: authority ? authority.publicKey : payer,
+          });
+        return authority ? builder.signers([authority]).rpc() : builder.rpc();
+      };
+
+      await setScoringAuthority(null, riskTeam.publicKey);
+
+      await program.methods
+        .updateScoringConfig({ reclaim: {} }, new anchor.BN(150))
+        .accountsStrict({
+          scoringConfig: scoringConfigPda,
+          authority: riskTeam.publicKey,
+        })
+        .signers([riskTeam])
+        .rpc();
+
+      try {
+        await program.methods
+          .initiateVerifierRotation(riskTeam.publicKey, new anchor.BN(1))
+          .accountsStrict({
+            registry: registryPda,
+            authority: riskTeam.publicKey,
+          })
+          .signers([riskTeam])
+          .rpc();
+        expect.fail("should have thrown error");
+      } catch (error: any) {
+        expect(error.error.errorCode.code).to.equal("Unauthorized");
+      }
+
+      await setScoringAuthority(riskTeam, payer);
+      const scoringConfig = await program.account.scoringConfig.fetch(
+        scoringConfigPda
+      );
+      expect(scoringConfig.authority.toBase58()).to.equal(payer.toBase58());
+    });
+
+    it("should bucket the live score into tiers at each boundary", async () => {
+      const user = anchor.web3.Keypair.generate();
+      await airdrop(user.publicKey);
+      const now = Math.floor(Date.now() / 1000);
+
+      // reclaim weight is 150, so a base score of 100 lands on exactly 150.
+      const { userProofPda, individualProofPda } = await submitProof(
+        user,
+        Buffer.alloc(32, "tier1"),
+        { reclaim: {} },
+        sourceData("reclaim", now),
+        new anchor.BN(100),
+        now
+      );
+      const setThresholds = (thresholds: number[]) =>
+        program.methods
+          .updateTierThresholds(thresholds.map((t) => new anchor.BN(t)))
+          .accountsStrict({
+            registry: registryPda,
+            authority: payer,
+          })
+          .rpc();
+      const tier = async () => {
+        const result = await program.methods
+          .getTier()
+          .accountsStrict({
+            userProof: userProofPda,
+            userStatus: deriveUserStatusPda(user.publicKey),
+            registry: registryPda,
+            scoringConfig: scoringConfigPda,
+            decayConfig: decayConfigPda,
+            user: user.publicKey,
+          })
+          .remainingAccounts([
+            {
+              pubkey: individualProofPda,
+              isSigner: false,
+              isWritable: false,
+            },
+          ])
+          .view();
+        return Object.keys(result)[0];
+      };
+
+      expect(await tier()).to.equal("none");
+
+      const cases: [number[], string][] = [
+        [[151, 152, 153, 154], "none"],
+        [[150, 151, 152, 153], "bronze"],
+        [[149, 150, 151, 152], "silver"],
+        [[148, 149, 150, 151], "gold"],
+        [[147, 148, 149, 150], "platinum"],
+      ];
+      for (const [thresholds, expected] of cases) {
+        await setThresholds(thresholds);
+        expect(await tier()).to.equal(expected);
+      }
+
+      try {
+        await setThresholds([100, 100, 200, 300]);
+        expect.fail("should have thrown error");
+      } catch (error: any) {
+        expect(error.error.errorCode.code).to.equal("InvalidConfig");
+      }
+
+      await setThresholds([0, 0, 0, 0]);
+    });
+
+    it("should treat a banned user as unverified", async () => {
+      const user = anchor.web3.Keypair.generate();
+      await airdrop(user.publicKey);
+      const now = Math.floor(Date.now() / 1000);
+      const payload = sourceData("reclaim", now);
+
+      const { userProofPda } = await submitProof(
+        user,
+        Buffer.alloc(32, "banned1"),
+        { reclaim: {} },
+        payload,
+        new anchor.BN(300),
+        now
+      );
+      const banAccounts = {
+        registry: registryPda,
+        userStatus: deriveUserStatusPda(user.publicKey),
+        user: user.publicKey,
+        authority: payer,
+      };
+      const verify = () =>
+        program.methods
+          .verifyProof()
+          .accountsStrict({
+            userProof: userProofPda,
+            userStatus: deriveUserStatusPda(user.publicKey),
+            registry: registryPda,
+            scoringConfig: scoringConfigPda,
+            decayConfig: decayConfigPda,
+            user: user.publicKey,
+          })
+          .view();
+
+      expect((await verify()).isVerified).to.equal(true);
+
+      await program.methods
+        .banUser()
+        .accountsStrict({
+          ...banAccounts,
+          systemProgram: anchor.web3.SystemProgram.programId,
+        })
+        .rpc();
+      const banned = await verify();
+      expect(banned.isVerified).to.equal(false);
+      expect(banned.reason).to.deep.equal({ banned: {} });
+
+      try {
+        await submitProof(
+          user,
+          Buffer.alloc(32, "banned2"),
+          { reclaim: {} },
+          payload,
+          new anchor.BN(300),
+          now
+        );
+        expect.fail("should have thrown error");
+      } catch (error: any) {
+        expect(error.error.errorCode.code).to.equal("UserBanned");
+      }
+
+      await program.methods.unbanUser().accountsStrict(banAccounts).rpc();
+      expect((await verify()).isVerified).to.equal(true);
+    });
+
+    it("should hold a flagged identity unverified until resolved", async () => {
+      const user = anchor.web3.Keypair.generate();
+      await airdrop(user.publicKey);
+      const now = Math.floor(Date.now() / 1000);
+      const reclaimPayload = sourceData("reclaim", now);
+      const worldIdPayload = sourceData("worldId", now);
+      const reclaim = await submitProof(
+        user,
+        Buffer.alloc(32, "flagged1"),
+        { reclaim: {} },
+        reclaimPayload,
+        new anchor.BN(300),
+        now
+      );
+      const worldId = await submitProof(
+        user,
+        Buffer.alloc(32, "flagged2"),
+        { worldId: {} },
+        worldIdPayload,
+        new anchor.BN(300),
+        now
+      );
+      const verify = async () =>
+        (
+          await program.methods
+            .verifyProof()
+            .accountsStrict({
+              userProof: reclaim.userProofPda,
+              userStatus: deriveUserStatusPda(user.publicKey),
+              registry: registryPda,
+              scoringConfig: scoringConfigPda,
+              decayConfig: decayConfigPda,
+              user: user.publicKey,
+            })
+            .view()
+        ).reason;
+      const flagAccounts = (
+        submitted: typeof reclaim,
+        flagger: anchor.web3.PublicKey
+      ) => ({
+        registry: registryPda,
+        identityNullifierRegistry: submitted.identityNullifierRegistryPda,
+        userProof: submitted.userProofPda,
+        flagger,
+      });
+      const setFlaggingPolicy = (watcher: anchor.web3.PublicKey) =>
+        program.methods
+          .setFlaggingPolicy(watcher, false)
+          .accountsStrict({
+            registry: registryPda,
+            authority: payer,
+          })
+          .rpc();
+      const reclaimNullifier = reclaimPayload.reclaim.identityHash;
+      const worldIdNullifier = worldIdPayload.worldId.nullifierHash;
+      expect(await verify()).to.deep.equal({ ok: {} });
+
+      const watcher = anchor.web3.Keypair.generate();
+      try {
+        await program.methods
+          .flagIdentity({ reclaim: {} }, reclaimNullifier)
+          .accountsStrict(flagAccounts(reclaim, watcher.publicKey))
+          .signers([watcher])
+          .rpc();
+        expect.fail("should have thrown error");
+      } catch (error: any) {
+        expect(error.error.errorCode.code).to.equal("Unauthorized");
+      }
+
+      await program.methods
+        .flagIdentity({ reclaim: {} }, reclaimNullifier)
+        .accountsStrict(flagAccounts(reclaim, payer))
+        .rpc();
+      const flagged = await program.account.identityNullifierRegistry.fetch(
+        reclaim.identityNullifierRegistryPda
+      );
+      expect(flagged.flagged).to.equal(true);
+      expect(flagged.flaggedAt.toNumber()).to.be.greaterThan(0);
+      expect(await verify()).to.deep.equal({ flagged: {} });
+
+      await program.methods
+        .clearFlag({ reclaim: {} }, reclaimNullifier)
+        .accountsStrict({
+          registry: registryPda,
+          identityNullifierRegistry: reclaim.identityNullifierRegistryPda,
+          userProof: reclaim.userProofPda,
+          authority: payer,
+        })
+        .rpc();
+      expect(await verify()).to.deep.equal({ ok: {} });
+
+      await setFlaggingPolicy(watcher.publicKey);
+      await program.methods
+        .flagIdentity({ worldId: {} }, worldIdNullifier)
+        .accountsStrict(flagAccounts(worldId, watcher.publicKey))
+        .signers([watcher])
+        .rpc();
+      expect(await verify()).to.deep.equal({ flagged: {} });
+
+      await program.methods
+        .adminRevokeProof({ worldId: {} })
+        .accountsStrict({
+          registry: registryPda,
+          userProof: worldId.userProofPda,
+          individualProof: worldId.individualProofPda,
+          identityNullifierRegistry: worldId.identityNullifierRegistryPda,
+          scoringConfig: scoringConfigPda,
+          user: user.publicKey,
+          authority: payer,
+        })
+        .rpc();
+      const resolved = await program.account.userProof.fetch(
+        worldId.userProofPda
+      );
+      expect(resolved.flaggedSourcesMask).to.equal(0);
+      expect(await verify()).to.deep.equal({ ok: {} });
+
+      await setFlaggingPolicy(anchor.web3.PublicKey.default);
+    });
+
+    it("should reject finalize before rotation delay elapses", async () => {
+      const newVerifier = anchor.web3.Keypair.generate();
+
+      await program.methods
+        .initiateVerifierRotation(newVerifier.publicKey, new anchor.BN(2))
+        .accountsStrict({
+          registry: registryPda,
+          authority: payer,
+        })
+        .rpc();
+
+      try {
+        await program.methods
+          .finalizeVerifierRotation()
+          .accountsStrict({
+            registry: registryPda,
+            authority: payer,
+          })
+          .rpc();
+        expect.fail("should have thrown error");
+      } catch (error: any) {
+        expect(error.error.errorCode.code).to.equal("VerifierRotationNotReady");
+      }
+
+      await new Promise((resolve) => setTimeout(resolve, 2500));
+
+      await program.methods
+        .finalizeVerifierRotation()
+        .accountsStrict({
+          registry: registryPda,
+          authority: payer,
+        })
+        .rpc();
+
+      await program.methods
+        .initiateVerifierRotation(payer, new anchor.BN(1))
+        .accountsStrict({
+          registry: registryPda,
+          authority: payer,
+        })
+        .rpc();
+
+      await new Promise((resolve) => setTimeout(resolve, 2500));
+
+      await program.methods
+        .finalizeVerifierRotation()
+        .accountsStrict({
+          registry: registryPda,
+          authority: payer,
+        })
+        .rpc();
+    });
+
+    it("should reject finalize before min score delay elapses", async () => {
+      const finalize = () =>
+        program.methods
+          .finalizeMinScoreChange()
+          .accountsStrict({
+            registry: registryPda,
+            authority: payer,
+          })
+          .rpc();
+
+      try {
+        await finalize();
+        expect.fail("should have thrown error");
+      } catch (error: any) {
+        expect(error.error.errorCode.code).to.equal("NoMinScoreChangePending");
+      }
+
+      const { minScore } = await program.account.registry.fetch(registryPda);
+      await program.methods
+        .initiateMinScoreChange(minScore.addn(50), new anchor.BN(2))
+        .accountsStrict({
+          registry: registryPda,
+          authority: payer,
+        })
+        .rpc();
+
+      try {
+        await finalize();
+        expect.fail("should have thrown error");
+      } catch (error: any) {
+        expect(error.error.errorCode.code).to.equal("MinScoreChangeNotReady");
+      }
+      expect(
+        (await program.account.registry.fetch(registryPda)).minScore.toNumber()
+      ).to.equal(minScore.toNumber());
+
+      await new Promise((resolve) => setTimeout(resolve, 2500));
+      await finalize();
+
+      const registry = await program.account.registry.fetch(registryPda);
+      expect(registry.minScore.toNumber()).to.equal(minScore.toNumber() + 50);
+      expect(registry.minScoreChangeAvailableAt.toNumber()).to.equal(0);
+
+      await program.methods
+        .updateMinScore(minScore)
+        .accountsStrict({
+          registry: registryPda,
+          authority: payer,
+        })
+        .rpc();
+    });
+
+    it("should expire a verifier rotation left unfinalized", async () => {
+      const setRotationExpiry = (seconds: number) =>
+        program.methods
+          .setRotationExpiry(new anchor.BN(seconds))
+          .accountsStrict({
+            registry: registryPda,
+            authority: payer,
+          })
+          .rpc();
+      const rotate = async (expirySeconds: number, waitMs: number) => {
+        await setRotationExpiry(expirySeconds);
+        await program.methods
+          .initiateVerifierRotation(payer, new anchor.BN(1))
+          .accountsStrict({
+            registry: registryPda,
+            authority: payer,
+          })
+          .rpc();
+        await new Promise((resolve) => setTimeout(resolve, waitMs));
+        return program.methods
+          .finalizeVerifierRotation()
+          .accountsStrict({
+            registry: registryPda,
+            authority: payer,
+          })
+          .rpc();
+      };
+
+      try {
+        // Inside the window: available after 1s, finalizable for 3s more.
+        await rotate(3, 2000);
+
+        try {
+          // Outside it: available after 1s, finalizable for only 1s more.
+          await rotate(1, 4000);
+          expect.fail("should have thrown error");
+        } catch (error: any) {
+          expect(error.error.errorCode.code).to.equal(
+            "VerifierRotationExpired"
+          );
+        }
+        await program.methods
+          .cancelVerifierRotation()
+          .accountsStrict({
+            registry: registryPda,
+            authority: payer,
+          })
+          .rpc();
+      } finally {
+        await setRotationExpiry(0);
+      }
+    });
+
+    it("should cancel a pending verifier rotation", async () => {
+      const rotationCall = (
+        method: "cancelVerifierRotation" | "finalizeVerifierRotation"
+      ) =>
+        program.methods[method]()
+          .accountsStrict({
+            registry: registryPda,
+            authority: payer,
+          })
+          .rpc();
+
+      await program.methods
+        .initiateVerifierRotation(
+          anchor.web3.Keypair.generate().publicKey,
+          new anchor.BN(1)
+        )
+        .accountsStrict({
+          registry: registryPda,
+          authority: payer,
+        })
+        .rpc();
+      await rotationCall("cancelVerifierRotation");
+
+      const registry = await program.account.registry.fetch(registryPda);
+      expect(registry.verifierAuthority.toString()).to.equal(payer.toString());
+      expect(registry.pendingVerifierAuthority.toString()).to.equal(
+        anchor.web3.PublicKey.default.toString()
+      );
+      expect(registry.verifierRotationAvailableAt.toNumber()).to.equal(0);
+
+      await new Promise((resolve) => setTimeout(resolve, 1500));
+      for (const method of [
+        "finalizeVerifierRotation",
+        "cancelVerifierRotation",
+      ] as const) {
+        try {
+          await rotationCall(method);
+          expect.fail("should have thrown error");
+        } catch (error: any) {
+          expect(error.error.errorCode.code).to.equal(
+            "NoVerifierRotationPending"
+          );
+        }
+      }
+    });
+
+    it("should let only the guardian rotate the verifier without delay", async () => {
+      const guardian = anchor.web3.Keypair.generate();
+      const leakedReplacement = anchor.web3.Keypair.generate();
+      const setGuardian = (key: anchor.web3.PublicKey) =>
+        program.methods
+          .setGuardian(key)
+          .accountsStrict({
+            registry: registryPda,
+            authority: payer,
+          })
+          .rpc();
+      const emergencyRotate = (
+        newVerifier: anchor.web3.PublicKey,
+        signer?: anchor.web3.Keypair
+      ) =>
+        program.methods
+          .emergencyRotateVerifier(newVerifier)
+          .accountsStrict({
+            registry: registryPda,
+            guardian: signer?.publicKey ?? payer,
+          })
+          .signers(signer ? [signer] : [])
+          .rpc();
+
+      await setGuardian(guardian.publicKey);
+      await program.methods
+        .initiateVerifierRotation(
+          anchor.web3.Keypair.generate().publicKey,
+          new anchor.BN(3600)
+        )
+        .accountsStrict({
+          registry: registryPda,
+          authority: payer,
+        })
+        .rpc();
+
+      try {
+        await emergencyRotate(leakedReplacement.publicKey);
+        expect.fail("should have thrown error");
+      } catch (error: any) {
+        expect(error.error.errorCode.code).to.equal("Unauthorized");
+      }
+
+      // A leaking key could also sit in the verifier set or the allowlist.
+      await program.methods
+        .initiateVerifierSetChange(
+          [payer, anchor.web3.Keypair.generate().publicKey],
+          2,
+          new anchor.BN(1)
+        )
+        .accountsStrict({
+          registry: registryPda,
+          authority: payer,
+        })
+        .rpc();
+      await new Promise((resolve) => setTimeout(resolve, 2500));
+      await program.methods
+        .finalizeVerifierSetChange()
+        .accountsStrict({
+          registry: registryPda,
+          authority: payer,
+        })
+        .rpc();
+      await program.methods
+        .setVerifierMerkleRoot(Array(32).fill(7))
+        .accountsStrict({
+          registry: registryPda,
+          authority: payer,
+        })
+        .rpc();
+
+      await emergencyRotate(leakedReplacement.publicKey, guardian);
+      let registry = await program.account.registry.fetch(registryPda);
+      expect(registry.verifierAuthority.toString()).to.equal(
+        leakedReplacement.publicKey.toString()
+      );
+      expect(registry.pendingVerifierAuthority.toString()).to.equal(
+        anchor.web3.PublicKey.default.toString()
+      );
+      expect(registry.verifierSet.length).to.equal(0);
+      expect(registry.verifierThreshold).to.equal(0);
+      expect(registry.verifierScheme).to.deep.equal({ ed25519: {} });
+      expect(registry.verifierMerkleRoot).to.deep.equal(Array(32).fill(0));
+
+      await emergencyRotate(payer, guardian);
+      await setGuardian(anchor.web3.PublicKey.default);
+      registry = await program.account.registry.fetch(registryPda);
+      expect(registry.verifierAuthority.toString()).to.equal(payer.toString());
+    });
+
+    it("should rotate verifier authority with delay", async () => {
+      const newVerifier = anchor.web3.Keypair.generate();
+
+      await program.methods
+        .initiateVerifierRotation(newVerifier.publicKey, new anchor.BN(1))
+        .accountsStrict({
+          registry: registryPda,
+          authority: payer,
+        })
+        .rpc();
+
+      await new Promise((resolve) => setTimeout(resolve, 2500));
+
+      await program.methods
+        .finalizeVerifierRotation()
+        .accountsStrict({
+          registry: registryPda,
+          authority: payer,
+        })
+        .rpc();
+
+      const registry = await program.account.registry.fetch(registryPda);
+      expect(registry.verifierAuthority.toString()).to.equal(
+        newVerifier.publicKey.toString()
+      );
+    });
+
+    it("should reject old verifier signature after rotation", async () => {
+      const user = anchor.web3.Keypair.generate();
+      await airdrop(user.publicKey);
+
+      const now = Math.floor(Date.now() / 1000);
+      const proofHash = Buffer.alloc(32, "oldver");
+      const source = { reclaim: {} };
+      const payload = sourceData("reclaim", now);
+      const identityNullifier = identityNullifierFromPayload(source, payload);
+      const nonce = 424242;
+
+      const attestationIx =
+        anchor.web3.Ed25519Program.createInstructionWithPrivateKey({
+          privateKey: verifier.secretKey,
+          message: buildAttestationMessage(
+            user.publicKey,
+            proofHash,
+            source,
+            identityNullifier,
+            nonce,
+            new anchor.BN(150),
+            now
+          ),
+        });
+
+      const userProofPda = deriveUserProofPda(user.publicKey);
+      const individualProofPda = deriveIndividualProofPda(
+        user.publicKey,
+        sourceIndex.reclaim
+      );
+      const identityNullifierRegistryPda = deriveIdentityNullifierPda(
+        sourceIndex.reclaim,
+        identityNullifier
+      );
+      const attestationNonceRegistryPda = deriveAttestationNoncePda(nonce);
+
+      try {
+        await program.methods
+          .submitProof(
+            Array.from(proofHash),
+            source,
+            identityNullifier,
+            new anchor.BN(nonce),
+            payload as any,
+            new anchor.BN(150),
+            new anchor.BN(now),
+            new anchor.BN(0),
+            1,
+            new anchor.BN(0),
+            100,
+            new anchor.BN(0),
+            []
+          )
+          .preInstructions([attestationIx])
+          .accountsStrict({
+            registry: registryPda,
+            userProof: userProofPda,
+            userStatus: deriveUserStatusPda(user.publicKey),
+            individualProof: individualProofPda,
+            identityNullifierRegistry: identityNullifierRegistryPda,
+            proofHashRegistry: deriveProofHashRegistryPda(
+              user.publicKey,
+              proofHash
+            ),
+            attestationNonceRegistry: attestationNonceRegistryPda,
+            scoringConfig: scoringConfigPda,
+            decayConfig: decayConfigPda,
+            instructionsSysvar: anchor.web3.SYSVAR_INSTRUCTIONS_PUBKEY,
+            slotHashes: anchor.web3.SYSVAR_SLOT_HASHES_PUBKEY,
+            feeDestination: null,
+            statusCache: null,
+            user: user.publicKey,
+            payer: user.publicKey,
+            systemProgram: anchor.web3.SystemProgram.programId,
+          })
+          .signers([user])
+          .rpc();
+
+        expect.fail("should have thrown error");
+      } catch (error: any) {
+        expect(error.error.errorCode.code).to.equal(
+          "InvalidAttestationMessage"
+        );
+      }
+
+      await program.methods
+        .initiateVerifierRotation(payer, new anchor.BN(1))
+        .accountsStrict({
+          registry: registryPda,
+          authority: payer,
+        })
+        .rpc();
+
+      await new Promise((resolve) => setTimeout(resolve, 2500));
+
+      await program.methods
+        .finalizeVerifierRotation()
+        .accountsStrict({
+          registry: registryPda,
+          authority: payer,
+        })
+        .rpc();
+    });
+
+    it("should require M-of-N verifier attestations", async () => {
+      const secondVerifier = anchor.web3.Keypair.generate();
+      const changeVerifierSet = async (
+        verifiers: anchor.web3.PublicKey[],
+        threshold: number
+      ) => {
+        await program.methods
+          .initiateVerifierSetChange(verifiers, threshold, new anchor.BN(1))
+          .accountsStrict({
+            registry: registryPda,
+            authority: payer,
+          })
+          .rpc();
+        await new Promise((resolve) => setTimeout(resolve, 2500));
+        await program.methods
+          .finalizeVerifierSetChange()
+          .accountsStrict({
+            registry: registryPda,
+            authority: payer,
+          })
+          .rpc();
+      };
+
+      await program.methods
+        .initiateVerifierSetChange(
+          [payer, secondVerifier.publicKey],
+          2,
+          new anchor.BN(3600)
+        )
+        .accountsStrict({
+          registry: registryPda,
+          authority: payer,
+        })
+        .rpc();
+      const pending = await program.account.registry.fetch(registryPda);
+      expect(pending.verifierSet.length).to.equal(0);
+      expect(pending.pendingVerifierSet.length).to.equal(2);
+      try {
+        await program.methods
+          .finalizeVerifierSetChange()
+          .accountsStrict({
+            registry: registryPda,
+            authority: payer,
+          })
+          .rpc();
+        expect.fail("should have thrown error");
+      } catch (error: any) {
+        expect(error.error.errorCode.code).to.equal(
+          "VerifierRotationNotReady"
+        );
+      }
+
+      await changeVerifierSet([payer, secondVerifier.publicKey], 2);
+
+      const now = Math.floor(Date.now() / 1000);
+      const user = anchor.web3.Keypair.generate();
+      await airdrop(user.publicKey);
+
+      try {
+        await submitProof(
+          user,
+          Buffer.alloc(32, "mofn1"),
+          { reclaim: {} },
+          sourceData("reclaim", now),
+          new anchor.BN(150),
+          now
+        );
+        expect.fail("should have thrown error");
+      } catch (error: any) {
+        expect(error.error.errorCode.code).to.equal(
+          "InvalidAttestationMessage"
+        );
+      }
+
+      try {
+        await submitProof(
+          user,
+          Buffer.alloc(32, "mofn2"),
+          { reclaim: {} },
+          sourceData("reclaim", now),
+          new anchor.BN(150),
+          now,
+          { verifiers: [verifier, verifier] }
+        );
+        expect.fail("should have thrown error");
+      } catch (error: any) {
+        expect(error.error.errorCode.code).to.equal(
+          "InvalidAttestationMessage"
+        );
+      }
+
+      const { userProofPda } = await submitProof(
+        user,
+        Buffer.alloc(32, "mofn3"),
+        { reclaim: {} },
+        sourceData("reclaim", now),
+        new anchor.BN(150),
+        now,
+        { verifiers: [verifier, secondVerifier] }
+      );
+      const userProof = await program.account.userProof.fetch(userProofPda);
+      expect(userProof.activeSourceCount).to.equal(1);
+
+      await changeVerifierSet([], 1);
+
+      const registry = await program.account.registry.fetch(registryPda);
+      expect(registry.verifierSet.length).to.equal(0);
+      expect(registry.verifierSetChangeAvailableAt.toNumber()).to.equal(0);
+    });
+
+    it("should reject verifier set with duplicate signers", async () => {
+      try {
+        await program.methods
+          .initiateVerifierSetChange([payer, payer], 2, new anchor.BN(1))
+          .accountsStrict({
+            registry: registryPda,
+            authority: payer,
+          })
+          .rpc();
+        expect.fail("should have thrown error");
+      } catch (error: any) {
+        expect(error.error.errorCode.code).to.equal("InvalidConfig");
+      }
+    });
+
+    it("should sign the same bytes as the on-chain message builder", async () => {
+      // Produced by the crate's `attestation::build_attestation_message`.
+      const fixture = JSON.parse(
+        fs.readFileSync("tests/fixtures/attestation-message.json", "utf8")
+      );
+      const message = buildAttestationMessage(
+        new anchor.web3.PublicKey(Buffer.from(fixture.user, "hex")),
+        Buffer.from(fixture.proofHash, "hex"),
+        { [fixture.source]: {} },
+        Array.from(Buffer.from(fixture.identityNullifier, "hex")),
+        fixture.attestationNonce,
+        new anchor.BN(fixture.baseScore),
+        fixture.timestamp,
+        {
+          slot: fixture.slotHash.slot,
+          hash: Buffer.from(fixture.slotHash.hash, "hex"),
+        },
+        fixture.attestationVersion,
+        fixture.expiresAt
+      );
+      expect(message.toString("hex")).to.equal(fixture.message);
+
+      // The same layout, signed for a live user, is accepted by submit_proof.
+      const user = anchor.web3.Keypair.generate();
+      await airdrop(user.publicKey);
+      const now = Math.floor(Date.now() / 1000);
+      const { individualProofPda } = await submitProof(
+        user,
+        Buffer.alloc(32, "layout"),
+        { gitcoinPassport: {} },
+        sourceData("gitcoin", now),
+        new anchor.BN(150),
+        now,
+        {
+          attestation: (signed) => [
+            anchor.web3.Ed25519Program.createInstructionWithPrivateKey({
+              privateKey: verifier.secretKey,
+              message: signed,
+            }),
+          ],
+        }
+      );
+      const individualProof = await program.account.individualProof.fetch(
+        individualProofPda
+      );
+      expect(individualProof.baseScore.toNumber()).to.equal(150);
+    });
+
+    it("should verify digest attestations in hashed mode", async () => {
+      const fixture = JSON.parse(
+        fs.readFileSync("tests/fixtures/attestation-message.json", "utf8")
+      );
+      const sha256 = (message: Buffer) =>
+        createHash("sha256").update(message).digest();
+      expect(
+        sha256(Buffer.from(fixture.message, "hex")).toString("hex")
+      ).to.equal(fixture.digest);
+
+      const setHashedAttestations = (enabled: boolean) =>
+        program.methods
+          .setHashedAttestations(enabled)
+          .accountsStrict({
+            registry: registryPda,
+            authority: payer,
+          })
+          .rpc();
+      const signed = (message: Buffer) => [
+        anchor.web3.Ed25519Program.createInstructionWithPrivateKey({
+          privateKey: verifier.secretKey,
+          message,
+        }),
+      ];
+
+      const user = anchor.web3.Keypair.generate();
+      await airdrop(user.publicKey);
+      const now = Math.floor(Date.now() / 1000);
+      await setHashedAttestations(true);
+      try {
+        try {
+          await submitProof(
+            user,
+            Buffer.alloc(32, "fullmessage"),
+            { gitcoinPassport: {} },
+            sourceData("gitcoin", now),
+            new anchor.BN(150),
+            now,
+            { attestation: signed }
+          );
+          expect.fail("should have thrown error");
+        } catch (error: any) {
+          expect(error.error.errorCode.code).to.equal(
+            "InvalidAttestationMessage"
+          );
+        }
+
+        const { individualProofPda } = await submitProof(
+          user,
+          Buffer.alloc(32, "digestmessage"),
+          { gitcoinPassport: {} },
+          sourceData("gitcoin", now),
+          new anchor.BN(150),
+          now,
+          { attestation: (message) => signed(sha256(message)) }
+        );
+        const individualProof = await program.account.individualProof.fetch(
+          individualProofPda
+        );
+        expect(individualProof.baseScore.toNumber()).to.equal(150);
+      } finally {
+        await setHashedAttestations(false);
+      }
+    });
+
+    it("should reject an attestation signed for another registry", async () => {
+      const user = anchor.web3.Keypair.generate();
+      await airdrop(user.publicKey);
+
+      const now = Math.floor(Date.now() / 1000);
+      const otherRegistry = anchor.web3.Keypair.generate().publicKey;
+      try {
+        await submitProof(
+          user,
+          Buffer.alloc(32, "xregistry"),
+          { worldId: {} },
+          sourceData("worldId", now),
+          new anchor.BN(150),
+          now,
+          {
+            attestation: (message) => {
+              const foreign = Buffer.from(message);
+              otherRegistry.toBuffer().copy(foreign, 36);
+              return [
+                anchor.web3.Ed25519Program.createInstructionWithPrivateKey({
+                  privateKey: verifier.secretKey,
+                  message: foreign,
+                }),
+              ];
+            },
+          }
+        );
+        expect.fail("should have thrown error");
+      } catch (error: any) {
+        expect(error.error.errorCode.code).to.equal(
+          "InvalidAttestationMessage"
+        );
+      }
+    });
+
+    it("should bind the attestation to the submitting instruction", async () => {
+      const user = anchor.web3.Keypair.generate();
+      await airdrop(user.publicKey);
+      const now = Math.floor(Date.now() / 1000);
+      const { userProofPda } = await submitProof(
+        user,
+        Buffer.alloc(32, "frontrun0"),
+        { worldId: {} },
+        sourceData("worldId", now),
+        new anchor.BN(150),
+        now
+      );
+      const signed = (message: Buffer) =>
+        anchor.web3.Ed25519Program.createInstructionWithPrivateKey({
+          privateKey: verifier.secretKey,
+          message,
+        });
+
+      // Anything between the attestation and the submission breaks the binding.
+      try {
+        await submitProof(
+          user,
+          Buffer.alloc(32, "frontrun1"),
+          { reclaim: {} },
+          sourceData("reclaim", now),
+          new anchor.BN(150),
+          now,
+          {
+            attestation: (message) => [
+              signed(message),
+              anchor.web3.SystemProgram.transfer({
+                fromPubkey: user.publicKey,
+                toPubkey: user.publicKey,
+                lamports: 0,
+              }),
+            ],
+          }
+        );
+        expect.fail("should have thrown error");
+      } catch (error: any) {
+        expect(error.error.errorCode.code).to.equal(
+          "InvalidAttestationInstruction"
+        );
+      }
+
+      // So does another instruction touching the same user's accounts.
+      const verifyIx = await program.methods
+        .verifyProof()
+        .accountsStrict({
+          userProof: userProofPda,
+          userStatus: deriveUserStatusPda(user.publicKey),
+          registry: registryPda,
+          scoringConfig: scoringConfigPda,
+          decayConfig: decayConfigPda,
+          user: user.publicKey,
+        })
+        .instruction();
+      try {
+        await submitProof(
+          user,
+          Buffer.alloc(32, "frontrun2"),
+          { reclaim: {} },
+          sourceData("reclaim", now),
+          new anchor.BN(150),
+          now,
+          { attestation: (message) => [verifyIx, signed(message)] }
+        );
+        expect.fail("should have thrown error");
+      } catch (error: any) {
+        expect(error.error.errorCode.code).to.equal(
+          "ConflictingUserInstruction"
+        );
+      }
+    });
+
+    it("should accept attestations from allowlisted verifier keys", async () => {
+      const sha256 = (...parts: Buffer[]) =>
+        createHash("sha256").update(Buffer.concat(parts)).digest();
+      const leaf = (key: anchor.web3.PublicKey) =>
+        sha256(Buffer.from([0]), key.toBuffer());
+      const node = (a: Buffer, b: Buffer) =>
+        Buffer.compare(a, b) <= 0
+          ? sha256(Buffer.from([1]), a, b)
+          : sha256(Buffer.from([1]), b, a);
+      const setRoot = (root: Buffer) =>
+        program.methods
+          .setVerifierMerkleRoot(Array.from(root))
+          .accountsStrict({
+            registry: registryPda,
+            authority: payer,
+          })
+          .rpc();
+
+      const regional = anchor.web3.Keypair.generate();
+      const leaves = [
+        regional.publicKey,
+        anchor.web3.Keypair.generate().publicKey,
+        anchor.web3.Keypair.generate().publicKey,
+        anchor.web3.Keypair.generate().publicKey,
+      ].map(leaf);
+      const left = node(leaves[0], leaves[1]);
+      const right = node(leaves[2], leaves[3]);
+      const verifierProof = [leaves[1], right].map((sibling) =>
+        Array.from(sibling)
+      );
+
+      await setRoot(node(left, right));
+
+      const now = Math.floor(Date.now() / 1000);
+      const user = anchor.web3.Keypair.generate();
+      await airdrop(user.publicKey);
+
+      const { userProofPda } = await submitProof(
+        user,
+        Buffer.alloc(32, "merkle1"),
+        { reclaim: {} },
+        sourceData("reclaim", now),
+        new anchor.BN(150),
+        now,
+        { verifiers: [regional], verifierProof }
+      );
+      const userProof = await program.account.userProof.fetch(userProofPda);
+      expect(userProof.activeSourceCount).to.equal(1);
+
+      try {
+        await submitProof(
+          user,
+          Buffer.alloc(32, "merkle2"),
+          { worldId: {} },
+          sourceData("worldId", now),
+          new anchor.BN(150),
+          now,
+          {
+            verifiers: [regional],
+            verifierProof: [Array.from(leaves[2]), Array.from(left)],
+          }
+        );
+        expect.fail("should have thrown error");
+      } catch (error: any) {
+        expect(error.error.errorCode.code).to.equal("InvalidVerifierProof");
+      }
+
+      // Rotating to a tree without the regional key revokes it.
+      await setRoot(node(leaves[2], leaves[3]));
+      try {
+        await submitProof(
+          user,
+          Buffer.alloc(32, "merkle3"),
+          { worldId: {} },
+          sourceData("worldId", now),
+          new anchor.BN(150),
+          now,
+          { verifiers: [regional], verifierProof }
+        );
+        expect.fail("should have thrown error");
+      } catch (error: any) {
+        expect(error.error.errorCode.code).to.equal("InvalidVerifierProof");
+      }
+
+      await setRoot(Buffer.alloc(32));
+      const registry = await program.account.registry.fetch(registryPda);
+      expect(registry.verifierMerkleRoot).to.deep.equal(Array(32).fill(0));
+    });
+
+    it("should reject a stale slot hash when binding is enabled", async () => {
+      const setSlotHashBinding = (enabled: boolean, maxAge: number) =>
+        program.methods
+          .setSlotHashBinding(enabled, new anchor.BN(maxAge))
+          .accountsStrict({
+            registry: registryPda,
+            authority: payer,
+          })
+          .rpc();
+      const latestSlotHash = async () => {
+        const info = await provider.connection.getAccountInfo(
+          anchor.web3.SYSVAR_SLOT_HASHES_PUBKEY
+        );
+        return {
+          slot: Number(info!.data.readBigUInt64LE(8)),
+          hash: Buffer.from(info!.data.subarray(16, 48)),
+        };
+      };
+
+      await setSlotHashBinding(true, 20);
+
+      const user = anchor.web3.Keypair.generate();
+      await airdrop(user.publicKey);
+      const now = Math.floor(Date.now() / 1000);
+
+      await submitProof(
+        user,
+        Buffer.alloc(32, "slot1"),
+        { reclaim: {} },
+        sourceData("reclaim", now),
+        new anchor.BN(150),
+        now,
+        { slotHash: await latestSlotHash() }
+      );
+
+      const staleSlotHash = await latestSlotHash();
+      await new Promise((resolve) => setTimeout(resolve, 12000));
+
+      try {
+        await submitProof(
+          user,
+          Buffer.alloc(32, "slot2"),
+          { worldId: {} },
+          sourceData("worldId", now),
+          new anchor.BN(150),
+          now,
+          { slotHash: staleSlotHash }
+        );
+        expect.fail("should have thrown error");
+      } catch (error: any) {
+        expect(error.error.errorCode.code).to.equal("StaleSlotHash");
+      }
+
+      await setSlotHashBinding(false, 0);
+    });
+
+    it("should accept secp256k1 verifier attestations", async () => {
+      const secpKey = anchor.web3.Keypair.generate().secretKey.slice(0, 32);
+      const secpAttestation = (message: Buffer) => [
+        anchor.web3.Secp256k1Program.createInstructionWithPrivateKey({
+          privateKey: secpKey,
+          message,
+        }),
+      ];
+      // The eth address sits right after the 12-byte offsets header.
+      const ethAddress = Array.from(
+        secpAttestation(Buffer.from("probe"))[0].data.subarray(12, 32)
+      );
+
+      await program.methods
+        .setVerifierScheme({ secp256k1: {} }, ethAddress)
+        .accountsStrict({
+          registry: registryPda,
+          authority: payer,
+        })
+        .rpc();
+
+      const now = Math.floor(Date.now() / 1000);
+      const user = anchor.web3.Keypair.generate();
+      await airdrop(user.publicKey);
+
+      try {
+        await submitProof(
+          user,
+          Buffer.alloc(32, "secp0"),
+          { reclaim: {} },
+          sourceData("reclaim", now),
+          new anchor.BN(150),
+          now,
+          {
+            attestation: () => [
+              new anchor.web3.TransactionInstruction({
+                programId: anchor.web3.Secp256k1Program.programId,
+                keys: [],
+                data: Buffer.from([0]),
+              }),
+            ],
+          }
+        );
+        expect.fail("should have thrown error");
+      } catch (error: any) {
+        expect(error.error.errorCode.code).to.equal(
+          "InvalidAttestationInstruction"
+        );
+      }
+
+      const { userProofPda } = await submitProof(
+        user,
+        Buffer.alloc(32, "secp1"),
+        { reclaim: {} },
+        sourceData("reclaim", now),
+        new anchor.BN(150),
+        now,
+        { attestation: secpAttestation }
+      );
+      const userProof = await program.account.userProof.fetch(userProofPda);
+      expect(userProof.activeSourceCount).to.equal(1);
+
+      await program.methods
+        .setVerifierScheme({ ed25519: {} }, Array(20).fill(0))
+        .accountsStrict({
+          registry: registryPda,
+          authority: payer,
+        })
+        .rpc();
+    });
+
+    it("should reject unauthorized update", async () => {
+      const unauthorized = anchor.web3.Keypair.generate();
+      try {
+        await program.methods
+          .updateMinScore(new anchor.BN(300))
+          .accountsStrict({
+            registry: registryPda,
+            authority: unauthorized.publicKey,
+          })
+          .signers([unauthorized])
+          .rpc();
+        expect.fail("should have thrown error");
+      } catch (error: any) {
+        expect(error.error.errorCode.code).to.equal("Unauthorized");
+      }
+    });
+  });
+
+  describe("Complex Scenarios", () => {
+    it("should apply diversity bonus with multiple sources", async () => {
+      const user = anchor.web3.Keypair.generate();
+      await airdrop(user.publicKey);
+      const now = Math.floor(Date.now() / 1000);
+
+      const userProofPda = deriveUserProofPda(user.publicKey);
+
+      await submitProof(
+        user,
+        Buffer.from(Uint8Array.from([100, ...Array(31).fill(0)])),
+        { reclaim: {} },
+        sourceData("reclaim", now),
+        new anchor.BN(100),
+        now
+      );
+
+      await submitProof(
+        user,
+        Buffer.from(Uint8Array.from([101, ...Array(31).fill(0)])),
+        { gitcoinPassport: {} },
+        sourceData("gitcoin", now, 100),
+        new anchor.BN(100),
+        now
+      );
+
+      const userProof = await program.account.userProof.fetch(userProofPda);
+      expect(userProof.activeSourceCount).to.equal(2);
+      expect(userProof.aggregatedScore.toNumber()).to.be.greaterThan(200);
+    });
+
+    it("should preview the same diversity bonus as a submission", async () => {
+      const user = anchor.web3.Keypair.generate();
+      await airdrop(user.publicKey);
+      const now = Math.floor(Date.now() / 1000);
+
+      const reclaim = await submitProof(
+        user,
+        Buffer.alloc(32, "preview1"),
+        { reclaim: {} },
+        sourceData("reclaim", now),
+        new anchor.BN(120),
+        now
+      );
+      const worldId = await submitProof(
+        user,
+        Buffer.alloc(32, "preview2"),
+        { worldId: {} },
+        sourceData("worldId", now),
+        new anchor.BN(130),
+        now
+      );
+
+      const proofs = await Promise.all(
+        [reclaim, worldId].map(({ individualProofPda }) =>
+          program.account.individualProof.fetch(individualProofPda)
+        )
+      );
+      const baseScore = proofs.reduce(
+        (sum, proof) => sum.add(proof.weightedScore),
+        new anchor.BN(0)
+      );
+
+      const preview = await program.methods
+        .previewDiversityBonus(baseScore, 2)
+        .accountsStrict({
+          registry: registryPda,
+          scoringConfig: scoringConfigPda,
+        })
+        .view();
+      const userProof = await program.account.userProof.fetch(
+        reclaim.userProofPda
+      );
+
+      expect(preview.toNumber()).to.equal(userProof.aggregatedScore.toNumber());
+      expect(preview.toNumber()).to.be.greaterThan(baseScore.toNumber());
+    });
+
+    it("should apply the cross-ecosystem bonus with both sides", async () => {
+      const setCrossBonusPercent = async (pct: number) => {
+        // The default masks put Reclaim on the Web2 side and Gitcoin Passport and
+        // World ID on the Web3 side.
+        const registry = await program.account.registry.fetch(registryPda);
+        await program.methods
+          .setCrossEcosystemBonus(registry.web2Mask, registry.web3Mask, pct)
+          .accountsStrict({
+            registry: registryPda,
+            authority: payer,
+          })
+          .rpc();
+      };
+      await setCrossBonusPercent(10);
+
+      const now = Math.floor(Date.now() / 1000);
+      // Pairs Gitcoin Passport with a source from either side.
+      const submitPair = async (tag: string, first: "reclaim" | "worldId") => {
+        const user = anchor.web3.Keypair.generate();
+        await airdrop(user.publicKey);
+        const one = await submitProof(
+          user,
+          Buffer.alloc(32, `${tag}1`),
+          first === "reclaim" ? { reclaim: {} } : { worldId: {} },
+          sourceData(first, now),
+          new anchor.BN(100),
+          now
+        );
+        const other = await submitProof(
+          user,
+          Buffer.alloc(32, `${tag}2`),
+          { gitcoinPassport: {} },
+          sourceData("gitcoin", now),
+          new anchor.BN(150),
+          now
+        );
+        const proofs = await Promise.all(
+          [one, other].map(({ individualProofPda }) =>
+            program.account.individualProof.fetch(individualProofPda)
+          )
+        );
+        const baseScore = proofs.reduce(
+          (sum, proof) => sum.add(proof.weightedScore),
+          new anchor.BN(0)
+        );
+        const diversityScore = await program.methods
+          .previewDiversityBonus(baseScore, 2)
+          .accountsStrict({
+            registry: registryPda,
+            scoringConfig: scoringConfigPda,
+          })
+          .view();
+        const userProof = await program.account.userProof.fetch(
+          one.userProofPda
+        );
+        return { user, other, proofs, diversityScore, userProof };
+      };
+
+      const oneSide = await submitPair("crossA", "worldId");
+      expect(oneSide.userProof.crossEcosystemBonusApplied).to.equal(false);
+      expect(oneSide.userProof.aggregatedScore.toNumber()).to.equal(
+        oneSide.diversityScore.toNumber()
+      );
+
+      const bothSides = await submitPair("crossB", "reclaim");
+      expect(bothSides.userProof.crossEcosystemBonusApplied).to.equal(true);
+      expect(bothSides.userProof.sourcesMask).to.equal(0b011);
+      expect(bothSides.userProof.aggregatedScore.toNumber()).to.equal(
+        Math.floor((bothSides.diversityScore.toNumber() * 110) / 100)
+      );
+
+      // Revoking the Web3 side strips both bonuses back to the Reclaim score.
+      await program.methods
+        .revokeProof({ gitcoinPassport: {} })
+        .accountsStrict({
+          registry: registryPda,
+          userProof: bothSides.other.userProofPda,
+          individualProof: bothSides.other.individualProofPda,
+          identityNullifierRegistry:
+            bothSides.other.identityNullifierRegistryPda,
+          scoringConfig: scoringConfigPda,
+          decayConfig: decayConfigPda,
+          userStatus: deriveUserStatusPda(bothSides.user.publicKey),
+          statusCache: null,
+          user: bothSides.user.publicKey,
+        })
+        .signers([bothSides.user])
+        .rpc();
+      const revoked = await program.account.userProof.fetch(
+        bothSides.other.userProofPda
+      );
+      expect(revoked.crossEcosystemBonusApplied).to.equal(false);
+      expect(revoked.aggregatedScore.toNumber()).to.equal(
+        bothSides.proofs[0].weightedScore.toNumber()
+      );
+
+      await setCrossBonusPercent(0);
+    });
+
+    const attestWalletAge = async (
+      user: anchor.web3.PublicKey,
+      firstSeen: number,
+      options: { nonce?: number; sequentialNonce?: boolean } = {}
+    ) => {
+      const now = Math.floor(Date.now() / 1000);
+      const nonce = options.nonce ?? attestationNonce++;
+      const firstSeenBuf = Buffer.alloc(8);
+      firstSeenBuf.writeBigInt64LE(BigInt(firstSeen));
+      const nonceBuf = Buffer.alloc(8);
+      nonceBuf.writeBigUInt64LE(BigInt(nonce));
+      const tsBuf = Buffer.alloc(8);
+      tsBuf.writeBigInt64LE(BigInt(now));
+      const message = Buffer.concat([
+        Buffer.from("sidw"),
+        program.programId.toBuffer(),
+        registryPda.toBuffer(),
+        user.toBuffer(),
+        firstSeenBuf,
+        nonceBuf,
+        tsBuf,
+      ]);
+      await program.methods
+        .attestWalletAge(
+          new anchor.BN(firstSeen),
+          new anchor.BN(nonce),
+          new anchor.BN(now)
+        )
+        .preInstructions([
+          anchor.web3.Ed25519Program.createInstructionWithPrivateKey({
+            privateKey: verifier.secretKey,
+            message,
+          }),
+        ])
+        .accountsStrict({
+          registry: registryPda,
+          userProof: deriveUserProofPda(user),
+          attestationNonceRegistry: options.sequentialNonce
+            ? null
+            : deriveAttestationNoncePda(nonce),
+          scoringConfig: scoringConfigPda,
+          user,
+          instructionsSysvar: anchor.web3.SYSVAR_INSTRUCTIONS_PUBKEY,
+          payer,
+          systemProgram: anchor.web3.SystemProgram.programId,
+        })
+        .rpc();
+      return program.account.userProof.fetch(deriveUserProofPda(user));
+    };
+
+    it("should only give the wallet-age bonus to old wallets", async () => {
+      const setWalletAgeBonus = (minAge: number, pct: number) =>
+        program.methods
+          .setWalletAgeBonus(new anchor.BN(minAge), pct)
+          .accountsStrict({
+            registry: registryPda,
+            authority: payer,
+          })
+          .rpc();
+      const day = 24 * 60 * 60;
+      await setWalletAgeBonus(30 * day, 20);
+
+      const now = Math.floor(Date.now() / 1000);
+      const submitOne = async (tag: string) => {
+        const user = anchor.web3.Keypair.generate();
+        await airdrop(user.publicKey);
+        const { userProofPda } = await submitProof(
+          user,
+          Buffer.alloc(32, tag),
+          { reclaim: {} },
+          sourceData("reclaim", now),
+          new anchor.BN(100),
+          now
+        );
+        const userProof = await program.account.userProof.fetch(userProofPda);
+        return { user, baseScore: userProof.aggregatedScore.toNumber() };
+      };
+
+      const young = await submitOne("walletAge1");
+      const youngProof = await attestWalletAge(young.user.publicKey, now - day);
+      expect(youngProof.walletAgeBonusApplied).to.equal(false);
+      expect(youngProof.aggregatedScore.toNumber()).to.equal(young.baseScore);
+
+      const old = await submitOne("walletAge2");
+      const oldProof = await attestWalletAge(
+        old.user.publicKey,
+        now - 400 * day
+      );
+      expect(oldProof.walletAgeBonusApplied).to.equal(true);
+      expect(oldProof.aggregatedScore.toNumber()).to.equal(
+        Math.floor((old.baseScore * 120) / 100)
+      );
+
+      try {
+        await attestWalletAge(old.user.publicKey, now + day);
+        expect.fail("should have thrown error");
+      } catch (error: any) {
+        expect(error.error.errorCode.code).to.equal("InvalidWalletFirstSeen");
+      }
+
+      await setWalletAgeBonus(0, 0);
+    });
+
+    it("should apply pause and nonce mode to attested instructions", async () => {
+      const setPaused = (paused: boolean) =>
+        program.methods
+          .setPaused(paused)
+          .accountsStrict({
+            registry: registryPda,
+            authority: payer,
+          })
+          .rpc();
+      const setSequentialNonces = (enabled: boolean) =>
+        program.methods
+          .setSequentialNonces(enabled)
+          .accountsStrict({
+            registry: registryPda,
+            authority: payer,
+          })
+          .rpc();
+      const expectRejected = async (
+        promise: Promise<unknown>,
+        code: string
+      ) => {
+        try {
+          await promise;
+          expect.fail("should have thrown error");
+        } catch (error: any) {
+          expect(error.error.errorCode.code).to.equal(code);
+        }
+      };
+
+      const user = anchor.web3.Keypair.generate();
+      await airdrop(user.publicKey);
+      const now = Math.floor(Date.now() / 1000);
+      const firstSeen = now - 24 * 60 * 60;
+      await submitProof(
+        user,
+        Buffer.alloc(32, "attestedModes"),
+        { reclaim: {} },
+        sourceData("reclaim", now),
+        new anchor.BN(100),
+        now
+      );
+
+      await setPaused(true);
+      try {
+        await expectRejected(
+          attestWalletAge(user.publicKey, firstSeen),
+          "RegistryPaused"
+        );
+      } finally {
+        await setPaused(false);
+      }
+
+      await setSequentialNonces(true);
+      try {
+        await expectRejected(
+          attestWalletAge(user.publicKey, firstSeen),
+          "NonceModeMismatch"
+        );
+        await expectRejected(
+          attestWalletAge(user.publicKey, firstSeen, {
+            nonce: 2,
+            sequentialNonce: true,
+          }),
+          "AttestationNonceOutOfOrder"
+        );
+        const userProof = await attestWalletAge(user.publicKey, firstSeen, {
+          nonce: 1,
+          sequentialNonce: true,
+        });
+        expect(userProof.lastNonce.toNumber()).to.equal(1);
+        expect(userProof.walletFirstSeen.toNumber()).to.equal(firstSeen);
+      } finally {
+        await setSequentialNonces(false);
+      }
+    });
+
+    const submitProofsBatch = async (
+      user: anchor.web3.Keypair,
+      submissions: {
+        proofHash: Buffer;
+        source: any;
+        proofData: any;
+        identityNullifier?: number[];
+        baseScore: anchor.BN;
+        timestamp: number;
+      }[],
+      statusCache: anchor.web3.PublicKey | null = null
+    ) => {
+      const entries = submissions.map((submission) => ({
+        ...submission,
+        identityNullifier:
+          submission.identityNullifier ??
+          identityNullifierFromPayload(submission.source, submission.proofData),
+        nonce: attestationNonce++,
+      }));
+
+      return program.methods
+        .submitProofsBatch(
+          entries.map((entry) => ({
+            proofHash: Array.from(entry.proofHash),
+            source: entry.source,
+            identityNullifier: entry.identityNullifier,
+            attestationNonce: new anchor.BN(entry.nonce),
+            proofData: entry.proofData,
+            baseScore: entry.baseScore,
+            timestamp: new anchor.BN(entry.timestamp),
+            recentSlot: new anchor.BN(0),
+            attestationVersion: 1,
+            expiresAt: new anchor.BN(0),
+            trustMultiplier: 100,
+            weightOverride: new anchor.BN(0),
+          }))
+        )
+        .preInstructions(
+          entries.map((entry) =>
+            anchor.web3.Ed25519Program.createInstructionWithPrivateKey({
+              privateKey: verifier.secretKey,
+              message: buildAttestationMessage(
+                user.publicKey,
+                entry.proofHash,
+                entry.source,
+                entry.identityNullifier,
+                entry.nonce,
+                entry.baseScore,
+                entry.timestamp
+              ),
+            })
+          )
+        )
+        .accountsStrict({
+          registry: registryPda,
+          userProof: deriveUserProofPda(user.publicKey),
+          userStatus: deriveUserStatusPda(user.publicKey),
+          scoringConfig: scoringConfigPda,
+          decayConfig: decayConfigPda,
+          instructionsSysvar: anchor.web3.SYSVAR_INSTRUCTIONS_PUBKEY,
+          slotHashes: anchor.web3.SYSVAR_SLOT_HASHES_PUBKEY,
+          feeDestination: null,
+          statusCache,
+          user: user.publicKey,
+          systemProgram: anchor.web3.SystemProgram.programId,
+        })
+        .remainingAccounts(
+          entries.flatMap((entry) =>
+            [
+              deriveIndividualProofPda(
+                user.publicKey,
+                sourceToIndex(entry.source)
+              ),
+              deriveIdentityNullifierPda(
+                sourceToIndex(entry.source),
+                entry.identityNullifier
+              ),
+              deriveProofHashRegistryPda(user.publicKey, entry.proofHash),
+              deriveAttestationNoncePda(entry.nonce),
+            ].map((pubkey) => ({ pubkey, isSigner: false, isWritable: true }))
+          )
+        )
+        .signers([user])
+        .rpc();
+    };
+
+    it("should submit a batch and apply the diversity bonus once", async () => {
+      const user = anchor.web3.Keypair.generate();
+      await airdrop(user.publicKey);
+      const now = Math.floor(Date.now() / 1000);
+
+      await submitProofsBatch(user, [
+        {
+          proofHash: Buffer.alloc(32, "batch1"),
+          source: { reclaim: {} },
+          proofData: sourceData("reclaim", now),
+          baseScore: new anchor.BN(120),
+          timestamp: now,
+        },
+        {
+          proofHash: Buffer.alloc(32, "batch2"),
+          source: { worldId: {} },
+          proofData: sourceData("worldId", now),
+          baseScore: new anchor.BN(130),
+          timestamp: now,
+        },
+      ]);
+
+      const proofs = await Promise.all(
+        [sourceIndex.reclaim, sourceIndex.worldId].map((index) =>
+          program.account.individualProof.fetch(
+            deriveIndividualProofPda(user.publicKey, index)
+          )
+        )
+      );
+      const baseScore = proofs.reduce(
+        (sum, proof) => sum + proof.weightedScore.toNumber(),
+        0
+      );
+      const registry = await program.account.registry.fetch(registryPda);
+      const userProof = await program.account.userProof.fetch(
+        deriveUserProofPda(user.publicKey)
+      );
+
+      expect(userProof.activeSourceCount).to.equal(2);
+      expect(userProof.bonusApplied).to.equal(true);
+      expect(userProof.aggregatedScore.toNumber()).to.equal(
+        baseScore +
+          Math.floor((baseScore * registry.diversityBonusPercent) / 100)
+      );
+    });
+
+    it("should refresh the status cache after a batch", async () => {
+      const user = anchor.web3.Keypair.generate();
+      await airdrop(user.publicKey);
+      const now = Math.floor(Date.now() / 1000);
+      const [statusCachePda] = anchor.web3.PublicKey.findProgramAddressSync(
+        [Buffer.from("status"), user.publicKey.toBuffer()],
+        program.programId
+      );
+      const { userProofPda } = await submitProof(
+        user,
+        Buffer.alloc(32, "batchCache1"),
+        { reclaim: {} },
+        sourceData("reclaim", now),
+        new anchor.BN(150),
+        now
+      );
+      const accounts = {
+        userProof: userProofPda,
+        userStatus: deriveUserStatusPda(user.publicKey),
+        registry: registryPda,
+        scoringConfig: scoringConfigPda,
+        decayConfig: decayConfigPda,
+        user: user.publicKey,
+      };
+      await program.methods
+        .enableStatusCache()
+        .accountsStrict({
+          statusCache: statusCachePda,
+          ...accounts,
+          systemProgram: anchor.web3.SystemProgram.programId,
+        })
+        .signers([user])
+        .rpc();
+      const enabled = await program.account.statusCache.fetch(statusCachePda);
+
+      await submitProofsBatch(
+        user,
+        [
+          {
+            proofHash: Buffer.alloc(32, "batchCache2"),
+            source: { worldId: {} },
+            proofData: sourceData("worldId", now),
+            baseScore: new anchor.BN(130),
+            timestamp: now,
+          },
+        ],
+        statusCachePda
+      );
+
+      const cache = await program.account.statusCache.fetch(statusCachePda);
+      const status = await program.methods
+        .verifyProof()
+        .accountsStrict(accounts)
+        .view();
+      expect(cache.liveScore.toNumber()).to.be.greaterThan(
+        enabled.liveScore.toNumber()
+      );
+      expect(cache.liveScore.toNumber()).to.equal(status.liveScore.toNumber());
+      expect(cache.isVerified).to.equal(status.isVerified);
+    });
+
+    it("should submit a batch to PDAs someone already funded", async () => {
+      const user = anchor.web3.Keypair.generate();
+      await airdrop(user.publicKey);
+      const now = Math.floor(Date.now() / 1000);
+      const proofHash = Buffer.alloc(32, "prefunded1");
+      const individualProofPda = deriveIndividualProofPda(
+        user.publicKey,
+        sourceIndex.reclaim
+      );
+
+      // Anyone can send lamports to a derivable PDA ahead of its creation.
+      await provider.sendAndConfirm(
+        new anchor.web3.Transaction().add(
+          ...[
+            individualProofPda,
+            deriveProofHashRegistryPda(user.publicKey, proofHash),
+          ].map((toPubkey) =>
+            anchor.web3.SystemProgram.transfer({
+              fromPubkey: payer,
+              toPubkey,
+              lamports: 1_000,
+            })
+          )
+        )
+      );
+
+      await submitProofsBatch(user, [
+        {
+          proofHash,
+          source: { reclaim: {} },
+          proofData: sourceData("reclaim", now),
+          baseScore: new anchor.BN(120),
+          timestamp: now,
+        },
+      ]);
+
+      const individualProof = await program.account.individualProof.fetch(
+        individualProofPda
+      );
+      expect(individualProof.user.toBase58()).to.equal(
+        user.publicKey.toBase58()
+      );
+      expect(individualProof.baseScore.toNumber()).to.equal(120);
+    });
+
+    it("should rate limit submissions within a slot", async () => {
+      const setLimit = (maxPerSlot: number) =>
+        program.methods
+          .updateSubmissionRateLimit(maxPerSlot)
+          .accountsStrict({
+            registry: registryPda,
+            authority: payer,
+          })
+          .rpc();
+      const user = anchor.web3.Keypair.generate();
+      await airdrop(user.publicKey);
+      const now = Math.floor(Date.now() / 1000);
+      const entry = (seed: string, source: any, kind: any) => ({
+        proofHash: Buffer.alloc(32, seed),
+        source,
+        proofData: sourceData(kind, now),
+        baseScore: new anchor.BN(100),
+        timestamp: now,
+      });
+
+      await setLimit(2);
+      // A batch lands in a single slot, so the third entry is over the cap.
+      try {
+        await submitProofsBatch(user, [
+          entry("ratelimit1", { reclaim: {} }, "reclaim"),
+          entry("ratelimit2", { gitcoinPassport: {} }, "gitcoin"),
+          entry("ratelimit3", { worldId: {} }, "worldId"),
+        ]);
+        expect.fail("should have thrown error");
+      } catch (error: any) {
+        expect(error.error.errorCode.code).to.equal("RateLimited");
+      }
+
+      await submitProofsBatch(user, [
+        entry("ratelimit1", { reclaim: {} }, "reclaim"),
+        entry("ratelimit2", { gitcoinPassport: {} }, "gitcoin"),
+      ]);
+      const registry = await program.account.registry.fetch(registryPda);
+      expect(registry.rateLimitCount).to.equal(2);
+      expect(registry.rateLimitSlot.toNumber()).to.be.greaterThan(0);
+
+      await setLimit(0);
+    });
+
+    it("should revert the whole batch if one proof fails", async () => {
+      const user = anchor.web3.Keypair.generate();
+      await airdrop(user.publicKey);
+      const now = Math.floor(Date.now() / 1000);
+
+      try {
+        await submitProofsBatch(user, [
+          {
+            proofHash: Buffer.alloc(32, "batch3"),
+            source: { reclaim: {} },
+            proofData: sourceData("reclaim", now),
+            baseScore: new anchor.BN(120),
+            timestamp: now,
+          },
+          {
+            proofHash: Buffer.alloc(32, "batch4"),
+            source: { worldId: {} },
+            proofData: sourceData("worldId", now),
+            identityNullifier: nextHash32(),
+            baseScore: new anchor.BN(130),
+            timestamp: now,
+          },
+        ]);
+        expect.fail("should have thrown error");
+      } catch (error: any) {
+        expect(error.error.errorCode.code).to.equal("InvalidIdentityNullifier");
+      }
+
+      expect(
+        await program.account.individualProof.fetchNullable(
+          deriveIndividualProofPda(user.publicKey, sourceIndex.reclaim)
+        )
+      ).to.equal(null);
+    });
+
+    it("should only apply diversity bonus above the base floor", async () => {
+      const submitTwoSources = async (seed: number) => {
+        const user = anchor.web3.Keypair.generate();
+        await airdrop(user.publicKey);
+        const now = Math.floor(Date.now() / 1000);
+
+        await submitProof(
+          user,
+          Buffer.from(Uint8Array.from([seed, ...Array(31).fill(1)])),
+          { reclaim: {} },
+          sourceData("reclaim", now),
+          new anchor.BN(100),
+          now
+        );
+        const { userProofPda } = await submitProof(
+          user,
+          Buffer.from(Uint8Array.from([seed + 1, ...Array(31).fill(1)])),
+          { gitcoinPassport: {} },
+          sourceData("gitcoin", now, 100),
+          new anchor.BN(100),
+          now
+        );
+        return program.account.userProof.fetch(userProofPda);
+      };
+
+      const setFloor = (floor: number) =>
+        program.methods
+          .updateMinBaseForBonus(new anchor.BN(floor))
+          .accountsStrict({
+            registry: registryPda,
+            authority: payer,
+          })
+          .rpc();
+
+      // reclaim weight is 150, so the base aggregate is 150 + 100 = 250.
+      await setFloor(500);
+      const belowFloor = await submitTwoSources(110);
+      expect(belowFloor.bonusApplied).to.equal(false);
+      expect(belowFloor.aggregatedScore.toNumber()).to.equal(250);
+
+      await setFloor(200);
+      const aboveFloor = await submitTwoSources(120);
+      expect(aboveFloor.bonusApplied).to.equal(true);
+      expect(aboveFloor.aggregatedScore.toNumber()).to.equal(300);
+
+      await setFloor(0);
+    });
+
+    it("should only count distinct categories toward the bonus", async () => {
+      const setCategory = (source: object, category: number) =>
+        program.methods
+          .updateSourceCategory(source as any, category)
+          .accountsStrict({
+            scoringConfig: scoringConfigPda,
+            authority: payer,
+          })
+          .rpc();
+
+      const user = anchor.web3.Keypair.generate();
+      await airdrop(user.publicKey);
+      const now = Math.floor(Date.now() / 1000);
+
+      await setCategory({ gitcoinPassport: {} }, 0);
+      await submitProof(
+        user,
+        Buffer.alloc(32, "category1"),
+        { reclaim: {} },
+        sourceData("reclaim", now),
+        new anchor.BN(100),
+        now
+      );
+      const { userProofPda } = await submitProof(
+        user,
+        Buffer.alloc(32, "category2"),
+        { gitcoinPassport: {} },
+        sourceData("gitcoin", now, 100),
+        new anchor.BN(100),
+        now
+      );
+      await setCategory({ gitcoinPassport: {} }, 1);
+
+      const sameCategory = await program.account.userProof.fetch(userProofPda);
+      expect(sameCategory.activeSourceCount).to.equal(2);
+      expect(sameCategory.bonusApplied).to.equal(false);
+      expect(sameCategory.aggregatedScore.toNumber()).to.equal(250);
+
+      await submitProof(
+        user,
+        Buffer.alloc(32, "category3"),
+        { worldId: {} },
+        sourceData("worldId", now),
+        new anchor.BN(100),
+        now
+      );
+      const mixed = await program.account.userProof.fetch(userProofPda);
+      expect(mixed.bonusApplied).to.equal(true);
+    });
+
+    it("should leave zero-weight sources out of the diversity bonus", async () => {
+      const setWorldIdWeight = (weight: number) =>
+        program.methods
+          .updateScoringConfig({ worldId: {} }, new anchor.BN(weight))
+          .accountsStrict({
+            scoringConfig: scoringConfigPda,
+            authority: payer,
+          })
+          .rpc();
+      const setRejectZeroWeight = (reject: boolean) =>
+        program.methods
+          .setRejectZeroWeightSources(reject)
+          .accountsStrict({
+            scoringConfig: scoringConfigPda,
+            authority: payer,
+          })
+          .rpc();
+
+      const user = anchor.web3.Keypair.generate();
+      await airdrop(user.publicKey);
+      const now = Math.floor(Date.now() / 1000);
+
+      await setWorldIdWeight(0);
+      try {
+        await submitProof(
+          user,
+          Buffer.alloc(32, "zeroweight1"),
+          { gitcoinPassport: {} },
+          sourceData("gitcoin", now, 100),
+          new anchor.BN(100),
+          now
+        );
+        const { userProofPda } = await submitProof(
+          user,
+          Buffer.alloc(32, "zeroweight2"),
+          { worldId: {} },
+          sourceData("worldId", now),
+          new anchor.BN(100),
+          now
+        );
+        const userProof = await program.account.userProof.fetch(userProofPda);
+        expect(userProof.activeSourceCount).to.equal(2);
+        expect(userProof.bonusApplied).to.equal(false);
+        expect(userProof.aggregatedScore.toNumber()).to.equal(100);
+
+        await setRejectZeroWeight(true);
+        const other = anchor.web3.Keypair.generate();
+        await airdrop(other.publicKey);
+        try {
+          await submitProof(
+            other,
+            Buffer.alloc(32, "zeroweight3"),
+            { worldId: {} },
+            sourceData("worldId", now),
+            new anchor.BN(100),
+            now
+          );
+          expect.fail("should have thrown error");
+        } catch (error: any) {
+          expect(error.error.errorCode.code).to.equal("SourceDisabled");
+        }
+      } finally {
+        await setRejectZeroWeight(false);
+        await setWorldIdWeight(100);
+      }
+    });
+
+    it("should scale the diversity bonus with a tiered schedule", async () => {
+      const setSchedule = (schedule: number[]) =>
+        program.methods
+          .updateDiversityBonusSchedule([
+            ...schedule,
+            ...Array(MAX_PROOF_SOURCES - schedule.length).fill(
+              schedule[schedule.length - 1]
+            ),
+          ])
+          .accountsStrict({
+            scoringConfig: scoringConfigPda,
+            authority: payer,
+          })
+          .rpc();
+      const preview = async (baseScore: anchor.BN, count: number) =>
+        (
+          await program.methods
+            .previewDiversityBonus(baseScore, count)
+            .accountsStrict({
+              registry: registryPda,
+              scoringConfig: scoringConfigPda,
+            })
+            .view()
+        ).toNumber();
+
+      try {
+        await setSchedule([0, 10, 5, 15, 20, 25, 30, 35]);
+        expect.fail("should have thrown error");
+      } catch (error: any) {
+        expect(error.error.errorCode.code).to.equal("InvalidConfig");
+      }
+
+      await setSchedule([0, 5, 10, 15, 20, 25, 30, 35]);
+      const base = new anchor.BN(1000);
+      expect(await preview(base, 1)).to.equal(1000);
+      expect(await preview(base, 2)).to.equal(1050);
+      expect(await preview(base, 3)).to.equal(1100);
+
+      const user = anchor.web3.Keypair.generate();
+      await airdrop(user.publicKey);
+      const now = Math.floor(Date.now() / 1000);
+      const submitted = [
+        await submitProof(
+          user,
+          Buffer.alloc(32, "tiered1"),
+          { reclaim: {} },
+          sourceData("reclaim", now),
+          new anchor.BN(100),
+          now
+        ),
+        await submitProof(
+          user,
+          Buffer.alloc(32, "tiered2"),
+          { gitcoinPassport: {} },
+          sourceData("gitcoin", now, 100),
+          new anchor.BN(100),
+          now
+        ),
+      ];
+      const worldId = await submitProof(
+        user,
+        Buffer.alloc(32, "tiered3"),
+        { worldId: {} },
+        sourceData("worldId", now),
+        new anchor.BN(100),
+        now
+      );
+      const contributed = async (proofs: { individualProofPda: any }[]) =>
+        (
+          await Promise.all(
+            proofs.map(({ individualProofPda }) =>
+              program.account.individualProof.fetch(individualProofPda)
+            )
+          )
+        ).reduce(
+          (sum, proof) => sum.add(proof.contributedScore),
+          new anchor.BN(0)
+        );
+
+      const three = await program.account.userProof.fetch(worldId.userProofPda);
+      expect(three.aggregatedScore.toNumber()).to.equal(
+        await preview(await contributed([...submitted, worldId]), 3)
+      );
+
+      await program.methods
+        .revokeProof({ worldId: {} })
+        .accountsStrict({
+          registry: registryPda,
+          userProof: worldId.userProofPda,
+          individualProof: worldId.individualProofPda,
+          identityNullifierRegistry: worldId.identityNullifierRegistryPda,
+          scoringConfig: scoringConfigPda,
+          decayConfig: decayConfigPda,
+          userStatus: deriveUserStatusPda(user.publicKey),
+          statusCache: null,
+          user: user.publicKey,
+        })
+        .signers([user])
+        .rpc();
+      const two = await program.account.userProof.fetch(worldId.userProofPda);
+      expect(two.appliedDiversityBonusPercent).to.equal(5);
+      expect(two.aggregatedScore.toNumber()).to.equal(
+        await preview(await contributed(submitted), 2)
+      );
+
+      await setSchedule([0, 0, 0, 0, 0, 0, 0, 0]);
+    });
+
+    it("should not stack the bonus across sources of one person", async () => {
+      const setPersonBinding = (enabled: boolean) =>
+        program.methods
+          .setPersonBinding(enabled)
+          .accountsStrict({
+            registry: registryPda,
+            authority: payer,
+          })
+          .rpc();
+      const personIdHash = Buffer.alloc(32, "person1");
+      const personPda = anchor.web3.PublicKey.findProgramAddressSync(
+        [Buffer.from("person"), personIdHash],
+        program.programId
+      )[0];
+      const linkPerson = async (
+        user: anchor.web3.PublicKey,
+        source: object,
+        index: number
+      ) => {
+        const individualProofPda = deriveIndividualProofPda(user, index);
+        const individualProof = await program.account.individualProof.fetch(
+          individualProofPda
+        );
+        const now = Math.floor(Date.now() / 1000);
+        const nonce = attestationNonce++;
+        const nonceBuf = Buffer.alloc(8);
+        nonceBuf.writeBigUInt64LE(BigInt(nonce));
+        const tsBuf = Buffer.alloc(8);
+        tsBuf.writeBigInt64LE(BigInt(now));
+        const message = Buffer.concat([
+          Buffer.from("sidp"),
+          program.programId.toBuffer(),
+          registryPda.toBuffer(),
+          user.toBuffer(),
+          Buffer.from([index]),
+          Buffer.from(individualProof.identityNullifier),
+          personIdHash,
+          nonceBuf,
+          tsBuf,
+        ]);
+        return program.methods
+          .linkPersonId(
+            source as any,
+            Array.from(personIdHash),
+            new anchor.BN(nonce),
+            new anchor.BN(now)
+          )
+          .preInstructions([
+            anchor.web3.Ed25519Program.createInstructionWithPrivateKey({
+              privateKey: verifier.secretKey,
+              message,
+            }),
+          ])
+          .accountsStrict({
+            registry: registryPda,
+            userProof: deriveUserProofPda(user),
+            individualProof: individualProofPda,
+            personRegistry: personPda,
+            attestationNonceRegistry: deriveAttestationNoncePda(nonce),
+            scoringConfig: scoringConfigPda,
+            user,
+            instructionsSysvar: anchor.web3.SYSVAR_INSTRUCTIONS_PUBKEY,
+            payer,
+            systemProgram: anchor.web3.SystemProgram.programId,
+          })
+          .rpc();
+      };
+
+      const user = anchor.web3.Keypair.generate();
+      await airdrop(user.publicKey);
+      const now = Math.floor(Date.now() / 1000);
+      await submitProof(
+        user,
+        Buffer.alloc(32, "person2"),
+        { reclaim: {} },
+        sourceData("reclaim", now),
+        new anchor.BN(100),
+        now
+      );
+      const { userProofPda } = await submitProof(
+        user,
+        Buffer.alloc(32, "person3"),
+        { gitcoinPassport: {} },
+        sourceData("gitcoin", now, 100),
+        new anchor.BN(100),
+        now
+      );
+      const separate = await program.account.userProof.fetch(userProofPda);
+      expect(separate.bonusApplied).to.equal(true);
+
+      try {
+        await linkPerson(user.publicKey, { reclaim: {} }, sourceIndex.reclaim);
+        expect.fail("should have thrown error");
+      } catch (error: any) {
+        expect(error.error.errorCode.code).to.equal("PersonBindingDisabled");
+      }
+
+      await setPersonBinding(true);
+      await linkPerson(user.publicKey, { reclaim: {} }, sourceIndex.reclaim);
+      await linkPerson(
+        user.publicKey,
+        { gitcoinPassport: {} },
+        sourceIndex.gitcoinPassport
+      );
+
+      const linked = await program.account.userProof.fetch(userProofPda);
+      const contributed = await Promise.all(
+        [sourceIndex.reclaim, sourceIndex.gitcoinPassport].map(async (index) =>
+          (
+            await program.account.individualProof.fetch(
+              deriveIndividualProofPda(user.publicKey, index)
+            )
+          ).contributedScore.toNumber()
+        )
+      );
+      expect(linked.bonusApplied).to.equal(false);
+      expect(linked.aggregatedScore.toNumber()).to.equal(
+        contributed[0] + contributed[1]
+      );
+      const person = await program.account.personRegistry.fetch(personPda);
+      expect(person.claimedBy.toBase58()).to.equal(user.publicKey.toBase58());
+
+      const other = anchor.web3.Keypair.generate();
+      await airdrop(other.publicKey);
+      await submitProof(
+        other,
+        Buffer.alloc(32, "person4"),
+        { reclaim: {} },
+        sourceData("reclaim", now),
+        new anchor.BN(100),
+        now
+      );
+      try {
+        await linkPerson(other.publicKey, { reclaim: {} }, sourceIndex.reclaim);
+        expect.fail("should have thrown error");
+      } catch (error: any) {
+        expect(error.error.errorCode.code).to.equal("DuplicateIdentityClaim");
+      }
+
+      await setPersonBinding(false);
+    });
+
+    it("should recompute a stored bonus after a config change", async () => {
+      const registry = await program.account.registry.fetch(registryPda);
+      const setBonus = (percent: number) =>
+        program.methods
+          .updateRegistryConfig(
+            registry.cooldownPeriod,
+            percent,
+            registry.proofTtlSeconds,
+            registry.gracePeriodSeconds,
+            registry.minSourceCount
+          )
+          .accountsStrict({
+            registry: registryPda,
+            authority: payer,
+          })
+          .rpc();
+
+      const user = anchor.web3.Keypair.generate();
+      await airdrop(user.publicKey);
+      const now = Math.floor(Date.now() / 1000);
+
+      await submitProof(
+        user,
+        Buffer.alloc(32, "recompute1"),
+        { reclaim: {} },
+        sourceData("reclaim", now),
+        new anchor.BN(100),
+        now
+      );
+      const { userProofPda } = await submitProof(
+        user,
+        Buffer.alloc(32, "recompute2"),
+        { worldId: {} },
+        sourceData("worldId", now),
+        new anchor.BN(100),
+        now
+      );
+
+      const recompute = async () => {
+        await program.methods
+          .recomputeBonus()
+          .accountsStrict({
+            registry: registryPda,
+            userProof: userProofPda,
+            scoringConfig: scoringConfigPda,
+            user: user.publicKey,
+          })
+          .rpc();
+        const { aggregatedScore } = await program.account.userProof.fetch(
+          userProofPda
+        );
+        return aggregatedScore.toNumber();
+      };
+
+      // reclaim weight is 150, so the base aggregate is 150 + 100 = 250.
+      const newPercent = registry.diversityBonusPercent + 10;
+      await setBonus(newPercent);
+      expect(await recompute()).to.equal(
+        250 + Math.floor((250 * newPercent) / 100)
+      );
+
+      await setBonus(registry.diversityBonusPercent);
+      expect(await recompute()).to.equal(
+        250 + Math.floor((250 * registry.diversityBonusPercent) / 100)
+      );
+    });
+
+    it("should apply the campaign bonus only inside the window", async () => {
+      const now = Math.floor(Date.now() / 1000);
+      const setCampaign = (start: number, end: number, percent: number) =>
+        program.methods
+          .setCampaign(new anchor.BN(start), new anchor.BN(end), percent)
+          .accountsStrict({
+            registry: registryPda,
+            authority: payer,
+          })
+          .rpc();
+
+      const submitWorldId = async (seed: string) => {
+        const user = anchor.web3.Keypair.generate();
+        await airdrop(user.publicKey);
+        const { individualProofPda } = await submitProof(
+          user,
+          Buffer.alloc(32, seed),
+          { worldId: {} },
+          sourceData("worldId", now),
+          new anchor.BN(150),
+          now
+        );
+        return program.account.individualProof.fetch(individualProofPda);
+      };
+
+      await setCampaign(now - 60, now + 60, 50);
+      const inWindow = await submitWorldId("campaign1");
+      expect(inWindow.campaignBonusApplied).to.equal(true);
+      expect(inWindow.weightedScore.toNumber()).to.equal(225);
+
+      await setCampaign(now - 7200, now - 3600, 50);
+      const outOfWindow = await submitWorldId("campaign2");
+      expect(outOfWindow.campaignBonusApplied).to.equal(false);
+      expect(outOfWindow.weightedScore.toNumber()).to.equal(150);
+
+      await setCampaign(0, 0, 0);
+    });
+
+    it("should scale a proof by the attested trust multiplier", async () => {
+      const now = Math.floor(Date.now() / 1000);
+      const setMaxTrustMultiplier = (max: number) =>
+        program.methods
+          .setMaxTrustMultiplier(max)
+          .accountsStrict({
+            registry: registryPda,
+            authority: payer,
+          })
+          .rpc();
+
+      const submitWorldId = async (seed: string, options: SubmitOptions) => {
+        const user = anchor.web3.Keypair.generate();
+        await airdrop(user.publicKey);
+        const { individualProofPda } = await submitProof(
+          user,
+          Buffer.alloc(32, seed),
+          { worldId: {} },
+          sourceData("worldId", now),
+          new anchor.BN(150),
+          now,
+          options
+        );
+        return program.account.individualProof.fetch(individualProofPda);
+      };
+
+      await setMaxTrustMultiplier(150);
+      try {
+        const neutral = await submitWorldId("trust1", {});
+        expect(neutral.trustMultiplier).to.equal(100);
+        expect(neutral.weightedScore.toNumber()).to.equal(150);
+
+        const trusted = await submitWorldId("trust2", { trustMultiplier: 150 });
+        expect(trusted.trustMultiplier).to.equal(150);
+        expect(trusted.weightedScore.toNumber()).to.equal(225);
+
+        try {
+          await submitWorldId("trust3", { trustMultiplier: 200 });
+          expect.fail("should have thrown error");
+        } catch (error: any) {
+          expect(error.error.errorCode.code).to.equal(
+            "InvalidTrustMultiplier"
+          );
+        }
+      } finally {
+        await setMaxTrustMultiplier(100);
+      }
+    });
+
+    it("should score a proof with the attested weight override", async () => {
+      const now = Math.floor(Date.now() / 1000);
+      const setMaxWeightOverride = (max: number) =>
+        program.methods
+          .setMaxWeightOverride(new anchor.BN(max))
+          .accountsStrict({
+            registry: registryPda,
+            authority: payer,
+          })
+          .rpc();
+
+      const user = anchor.web3.Keypair.generate();
+      await airdrop(user.publicKey);
+
+      await setMaxWeightOverride(200);
+      try {
+        try {
+          await submitProof(
+            user,
+            Buffer.alloc(32, "override1"),
+            { worldId: {} },
+            sourceData("worldId", now),
+            new anchor.BN(150),
+            now,
+            { weightOverride: 300 }
+          );
+          expect.fail("should have thrown error");
+        } catch (error: any) {
+          expect(error.error.errorCode.code).to.equal("InvalidWeightOverride");
+        }
+
+        const submitted = await submitProof(
+          user,
+          Buffer.alloc(32, "override2"),
+          { worldId: {} },
+          sourceData("worldId", now),
+          new anchor.BN(150),
+          now,
+          { weightOverride: 200 }
+        );
+        const individualProof = await program.account.individualProof.fetch(
+          submitted.individualProofPda
+        );
+        expect(individualProof.weightOverride.toNumber()).to.equal(200);
+        expect(individualProof.weightedScore.toNumber()).to.equal(300);
+
+        // Revoking removes the stored contribution, not one recomputed from
+        // the configured source weight.
+        await program.methods
+          .revokeProof({ worldId: {} })
+          .accountsStrict({
+            registry: registryPda,
+            userProof: submitted.userProofPda,
+            individualProof: submitted.individualProofPda,
+            identityNullifierRegistry: submitted.identityNullifierRegistryPda,
+            scoringConfig: scoringConfigPda,
+            decayConfig: decayConfigPda,
+            userStatus: deriveUserStatusPda(user.publicKey),
+            statusCache: null,
+            user: user.publicKey,
+          })
+          .signers([user])
+          .rpc();
+        const userProof = await program.account.userProof.fetch(
+          submitted.userProofPda
+        );
+        expect(userProof.aggregatedScore.toNumber()).to.equal(0);
+      } finally {
+        await setMaxWeightOverride(0);
+      }
+    });
+
+    it("should only accept sources in the allowed mask", async () => {
+      const setAllowedSources = (mask: number) =>
+        program.methods
+          .setAllowedSources(mask)
+          .accountsStrict({
+            registry: registryPda,
+            authority: payer,
+          })
+          .rpc();
+
+      const user = anchor.web3.Keypair.generate();
       await airdrop(user.publicKey);
+      const now = Math.floor(Date.now() / 1000);
+
+      // World ID (2) and Gitcoin Passport (1) only.
+      await setAllowedSources((1 << 2) | (1 << 1));
+      try {
+        try {
+          await submitProof(
+            user,
+            Buffer.alloc(32, "allowed1"),
+            { reclaim: {} },
+            sourceData("reclaim", now),
+            new anchor.BN(100),
+            now
+          );
+          expect.fail("should have thrown error");
+        } catch (error: any) {
+          expect(error.error.errorCode.code).to.equal("SourceNotAllowed");
+        }
+
+        const { individualProofPda } = await submitProof(
+          user,
+          Buffer.alloc(32, "allowed2"),
+          { worldId: {} },
+          sourceData("worldId", now),
+          new anchor.BN(100),
+          now
+        );
+        const individualProof = await program.account.individualProof.fetch(
+          individualProofPda
+        );
+        expect(individualProof.baseScore.toNumber()).to.equal(100);
+      } finally {
+        await setAllowedSources(0xffff);
+      }
+    });
+
+    it("should cap recency when resubmitting an aged source", async () => {
+      const setFirstDecayBoundary = (seconds: number) =>
+        program.methods
+          .updateDecayConfig(
+            [
+              new anchor.BN(seconds),
+              new anchor.BN(7776000),
+              new anchor.BN(15552000),
+              new anchor.BN("9223372036854775807"),
+            ],
+            [100, 75, 50, 25].map((factor) => new anchor.BN(factor))
+          )
+          .accountsStrict({
+            decayConfig: decayConfigPda,
+            authority: payer,
+          })
+          .rpc();
+      const setRecencyCap = (cap: number) =>
+        program.methods
+          .updateMaxRecencyAfterResubmit(cap)
+          .accountsStrict({
+            registry: registryPda,
+            authority: payer,
+          })
+          .rpc();
+
+      await setFirstDecayBoundary(2);
 
       const now = Math.floor(Date.now() / 1000);
-      const source = { reclaim: {} };
-      const payload = sourceData("reclaim", now);
+      const users = [
+        anchor.web3.Keypair.generate(),
+        anchor.web3.Keypair.generate(),
+      ];
+      const payloads = users.map(() => sourceData("worldId", now));
+      for (const [i, user] of users.entries()) {
+        await airdrop(user.publicKey);
+        await submitProof(
+          user,
+          Buffer.alloc(32, `recency${i}`),
+          { worldId: {} },
+          payloads[i],
+          new anchor.BN(150),
+          now
+        );
+      }
 
-      const { userProofPda } = await submitProof(
-        user,
-        Buffer.alloc(32, "proof4"),
-        source,
-        payload,
-        new anchor.BN(200),
-        now
-      );
+      await new Promise((resolve) => setTimeout(resolve, 4000));
 
-      const result = await program.methods
-        .verifyProof()
-        .accountsStrict({
-          userProof: userProofPda,
-          registry: registryPda,
-          user: user.publicKey,
-        })
-        .view();
+      const resubmit = async (i: number) => {
+        const { userProofPda, individualProofPda } = await submitProof(
+          users[i],
+          Buffer.alloc(32, `recency${i}b`),
+          { worldId: {} },
+          payloads[i],
+          new anchor.BN(150),
+          Math.floor(Date.now() / 1000)
+        );
+        return {
+          userProof: await program.account.userProof.fetch(userProofPda),
+          individualProof: await program.account.individualProof.fetch(
+            individualProofPda
+          ),
+        };
+      };
 
-      expect(result.isVerified).to.equal(true);
+      const uncapped = await resubmit(0);
+      await setRecencyCap(50);
+      const capped = await resubmit(1);
+
+      expect(capped.individualProof.firstVerifiedAt.toNumber()).to.equal(now);
+      // The refresh replaces the original's whole contribution, capped at 50%.
+      expect(capped.userProof.aggregatedScore.toNumber()).to.equal(
+        Math.floor((150 * 50) / 100)
+      );
+      expect(uncapped.userProof.aggregatedScore.toNumber()).to.be.greaterThan(
+        capped.userProof.aggregatedScore.toNumber()
+      );
+
+      await setRecencyCap(100);
+      await setFirstDecayBoundary(2592000);
     });
 
-    it("should require renewal after ttl expiry", async () => {
-      await program.methods
-        .updateRegistryConfig(new anchor.BN(0), 10, new anchor.BN(1))
-        .accountsStrict({
-          registry: registryPda,
-          authority: payer,
-        })
-        .rpc();
+    it("should decay continuously in linear mode", async () => {
+      const setDecay = (boundaries: number[], mode: any) =>
+        program.methods
+          .updateDecayConfig(
+            [
+              ...boundaries.map((b) => new anchor.BN(b)),
+              new anchor.BN("9223372036854775807"),
+            ],
+            [100, 75, 50, 25].map((factor) => new anchor.BN(factor))
+          )
+          .postInstructions([
+            await program.methods
+              .updateDecayMode(mode)
+              .accountsStrict({
+                decayConfig: decayConfigPda,
+                authority: payer,
+              })
+              .instruction(),
+          ])
+          .accountsStrict({
+            decayConfig: decayConfigPda,
+            authority: payer,
+          })
+          .rpc();
+
+      await setDecay([100, 200, 400], { linear: {} });
 
       const user = anchor.web3.Keypair.generate();
       await airdrop(user.publicKey);
-      const now = Math.floor(Date.now() / 1000);
-
+      const attestedAt = Math.floor(Date.now() / 1000) - 100;
       const { userProofPda } = await submitProof(
         user,
-        Buffer.alloc(32, "proof4b"),
-        { reclaim: {} },
-        sourceData("reclaim", now),
-        new anchor.BN(250),
-        now
+        Buffer.alloc(32, "lineardecay"),
+        { worldId: {} },
+        sourceData("worldId", attestedAt),
+        new anchor.BN(150),
+        attestedAt
       );
 
-      await new Promise((resolve) => setTimeout(resolve, 2200));
+      // Bracketed mode would already be at 75% here; linear mode is still
+      // about a quarter of the way down to the floor.
+      const userProof = await program.account.userProof.fetch(userProofPda);
+      expect(userProof.aggregatedScore.toNumber()).to.be.greaterThan(
+        (150 * 75) / 100
+      );
+      expect(userProof.aggregatedScore.toNumber()).to.be.lessThan(150);
 
-      const result = await program.methods
-        .verifyProof()
-        .accountsStrict({
-          userProof: userProofPda,
-          registry: registryPda,
-          user: user.publicKey,
-        })
-        .view();
+      await setDecay([2592000, 7776000, 15552000], { bracketed: {} });
+      const decayConfig = await program.account.decayConfig.fetch(
+        decayConfigPda
+      );
+      expect(decayConfig.decayMode).to.deep.equal({ bracketed: {} });
+    });
 
-      expect(result.isVerified).to.equal(false);
+    it("should not drift the aggregate across refreshes", async () => {
+      const setDecayBoundaries = (boundaries: number[]) =>
+        program.methods
+          .updateDecayConfig(
+            [
+              ...boundaries.map((b) => new anchor.BN(b)),
+              new anchor.BN("9223372036854775807"),
+            ],
+            [100, 75, 50, 25].map((factor) => new anchor.BN(factor))
+          )
+          .accountsStrict({
+            decayConfig: decayConfigPda,
+            authority: payer,
+          })
+          .rpc();
 
-      await program.methods
-        .updateRegistryConfig(new anchor.BN(0), 10, new anchor.BN(3600))
-        .accountsStrict({
-          registry: registryPda,
-          authority: payer,
-        })
-        .rpc();
-    });
-  });
+      await setDecayBoundaries([2, 4, 6]);
 
-  describe("Proof Revocation", () => {
-    it("should revoke proof successfully", async () => {
       const user = anchor.web3.Keypair.generate();
       await airdrop(user.publicKey);
+      const payload = sourceData("worldId", Math.floor(Date.now() / 1000));
 
-      const now = Math.floor(Date.now() / 1000);
-      const source = { gitcoinPassport: {} };
-      const payload = sourceData("gitcoin", now, 200);
-
-      const { userProofPda, individualProofPda, identityNullifierRegistryPda } =
-        await submitProof(
+      // Each refresh is attested a few seconds in the past, so the outgoing
+      // proof has decayed further by the time it is replaced.
+      for (let i = 0; i < 4; i++) {
+        const { userProofPda, individualProofPda } = await submitProof(
           user,
-          Buffer.alloc(32, "proof5"),
-          source,
+          Buffer.alloc(32, `refresh${i}`),
+          { worldId: {} },
           payload,
-          new anchor.BN(200),
-          now
+          new anchor.BN(150),
+          Math.floor(Date.now() / 1000) - 3
+        );
+        const userProof = await program.account.userProof.fetch(userProofPda);
+        const individualProof = await program.account.individualProof.fetch(
+          individualProofPda
         );
+        expect(userProof.aggregatedScore.toNumber()).to.equal(
+          individualProof.contributedScore.toNumber()
+        );
+        await new Promise((resolve) => setTimeout(resolve, 1500));
+      }
 
-      const before = await program.account.userProof.fetch(userProofPda);
+      await setDecayBoundaries([2592000, 7776000, 15552000]);
+    });
 
-      await program.methods
-        .revokeProof(source)
-        .accountsStrict({
-          registry: registryPda,
-          userProof: userProofPda,
-          individualProof: individualProofPda,
-          identityNullifierRegistry: identityNullifierRegistryPda,
-          user: user.publicKey,
-        })
-        .signers([user])
-        .rpc();
+    it("should flag a stale score after a decay boundary", async () => {
+      const setFirstDecayBoundary = (seconds: number) =>
+        program.methods
+          .updateDecayConfig(
+            [
+              new anchor.BN(seconds),
+              new anchor.BN(7776000),
+              new anchor.BN(15552000),
+              new anchor.BN("9223372036854775807"),
+            ],
+            [100, 75, 50, 25].map((factor) => new anchor.BN(factor))
+          )
+          .accountsStrict({
+            decayConfig: decayConfigPda,
+            authority: payer,
+          })
+          .rpc();
 
-      const after = await program.account.userProof.fetch(userProofPda);
-      expect(after.aggregatedScore.toNumber()).to.be.lessThan(
-        before.aggregatedScore.toNumber()
+      await setFirstDecayBoundary(3);
+
+      const user = anchor.web3.Keypair.generate();
+      await airdrop(user.publicKey);
+      const now = Math.floor(Date.now() / 1000);
+      const { userProofPda, individualProofPda } = await submitProof(
+        user,
+        Buffer.alloc(32, "stale1"),
+        { worldId: {} },
+        sourceData("worldId", now),
+        new anchor.BN(150),
+        now
+      );
+      const verify = () =>
+        program.methods
+          .verifyProof()
+          .accountsStrict({
+            userProof: userProofPda,
+            userStatus: deriveUserStatusPda(user.publicKey),
+            registry: registryPda,
+            scoringConfig: scoringConfigPda,
+            decayConfig: decayConfigPda,
+            user: user.publicKey,
+          })
+          .remainingAccounts([
+            { pubkey: individualProofPda, isSigner: false, isWritable: false },
+          ])
+          .view();
+
+      const fresh = await verify();
+      expect(fresh.isStale).to.equal(false);
+      expect(fresh.liveScore.toNumber()).to.equal(
+        fresh.aggregatedScore.toNumber()
       );
-      expect(after.activeSourceCount).to.equal(0);
-    });
 
-    it("should reject revoke from mismatched signer", async () => {
-      const owner = anchor.web3.Keypair.generate();
-      const attacker = anchor.web3.Keypair.generate();
-      await airdrop(owner.publicKey);
+      await new Promise((resolve) => setTimeout(resolve, 5000));
 
-      const now = Math.floor(Date.now() / 1000);
+      const { weightedScore } = await program.account.individualProof.fetch(
+        individualProofPda
+      );
+      const stale = await verify();
+      expect(stale.isStale).to.equal(true);
+      expect(stale.aggregatedScore.toNumber()).to.equal(
+        fresh.aggregatedScore.toNumber()
+      );
+      expect(stale.liveScore.toNumber()).to.equal(
+        Math.floor((weightedScore.toNumber() * 75) / 100)
+      );
 
-      const { userProofPda, individualProofPda, identityNullifierRegistryPda } =
-        await submitProof(
-          owner,
-          Buffer.alloc(32, "proof6"),
-          { worldId: {} },
-          sourceData("worldId", now),
-          new anchor.BN(150),
-          now
-        );
+      await setFirstDecayBoundary(2592000);
+    });
 
-      try {
+    it("should stop counting a source past its hard expiry", async () => {
+      const setDecay = async (firstBoundary: number, worldIdExpiry: number) => {
         await program.methods
-          .revokeProof({ worldId: {} })
+          .updateDecayConfig(
+            [
+              new anchor.BN(firstBoundary),
+              new anchor.BN(7776000),
+              new anchor.BN(15552000),
+              new anchor.BN("9223372036854775807"),
+            ],
+            [100, 75, 50, 25].map((factor) => new anchor.BN(factor))
+          )
           .accountsStrict({
-            registry: registryPda,
-            userProof: userProofPda,
-            individualProof: individualProofPda,
-            identityNullifierRegistry: identityNullifierRegistryPda,
-            user: attacker.publicKey,
+            decayConfig: decayConfigPda,
+            authority: payer,
           })
-          .signers([attacker])
           .rpc();
-        expect.fail("should have thrown error");
-      } catch (error: any) {
-        expect(error.error.errorCode.code).to.equal("ConstraintSeeds");
-      }
-    });
+        await program.methods
+          .updateSourceHardExpiry(
+            Array.from(
+              { length: MAX_PROOF_SOURCES },
+              (_, index) =>
+                new anchor.BN(index === sourceIndex.worldId ? worldIdExpiry : 0)
+            )
+          )
+          .accountsStrict({
+            decayConfig: decayConfigPda,
+            authority: payer,
+          })
+          .rpc();
+      };
+
+      await setDecay(2, 4);
 
-    it("should permanently burn identity after revoke", async () => {
       const user = anchor.web3.Keypair.generate();
       await airdrop(user.publicKey);
-
       const now = Math.floor(Date.now() / 1000);
-      const source = { worldId: {} };
-      const payload = sourceData("worldId", now);
+      const worldId = await submitProof(
+        user,
+        Buffer.alloc(32, "expiry1"),
+        { worldId: {} },
+        sourceData("worldId", now),
+        new anchor.BN(150),
+        now
+      );
+      const reclaim = await submitProof(
+        user,
+        Buffer.alloc(32, "expiry2"),
+        { reclaim: {} },
+        sourceData("reclaim", now),
+        new anchor.BN(150),
+        now
+      );
 
-      const { individualProofPda, userProofPda, identityNullifierRegistryPda } =
-        await submitProof(
-          user,
-          Buffer.alloc(32, "burn1"),
-          source,
-          payload,
-          new anchor.BN(180),
-          now
-        );
+      await new Promise((resolve) => setTimeout(resolve, 5000));
 
-      await program.methods
-        .revokeProof(source)
+      const { weightedScore } = await program.account.individualProof.fetch(
+        reclaim.individualProofPda
+      );
+      const status = await program.methods
+        .verifyProof()
         .accountsStrict({
+          userProof: reclaim.userProofPda,
+          userStatus: deriveUserStatusPda(user.publicKey),
           registry: registryPda,
-          userProof: userProofPda,
-          individualProof: individualProofPda,
-          identityNullifierRegistry: identityNullifierRegistryPda,
+          scoringConfig: scoringConfigPda,
+          decayConfig: decayConfigPda,
           user: user.publicKey,
         })
-        .signers([user])
-        .rpc();
+        .remainingAccounts(
+          [worldId.individualProofPda, reclaim.individualProofPda].map(
+            (pubkey) => ({ pubkey, isSigner: false, isWritable: false })
+          )
+        )
+        .view();
+
+      // World ID is past its hard expiry; Reclaim is decayed but still counts.
+      expect(status.isStale).to.equal(true);
+      expect(status.liveScore.toNumber()).to.equal(
+        Math.floor((weightedScore.toNumber() * 75) / 100)
+      );
+
+      const userProof = await program.account.userProof.fetch(
+        reclaim.userProofPda
+      );
+      expect(userProof.activeSourceCount).to.equal(2);
+
+      await setDecay(2592000, 0);
+    });
+
+    it("should reject expired proof timestamp", async () => {
+      const user = anchor.web3.Keypair.generate();
+      await airdrop(user.publicKey);
+
+      const oldTimestamp = Math.floor(Date.now() / 1000) - 7200;
 
       try {
         await submitProof(
           user,
-          Buffer.alloc(32, "burn2"),
-          source,
-          payload,
-          new anchor.BN(180),
-          now
+          Buffer.alloc(32, "proof7"),
+          { reclaim: {} },
+          sourceData("reclaim", oldTimestamp),
+          new anchor.BN(100),
+          oldTimestamp
         );
         expect.fail("should have thrown error");
       } catch (error: any) {
-        expect(error.error.errorCode.code).to.equal("IdentityRevokedPermanent");
+        expect(error.error.errorCode.code).to.equal("ProofExpired");
       }
     });
-  });
-
-  describe("Admin Functions", () => {
-    it("should update min score", async () => {
-      await program.methods
-        .updateMinScore(new anchor.BN(250))
-        .accountsStrict({
-          registry: registryPda,
-          authority: payer,
-        })
-        .rpc();
-
-      const registry = await program.account.registry.fetch(registryPda);
-      expect(registry.minScore.toNumber()).to.equal(250);
-    });
 
-    it("should update scoring config", async () => {
+    it("should clamp weighted score to the configured max", async () => {
       await program.methods
-        .updateScoringConfig({ reclaim: {} }, new anchor.BN(150))
+        .updateMaxWeightedScore(new anchor.BN(120))
         .accountsStrict({
-          scoringConfig: scoringConfigPda,
+          registry: registryPda,
           authority: payer,
         })
         .rpc();
 
-      const scoringConfig = await program.account.scoringConfig.fetch(
-        scoringConfigPda
+      const user = anchor.web3.Keypair.generate();
+      await airdrop(user.publicKey);
+      const now = Math.floor(Date.now() / 1000);
+
+      const { userProofPda, individualProofPda } = await submitProof(
+        user,
+        Buffer.alloc(32, "clamp1"),
+        { reclaim: {} },
+        sourceData("reclaim", now),
+        new anchor.BN(400),
+        now
       );
-      expect(scoringConfig.weights[0].toNumber()).to.equal(150);
-    });
 
-    it("should update registry config", async () => {
+      const individualProof = await program.account.individualProof.fetch(
+        individualProofPda
+      );
+      const userProof = await program.account.userProof.fetch(userProofPda);
+      expect(individualProof.baseScore.toNumber()).to.equal(400);
+      expect(individualProof.weightedScore.toNumber()).to.equal(120);
+      expect(userProof.aggregatedScore.toNumber()).to.equal(120);
+
       await program.methods
-        .updateRegistryConfig(new anchor.BN(0), 20, new anchor.BN(3600))
+        .updateMaxWeightedScore(new anchor.BN("18446744073709551615"))
         .accountsStrict({
           registry: registryPda,
           authority: payer,
         })
         .rpc();
-
-      const registry = await program.account.registry.fetch(registryPda);
-      expect(registry.cooldownPeriod.toNumber()).to.equal(0);
-      expect(registry.diversityBonusPercent).to.equal(20);
-      expect(registry.proofTtlSeconds.toNumber()).to.equal(3600);
-      expect(registry.verifierAuthority.toString()).to.equal(payer.toString());
     });
+  });
+
+  describe("Edge Cases", () => {
+    it("should handle score overflow safely", async () => {
+      const user = anchor.web3.Keypair.generate();
+      await airdrop(user.publicKey);
+
+      const now = Math.floor(Date.now() / 1000);
+      const massiveScore = new anchor.BN(2).pow(new anchor.BN(60));
 
-    it("should reject finalize when no verifier rotation pending", async () => {
       try {
-        await program.methods
-          .finalizeVerifierRotation()
-          .accountsStrict({
-            registry: registryPda,
-            authority: payer,
-          })
-          .rpc();
-        expect.fail("should have thrown error");
-      } catch (error: any) {
-        expect(error.error.errorCode.code).to.equal(
-          "NoVerifierRotationPending"
+        await submitProof(
+          user,
+          Buffer.alloc(32, "proof8"),
+          { reclaim: {} },
+          sourceData("reclaim", now),
+          massiveScore,
+          now
         );
+        expect.fail("should have thrown overflow error");
+      } catch (error: any) {
+        expect(error.error.errorCode.code).to.equal("Overflow");
       }
     });
 
-    it("should reject invalid verifier rotation params", async () => {
-      try {
-        await program.methods
-          .initiateVerifierRotation(
-            anchor.web3.PublicKey.default,
-            new anchor.BN(1)
-          )
+    it("should verify proof status correctly", async () => {
+      const user = anchor.web3.Keypair.generate();
+      await airdrop(user.publicKey);
+
+      const now = Math.floor(Date.now() / 1000);
+      const { userProofPda } = await submitProof(
+        user,
+        Buffer.alloc(32, "proof9"),
+        { gitcoinPassport: {} },
+        sourceData("gitcoin", now, 300),
+        new anchor.BN(300),
+        now
+      );
+
+      const result = await program.methods
+        .verifyProof()
+        .accountsStrict({
+          userProof: userProofPda,
+          userStatus: deriveUserStatusPda(user.publicKey),
+          registry: registryPda,
+          scoringConfig: scoringConfigPda,
+          decayConfig: decayConfigPda,
+          user: user.publicKey,
+        })
+        .view();
+
+      expect(result.isVerified).to.equal(true);
+      expect(result.verifiedAt.toNumber()).to.be.greaterThan(0);
+    });
+
+    it("should report points needed to reach a threshold", async () => {
+      const user = anchor.web3.Keypair.generate();
+      await airdrop(user.publicKey);
+
+      const now = Math.floor(Date.now() / 1000);
+      const { userProofPda } = await submitProof(
+        user,
+        Buffer.alloc(32, "points1"),
+        { worldId: {} },
+        sourceData("worldId", now),
+        new anchor.BN(150),
+        now
+      );
+
+      const pointsTo = (minScore: number) =>
+        program.methods
+          .pointsToThreshold(new anchor.BN(minScore))
           .accountsStrict({
-            registry: registryPda,
-            authority: payer,
+            userProof: userProofPda,
+            user: user.publicKey,
           })
-          .rpc();
-        expect.fail("should have thrown error");
-      } catch (error: any) {
-        expect(error.error.errorCode.code).to.equal("InvalidConfig");
-      }
+          .view();
 
-      try {
-        await program.methods
-          .initiateVerifierRotation(payer, new anchor.BN(0))
+      expect((await pointsTo(100)).toNumber()).to.equal(0);
+      expect((await pointsTo(165)).toNumber()).to.equal(15);
+    });
+
+    it("should reject closing an attestation nonce too early", async () => {
+      const setNonceRetention = (seconds: number) =>
+        program.methods
+          .setNonceRetention(new anchor.BN(seconds))
           .accountsStrict({
             registry: registryPda,
             authority: payer,
           })
           .rpc();
+
+      // Retention must outlive the proof ttl plus the allowed clock skew.
+      try {
+        await setNonceRetention(3600);
         expect.fail("should have thrown error");
       } catch (error: any) {
         expect(error.error.errorCode.code).to.equal("InvalidConfig");
       }
-    });
-
-    it("should reject finalize before rotation delay elapses", async () => {
-      const newVerifier = anchor.web3.Keypair.generate();
-
-      await program.methods
-        .initiateVerifierRotation(newVerifier.publicKey, new anchor.BN(2))
-        .accountsStrict({
-          registry: registryPda,
-          authority: payer,
-        })
-        .rpc();
 
-      try {
-        await program.methods
-          .finalizeVerifierRotation()
+      const user = anchor.web3.Keypair.generate();
+      await airdrop(user.publicKey);
+      const now = Math.floor(Date.now() / 1000);
+      const { attestationNonceRegistryPda, nonce } = await submitProof(
+        user,
+        Buffer.alloc(32, "nonceClose1"),
+        { worldId: {} },
+        sourceData("worldId", now),
+        new anchor.BN(150),
+        now
+      );
+      const closeNonce = () =>
+        program.methods
+          .closeAttestationNonce(new anchor.BN(nonce))
           .accountsStrict({
             registry: registryPda,
-            authority: payer,
+            attestationNonceRegistry: attestationNonceRegistryPda,
+            user: user.publicKey,
           })
           .rpc();
-        expect.fail("should have thrown error");
-      } catch (error: any) {
-        expect(error.error.errorCode.code).to.equal("VerifierRotationNotReady");
-      }
-
-      await new Promise((resolve) => setTimeout(resolve, 2500));
-
-      await program.methods
-        .finalizeVerifierRotation()
-        .accountsStrict({
-          registry: registryPda,
-          authority: payer,
-        })
-        .rpc();
 
-      await program.methods
-        .initiateVerifierRotation(payer, new anchor.BN(1))
-        .accountsStrict({
-          registry: registryPda,
-          authority: payer,
-        })
-        .rpc();
+      for (const retention of [0, 7200]) {
+        await setNonceRetention(retention);
+        try {
+          await closeNonce();
+          expect.fail("should have thrown error");
+        } catch (error: any) {
+          expect(error.error.errorCode.code).to.equal("NonceRetentionActive");
+        }
+      }
 
-      await new Promise((resolve) => setTimeout(resolve, 2500));
+      const nonceAccount =
+        await program.account.attestationNonceRegistry.fetch(
+          attestationNonceRegistryPda
+        );
+      expect(nonceAccount.isUsed).to.equal(true);
 
-      await program.methods
-        .finalizeVerifierRotation()
-        .accountsStrict({
-          registry: registryPda,
-          authority: payer,
-        })
-        .rpc();
+      await setNonceRetention(0);
     });
 
-    it("should rotate verifier authority with delay", async () => {
-      const newVerifier = anchor.web3.Keypair.generate();
-
-      await program.methods
-        .initiateVerifierRotation(newVerifier.publicKey, new anchor.BN(1))
-        .accountsStrict({
-          registry: registryPda,
-          authority: payer,
-        })
-        .rpc();
-
-      await new Promise((resolve) => setTimeout(resolve, 2500));
+    it("should change the fingerprint only on updates", async () => {
+      const user = anchor.web3.Keypair.generate();
+      await airdrop(user.publicKey);
 
-      await program.methods
-        .finalizeVerifierRotation()
-        .accountsStrict({
-          registry: registryPda,
-          authority: payer,
-        })
-        .rpc();
+      const now = Math.floor(Date.now() / 1000);
+      const { userProofPda } = await submitProof(
+        user,
+        Buffer.alloc(32, "fingerprint1"),
+        { worldId: {} },
+        sourceData("worldId", now),
+        new anchor.BN(150),
+        now
+      );
+      const fingerprint = async () =>
+        Buffer.from(
+          await program.methods
+            .getVerificationFingerprint()
+            .accountsStrict({
+              userProof: userProofPda,
+              user: user.publicKey,
+            })
+            .view()
+        ).toString("hex");
+
+      const initial = await fingerprint();
+      expect(await fingerprint()).to.equal(initial);
 
-      const registry = await program.account.registry.fetch(registryPda);
-      expect(registry.verifierAuthority.toString()).to.equal(
-        newVerifier.publicKey.toString()
+      await submitProof(
+        user,
+        Buffer.alloc(32, "fingerprint2"),
+        { reclaim: {} },
+        sourceData("reclaim", now),
+        new anchor.BN(150),
+        now
       );
+      expect(await fingerprint()).to.not.equal(initial);
     });
 
-    it("should reject old verifier signature after rotation", async () => {
+    it("should cap new sources but allow resubmitting one", async () => {
+      const setMaxActiveSources = (max: number) =>
+        program.methods
+          .setMaxActiveSources(max)
+          .accountsStrict({
+            registry: registryPda,
+            authority: payer,
+          })
+          .rpc();
+      await setMaxActiveSources(2);
+
       const user = anchor.web3.Keypair.generate();
       await airdrop(user.publicKey);
-
       const now = Math.floor(Date.now() / 1000);
-      const proofHash = Buffer.alloc(32, "oldver");
-      const source = { reclaim: {} };
-      const payload = sourceData("reclaim", now);
-      const identityNullifier = identityNullifierFromPayload(source, payload);
-      const nonce = 424242;
+      const worldIdPayload = sourceData("worldId", now);
+      await submitProof(
+        user,
+        Buffer.alloc(32, "maxSources1"),
+        { worldId: {} },
+        worldIdPayload,
+        new anchor.BN(150),
+        now
+      );
+      await submitProof(
+        user,
+        Buffer.alloc(32, "maxSources2"),
+        { reclaim: {} },
+        sourceData("reclaim", now),
+        new anchor.BN(150),
+        now
+      );
 
-      const attestationIx =
-        anchor.web3.Ed25519Program.createInstructionWithPrivateKey({
-          privateKey: verifier.secretKey,
-          message: buildAttestationMessage(
-            user.publicKey,
-            proofHash,
-            source,
-            identityNullifier,
-            nonce,
-            new anchor.BN(150),
-            now
-          ),
-        });
+      try {
+        await submitProof(
+          user,
+          Buffer.alloc(32, "maxSources3"),
+          { gitcoinPassport: {} },
+          sourceData("gitcoin", now),
+          new anchor.BN(150),
+          now
+        );
+        expect.fail("should have thrown error");
+      } catch (error: any) {
+        expect(error.error.errorCode.code).to.equal("MaxSourcesReached");
+      }
 
-      const userProofPda = deriveUserProofPda(user.publicKey);
-      const individualProofPda = deriveIndividualProofPda(
-        user.publicKey,
-        sourceIndex.reclaim
+      const { userProofPda } = await submitProof(
+        user,
+        Buffer.alloc(32, "maxSources4"),
+        { worldId: {} },
+        worldIdPayload,
+        new anchor.BN(160),
+        now
       );
-      const identityNullifierRegistryPda =
-        deriveIdentityNullifierPda(identityNullifier);
-      const attestationNonceRegistryPda = deriveAttestationNoncePda(nonce);
+      const userProof = await program.account.userProof.fetch(userProofPda);
+      expect(userProof.activeSourceCount).to.equal(2);
 
-      try {
-        await program.methods
-          .submitProof(
-            Array.from(proofHash),
-            source,
-            identityNullifier,
-            new anchor.BN(nonce),
-            payload as any,
-            new anchor.BN(150),
-            new anchor.BN(now)
-          )
-          .preInstructions([attestationIx])
+      await setMaxActiveSources(8);
+    });
+
+    it("should reject v1 attestations once only v2 is accepted", async () => {
+      const setAttestationVersion = (version: number, minVersion: number) =>
+        program.methods
+          .setAttestationVersion(version, minVersion)
           .accountsStrict({
             registry: registryPda,
-            userProof: userProofPda,
-            individualProof: individualProofPda,
-            identityNullifierRegistry: identityNullifierRegistryPda,
-            attestationNonceRegistry: attestationNonceRegistryPda,
-            scoringConfig: scoringConfigPda,
-            instructionsSysvar: anchor.web3.SYSVAR_INSTRUCTIONS_PUBKEY,
-            user: user.publicKey,
-            systemProgram: anchor.web3.SystemProgram.programId,
+            authority: payer,
           })
-          .signers([user])
           .rpc();
+      const user = anchor.web3.Keypair.generate();
+      await airdrop(user.publicKey);
+      const now = Math.floor(Date.now() / 1000);
+      const submitVersion = (seed: string, attestationVersion: number) =>
+        submitProof(
+          user,
+          Buffer.alloc(32, seed),
+          { worldId: {} },
+          sourceData("worldId", now),
+          new anchor.BN(150),
+          now,
+          { attestationVersion }
+        );
+
+      // During the rollover both formats are accepted.
+      await setAttestationVersion(2, 1);
+      await submitVersion("attestationV1", 1);
+      await submitVersion("attestationV2", 2);
 
+      await setAttestationVersion(2, 2);
+      try {
+        await submitVersion("attestationV1b", 1);
         expect.fail("should have thrown error");
       } catch (error: any) {
         expect(error.error.errorCode.code).to.equal(
-          "InvalidAttestationMessage"
+          "UnsupportedAttestationVersion"
         );
       }
+      await submitVersion("attestationV2b", 2);
 
-      await program.methods
-        .initiateVerifierRotation(payer, new anchor.BN(1))
-        .accountsStrict({
-          registry: registryPda,
-          authority: payer,
-        })
-        .rpc();
-
-      await new Promise((resolve) => setTimeout(resolve, 2500));
-
-      await program.methods
-        .finalizeVerifierRotation()
-        .accountsStrict({
-          registry: registryPda,
-          authority: payer,
-        })
-        .rpc();
+      await setAttestationVersion(1, 1);
     });
 
-    it("should reject unauthorized update", async () => {
-      const unauthorized = anchor.web3.Keypair.generate();
-      try {
-        await program.methods
-          .updateMinScore(new anchor.BN(300))
+    it("should enforce sequential nonces when enabled", async () => {
+      const setSequentialNonces = (enabled: boolean) =>
+        program.methods
+          .setSequentialNonces(enabled)
           .accountsStrict({
             registry: registryPda,
-            authority: unauthorized.publicKey,
+            authority: payer,
           })
-          .signers([unauthorized])
           .rpc();
-        expect.fail("should have thrown error");
-      } catch (error: any) {
-        expect(error.error.errorCode.code).to.equal("Unauthorized");
-      }
+      const user = anchor.web3.Keypair.generate();
+      await airdrop(user.publicKey);
+      const now = Math.floor(Date.now() / 1000);
+      const payload = sourceData("worldId", now);
+      const submitNonce = (
+        seed: string,
+        nonce: number,
+        sequentialNonce = true
+      ) =>
+        submitProof(
+          user,
+          Buffer.alloc(32, seed),
+          { worldId: {} },
+          payload,
+          new anchor.BN(150),
+          now,
+          { nonce, sequentialNonce }
+        );
+      const expectRejected = async (
+        promise: Promise<unknown>,
+        code: string
+      ) => {
+        try {
+          await promise;
+          expect.fail("should have thrown error");
+        } catch (error: any) {
+          expect(error.error.errorCode.code).to.equal(code);
+        }
+      };
+
+      await setSequentialNonces(true);
+      await expectRejected(
+        submitNonce("sequential1", 2),
+        "AttestationNonceOutOfOrder"
+      );
+      await submitNonce("sequential1", 1);
+      await expectRejected(
+        submitNonce("sequential2", 1),
+        "AttestationNonceOutOfOrder"
+      );
+      await expectRejected(
+        submitNonce("sequential2", 2, false),
+        "NonceModeMismatch"
+      );
+      const { userProofPda } = await submitNonce("sequential2", 2);
+
+      const userProof = await program.account.userProof.fetch(userProofPda);
+      expect(userProof.lastNonce.toNumber()).to.equal(2);
+
+      await setSequentialNonces(false);
     });
-  });
 
-  describe("Complex Scenarios", () => {
-    it("should apply diversity bonus with multiple sources", async () => {
+    it("should report nullifier claim status", async () => {
       const user = anchor.web3.Keypair.generate();
       await airdrop(user.publicKey);
       const now = Math.floor(Date.now() / 1000);
+      const payload = sourceData("worldId", now);
+      await submitProof(
+        user,
+        Buffer.alloc(32, "nullifierStatus1"),
+        { worldId: {} },
+        payload,
+        new anchor.BN(150),
+        now
+      );
+      const nullifierStatus = (nullifier: number[]) =>
+        program.methods
+          .getNullifierStatus({ worldId: {} }, nullifier)
+          .accountsStrict({
+            identityNullifierRegistry: deriveIdentityNullifierPda(2, nullifier),
+          })
+          .view();
+
+      const claimed = await nullifierStatus(payload.worldId.nullifierHash);
+      expect(claimed.isClaimed).to.equal(true);
+      expect(claimed.claimedBy.toBase58()).to.equal(user.publicKey.toBase58());
+      expect(claimed.isBurned).to.equal(false);
+      expect(claimed.claimedAt.toNumber()).to.be.greaterThan(0);
+
+      const unclaimed = await nullifierStatus(nextHash32());
+      expect(unclaimed.isClaimed).to.equal(false);
+      expect(unclaimed.claimedBy.toBase58()).to.equal(
+        anchor.web3.PublicKey.default.toBase58()
+      );
+    });
 
-      const userProofPda = deriveUserProofPda(user.publicKey);
-
+    it("should report registry stats matching the accounts", async () => {
+      const user = anchor.web3.Keypair.generate();
+      await airdrop(user.publicKey);
+      const now = Math.floor(Date.now() / 1000);
       await submitProof(
         user,
-        Buffer.from(Uint8Array.from([100, ...Array(31).fill(0)])),
+        Buffer.alloc(32, "registryStats1"),
         { reclaim: {} },
         sourceData("reclaim", now),
         new anchor.BN(100),
         now
       );
-
       await submitProof(
         user,
-        Buffer.from(Uint8Array.from([101, ...Array(31).fill(0)])),
-        { gitcoinPassport: {} },
-        sourceData("gitcoin", now, 100),
-        new anchor.BN(100),
+        Buffer.alloc(32, "registryStats2"),
+        { worldId: {} },
+        sourceData("worldId", now),
+        new anchor.BN(150),
         now
       );
 
-      const userProof = await program.account.userProof.fetch(userProofPda);
-      expect(userProof.activeSourceCount).to.equal(2);
-      expect(userProof.aggregatedScore.toNumber()).to.be.greaterThan(200);
+      const stats = await program.methods
+        .getRegistryStats()
+        .accountsStrict({
+          registry: registryPda,
+          scoringConfig: scoringConfigPda,
+        })
+        .view();
+      const registry = await program.account.registry.fetch(registryPda);
+      const scoringConfig = await program.account.scoringConfig.fetch(
+        scoringConfigPda
+      );
+      expect(stats.totalVerifiedUsers.toNumber()).to.equal(
+        registry.totalVerifiedUsers.toNumber()
+      );
+      expect(stats.minScore.toNumber()).to.equal(registry.minScore.toNumber());
+      expect(
+        stats.activeSourceWeights.map((weight: anchor.BN) => weight.toNumber())
+      ).to.deep.equal(
+        scoringConfig.weights.map((weight: anchor.BN) => weight.toNumber())
+      );
+      expect(stats.verifierAuthority.toBase58()).to.equal(
+        registry.verifierAuthority.toBase58()
+      );
+      expect(stats.paused).to.equal(registry.paused);
+      expect(stats.proofTtlSeconds.toNumber()).to.equal(
+        registry.proofTtlSeconds.toNumber()
+      );
     });
+  });
 
-    it("should reject expired proof timestamp", async () => {
-      const user = anchor.web3.Keypair.generate();
-      await airdrop(user.publicKey);
+  describe("Legacy Migration", () => {
+    // Loaded by the test validator from tests/fixtures with the original
+    // IndividualProof layout (no trailing `migrated` flag).
+    const legacyUser = anchor.web3.Keypair.fromSecretKey(
+      Uint8Array.from(
+        JSON.parse(
+          fs.readFileSync("tests/fixtures/legacy-user.json", "utf8")
+        ) as number[]
+      )
+    );
 
-      const oldTimestamp = Math.floor(Date.now() / 1000) - 7200;
+    it("should import a legacy individual proof", async () => {
+      await airdrop(legacyUser.publicKey);
+      const individualProofPda = deriveIndividualProofPda(
+        legacyUser.publicKey,
+        sourceIndex.reclaim
+      );
 
-      try {
-        await submitProof(
-          user,
-          Buffer.alloc(32, "proof7"),
-          { reclaim: {} },
-          sourceData("reclaim", oldTimestamp),
-          new anchor.BN(100),
-          oldTimestamp
-        );
-        expect.fail("should have thrown error");
-      } catch (error: any) {
-        expect(error.error.errorCode.code).to.equal("ProofExpired");
-      }
-    });
-  });
+      const before = await provider.connection.getAccountInfo(
+        individualProofPda
+      );
+      expect(before?.data.length).to.equal(236);
 
-  describe("Edge Cases", () => {
-    it("should handle score overflow safely", async () => {
-      const user = anchor.web3.Keypair.generate();
-      await airdrop(user.publicKey);
+      await program.methods
+        .importLegacyProof({ reclaim: {} })
+        .accountsStrict({
+          individualProof: individualProofPda,
+          user: legacyUser.publicKey,
+          systemProgram: anchor.web3.SystemProgram.programId,
+        })
+        .signers([legacyUser])
+        .rpc();
 
-      const now = Math.floor(Date.now() / 1000);
-      const massiveScore = new anchor.BN(2).pow(new anchor.BN(60));
+      const individualProof = await program.account.individualProof.fetch(
+        individualProofPda
+      );
+      expect(individualProof.migrated).to.equal(true);
+      expect(individualProof.user.toString()).to.equal(
+        legacyUser.publicKey.toString()
+      );
+      expect(individualProof.baseScore.toNumber()).to.equal(150);
+      expect(individualProof.weightedScore.toNumber()).to.equal(150);
+      expect(individualProof.isRevoked).to.equal(false);
+      expect(individualProof.identityNullifier).to.deep.equal(hash32(8));
 
       try {
-        await submitProof(
-          user,
-          Buffer.alloc(32, "proof8"),
-          { reclaim: {} },
-          sourceData("reclaim", now),
-          massiveScore,
-          now
-        );
-        expect.fail("should have thrown overflow error");
+        await program.methods
+          .importLegacyProof({ reclaim: {} })
+          .accountsStrict({
+            individualProof: individualProofPda,
+            user: legacyUser.publicKey,
+            systemProgram: anchor.web3.SystemProgram.programId,
+          })
+          .signers([legacyUser])
+          .rpc();
+        expect.fail("should have thrown error");
       } catch (error: any) {
-        expect(error.error.errorCode.code).to.equal("Overflow");
+        expect(error.error.errorCode.code).to.equal("InvalidLegacyAccount");
       }
     });
 
-    it("should verify proof status correctly", async () => {
-      const user = anchor.web3.Keypair.generate();
-      await airdrop(user.publicKey);
-
+    it("should reconcile a desynced active source count", async () => {
+      // The imported legacy proof was never counted on a UserProof, so a new
+      // submission leaves the count one short of the non-revoked proofs.
       const now = Math.floor(Date.now() / 1000);
       const { userProofPda } = await submitProof(
-        user,
-        Buffer.alloc(32, "proof9"),
+        legacyUser,
+        Buffer.alloc(32, "reconcile1"),
         { gitcoinPassport: {} },
-        sourceData("gitcoin", now, 300),
-        new anchor.BN(300),
+        sourceData("gitcoin", now),
+        new anchor.BN(150),
         now
       );
-
-      const result = await program.methods
-        .verifyProof()
+      const desynced = await program.account.userProof.fetch(userProofPda);
+      expect(desynced.activeSourceCount).to.equal(1);
+
+      const individualProofs = Array.from(
+        { length: PROOF_SOURCE_COUNT },
+        (_, index) => ({
+          pubkey: deriveIndividualProofPda(legacyUser.publicKey, index),
+          isSigner: false,
+          isWritable: false,
+        })
+      );
+      await program.methods
+        .reconcileSourceCount()
         .accountsStrict({
-          userProof: userProofPda,
           registry: registryPda,
-          user: user.publicKey,
+          userProof: userProofPda,
+          scoringConfig: scoringConfigPda,
+          user: legacyUser.publicKey,
         })
-        .view();
+        .remainingAccounts(individualProofs)
+        .rpc();
+      const reconciled = await program.account.userProof.fetch(userProofPda);
+      expect(reconciled.activeSourceCount).to.equal(2);
+      expect(reconciled.sourcesMask).to.equal(0b011);
+    });
 
-      expect(result.isVerified).to.equal(true);
-      expect(result.verifiedAt.toNumber()).to.be.greaterThan(0);
+    it("should migrate an unversioned account without data loss", async () => {
+      // Fixture UserProof written before the trailing `version` byte existed.
+      const user = new anchor.web3.PublicKey(Buffer.alloc(32, 0x51));
+      const userProofPda = deriveUserProofPda(user);
+      const before = await provider.connection.getAccountInfo(userProofPda);
+      expect(before?.data.length).to.equal(81);
+
+      const migrate = (account: anchor.web3.PublicKey) =>
+        program.methods
+          .migrateAccount()
+          .accountsStrict({
+            account,
+            payer,
+            systemProgram: anchor.web3.SystemProgram.programId,
+          })
+          .rpc();
+
+      await migrate(userProofPda);
+      const after = await provider.connection.getAccountInfo(userProofPda);
+      expect(after?.data.length).to.equal(250);
+      // Fields before `sources_mask` keep their offsets; it and the later masks widen.
+      expect(after?.data.subarray(0, 69)).to.deep.equal(
+        before?.data.subarray(0, 69)
+      );
+
+      const migrated = await program.account.userProof.fetch(userProofPda);
+      expect(migrated.version).to.equal(5);
+      expect(migrated.user.toBase58()).to.equal(user.toBase58());
+      expect(migrated.aggregatedScore.toNumber()).to.equal(300);
+      expect(migrated.activeSourceCount).to.equal(2);
+      expect(migrated.sourcesMask).to.equal(0b101);
+      expect(migrated.appliedDiversityBonusPercent).to.equal(10);
+
+      // Migrating a current account is a no-op.
+      await migrate(userProofPda);
+      const again = await provider.connection.getAccountInfo(userProofPda);
+      expect(again?.data).to.deep.equal(after?.data);
+
+      // Fixture stamped with a version this program does not know yet.
+      const future = deriveUserProofPda(
+        new anchor.web3.PublicKey(Buffer.alloc(32, 0x52))
+      );
+      try {
+        await migrate(future);
+        expect.fail("should have thrown error");
+      } catch (error: any) {
+        expect(error.error.errorCode.code).to.equal("AccountVersionDowngrade");
+      }
+
+      try {
+        await migrate(payer);
+        expect.fail("should have thrown error");
+      } catch (error: any) {
+        expect(error.error.errorCode.code).to.equal("InvalidMigrationAccount");
+      }
+    });
+
+    it("should migrate a baseline registry with working defaults", async () => {
+      // Fixture Registry in the original layout, ending at `proof_ttl_seconds`
+      // and `bump`.
+      const baseline = new anchor.web3.PublicKey(Buffer.alloc(32, 0x53));
+      const before = await provider.connection.getAccountInfo(baseline);
+      expect(before?.data.length).to.equal(146);
+
+      await program.methods
+        .migrateAccount()
+        .accountsStrict({
+          account: baseline,
+          payer,
+          systemProgram: anchor.web3.SystemProgram.programId,
+        })
+        .rpc();
+
+      const migrated = await program.account.registry.fetch(baseline);
+      expect(migrated.version).to.equal(5);
+      expect(migrated.authority.toBase58()).to.equal(
+        new anchor.web3.PublicKey(Buffer.alloc(32, 0x61)).toBase58()
+      );
+      expect(migrated.verifierAuthority.toBase58()).to.equal(
+        new anchor.web3.PublicKey(Buffer.alloc(32, 0x62)).toBase58()
+      );
+      expect(migrated.totalVerifiedUsers.toNumber()).to.equal(7);
+      expect(migrated.minScore.toNumber()).to.equal(100);
+      expect(migrated.diversityBonusPercent).to.equal(10);
+      expect(migrated.proofTtlSeconds.toNumber()).to.equal(3600);
+      expect(migrated.bump).to.equal(254);
+
+      // Fields the baseline lacked read as `initialize_registry` sets them.
+      expect(migrated.maxWeightedScore.toString()).to.equal(
+        "18446744073709551615"
+      );
+      expect(migrated.maxActiveSources).to.equal(PROOF_SOURCE_COUNT);
+      expect(migrated.attestationVersion).to.equal(1);
+      expect(migrated.minAttestationVersion).to.equal(1);
+      expect(migrated.maxRecencyAfterResubmit).to.equal(100);
+      expect(migrated.minSourceCount).to.equal(1);
+      expect(migrated.web2Mask).to.not.equal(0);
+      expect(migrated.web3Mask).to.not.equal(0);
+      expect(migrated.allowedSources).to.equal(0xffff);
+      expect(migrated.maxTrustMultiplier).to.equal(100);
+      expect(migrated.clockSkewTolerance.toNumber()).to.equal(300);
     });
   });
 });